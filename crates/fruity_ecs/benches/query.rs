@@ -0,0 +1,133 @@
+// The component derive refers to the fruity_ecs modules trough `crate` when used inside the fruity_ecs package
+use fruity_ecs::{component, entity, serialization};
+
+use component::Component;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use entity::{EntityId, EntityStorage};
+use fruity_ecs::query::With;
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
+
+macro_rules! bench_components {
+    ($($name:ident),*) => {
+        $(
+            #[derive(Debug, Clone, Default, Component, FruityAny)]
+            #[fruity_export]
+            pub struct $name(pub u32);
+
+            #[fruity_export]
+            impl $name {}
+        )*
+
+        fn instantiate_component(index: usize) -> Box<dyn Component> {
+            let constructors: &[fn() -> Box<dyn Component>] = &[$(|| Box::new($name(1))),*];
+            constructors[index]()
+        }
+    };
+}
+
+bench_components!(C0, C1, C2, C3, C4, C5, C6, C7, C8, C9);
+
+const COMPONENT_TYPE_COUNT: usize = 10;
+const ENTITY_PER_ARCHETYPE: usize = 4;
+
+/// Create one archetype for every combination of the bench components, so 1023 archetypes
+fn create_storage() -> EntityStorage {
+    let mut entity_storage = EntityStorage::new();
+    let mut entity_id = 0;
+
+    for mask in 1..(1 << COMPONENT_TYPE_COUNT) {
+        for _ in 0..ENTITY_PER_ARCHETYPE {
+            let components = (0..COMPONENT_TYPE_COUNT)
+                .filter(|index| mask & (1 << index) != 0)
+                .map(instantiate_component)
+                .collect::<Vec<_>>();
+
+            entity_id += 1;
+            entity_storage
+                .create_entity(EntityId::new(entity_id, 0), components, None, None)
+                .unwrap();
+        }
+    }
+
+    entity_storage
+}
+
+/// Create the same query again, as a system does when it is injected
+fn create_query_with_many_archetypes(criterion: &mut Criterion) {
+    let entity_storage = create_storage();
+
+    criterion.bench_function("create_query_with_many_archetypes", |bencher| {
+        bencher.iter(|| entity_storage.query::<(With<C0>, With<C1>, With<C2>)>())
+    });
+}
+
+/// Iterate a query that match only a few of the archetypes
+fn for_each_with_many_archetypes(criterion: &mut Criterion) {
+    let entity_storage = create_storage();
+    let query = entity_storage.query::<(With<C0>, With<C1>, With<C2>, With<C3>, With<C4>)>();
+
+    criterion.bench_function("for_each_with_many_archetypes", |bencher| {
+        bencher.iter(|| {
+            query
+                .for_each(|(c0, c1, c2, c3, c4)| {
+                    black_box(c0.0 + c1.0 + c2.0 + c3.0 + c4.0);
+                    Ok(())
+                })
+                .unwrap()
+        })
+    });
+}
+
+/// Iterate a query in parallel, the storages of the matching archetypes are locked at every frame
+fn par_for_each_with_many_archetypes(criterion: &mut Criterion) {
+    let entity_storage = create_storage();
+    let query = entity_storage.query::<(With<C0>, With<C1>, With<C2>, With<C3>, With<C4>)>();
+
+    criterion.bench_function("par_for_each_with_many_archetypes", |bencher| {
+        bencher.iter(|| {
+            query
+                .par_for_each(ENTITY_PER_ARCHETYPE, |(c0, c1, c2, c3, c4)| {
+                    black_box(c0.0 + c1.0 + c2.0 + c3.0 + c4.0);
+                    Ok(())
+                })
+                .unwrap()
+        })
+    });
+}
+
+/// Create a storage while several queries are listening to the archetype creations
+fn create_archetypes_with_queries(criterion: &mut Criterion) {
+    criterion.bench_function("create_archetypes_with_queries", |bencher| {
+        bencher.iter(|| {
+            let mut entity_storage = EntityStorage::new();
+            let queries = (
+                entity_storage.query::<With<C0>>(),
+                entity_storage.query::<(With<C1>, With<C2>)>(),
+                entity_storage.query::<(With<C3>, With<C4>, With<C5>)>(),
+            );
+
+            for mask in 1..(1 << COMPONENT_TYPE_COUNT) {
+                let components = (0..COMPONENT_TYPE_COUNT)
+                    .filter(|index| mask & (1 << index) != 0)
+                    .map(instantiate_component)
+                    .collect::<Vec<_>>();
+
+                entity_storage
+                    .create_entity(EntityId::new(mask as u32, 0), components, None, None)
+                    .unwrap();
+            }
+
+            black_box(queries);
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    create_query_with_many_archetypes,
+    for_each_with_many_archetypes,
+    par_for_each_with_many_archetypes,
+    create_archetypes_with_queries
+);
+criterion_main!(benches);
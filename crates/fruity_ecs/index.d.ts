@@ -3,53 +3,504 @@ import {
   ObserverHandler,
   Module,
   ScriptValue,
+  ResourceReference,
+  Settings,
 } from "fruity_game_engine"
 
 export type EntityServiceSnapshot = SerializedEntity[]
 
+export type EntityId = bigint
+
+/**
+ * Define how an async system behaves when it's future is still running at the next frame
+ */
+export type AsyncSystemPolicy = "block" | "skip" | "queue"
+
+/**
+ * A buffer of entity mutations, applied by the [`EntityService`] at the next sync point
+ *
+ * Sync points are the end of the start, frame and end runs, so the mutations recorded by a system
+ * are visible to every system of the next frame, even if the buffer is dropped before
+ *
+ * The ids of the spawned entities are reserved immediately, so they can be stored in components or
+ * used by the next commands of the same buffer
+ */
+export class Commands {
+
+  /**
+   * Spawn an entity at the next sync point
+   * Returns the id reserved for the entity
+   *
+   * @param name - The name of the entity
+   * @param enabled - Is the entity active
+   * @param components - The components that will be added
+   */
+  spawn(name: string, enabled: boolean, components: Component[]): EntityId
+  /**
+   * Remove an entity at the next sync point
+   */
+  despawn(entityId: EntityId)
+  /**
+   * Add components to an entity at the next sync point, the existing components of the same types are replaced
+   */
+  insert(entityId: EntityId, components: Component[])
+  /**
+   * Remove the components of a given type from an entity at the next sync point
+   */
+  remove(entityId: EntityId, scriptObjectType: ScriptObjectType)
+}
+
+/**
+ * A history of the edits made by the editing tools over the entities, the edits can be undone and redone
+ *
+ * The edits should be made through this service instead of the `EntityService` to be recorded, the
+ * consecutive edits can be grouped into a transaction that is undone at once
+ */
+export class EditHistoryService {
+
+  /**
+   * Change a field of a component
+   *
+   * @param component - The component
+   * @param field - The field name
+   * @param value - The new value
+   */
+  setFieldValue(component: AnyComponentReference, field: string, value: ScriptValue): void
+  /**
+   * Add components to an entity, the existing components of the same types are kept
+   *
+   * @param entityId - The entity id
+   * @param components - The added components
+   */
+  addComponents(entityId: EntityId, components: Component[]): void
+  /**
+   * Remove the first component of a given type from an entity
+   *
+   * @param entityId - The entity id
+   * @param scriptObjectType - The type of the removed component
+   */
+  removeComponent(entityId: EntityId, scriptObjectType: ScriptObjectType): void
+  /**
+   * Create an entity
+   * Returns the newly created entity id
+   *
+   * @param name - The name of the entity
+   * @param enabled - Is the entity active
+   * @param components - The components that will be added
+   */
+  createEntity(name: string, enabled: boolean, components: Component[]): EntityId
+  /**
+   * Remove an entity
+   *
+   * @param entityId - The entity id
+   */
+  removeEntity(entityId: EntityId): void
+  /**
+   * Start a transaction, the edits are grouped until the transaction is ended and are undone at once
+   * The transactions can be nested, the edits are grouped until the outer transaction is ended
+   */
+  beginTransaction()
+  /**
+   * End a transaction
+   */
+  endTransaction(): void
+  /**
+   * Undo the last edit or transaction
+   * Returns false if there is nothing to undo
+   *
+   * If an edit can't be undone, the edits of the transaction that have already been undone are redone and
+   * the transaction stays the next one to undo
+   */
+  undo(): boolean
+  /**
+   * Redo the last undone edit or transaction
+   * Returns false if there is nothing to redo
+   *
+   * If an edit can't be redone, the edits of the transaction that have already been redone are undone and
+   * the transaction stays the next one to redo
+   */
+  redo(): boolean
+  /**
+   * Returns true if there is an edit to undo
+   */
+  canUndo(): boolean
+  /**
+   * Returns true if there is an edit to redo
+   */
+  canRedo(): boolean
+  /**
+   * Forget all the recorded edits
+   */
+  clear()
+  /**
+   * Get the maximum number of edits or transactions that can be undone
+   */
+  getMaxHistory(): number
+  /**
+   * Set the maximum number of edits or transactions that can be undone, the oldest ones are forgotten
+   */
+  setMaxHistory(maxHistory: number)
+}
+
+/**
+ * A component to enable or disable an entity
+ */
 export class Enabled {
   0: boolean
+  /**
+   * Returns a new RectCollider
+   */
   constructor(enabled: boolean)
 }
 
+/**
+ * A reference to an entity
+ * Update its own state when an entity is moved
+ */
 export class EntityReference {
 
+  /**
+   * Get entity id
+   */
   getEntityId(): EntityId
+  /**
+   * Get entity name
+   */
   getName(): string
+  /**
+   * Set entity name
+   */
   setName(name: string): void
+  /**
+   * Get entity enabled
+   */
   isEnabled(): boolean
+  /**
+   * Set entity enabled
+   */
   setEnabled(enabled: boolean): void
+  /**
+   * Get all components
+   */
   getAllComponents(): AnyComponentReference[]
+  /**
+   * Get components with a given component type id
+   */
   getComponentsByType(componentTypeId: ScriptObjectType): AnyComponentReference[]
 }
 
+/**
+ * A storage for every entities, use [’Archetypes’] to store entities of different types
+ */
 export class EntityService {
+  /**
+   * Signal notified when an entity is created
+   */
   onCreated: Signal<EntityReference>
+  /**
+   * Signal notified when an entity is deleted
+   */
   onDeleted: Signal<EntityId>
+  /**
+   * Signal notified once when a batch of entities is spawned, see [`EntityService::spawn_batch`]
+   * The entities of the batch are not notified through [`EntityService::on_created`]
+   */
+  onBatchCreated: Signal<EntityReference[]>
+  /**
+   * Get an entity specific components
+   *
+   * @param entityId - The entity id
+   */
   getEntityReference(entityId: EntityId): EntityReference | null
+  /**
+   * Get the first entity with a given name
+   * The entities are indexed at the sync points, a new or renamed entity is found after the next one
+   *
+   * @param name - The name of the entity
+   */
+  findByName(name: string): EntityId | null
+  /**
+   * Get all the entities with a given tag, see [`Tag`]
+   * The entities are indexed at the sync points, a new or tagged entity is found after the next one
+   *
+   * @param tag - The tag
+   */
+  findAllByTag(tag: string): EntityId[]
+  /**
+   * Get an entity by its path into the hierarchy, the path is made of the entity names from a root
+   * entity separated by slashes, for example "Level/Enemies/Boss"
+   * Without a parent resolver, every entity is a root
+   *
+   * @param path - The path of the entity
+   */
+  findByPath(path: string): EntityId | null
+  /**
+   * Get the signal notified when a component of a given type is added to an entity, the entity
+   * creation included
+   */
+  onComponentAdded(scriptObjectType: ScriptObjectType): Signal<EntityReference>
+  /**
+   * Get the signal notified when the last component of a given type is removed from an entity,
+   * the entity deletion included, the entity still has the component when the signal is sent
+   * for a deletion
+   */
+  onComponentRemoved(scriptObjectType: ScriptObjectType): Signal<EntityReference>
+  /**
+   * Get the signal notified when a component of a given type has been mutably accessed
+   * The changes are checked at the sync points, the signal is sent once per entity
+   */
+  onComponentChanged(scriptObjectType: ScriptObjectType): Signal<EntityReference>
+  /**
+   * Create a buffer of entity mutations that are applied at the next sync point
+   */
+  commands(): Commands
+  /**
+   * Create a query over entities
+   */
   query(): ScriptQueryBuilder
+  /**
+   * Add a new entity in the storage
+   * Create the archetype if it don't exists
+   * Returns the newly created entity id
+   *
+   * @param name - The name of the entity
+   * @param enabled - Is the entity active
+   * @param components - The components that will be added
+   */
   createEntity(name: string, enabled: boolean, components: Component[]): EntityId
+  /**
+   * Add several entities in the storage, it's faster than creating them one by one
+   * The archetypes are looked up and allocated once for all the entities that share them
+   * Returns the ids of the created entities
+   *
+   * The entities are named with the prefix followed by their index in the batch, they are enabled
+   * The whole batch is notified once through [`EntityService::on_batch_created`], the entities are not
+   * notified through [`EntityService::on_created`]
+   *
+   * @param namePrefix - The prefix of the entity names
+   * @param entities - The components of every entity
+   */
+  spawnBatch(namePrefix: string, entities: Component[][]): EntityId[]
+  /**
+   * Remove an entity based on its id
+   * The entity is removed at the next sync point, even if it has been created since the last one
+   *
+   * @param entityId - The entity id
+   */
   removeEntity(entityId: EntityId): Component[]
+  /**
+   * Add components to an entity, the existing components of the same types are kept
+   */
   addComponents(entityId: EntityId, newComponents: Component[]): void
+  /**
+   * Add a component to an entity, replace the existing component of the same type if there is one
+   */
+  insert(entityId: EntityId, component: Component): void
+  /**
+   * Remove the first component of a given type from an entity
+   */
+  remove(entityId: EntityId, scriptObjectType: ScriptObjectType): void
+  /**
+   * Remove all the components of a given type from an entity
+   */
+  removeAll(entityId: EntityId, scriptObjectType: ScriptObjectType): void
+  /**
+   * Remove a component from an entity based on its index, prefer [`EntityService::remove`]
+   */
   removeComponent(entityId: EntityId, componentIndex: number): void
+  /**
+   * Clear all the entities
+   * The ids of the cleared entities become stale, they are not reused as is
+   */
   clear(): void
+  /**
+   * Create a snapshot over all the entities
+   */
   snapshot(): EntityServiceSnapshot
+  /**
+   * Restore an entity snapshot
+   *
+   * @param snapshot - The snapshot
+   */
   restore(clearBefore: boolean, snapshot: EntityServiceSnapshot): void
+  /**
+   * Load the entities of a scene additively, the existing entities are kept
+   * Every entity is tagged with a [`SceneInstance`] component so it can be removed with [`EntityService::unload_scene`]
+   * Returns the ids of the created entities
+   *
+   * @param scene - The scene resource
+   */
+  loadScene(scene: ResourceReference<SceneResource>): EntityId[]
+  /**
+   * Remove all the entities spawned by a scene, the entities are removed at the next sync point
+   * Returns the ids of the removed entities
+   *
+   * @param sceneIdentifier - The resource identifier of the scene
+   */
+  unloadScene(sceneIdentifier: string): EntityId[]
+  /**
+   * Spawn the entities of a prefab, every entity keeps a link to the prefab with a [`PrefabInstance`] component
+   * Returns the ids of the created entities, the first one is the root of the instance
+   *
+   * @param prefab - The prefab resource
+   * @param overrides - The fields changed for this instance
+   */
+  instantiatePrefab(prefab: ResourceReference<PrefabResource>, overrides?: PrefabOverride[] | null | undefined | void): EntityId[]
+  /**
+   * Apply the current state of a prefab to all its instances
+   *
+   * The components of the prefab replace the instance components of the same type, the instance overrides
+   * are applied again
+   * The entities added or removed from the prefab since the instances have been spawned are ignored
+   *
+   * @param prefab - The prefab resource
+   */
+  propagatePrefab(prefab: ResourceReference<PrefabResource>): void
 }
 
+/**
+ * A service to store components extensions
+ * When a component is created, if an extension is registered, an other component with a given
+ * type is created, this can be use if ou want to extend already existing components with other
+ * attributes. This is for example used into the physic engine implementations.
+ *
+ * Warning: The same extension type cannot be shared across multiple based component types
+ */
 export class ExtensionComponentService {
 
 }
 
+/**
+ * A component to name an entity
+ */
 export class Name {
   0: string
+  /**
+   * Returns a new RectCollider
+   */
   constructor(string: string)
 }
 
+/**
+ * A component for an entity that is spawned from a prefab, keep the link with the prefab so the
+ * prefab changes can be propagated to the instance
+ */
+export class PrefabInstance {
+  /**
+   * The resource identifier of the prefab
+   */
+  prefab: string
+  /**
+   * The local id of the entity into the prefab
+   */
+  localId: number
+  /**
+   * The root entity of the instance, none if the entity is the root
+   */
+  root?: EntityId | null | undefined | void
+  /**
+   * The overrides applied to this entity
+   */
+  overrides: PrefabOverride[]
+}
+
+/**
+ * A field of a prefab component that is changed for a single instance
+ */
+export interface PrefabOverride {
+  /**
+   * The local id of the overridden entity into the prefab, the root entity if none
+   */
+  localId?: number | null | undefined | void
+  /**
+   * The class name of the overridden component
+   */
+  component: string
+  /**
+   * The overridden field
+   */
+  field: string
+  /**
+   * The serialized value of the field
+   */
+  value: Settings
+}
+
+/**
+ * A reusable template of entities, described in the same format as an [`EntityServiceSnapshot`]
+ *
+ * The first entity is the root of the prefab, the hierarchy between the entities is kept with the
+ * entity references of the components, they refer to the local ids of the prefab
+ */
+export class PrefabResource {
+
+  /**
+   * Get the resource identifier of the prefab
+   */
+  getIdentifier(): string
+  /**
+   * Get the entities of the prefab
+   */
+  getSnapshot(): EntityServiceSnapshot
+  /**
+   * Change the entities of the prefab
+   * The existing instances are not modified, call `EntityService.propagatePrefab` to update them
+   */
+  setSnapshot(snapshot: EntityServiceSnapshot)
+}
+
+/**
+ * The observers registered by [`Query::on_created`] and [`ScriptQuery::on_created`]
+ */
+export class QueryObserverHandler {
+
+  /**
+   * Remove the observers from the signals
+   */
+  dispose()
+}
+
+/**
+ * A component for an entity that is spawned by a scene, the entity is removed when the scene is unloaded
+ */
+export class SceneInstance {
+  /**
+   * The resource identifier of the scene
+   */
+  scene: string
+  /**
+   * Returns a new SceneInstance
+   */
+  constructor(scene: string)
+}
+
+/**
+ * A set of entities loaded additively into the world, described in the same format as an [`EntityServiceSnapshot`]
+ *
+ * Several scenes can be loaded at the same time, use `EntityService.loadScene` and `EntityService.unloadScene`
+ */
+export class SceneResource {
+
+  /**
+   * Get the resource identifier of the scene
+   */
+  getIdentifier(): string
+  /**
+   * Get the entities of the scene
+   */
+  getSnapshot(): EntityServiceSnapshot
+}
+
+/**
+ * A query over entities
+ */
 export class ScriptQuery<Args extends any[] = []> {
   forEach(callback: (args: Args) => void);
-  onCreated(callback: (args: Args) => undefined | (() => void)): ObserverHandler;
+  onCreated(callback: (args: Args) => undefined | (() => void)): QueryObserverHandler;
 }
+/**
+ * Query builder for script queries
+ */
 export class ScriptQueryBuilder<Args extends any[] = []> {
   withEntity(): ScriptQueryBuilder<[...Args, EntityReference]>;
   withId(): ScriptQueryBuilder<[...Args, EntityId]>;
@@ -58,29 +509,208 @@ export class ScriptQueryBuilder<Args extends any[] = []> {
   with<T>(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, T]>;
   withOptional<T>(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, T | null]>;
   without(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  withFilter(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  withTag(tag: string): ScriptQueryBuilder<[...Args, null]>;
+  or(filters: ScriptQueryBuilder<any[]>[]): ScriptQueryBuilder<[...Args, null]>;
+  anyOf(constructors: (new (...args) => any)[]): ScriptQueryBuilder<[...Args, any[]]>;
+  added(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  changed(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  removed(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
   build(): ScriptQuery<[...Args]>
 }
+/**
+ * Utility used to deserialize objects, mostly used to restore snapshot
+ */
 export class SerializationService {
 
+  /**
+   * Register a function that upgrades the serialized fields of an object from a version to the next one
+   *
+   * The current version of an object type is the last upgraded version plus one, the objects serialized
+   * with an older version are upgraded before being instantiated
+   *
+   * @param className - The class name of the serialized object
+   * @param fromVersion - The version upgraded by the migration
+   * @param migration - A function that takes the old fields and returns the upgraded ones
+   */
+  registerMigration(className: string, fromVersion: number, migration: ((arg0: Settings) => Settings))
+  /**
+   * Get the current serialization version of an object type, 0 if it has no migration
+   */
+  getVersion(className: string): number
+  /**
+   * Upgrade all the serialized objects of a tree to their current version, for example a whole snapshot or scene
+   *
+   * @param serialized - The serialized tree
+   */
+  migrate(serialized: Settings): Settings
+  /**
+   * Upgrade in place a file that contains serialized objects, for example a scene or a snapshot written by
+   * `EntityService::write_snapshot`
+   *
+   * The format is chosen from the file extension, json, yaml or yml, the other files are read in the binary format
+   * The upgraded file is written next to the original one before replacing it, so it is not lost if the upgrade fails
+   *
+   * @param path - The path of the file
+   */
+  upgradeFile(path: string): void
 }
 
+/**
+ * Params for a system
+ */
 export interface StartupSystemParams {
+  /**
+   * If true, the system is still running while pause
+   */
   ignorePause?: boolean | null | undefined | void
+  /**
+   * If true, the system will be executed in the main thread
+   */
   executeInMainThread?: boolean | null | undefined | void
+  /**
+   * If set, the system runs when the application enters this state and its dispose callback runs
+   * when the application exits it, see [`StateService`]
+   * The pause is ignored by the state startup systems
+   */
+  state?: string | null | undefined | void
+}
+
+/**
+ * A state machine over the application states, for example MainMenu, Loading, Playing and GameOver
+ *
+ * A state change is applied by the `SystemService` at the beginning of the next frame, the dispose
+ * callbacks of the previous state are called then the startup systems of the new state are run
+ * The frame systems can be restricted to some states with the `states` system param
+ */
+export class StateService {
+
+  /**
+   * Get the current state
+   */
+  getState(): string | null
+  /**
+   * Returns true if the application is in a given state
+   */
+  isInState(state: string): boolean
+  /**
+   * Change the state, the transition is applied at the beginning of the next frame
+   * If the state is changed several times during a frame, only the last one is kept
+   *
+   * @param state - The new state
+   */
+  setState(state: string): void
+  /**
+   * Get the state that will be applied at the beginning of the next frame
+   */
+  getNextState(): string | null
 }
 
+/**
+ * Params for a system
+ */
 export interface SystemParams {
+  /**
+   * The pool index, the pools run one after the other
+   * Prefer the labels and the before/after constraints to order the systems of a pool
+   */
   poolIndex?: number | null | undefined | void
+  /**
+   * Labels used by the other systems to be ordered relatively to this one, the identifier is always a label
+   */
+  labels?: string[] | null | undefined | void
+  /**
+   * Labels of the systems that should run after this one
+   */
+  before?: string[] | null | undefined | void
+  /**
+   * Labels of the systems that should run before this one
+   */
+  after?: string[] | null | undefined | void
+  /**
+   * Resources and components read by the system, it doesn't run in parallel with a system that writes them
+   */
+  reads?: string[] | null | undefined | void
+  /**
+   * Resources and components written by the system, it doesn't run in parallel with a system that accesses them
+   */
+  writes?: string[] | null | undefined | void
+  /**
+   * If true, the system is still running while pause
+   */
   ignorePause?: boolean | null | undefined | void
+  /**
+   * If true, the system will be executed in the main thread
+   */
   executeInMainThread?: boolean | null | undefined | void
+  /**
+   * What to do when an async system is still running at the next frame, default is skip
+   */
+  asyncPolicy?: AsyncSystemPolicy | null | undefined | void
+  /**
+   * If true, an error in the system is logged and the system is disabled instead of stopping the frame
+   * A panic is turned into an error too, except on wasm32 where a panic aborts the application
+   */
+  disableOnError?: boolean | null | undefined | void
+  /**
+   * If set, the system runs only while the application is in one of those states, see [`StateService`]
+   */
+  states?: string[] | null | undefined | void
 }
 
+/**
+ * System service
+ */
 export class SystemService {
 
+  /**
+   * Add a system to the collection
+   *
+   * @param system - A function that will compute the world
+   * @param poolIndex - A pool identifier, all the systems of the same pool will be processed together in parallel
+   */
   addSystem(identifier: string, callback: (() => void), params?: SystemParams | null | undefined | void)
+  /**
+   * Add an async system to the collection
+   * The returned promise is awaited across the frames, the block policy is rejected
+   *
+   * @param system - A function that will compute the world
+   * @param poolIndex - A pool identifier, all the systems of the same pool will be processed together in parallel
+   */
+  addAsyncSystem(identifier: string, callback: (() => Promise<unknown>), params?: SystemParams | null | undefined | void): void
+  /**
+   * Add a startup system
+   *
+   * @param system - A function that will compute the world
+   * @param poolIndex - A pool identifier, all the systems of the same pool will be processed together in parallel
+   */
   addStartupSystem(identifier: string, callback: (() => (() => void) | null | undefined | void), params?: StartupSystemParams | null | undefined | void)
+  /**
+   * Is systems paused
+   */
   isPaused(): boolean
+  /**
+   * Set if systems are paused, only systems that ignore pause will be executed
+   *
+   * @param paused - The paused value
+   */
   setPaused(paused: boolean): void
 }
 
+/**
+ * A component to tag an entity, an entity can have several tags
+ *
+ * The tags are stored in a sparse set, so adding or removing a tag doesn't move the entity to another archetype
+ */
+export class Tag {
+  0: string
+  /**
+   * Returns a new Tag
+   */
+  constructor(tag: string)
+}
+
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityEcsModule(): Module
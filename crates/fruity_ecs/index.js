@@ -19,3 +19,19 @@ export function Name(...args) {
 Name.fruityGetType = function() {
   return getBundle().Name_getType()
 }
+
+export function SceneInstance(...args) {
+  return getBundle().SceneInstance(...args)
+}
+
+SceneInstance.fruityGetType = function() {
+  return getBundle().SceneInstance_getType()
+}
+
+export function Tag(...args) {
+  return getBundle().Tag(...args)
+}
+
+Tag.fruityGetType = function() {
+  return getBundle().Tag_getType()
+}
@@ -0,0 +1,32 @@
+use super::Component;
+
+/// A set of components spawned together, it's implemented for the tuples of components
+///
+/// It's used by [`EntityService::spawn_batch_typed`]
+///
+/// [`EntityService::spawn_batch_typed`]: crate::entity::EntityService::spawn_batch_typed
+pub trait ComponentBundle: Send + 'static {
+    /// Box the components of the bundle
+    fn into_components(self) -> Vec<Box<dyn Component>>;
+}
+
+macro_rules! tuple_impl_bundle {
+    ($($tn:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<$($tn: Component + 'static),+> ComponentBundle for ($($tn,)+) {
+            fn into_components(self) -> Vec<Box<dyn Component>> {
+                let ($($tn,)+) = self;
+                vec![$(Box::new($tn) as Box<dyn Component>),+]
+            }
+        }
+    };
+}
+
+tuple_impl_bundle!(T1);
+tuple_impl_bundle!(T1, T2);
+tuple_impl_bundle!(T1, T2, T3);
+tuple_impl_bundle!(T1, T2, T3, T4);
+tuple_impl_bundle!(T1, T2, T3, T4, T5);
+tuple_impl_bundle!(T1, T2, T3, T4, T5, T6);
+tuple_impl_bundle!(T1, T2, T3, T4, T5, T6, T7);
+tuple_impl_bundle!(T1, T2, T3, T4, T5, T6, T7, T8);
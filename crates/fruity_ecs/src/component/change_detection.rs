@@ -0,0 +1,196 @@
+use super::ComponentTypeIndex;
+use crate::entity::EntityId;
+use fruity_game_engine::{script_value::ScriptObjectType, sync::RwLock};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The world change tick, incremented every time a system is run
+/// Starts at 1 so a system that never ran sees every components as added and changed
+static CHANGE_TICK: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static CURRENT_SYSTEM_TICKS: Cell<Option<SystemTicks>> = Cell::new(None);
+}
+
+/// Increment the world change tick, returns the new tick
+pub fn increment_change_tick() -> u64 {
+    CHANGE_TICK.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Returns the tick that is stamped on the components added or changed right now
+/// It's the tick of the system running on this thread if there is one
+pub fn get_change_tick() -> u64 {
+    CURRENT_SYSTEM_TICKS
+        .with(|ticks| ticks.get())
+        .map(|ticks| ticks.this_run)
+        .unwrap_or_else(|| CHANGE_TICK.load(Ordering::Relaxed))
+}
+
+/// The ticks of a system run, used by the change detection query filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTicks {
+    /// The tick of the previous run of the system, 0 if the system never ran
+    pub last_run: u64,
+
+    /// The tick of the current run of the system
+    pub this_run: u64,
+}
+
+impl SystemTicks {
+    /// Returns the ticks of the system running on this thread
+    /// Outside of a system, everything is considered as added and changed
+    pub fn current() -> Self {
+        CURRENT_SYSTEM_TICKS
+            .with(|ticks| ticks.get())
+            .unwrap_or_else(|| SystemTicks {
+                last_run: 0,
+                this_run: CHANGE_TICK.load(Ordering::Relaxed),
+            })
+    }
+
+    /// Run a callback with the given ticks as the ticks of the system running on this thread
+    pub fn scope<R>(self, callback: impl FnOnce() -> R) -> R {
+        let previous = CURRENT_SYSTEM_TICKS.with(|ticks| ticks.replace(Some(self)));
+        let result = callback();
+        CURRENT_SYSTEM_TICKS.with(|ticks| ticks.set(previous));
+
+        result
+    }
+
+    /// Returns true if a tick happened since the last run of the system
+    pub fn is_newer(&self, tick: u64) -> bool {
+        tick > self.last_run
+    }
+}
+
+/// The change ticks of the components of a given type in an entity
+#[derive(Debug)]
+pub struct ComponentTicks {
+    /// The tick when the components have been added to the entity
+    pub(crate) added: AtomicU64,
+
+    /// The tick when the components have been mutably accessed for the last time
+    pub(crate) changed: AtomicU64,
+}
+
+impl ComponentTicks {
+    /// Returns ticks for components that are added right now
+    pub fn new() -> Self {
+        let tick = get_change_tick();
+
+        Self {
+            added: AtomicU64::new(tick),
+            changed: AtomicU64::new(tick),
+        }
+    }
+
+    /// Returns the tick when the components have been added to the entity
+    pub fn get_added(&self) -> u64 {
+        self.added.load(Ordering::Relaxed)
+    }
+
+    /// Returns the tick when the components have been mutably accessed for the last time
+    pub fn get_changed(&self) -> u64 {
+        self.changed.load(Ordering::Relaxed)
+    }
+
+    /// Mark the components as changed
+    pub fn set_changed(&self) {
+        self.changed.store(get_change_tick(), Ordering::Relaxed);
+    }
+}
+
+impl Default for ComponentTicks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A mutable access to a component that marks it as changed when it is dereferenced mutably
+pub struct Mut<'a, T> {
+    pub(crate) value: &'a mut T,
+    pub(crate) ticks: &'a ComponentTicks,
+}
+
+impl<'a, T> Mut<'a, T> {
+    /// Returns the mutable reference without marking the component as changed
+    pub fn bypass_change_detection(&mut self) -> &mut T {
+        self.value
+    }
+
+    /// Returns true if the component has been added or mutably accessed since the last run of the system
+    pub fn is_changed(&self) -> bool {
+        SystemTicks::current().is_newer(self.ticks.get_changed())
+    }
+}
+
+impl<'a, T: Debug> Debug for Mut<'a, T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), std::fmt::Error> {
+        self.value.fmt(formatter)
+    }
+}
+
+impl<'a, T> Deref for Mut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<'a, T> DerefMut for Mut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ticks.set_changed();
+        self.value
+    }
+}
+
+/// The components removed from the entities that still exist, with the tick of their removal
+/// Used by the [`Removed`] query filter
+///
+/// [`Removed`]: crate::query::Removed
+#[derive(Default)]
+pub struct RemovedComponents(RwLock<HashMap<ComponentTypeIndex, HashMap<EntityId, u64>>>);
+
+impl RemovedComponents {
+    /// Keep track that a component has been removed from an entity
+    pub fn insert(&self, script_object_type: ScriptObjectType, entity_id: EntityId) {
+        self.0
+            .write()
+            .entry(ComponentTypeIndex::register(&script_object_type))
+            .or_default()
+            .insert(entity_id, get_change_tick());
+    }
+
+    /// Returns the tick when a component type has been removed from an entity for the last time
+    /// The component type is known by index, so the component type registry is not accessed
+    pub fn get(
+        &self,
+        component_type_index: ComponentTypeIndex,
+        entity_id: EntityId,
+    ) -> Option<u64> {
+        self.0
+            .read()
+            .get(&component_type_index)
+            .and_then(|entities| entities.get(&entity_id).copied())
+    }
+
+    /// Forget the entities that don't match a predicate, used to forget the deleted entities
+    pub fn retain(&self, predicate: impl Fn(EntityId) -> bool) {
+        self.0
+            .write()
+            .values_mut()
+            .for_each(|entities| entities.retain(|entity_id, _| predicate(*entity_id)));
+    }
+}
+
+impl Debug for RemovedComponents {
+    fn fmt(&self, _: &mut Formatter) -> Result<(), std::fmt::Error> {
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
-use super::{Component, ComponentStorage};
+use super::{Component, ComponentStorage, ComponentTicks};
+use crate::entity::SparseSet;
 use fruity_game_engine::{
     sync::{RwLockReadGuard, RwLockWriteGuard},
     FruityError,
@@ -9,6 +10,44 @@ use std::{
     ptr::NonNull,
 };
 
+/// The shared lock kept by a component guard, over an archetype storage or a sparse set
+#[allow(dead_code)]
+pub(crate) enum StorageReadGuard<'a> {
+    Archetype(RwLockReadGuard<'a, Box<dyn ComponentStorage>>),
+    SparseSet(RwLockReadGuard<'a, SparseSet>),
+}
+
+impl<'a> From<RwLockReadGuard<'a, Box<dyn ComponentStorage>>> for StorageReadGuard<'a> {
+    fn from(guard: RwLockReadGuard<'a, Box<dyn ComponentStorage>>) -> Self {
+        Self::Archetype(guard)
+    }
+}
+
+impl<'a> From<RwLockReadGuard<'a, SparseSet>> for StorageReadGuard<'a> {
+    fn from(guard: RwLockReadGuard<'a, SparseSet>) -> Self {
+        Self::SparseSet(guard)
+    }
+}
+
+/// The exclusive lock kept by a component guard, over an archetype storage or a sparse set
+#[allow(dead_code)]
+pub(crate) enum StorageWriteGuard<'a> {
+    Archetype(RwLockWriteGuard<'a, Box<dyn ComponentStorage>>),
+    SparseSet(RwLockWriteGuard<'a, SparseSet>),
+}
+
+impl<'a> From<RwLockWriteGuard<'a, Box<dyn ComponentStorage>>> for StorageWriteGuard<'a> {
+    fn from(guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>) -> Self {
+        Self::Archetype(guard)
+    }
+}
+
+impl<'a> From<RwLockWriteGuard<'a, SparseSet>> for StorageWriteGuard<'a> {
+    fn from(guard: RwLockWriteGuard<'a, SparseSet>) -> Self {
+        Self::SparseSet(guard)
+    }
+}
+
 /// RAII structure used to release the shared read access of a lock when dropped.
 ///
 /// This structure is created by the [`read`] methods on [`ComponentReference`].
@@ -16,7 +55,7 @@ use std::{
 /// [`read`]: ComponentReference::read
 ///
 pub struct AnyComponentReadGuard<'a> {
-    pub(crate) storage_guard: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
+    pub(crate) storage_guard: StorageReadGuard<'a>,
     pub(crate) component_ptr: NonNull<dyn Component>,
 }
 
@@ -61,8 +100,9 @@ impl<'a, T: Component> TryInto<ComponentReadGuard<'a, T>> for AnyComponentReadGu
 /// [`write`]: ComponentReference::write
 ///
 pub struct AnyComponentWriteGuard<'a> {
-    pub(crate) storage_guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    pub(crate) storage_guard: StorageWriteGuard<'a>,
     pub(crate) component_ptr: NonNull<dyn Component>,
+    pub(crate) ticks: &'a ComponentTicks,
 }
 
 impl<'a> Debug for AnyComponentWriteGuard<'a> {
@@ -81,6 +121,7 @@ impl<'a> Deref for AnyComponentWriteGuard<'a> {
 
 impl<'a> DerefMut for AnyComponentWriteGuard<'a> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ticks.set_changed();
         unsafe { self.component_ptr.as_mut() }
     }
 }
@@ -96,6 +137,7 @@ impl<'a, T: Component> TryInto<ComponentWriteGuard<'a, T>> for AnyComponentWrite
             Some(result) => Ok(ComponentWriteGuard {
                 storage_guard: self.storage_guard,
                 component_ptr: NonNull::<T>::from(result),
+                ticks: self.ticks,
             }),
             None => Err(FruityError::GenericFailure(format!(
                 "Couldn't convert {:?} to typed component",
@@ -112,7 +154,7 @@ impl<'a, T: Component> TryInto<ComponentWriteGuard<'a, T>> for AnyComponentWrite
 /// [`read`]: ComponentReference::read
 ///
 pub struct ComponentReadGuard<'a, T: Component> {
-    pub(crate) storage_guard: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
+    pub(crate) storage_guard: StorageReadGuard<'a>,
     pub(crate) component_ptr: NonNull<T>,
 }
 
@@ -157,8 +199,9 @@ impl<'a, T: Component> TryInto<AnyComponentReadGuard<'a>> for ComponentReadGuard
 /// [`write`]: ComponentReference::write
 ///
 pub struct ComponentWriteGuard<'a, T: Component> {
-    pub(crate) storage_guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    pub(crate) storage_guard: StorageWriteGuard<'a>,
     pub(crate) component_ptr: NonNull<T>,
+    pub(crate) ticks: &'a ComponentTicks,
 }
 
 impl<'a, T: Component> Debug for ComponentWriteGuard<'a, T> {
@@ -177,6 +220,7 @@ impl<'a, T: Component> Deref for ComponentWriteGuard<'a, T> {
 
 impl<'a, T: Component> DerefMut for ComponentWriteGuard<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        self.ticks.set_changed();
         unsafe { self.component_ptr.as_mut() }
     }
 }
@@ -192,6 +236,7 @@ impl<'a, T: Component> TryInto<AnyComponentWriteGuard<'a>> for ComponentWriteGua
             Some(result) => Ok(AnyComponentWriteGuard {
                 storage_guard: self.storage_guard,
                 component_ptr: NonNull::<T>::from(result),
+                ticks: self.ticks,
             }),
             None => Err(FruityError::GenericFailure(format!(
                 "Couldn't convert {:?} to typed component",
@@ -238,8 +283,10 @@ impl<'a, T: Component + 'static> Iterator for ComponentReadGuardIterator<'a, T>
 }
 
 /// A mut iterator over all components of a specific type.
+/// The yielded components are marked as changed
 pub struct ComponentWriteGuardIterator<'a, T: Component + 'static> {
     _storage_guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    ticks: &'a ComponentTicks,
     current: NonNull<T>,
     end: NonNull<T>,
 }
@@ -247,11 +294,13 @@ pub struct ComponentWriteGuardIterator<'a, T: Component + 'static> {
 impl<'a, T: Component + 'static> ComponentWriteGuardIterator<'a, T> {
     pub(crate) fn new(
         storage_guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+        ticks: &'a ComponentTicks,
         begin: NonNull<T>,
         count: usize,
     ) -> Self {
         Self {
             _storage_guard: storage_guard,
+            ticks,
             current: begin,
             end: unsafe { NonNull::new_unchecked(begin.as_ptr().add(count)) },
         }
@@ -263,6 +312,7 @@ impl<'a, T: Component + 'static> Iterator for ComponentWriteGuardIterator<'a, T>
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current < self.end {
+            self.ticks.set_changed();
             let result = unsafe { self.current.as_mut() };
             self.current = unsafe { NonNull::new_unchecked(self.current.as_ptr().add(1)) };
 
@@ -317,8 +367,10 @@ impl<'a> Iterator for AnyComponentReadGuardIterator<'a> {
 }
 
 /// A mut iterator over components of an any type.
+/// The yielded components are marked as changed
 pub struct AnyComponentWriteGuardIterator<'a> {
     _storage_guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    ticks: &'a ComponentTicks,
     component_type_size: usize,
     current: NonNull<dyn Component>,
     end: NonNull<dyn Component>,
@@ -327,12 +379,14 @@ pub struct AnyComponentWriteGuardIterator<'a> {
 impl<'a> AnyComponentWriteGuardIterator<'a> {
     pub(crate) fn new(
         storage_guard: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+        ticks: &'a ComponentTicks,
         component_type_size: usize,
         begin: NonNull<dyn Component>,
         count: usize,
     ) -> Self {
         Self {
             _storage_guard: storage_guard,
+            ticks,
             component_type_size,
             current: begin,
             end: unsafe {
@@ -347,6 +401,7 @@ impl<'a> Iterator for AnyComponentWriteGuardIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current < self.end {
+            self.ticks.set_changed();
             let result = unsafe { self.current.as_mut() };
             self.current = unsafe {
                 NonNull::new_unchecked(self.current.as_ptr().byte_add(self.component_type_size))
@@ -1,8 +1,8 @@
 use super::{
-    AnyComponentReadGuard, AnyComponentWriteGuard, Component, ComponentReadGuard,
-    ComponentWriteGuard,
+    AnyComponentReadGuard, AnyComponentWriteGuard, Component, ComponentReadGuard, ComponentTicks,
+    ComponentWriteGuard, StorageReadGuard, StorageWriteGuard,
 };
-use crate::entity::EntityReference;
+use crate::entity::{Archetype, EntityReference};
 use fruity_game_engine::{
     any::FruityAny,
     introspect::{IntrospectFields, IntrospectMethods},
@@ -31,142 +31,60 @@ impl<T: Component> ComponentReference<T> {
 
     /// Read the component with a given type
     pub fn read(&self) -> FruityResult<ComponentReadGuard<'_, T>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&ScriptObjectType::of::<T>())
-                    .unwrap();
-                let storage_reader = storage.read();
-
-                let component_ptr = NonNull::from(storage_reader.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(ComponentReadGuard {
-                    storage_guard: storage_reader,
-                    component_ptr: component_ptr.cast(),
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr) = read_component(
+            &self.entity_reference,
+            &ScriptObjectType::of::<T>(),
+            self.component_index,
+        )?;
+
+        Ok(ComponentReadGuard {
+            storage_guard,
+            component_ptr: component_ptr.cast(),
+        })
     }
 
     /// Write the component with a given type
     pub fn write(&self) -> FruityResult<ComponentWriteGuard<'_, T>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&ScriptObjectType::of::<T>())
-                    .unwrap();
-                let storage_writer = storage.write();
-
-                let component_ptr = NonNull::from(storage_writer.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(ComponentWriteGuard {
-                    storage_guard: storage_writer,
-                    component_ptr: component_ptr.cast(),
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr, ticks) = write_component(
+            &self.entity_reference,
+            &ScriptObjectType::of::<T>(),
+            self.component_index,
+        )?;
+
+        Ok(ComponentWriteGuard {
+            storage_guard,
+            component_ptr: component_ptr.cast(),
+            ticks,
+        })
     }
 
     /// Read the component with an any type
     pub fn read_any(&self) -> FruityResult<AnyComponentReadGuard<'_>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&ScriptObjectType::of::<T>())
-                    .unwrap();
-                let storage_reader = storage.read();
-
-                let component_ptr = NonNull::from(storage_reader.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(AnyComponentReadGuard {
-                    storage_guard: storage_reader,
-                    component_ptr,
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr) = read_component(
+            &self.entity_reference,
+            &ScriptObjectType::of::<T>(),
+            self.component_index,
+        )?;
+
+        Ok(AnyComponentReadGuard {
+            storage_guard,
+            component_ptr,
+        })
     }
 
     /// Write the component with an any type
     pub fn write_any(&self) -> FruityResult<AnyComponentWriteGuard<'_>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&ScriptObjectType::of::<T>())
-                    .unwrap();
-                let storage_writer = storage.write();
-
-                let component_ptr = NonNull::from(storage_writer.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(AnyComponentWriteGuard {
-                    storage_guard: storage_writer,
-                    component_ptr,
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr, ticks) = write_component(
+            &self.entity_reference,
+            &ScriptObjectType::of::<T>(),
+            self.component_index,
+        )?;
+
+        Ok(AnyComponentWriteGuard {
+            storage_guard,
+            component_ptr,
+            ticks,
+        })
     }
 }
 
@@ -192,147 +110,141 @@ impl AnyComponentReference {
         }
     }
 
+    /// Get the entity of the component
+    pub fn get_entity_reference(&self) -> EntityReference {
+        self.entity_reference.clone()
+    }
+
+    /// Get the type of the component
+    pub fn get_script_object_type(&self) -> ScriptObjectType {
+        self.script_object_type.clone()
+    }
+
+    /// Get the index of the component among the components of the same type of its entity
+    pub fn get_component_index(&self) -> usize {
+        self.component_index
+    }
+
     /// Read the component with a given type
     pub fn read<T: Component>(&self) -> FruityResult<ComponentReadGuard<'_, T>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&self.script_object_type)
-                    .unwrap();
-                let storage_reader = storage.read();
-
-                let component_ptr = NonNull::from(storage_reader.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(ComponentReadGuard {
-                    storage_guard: storage_reader,
-                    component_ptr: component_ptr.cast(),
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr) = read_component(
+            &self.entity_reference,
+            &self.script_object_type,
+            self.component_index,
+        )?;
+
+        Ok(ComponentReadGuard {
+            storage_guard,
+            component_ptr: component_ptr.cast(),
+        })
     }
 
     /// Write the component with a given type
     pub fn write<T: Component>(&self) -> FruityResult<ComponentWriteGuard<'_, T>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&self.script_object_type)
-                    .unwrap();
-                let storage_writer = storage.write();
-
-                let component_ptr = NonNull::from(storage_writer.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(ComponentWriteGuard {
-                    storage_guard: storage_writer,
-                    component_ptr: component_ptr.cast(),
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr, ticks) = write_component(
+            &self.entity_reference,
+            &self.script_object_type,
+            self.component_index,
+        )?;
+
+        Ok(ComponentWriteGuard {
+            storage_guard,
+            component_ptr: component_ptr.cast(),
+            ticks,
+        })
     }
 
     /// Read the component with an any type
     pub fn read_any(&self) -> FruityResult<AnyComponentReadGuard<'_>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
-
-                let archetype = entity_storage_reader
-                    .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&self.script_object_type)
-                    .unwrap();
-                let storage_reader = storage.read();
-
-                let component_ptr = NonNull::from(storage_reader.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(AnyComponentReadGuard {
-                    storage_guard: storage_reader,
-                    component_ptr: component_ptr,
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+        let (storage_guard, component_ptr) = read_component(
+            &self.entity_reference,
+            &self.script_object_type,
+            self.component_index,
+        )?;
+
+        Ok(AnyComponentReadGuard {
+            storage_guard,
+            component_ptr,
+        })
     }
 
     /// Write the component with an any type
     pub fn write_any(&self) -> FruityResult<AnyComponentWriteGuard<'_>> {
-        unsafe {
-            let entity_reference_inner = self.entity_reference.inner.read();
-            if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
-                let entity_storage_reader = entity_reference_inner.entity_storage.read();
+        let (storage_guard, component_ptr, ticks) = write_component(
+            &self.entity_reference,
+            &self.script_object_type,
+            self.component_index,
+        )?;
+
+        Ok(AnyComponentWriteGuard {
+            storage_guard,
+            component_ptr,
+            ticks,
+        })
+    }
+}
+
+/// Lock the storage of a component of a referenced entity, the storage can be a sparse set
+fn read_component<'a>(
+    entity_reference: &'a EntityReference,
+    script_object_type: &ScriptObjectType,
+    component_index: usize,
+) -> FruityResult<(StorageReadGuard<'a>, NonNull<dyn Component>)> {
+    let (archetype, entity_index) = get_archetype(entity_reference)?;
+
+    archetype
+        .read_component(script_object_type, entity_index, component_index)
+        .ok_or_else(|| missing_component_error(script_object_type))
+}
+
+/// Lock the storage of a component of a referenced entity with an exclusive access, the storage can
+/// be a sparse set
+fn write_component<'a>(
+    entity_reference: &'a EntityReference,
+    script_object_type: &ScriptObjectType,
+    component_index: usize,
+) -> FruityResult<(
+    StorageWriteGuard<'a>,
+    NonNull<dyn Component>,
+    &'a ComponentTicks,
+)> {
+    let (archetype, entity_index) = get_archetype(entity_reference)?;
+
+    archetype
+        .write_component(script_object_type, entity_index, component_index)
+        .ok_or_else(|| missing_component_error(script_object_type))
+}
+
+fn get_archetype(entity_reference: &EntityReference) -> FruityResult<(&Archetype, usize)> {
+    let entity_reference_inner = entity_reference.inner.read();
+    if let Some(entity_reference_inner) = entity_reference_inner.as_ref() {
+        let entity_storage_reader = entity_reference_inner.entity_storage.read();
 
-                let archetype = entity_storage_reader
+        // TODO: Find a way to remove it
+        let archetype = unsafe {
+            NonNull::from(
+                entity_storage_reader
                     .archetypes
-                    .get_unchecked(entity_reference_inner.location.archetype_index);
-                let archetype = NonNull::from(archetype).as_ref();
-
-                let storage = archetype
-                    .component_storages
-                    .get(&self.script_object_type)
-                    .unwrap();
-                let storage_writer = storage.write();
-
-                let component_ptr = NonNull::from(storage_writer.get_unchecked(
-                    entity_reference_inner.location.entity_index,
-                    self.component_index,
-                ));
-
-                Ok(AnyComponentWriteGuard {
-                    storage_guard: storage_writer,
-                    component_ptr: component_ptr,
-                })
-            } else {
-                Err(FruityError::GenericFailure(
-                    "You try to access a deleted entity".to_string(),
-                ))
-            }
-        }
+                    .get_unchecked(entity_reference_inner.location.archetype_index),
+            )
+            .as_ref()
+        };
+
+        Ok((archetype, entity_reference_inner.location.entity_index))
+    } else {
+        Err(FruityError::GenericFailure(
+            "You try to access a deleted entity".to_string(),
+        ))
     }
 }
 
+fn missing_component_error(script_object_type: &ScriptObjectType) -> FruityError {
+    FruityError::GenericFailure(format!(
+        "The entity has no component of type {:?} at this index",
+        script_object_type
+    ))
+}
+
 impl IntrospectFields for AnyComponentReference {
     fn is_static(&self) -> FruityResult<bool> {
         self.read_any()?.is_static()
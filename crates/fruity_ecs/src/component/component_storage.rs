@@ -37,6 +37,9 @@ pub trait ComponentStorage: Debug + FruityAny + Send + Sync {
     /// Removes the component at the given index in a given entity
     fn remove_slice(&mut self, entity_index: usize) -> Vec<Box<dyn Component>>;
 
+    /// Removes the components of a given entity, the last entity takes its place
+    fn swap_remove_slice(&mut self, entity_index: usize) -> Vec<Box<dyn Component>>;
+
     /// Get length of a slice
     fn slice_len(&self, entity_index: usize) -> usize;
 
@@ -55,6 +58,9 @@ pub trait ComponentStorage: Debug + FruityAny + Send + Sync {
     /// Reserves capacity for at least `additional` more components to be inserted in the storage
     fn reserve(&mut self, additional: usize);
 
+    /// Reserves capacity for at least `additional` more entities with `slice_len` components each
+    fn reserve_slices(&mut self, additional: usize, slice_len: usize);
+
     /// Returns a raw pointer to the data
     fn as_ptr(&self) -> *mut dyn Component;
 
@@ -66,12 +72,20 @@ pub trait ComponentStorage: Debug + FruityAny + Send + Sync {
 
     /// Returns the size of the component type
     fn get_component_type_size(&self) -> usize;
+
+    /// Returns true if the components are stored in a sparse set outside of the archetypes, see [`SparseSet`]
+    ///
+    /// [`SparseSet`]: crate::entity::SparseSet
+    fn is_sparse_set(&self) -> bool {
+        false
+    }
 }
 
 /// A component storage that uses a sliced vec
 #[derive(FruityAny, Debug, Clone, Default)]
 pub struct VecComponentStorage<T: Component> {
     pub(crate) data: SlicedVec<T>,
+    sparse_set: bool,
 }
 
 impl<T: Component> VecComponentStorage<T> {
@@ -79,6 +93,18 @@ impl<T: Component> VecComponentStorage<T> {
     pub fn new() -> Self {
         Self {
             data: SlicedVec::new(),
+            sparse_set: false,
+        }
+    }
+
+    /// Create a new component storage for the sparse sets, the entities don't change of archetype
+    /// when a component of this type is added or removed
+    ///
+    /// It suits the components that are often toggled, like a marker component
+    pub fn new_sparse_set() -> Self {
+        Self {
+            data: SlicedVec::new(),
+            sparse_set: true,
         }
     }
 }
@@ -161,6 +187,14 @@ impl<T: Component> ComponentStorage for VecComponentStorage<T> {
             .collect()
     }
 
+    fn swap_remove_slice(&mut self, entity_index: usize) -> Vec<Box<dyn Component>> {
+        self.data
+            .swap_remove_slice(entity_index)
+            .into_iter()
+            .map(|component| Box::new(component) as Box<dyn Component>)
+            .collect()
+    }
+
     fn slice_len(&self, entity_index: usize) -> usize {
         self.data.slice_len(entity_index)
     }
@@ -196,6 +230,10 @@ impl<T: Component> ComponentStorage for VecComponentStorage<T> {
         self.data.reserve(additional);
     }
 
+    fn reserve_slices(&mut self, additional: usize, slice_len: usize) {
+        self.data.reserve_slices(additional, slice_len);
+    }
+
     fn as_ptr(&self) -> *mut dyn Component {
         self.data.as_ptr() as *mut dyn Component
     }
@@ -211,4 +249,8 @@ impl<T: Component> ComponentStorage for VecComponentStorage<T> {
     fn get_component_type_size(&self) -> usize {
         std::mem::size_of::<T>()
     }
+
+    fn is_sparse_set(&self) -> bool {
+        self.sparse_set
+    }
 }
@@ -0,0 +1,262 @@
+use super::Component;
+use fruity_game_engine::{lazy_static, script_value::ScriptObjectType, sync::RwLock};
+use std::{collections::HashMap, ops::Index};
+
+/// A dense identifier of a component type
+///
+/// Every Rust and script component type gets an index the first time it is stored, so the archetypes can keep
+/// their storages in a vector and match the queries with a [`ComponentTypeBitSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ComponentTypeIndex(pub u32);
+
+#[derive(Default)]
+struct ComponentTypeRegistry {
+    indexes: HashMap<ScriptObjectType, ComponentTypeIndex>,
+    script_object_types: Vec<ScriptObjectType>,
+    sparse_sets: Vec<bool>,
+}
+
+lazy_static! {
+    static ref COMPONENT_TYPE_REGISTRY: RwLock<ComponentTypeRegistry> =
+        RwLock::new(ComponentTypeRegistry::default());
+}
+
+impl ComponentTypeIndex {
+    /// Get the index of a rust component type, register it if needed
+    /// The index is cached by the components that derive [`Component`]
+    pub fn of<T: Component>() -> Self {
+        T::get_component_type_index()
+    }
+
+    /// Get the index of a component type, register it if needed
+    pub fn register(script_object_type: &ScriptObjectType) -> Self {
+        if let Some(index) = Self::find(script_object_type) {
+            return index;
+        }
+
+        let mut registry = COMPONENT_TYPE_REGISTRY.write();
+
+        // The type may have been registered between the two locks
+        if let Some(index) = registry.indexes.get(script_object_type) {
+            return *index;
+        }
+
+        let index = ComponentTypeIndex(registry.script_object_types.len() as u32);
+        registry
+            .script_object_types
+            .push(script_object_type.clone());
+        registry.sparse_sets.push(false);
+        registry.indexes.insert(script_object_type.clone(), index);
+
+        index
+    }
+
+    /// Get the index of a component type, returns None if the type has never been stored
+    pub fn find(script_object_type: &ScriptObjectType) -> Option<Self> {
+        COMPONENT_TYPE_REGISTRY
+            .read()
+            .indexes
+            .get(script_object_type)
+            .copied()
+    }
+
+    /// Get the component type associated with this index
+    pub fn get_script_object_type(self) -> ScriptObjectType {
+        COMPONENT_TYPE_REGISTRY.read().script_object_types[self.0 as usize].clone()
+    }
+
+    /// Mark the component type as stored in a sparse set
+    ///
+    /// The script queries only know the component types by their names, they use it to look for the
+    /// components into the sparse sets
+    pub fn set_sparse_set(self) {
+        COMPONENT_TYPE_REGISTRY.write().sparse_sets[self.0 as usize] = true;
+    }
+
+    /// Returns true if the component type is stored in a sparse set, see [`ComponentTypeIndex::set_sparse_set`]
+    pub fn is_sparse_set(self) -> bool {
+        COMPONENT_TYPE_REGISTRY.read().sparse_sets[self.0 as usize]
+    }
+}
+
+/// A set of component types, one bit per [`ComponentTypeIndex`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ComponentTypeBitSet(Vec<u64>);
+
+impl ComponentTypeBitSet {
+    /// Add a component type to the set
+    pub fn insert(&mut self, index: ComponentTypeIndex) {
+        let (block, bit) = Self::position(index);
+        if self.0.len() <= block {
+            self.0.resize(block + 1, 0);
+        }
+
+        self.0[block] |= bit;
+    }
+
+    /// Returns true if the component type is in the set
+    pub fn contains(&self, index: ComponentTypeIndex) -> bool {
+        let (block, bit) = Self::position(index);
+        self.0
+            .get(block)
+            .map(|block| block & bit != 0)
+            .unwrap_or(false)
+    }
+
+    /// Returns true if every component type of the other set is in this one
+    pub fn is_superset(&self, other: &ComponentTypeBitSet) -> bool {
+        other.0.iter().enumerate().all(|(index, other_block)| {
+            let block = self.0.get(index).copied().unwrap_or(0);
+            block & other_block == *other_block
+        })
+    }
+
+    /// Returns true if at least one component type is in both sets
+    pub fn intersects(&self, other: &ComponentTypeBitSet) -> bool {
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .any(|(block, other_block)| block & other_block != 0)
+    }
+
+    fn position(index: ComponentTypeIndex) -> (usize, u64) {
+        ((index.0 / 64) as usize, 1 << (index.0 % 64))
+    }
+}
+
+/// A map keyed by component type, the values are stored in a vector indexed by [`ComponentTypeIndex`]
+#[derive(Debug)]
+pub struct ComponentTypeMap<V> {
+    slots: Vec<Option<(ScriptObjectType, V)>>,
+}
+
+impl<V> Default for ComponentTypeMap<V> {
+    fn default() -> Self {
+        Self { slots: Vec::new() }
+    }
+}
+
+impl<V> ComponentTypeMap<V> {
+    /// Returns a ComponentTypeMap
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value for a component type, returns the previous one
+    pub fn insert(&mut self, script_object_type: ScriptObjectType, value: V) -> Option<V> {
+        let index = ComponentTypeIndex::register(&script_object_type).0 as usize;
+        if self.slots.len() <= index {
+            self.slots.resize_with(index + 1, || None);
+        }
+
+        self.slots[index]
+            .replace((script_object_type, value))
+            .map(|(_, value)| value)
+    }
+
+    /// Get the value of a component type
+    ///
+    /// Each call locks the component type registry and hashes the component type, the queries and
+    /// the other hot paths resolve the [`ComponentTypeIndex`] once and use [`ComponentTypeMap::get_by_index`]
+    pub fn get(&self, script_object_type: &ScriptObjectType) -> Option<&V> {
+        self.get_by_index(ComponentTypeIndex::find(script_object_type)?)
+    }
+
+    /// Get the value of a component type
+    pub fn get_mut(&mut self, script_object_type: &ScriptObjectType) -> Option<&mut V> {
+        let index = ComponentTypeIndex::find(script_object_type)?;
+        self.slots
+            .get_mut(index.0 as usize)
+            .and_then(|slot| slot.as_mut())
+            .map(|(_, value)| value)
+    }
+
+    /// Get the value of a component type from its index, doesn't access the registry
+    pub fn get_by_index(&self, index: ComponentTypeIndex) -> Option<&V> {
+        self.slots
+            .get(index.0 as usize)
+            .and_then(|slot| slot.as_ref())
+            .map(|(_, value)| value)
+    }
+
+    /// Iterate over the component types and their values
+    pub fn iter(&self) -> impl Iterator<Item = (&ScriptObjectType, &V)> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.as_ref())
+            .map(|(script_object_type, value)| (script_object_type, value))
+    }
+
+    /// Iterate over the component types and their values
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ScriptObjectType, &mut V)> {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.as_mut())
+            .map(|(script_object_type, value)| (&*script_object_type, value))
+    }
+
+    /// Iterate over the values
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Iterate over the values
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    /// Remove all the values
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+}
+
+impl<V> Index<&ScriptObjectType> for ComponentTypeMap<V> {
+    type Output = V;
+
+    fn index(&self, script_object_type: &ScriptObjectType) -> &Self::Output {
+        self.get(script_object_type)
+            .expect("no value stored for this component type")
+    }
+}
+
+impl<V> Index<ComponentTypeIndex> for ComponentTypeMap<V> {
+    type Output = V;
+
+    fn index(&self, index: ComponentTypeIndex) -> &Self::Output {
+        self.get_by_index(index)
+            .expect("no value stored for this component type")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::component::{Name, Tag};
+
+    #[test]
+    fn test_cached_index_matches_the_registry() {
+        let index = ComponentTypeIndex::of::<Name>();
+
+        assert_eq!(ComponentTypeIndex::of::<Name>(), index);
+        assert_eq!(
+            ComponentTypeIndex::find(&ScriptObjectType::of::<Name>()),
+            Some(index)
+        );
+        assert_eq!(
+            index.get_script_object_type(),
+            ScriptObjectType::of::<Name>()
+        );
+        assert_ne!(ComponentTypeIndex::of::<Tag>(), index);
+    }
+
+    #[test]
+    fn test_component_type_map_by_index() {
+        let mut map = ComponentTypeMap::new();
+        map.insert(ScriptObjectType::of::<Name>(), 1);
+
+        assert_eq!(map.get_by_index(ComponentTypeIndex::of::<Name>()), Some(&1));
+        assert_eq!(map.get(&ScriptObjectType::of::<Name>()), Some(&1));
+        assert_eq!(map.get_by_index(ComponentTypeIndex::of::<Tag>()), None);
+    }
+}
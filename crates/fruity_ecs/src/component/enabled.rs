@@ -1,15 +1,16 @@
 use fruity_ecs_macro::Component;
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 
 /// A component to enable or disable an entity
 #[derive(Debug, Clone, Default, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Enabled(pub bool);
 
-#[export_impl]
+#[fruity_export]
 impl Enabled {
     /// Returns a new RectCollider
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(enabled: bool) -> Enabled {
         Self(enabled)
     }
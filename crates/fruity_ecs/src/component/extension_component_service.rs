@@ -1,7 +1,7 @@
 use super::Component;
 use fruity_game_engine::{
-    any::FruityAny, export_impl, export_struct, resource::ResourceContainer,
-    script_value::ScriptObjectType, FruityResult,
+    any::FruityAny, fruity_export, resource::ResourceContainer, script_value::ScriptObjectType,
+    FruityResult,
 };
 use std::{
     collections::HashMap,
@@ -15,13 +15,13 @@ use std::{
 ///
 /// Warning: The same extension type cannot be shared across multiple based component types
 #[derive(FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct ExtensionComponentService {
     extension_constructors:
         HashMap<ScriptObjectType, Vec<Box<dyn Fn() -> Box<dyn Component> + Send + Sync>>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ExtensionComponentService {
     /// Returns an ExtensionComponentService
     pub fn new(_resource_container: ResourceContainer) -> Self {
@@ -21,6 +21,12 @@ pub use name::*;
 mod enabled;
 pub use enabled::*;
 
+mod tag;
+pub use tag::*;
+
+mod bundle;
+pub use bundle::*;
+
 mod extension_component_service;
 pub use extension_component_service::*;
 
@@ -33,6 +39,12 @@ pub use component_guard::*;
 mod component_reference;
 pub use component_reference::*;
 
+mod change_detection;
+pub use change_detection::*;
+
+mod component_type_registry;
+pub use component_type_registry::*;
+
 pub use fruity_ecs_macro::Component;
 
 /// A component is a piece of data that can be attached to an entity.
@@ -67,6 +79,34 @@ pub trait Component:
 
     /// Get a collection to store this component in the archetype
     fn get_storage(&self) -> Box<dyn ComponentStorage>;
+
+    /// Returns true if the components of this type are stored in a sparse set, it should match the
+    /// storage returned by [`Component::get_storage`]
+    ///
+    /// The queries use it to know that the component is not stored into the archetypes
+    fn is_sparse_set() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Returns the index of the component type, see [`ComponentTypeIndex::of`]
+    ///
+    /// The derive macro caches it, so the queries don't access the component type registry
+    fn get_component_type_index() -> ComponentTypeIndex
+    where
+        Self: Sized,
+    {
+        ComponentTypeIndex::register(&ScriptObjectType::of::<Self>())
+    }
+
+    /// Returns true if this component is stored in a sparse set, see [`Component::is_sparse_set`]
+    ///
+    /// Unlike [`Component::get_storage`], it doesn't allocate a storage
+    fn is_in_sparse_set(&self) -> bool {
+        false
+    }
 }
 
 impl Clone for Box<dyn Component> {
@@ -77,8 +117,13 @@ impl Clone for Box<dyn Component> {
 
 impl Serialize for Box<dyn Component> {
     fn serialize(&self, resource_container: &ResourceContainer) -> FruityResult<Settings> {
+        let class_name = self.get_class_name()?;
+        let serialization_service = resource_container.require::<SerializationService>();
+        let version = serialization_service.read().get_version(class_name.clone());
+
         Ok(Settings::Object(hashmap!(
-            "class_name".to_string() => Settings::String(self.get_class_name()?),
+            "class_name".to_string() => Settings::String(class_name),
+            "version".to_string() => Settings::F64(version as f64),
             "fields".to_string() =>  self.deref().serialize(resource_container)?,
         )))
     }
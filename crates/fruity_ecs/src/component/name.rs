@@ -1,15 +1,16 @@
 use crate::component::Component;
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 
 /// A component to name an entity
 #[derive(Debug, Clone, Default, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Name(pub String);
 
-#[export_impl]
+#[fruity_export]
 impl Name {
     /// Returns a new RectCollider
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(string: String) -> Name {
         Self(string)
     }
@@ -0,0 +1,66 @@
+use super::ComponentTypeIndex;
+use crate::entity::Archetype;
+use fruity_ecs_macro::Component;
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
+
+/// A component to tag an entity, an entity can have several tags
+///
+/// The tags are stored in a sparse set, so adding or removing a tag doesn't move the entity to another archetype
+#[derive(Debug, Clone, Default, Component, FruityAny)]
+#[component(sparse_set)]
+#[fruity_export]
+pub struct Tag(pub String);
+
+#[fruity_export]
+impl Tag {
+    /// Returns a new Tag
+    #[fruity_export(constructor)]
+    pub fn new(tag: String) -> Tag {
+        Self(tag)
+    }
+
+    /// Returns the tag of a typed tag
+    pub fn of<T: TypedTag>() -> Tag {
+        Self(T::NAME.to_string())
+    }
+}
+
+/// A tag known at compile time, it is stored as a [`Tag`] with the given name
+///
+/// It's used to filter a [`Query`] with [`WithTag`]
+///
+/// [`Query`]: crate::query::Query
+/// [`WithTag`]: crate::query::WithTag
+pub trait TypedTag: 'static {
+    /// The name of the tag
+    const NAME: &'static str;
+}
+
+/// Returns true if an entity of an archetype has a given tag
+///
+/// Each call locks the tag sparse set with a shared access and hashes the entity id to find its tags,
+/// prefer [`EntityService::find_all_by_tag`] that uses the tag index to look up a few entities
+///
+/// [`EntityService::find_all_by_tag`]: crate::entity::EntityService::find_all_by_tag
+pub(crate) fn has_tag(archetype: &Archetype, entity_index: usize, tag: &str) -> bool {
+    archetype
+        .sparse_sets
+        .get_by_index(ComponentTypeIndex::of::<Tag>())
+        .map(|sparse_set| {
+            sparse_set
+                .read()
+                .iter_entity(archetype.entity_ids[entity_index])
+                .map(|mut components| {
+                    components.any(|component| {
+                        component
+                            .as_any_ref()
+                            .downcast_ref::<Tag>()
+                            .map(|component| component.0 == tag)
+                            .unwrap_or(false)
+                    })
+                })
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
@@ -1,17 +1,19 @@
-use super::EntityId;
-use crate::component::{Component, ComponentStorage, ComponentTypeId, ExtensionComponentService};
+use super::{EntityId, SparseSet, SparseSets};
+use crate::component::{
+    Component, ComponentStorage, ComponentTicks, ComponentTypeBitSet, ComponentTypeId,
+    ComponentTypeIndex, ComponentTypeMap, ExtensionComponentService, StorageReadGuard,
+    StorageWriteGuard,
+};
 use fruity_game_engine::{
     script_value::ScriptObjectType,
-    sync::{RwLock, RwLockReadGuard},
+    sync::{Arc, RwLock, RwLockReadGuard},
     FruityResult,
 };
-use std::{
-    collections::{BTreeMap, HashMap},
-    ops::DerefMut,
-};
+use std::{collections::HashMap, ops::DerefMut, ptr::NonNull};
 
+/// The component types of an archetype, the bitset is used to match the queries
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct ArchetypeComponentTypes(Vec<ComponentTypeId>);
+pub struct ArchetypeComponentTypes(Vec<ComponentTypeId>, ComponentTypeBitSet);
 
 impl ArchetypeComponentTypes {
     pub fn from_boxed_components(components: &[Box<dyn Component>]) -> FruityResult<Self> {
@@ -22,17 +24,31 @@ impl ArchetypeComponentTypes {
 
         component_types.sort();
 
-        Ok(Self(component_types))
+        let mut bitset = ComponentTypeBitSet::default();
+        component_types.iter().for_each(|component_type_id| {
+            bitset.insert(ComponentTypeIndex::register(
+                &component_type_id.clone().get_script_object_type(),
+            ))
+        });
+
+        Ok(Self(component_types, bitset))
     }
 
     /// Returns true if the given component type id is in the archetype
     pub fn contains(&self, script_object_type: &ScriptObjectType) -> bool {
-        self.0
-            .iter()
-            .any(|component_type_id| match component_type_id {
-                ComponentTypeId::Normal(ty) => ty == script_object_type,
-                ComponentTypeId::OrderedRust(ty, _) => ty == script_object_type,
-            })
+        ComponentTypeIndex::find(script_object_type)
+            .map(|index| self.1.contains(index))
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the given component type index is in the archetype
+    pub fn contains_index(&self, index: ComponentTypeIndex) -> bool {
+        self.1.contains(index)
+    }
+
+    /// Returns the set of the component types of the archetype
+    pub fn get_bitset(&self) -> &ComponentTypeBitSet {
+        &self.1
     }
 
     /// Returns the given component type id associated with a script object id
@@ -74,7 +90,14 @@ pub struct Archetype {
     pub(crate) entity_ids: Vec<EntityId>,
 
     /// The component storages
-    pub(crate) component_storages: BTreeMap<ScriptObjectType, RwLock<Box<dyn ComponentStorage>>>,
+    pub(crate) component_storages: ComponentTypeMap<RwLock<Box<dyn ComponentStorage>>>,
+
+    /// The change ticks of the components, one per entity for each component type
+    /// They are kept outside of the storages so a query can check them while it holds a storage lock
+    pub(crate) component_ticks: ComponentTypeMap<Vec<ComponentTicks>>,
+
+    /// The sparse sets of the entity storage, they hold the components that are not stored in the archetypes
+    pub(crate) sparse_sets: Arc<SparseSets>,
 }
 
 impl Archetype {
@@ -85,6 +108,7 @@ impl Archetype {
         mut components: Vec<Box<dyn Component>>,
         extension_component_service: Option<&ExtensionComponentService>,
         default_components: Option<Vec<Box<dyn Component>>>,
+        sparse_sets: Arc<SparseSets>,
     ) -> FruityResult<Self> {
         let component_types = ArchetypeComponentTypes::from_boxed_components(&components)?;
 
@@ -109,13 +133,15 @@ impl Archetype {
 
         // Create component storages with the first entity component
         let grouped_components = Self::group_components_by_type(components);
-        let mut component_storages = BTreeMap::new();
+        let mut component_storages = ComponentTypeMap::new();
+        let mut component_ticks = ComponentTypeMap::new();
         for (class_name, components) in grouped_components {
             let first_components = components.first().unwrap();
             let mut component_storage = first_components.get_storage();
             component_storage.push_slice(components)?;
 
-            component_storages.insert(class_name, RwLock::new(component_storage));
+            component_storages.insert(class_name.clone(), RwLock::new(component_storage));
+            component_ticks.insert(class_name, vec![ComponentTicks::new()]);
         }
 
         Ok(Self {
@@ -123,6 +149,8 @@ impl Archetype {
             component_types,
             entity_ids: vec![entity_id],
             component_storages,
+            component_ticks,
+            sparse_sets,
         })
     }
 
@@ -146,6 +174,7 @@ impl Archetype {
                 .iter()
                 .map(|(_, storage)| storage.read())
                 .collect(),
+            sparse_set_guards: self.sparse_sets.read_all(),
         }
     }
 
@@ -154,12 +183,15 @@ impl Archetype {
         // Clear data
         self.entity_ids.clear();
         self.component_storages.clear();
+        self.component_ticks.clear();
 
         Ok(())
     }
 
+    /// Get the components of an entity, the ones stored in the sparse sets are after the others
     pub fn get_entity_components(&self, entity_index: usize) -> Vec<Box<dyn Component>> {
-        self.component_storages
+        let mut components = self
+            .component_storages
             .values()
             .map(|component_storage| {
                 let component_storage = component_storage.read();
@@ -170,7 +202,15 @@ impl Archetype {
                     .collect::<Vec<_>>()
             })
             .flatten()
-            .collect::<Vec<Box<dyn Component>>>()
+            .collect::<Vec<Box<dyn Component>>>();
+
+        components.append(
+            &mut self
+                .sparse_sets
+                .get_entity_components(self.entity_ids[entity_index]),
+        );
+
+        components
     }
 
     /// Add an entity to the archetype
@@ -210,21 +250,72 @@ impl Archetype {
             component_storage.write().push_slice(components)?;
         }
 
+        // Stamp the new components
+        self.component_ticks
+            .values_mut()
+            .for_each(|ticks| ticks.push(ComponentTicks::new()));
+
         Ok(())
     }
 
+    /// Reserve capacity for at least `additional` more entities, the components per entity are
+    /// estimated from the entities already stored
+    pub fn reserve(&mut self, additional: usize) {
+        self.entity_ids.reserve(additional);
+        self.component_storages
+            .values_mut()
+            .for_each(|component_storage| {
+                let mut component_storage = component_storage.write();
+                let slice_len = component_storage.len() / component_storage.slice_count().max(1);
+                component_storage.reserve_slices(additional, slice_len.max(1));
+            });
+        self.component_ticks
+            .values_mut()
+            .for_each(|ticks| ticks.reserve(additional));
+    }
+
     pub fn remove_entity(&mut self, entity_index: usize) -> FruityResult<Vec<Box<dyn Component>>> {
+        Ok(self
+            .take_entity(entity_index)
+            .into_iter()
+            .flat_map(|(_, components, _)| components)
+            .collect())
+    }
+
+    /// Remove an entity, its components are returned grouped by type with their change ticks
+    pub(crate) fn take_entity(
+        &mut self,
+        entity_index: usize,
+    ) -> Vec<(ScriptObjectType, Vec<Box<dyn Component>>, ComponentTicks)> {
         // Remove the entity id
         self.entity_ids.remove(entity_index);
 
-        // Remove the components
-        let mut components = Vec::new();
-        for component_storage in self.component_storages.values_mut() {
-            let mut component = component_storage.write().remove_slice(entity_index);
-            components.append(&mut component);
-        }
+        // Remove the components and their change ticks
+        let component_ticks = &mut self.component_ticks;
+        self.component_storages
+            .iter_mut()
+            .map(|(script_object_type, component_storage)| {
+                let components = component_storage.write().remove_slice(entity_index);
+                let ticks = component_ticks
+                    .get_mut(script_object_type)
+                    .unwrap()
+                    .remove(entity_index);
 
-        Ok(components)
+                (script_object_type.clone(), components, ticks)
+            })
+            .collect()
+    }
+
+    /// Replace the change ticks of the components with a given type in an entity
+    pub(crate) fn set_component_ticks(
+        &mut self,
+        script_object_type: &ScriptObjectType,
+        entity_index: usize,
+        ticks: ComponentTicks,
+    ) {
+        if let Some(component_ticks) = self.component_ticks.get_mut(script_object_type) {
+            component_ticks[entity_index] = ticks;
+        }
     }
 
     /// Merge two archetypes
@@ -240,25 +331,122 @@ impl Archetype {
                 .append(other_component_storage.write().deref_mut().deref_mut())?;
         }
 
+        // Merge change ticks
+        for (component_type_id, other_ticks) in other.component_ticks.iter_mut() {
+            self.component_ticks
+                .get_mut(component_type_id)
+                .unwrap()
+                .append(other_ticks);
+        }
+
         Ok(())
     }
 
+    /// Returns the change ticks of the components with a given type in an entity
+    pub fn get_component_ticks(
+        &self,
+        script_object_type: &ScriptObjectType,
+        entity_index: usize,
+    ) -> Option<&ComponentTicks> {
+        self.component_ticks
+            .get(script_object_type)
+            .and_then(|ticks| ticks.get(entity_index))
+    }
+
+    /// Get the change ticks of a component of an entity from the component type index, doesn't access the
+    /// component type registry
+    pub fn get_component_ticks_by_index(
+        &self,
+        index: ComponentTypeIndex,
+        entity_index: usize,
+    ) -> Option<&ComponentTicks> {
+        self.component_ticks
+            .get_by_index(index)
+            .and_then(|ticks| ticks.get(entity_index))
+    }
+
+    /// Lock the storage of a component type and get a component of an entity
+    /// The component can be stored in the archetype or in a sparse set
+    pub(crate) fn read_component(
+        &self,
+        script_object_type: &ScriptObjectType,
+        entity_index: usize,
+        component_index: usize,
+    ) -> Option<(StorageReadGuard<'_>, NonNull<dyn Component>)> {
+        if let Some(storage) = self.component_storages.get(script_object_type) {
+            let storage_reader = storage.read();
+            let component_ptr = NonNull::from(storage_reader.get(entity_index, component_index)?);
+
+            return Some((storage_reader.into(), component_ptr));
+        }
+
+        let sparse_set_reader = self.sparse_sets.get(script_object_type)?.read();
+        let index = sparse_set_reader.get_index(self.entity_ids[entity_index])?;
+        let component_ptr = NonNull::from(sparse_set_reader.storage.get(index, component_index)?);
+
+        Some((sparse_set_reader.into(), component_ptr))
+    }
+
+    /// Lock the storage of a component type with an exclusive access and get a component of an entity
+    /// The component can be stored in the archetype or in a sparse set
+    pub(crate) fn write_component(
+        &self,
+        script_object_type: &ScriptObjectType,
+        entity_index: usize,
+        component_index: usize,
+    ) -> Option<(
+        StorageWriteGuard<'_>,
+        NonNull<dyn Component>,
+        &ComponentTicks,
+    )> {
+        if let Some(storage) = self.component_storages.get(script_object_type) {
+            let ticks = self.get_component_ticks(script_object_type, entity_index)?;
+            let storage_writer = storage.write();
+            let component_ptr = NonNull::from(storage_writer.get(entity_index, component_index)?);
+
+            return Some((storage_writer.into(), component_ptr, ticks));
+        }
+
+        let sparse_set_writer = self.sparse_sets.get(script_object_type)?.write();
+        let index = sparse_set_writer.get_index(self.entity_ids[entity_index])?;
+        let component_ptr = NonNull::from(sparse_set_writer.storage.get(index, component_index)?);
+
+        // Safe cause the ticks are not moved as long as the sparse set is locked
+        let ticks =
+            unsafe { &*(&sparse_set_writer.component_ticks[index] as *const ComponentTicks) };
+
+        Some((sparse_set_writer.into(), component_ptr, ticks))
+    }
+
+    /// Mark all the components of the archetype as added right now
+    pub(crate) fn mark_as_added(&mut self) {
+        self.component_ticks.values_mut().for_each(|ticks| {
+            ticks
+                .iter_mut()
+                .for_each(|ticks| *ticks = ComponentTicks::new())
+        });
+    }
+
     fn group_components_by_type(
         components: Vec<Box<dyn Component>>,
     ) -> HashMap<ScriptObjectType, Vec<Box<dyn Component>>> {
-        use itertools::Itertools;
-
-        components
-            .into_iter()
-            .group_by(
-                |component| match component.get_component_type_id().unwrap() {
-                    ComponentTypeId::Normal(component_type_id) => component_type_id,
-                    ComponentTypeId::OrderedRust(component_type_id, _) => component_type_id,
-                },
-            )
-            .into_iter()
-            .map(|(class_name, component)| (class_name, component.collect::<Vec<_>>()))
-            .collect::<HashMap<_, _>>()
+        // The components of a same type are not always consecutive, they are grouped without losing any
+        components.into_iter().fold(
+            HashMap::<ScriptObjectType, Vec<Box<dyn Component>>>::new(),
+            |mut grouped_components, component| {
+                grouped_components
+                    .entry(
+                        component
+                            .get_component_type_id()
+                            .unwrap()
+                            .get_script_object_type(),
+                    )
+                    .or_default()
+                    .push(component);
+
+                grouped_components
+            },
+        )
     }
 }
 
@@ -286,6 +474,7 @@ pub struct ArchetypeEntityIterator<'a> {
     entity_index: usize,
     archetype: &'a Archetype,
     component_storage_guards: Vec<RwLockReadGuard<'a, Box<dyn ComponentStorage>>>,
+    sparse_set_guards: Vec<RwLockReadGuard<'a, SparseSet>>,
 }
 
 impl<'a> Iterator for ArchetypeEntityIterator<'a> {
@@ -307,7 +496,13 @@ impl<'a> Iterator for ArchetypeEntityIterator<'a> {
                 .map(move |component_storage_guard| {
                     component_storage_guard.iter_slice(entity_index).unwrap()
                 })
-                .flatten(),
+                .flatten()
+                .chain(
+                    this.sparse_set_guards
+                        .iter()
+                        .filter_map(move |sparse_set_guard| sparse_set_guard.iter_entity(entity_id))
+                        .flatten(),
+                ),
         ) as Box<dyn Iterator<Item = &'a dyn Component> + 'a>;
 
         self.entity_index += 1;
@@ -0,0 +1,266 @@
+use super::{EntityId, EntityIdAllocator, EntityService};
+use crate::component::Component;
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{
+    any::FruityAny,
+    inject::Injectable,
+    resource::ResourceContainer,
+    script_value::ScriptObjectType,
+    sync::{Arc, Mutex},
+};
+use std::fmt::Debug;
+
+/// An entity mutation recorded by [`Commands`]
+pub(crate) enum Command {
+    Spawn {
+        entity_id: EntityId,
+        name: String,
+        enabled: bool,
+        components: Vec<Box<dyn Component>>,
+    },
+    Despawn(EntityId),
+    Insert {
+        entity_id: EntityId,
+        components: Vec<Box<dyn Component>>,
+    },
+    Remove {
+        entity_id: EntityId,
+        script_object_type: ScriptObjectType,
+    },
+}
+
+/// A buffer of entity mutations, applied by the [`EntityService`] at the next sync point
+///
+/// Sync points are the end of the start, frame and end runs, so the mutations recorded by a system
+/// are visible to every system of the next frame, even if the buffer is dropped before
+///
+/// The ids of the spawned entities are reserved immediately, so they can be stored in components or
+/// used by the next commands of the same buffer
+#[derive(FruityAny, Clone)]
+#[fruity_export]
+pub struct Commands {
+    pub(crate) id_allocator: Arc<Mutex<EntityIdAllocator>>,
+    pub(crate) queue: Arc<Mutex<Vec<Command>>>,
+}
+
+#[fruity_export]
+impl Commands {
+    /// Spawn an entity at the next sync point
+    /// Returns the id reserved for the entity
+    ///
+    /// # Arguments
+    /// * `name` - The name of the entity
+    /// * `enabled` - Is the entity active
+    /// * `components` - The components that will be added
+    ///
+    #[fruity_export]
+    pub fn spawn(
+        &self,
+        name: String,
+        enabled: bool,
+        components: Vec<Box<dyn Component>>,
+    ) -> EntityId {
+        let entity_id = self.id_allocator.lock().reserve();
+        self.queue.lock().push(Command::Spawn {
+            entity_id,
+            name,
+            enabled,
+            components,
+        });
+
+        entity_id
+    }
+
+    /// Remove an entity at the next sync point
+    #[fruity_export]
+    pub fn despawn(&self, entity_id: EntityId) {
+        self.queue.lock().push(Command::Despawn(entity_id));
+    }
+
+    /// Add components to an entity at the next sync point, the existing components of the same types are replaced
+    #[fruity_export]
+    pub fn insert(&self, entity_id: EntityId, components: Vec<Box<dyn Component>>) {
+        self.queue.lock().push(Command::Insert {
+            entity_id,
+            components,
+        });
+    }
+
+    /// Remove the components of a given type from an entity at the next sync point
+    pub fn remove<T: Component + 'static>(&self, entity_id: EntityId) {
+        self.script_remove(entity_id, ScriptObjectType::of::<T>());
+    }
+
+    /// Remove the components of a given type from an entity at the next sync point
+    #[fruity_export(name = "remove")]
+    pub fn script_remove(&self, entity_id: EntityId, script_object_type: ScriptObjectType) {
+        self.queue.lock().push(Command::Remove {
+            entity_id,
+            script_object_type,
+        });
+    }
+}
+
+impl Debug for Commands {
+    fn fmt(&self, _: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        Ok(())
+    }
+}
+
+impl Injectable for Commands {
+    type StoredType = Commands;
+
+    /// Every system gets its own buffer
+    fn from_resource_container(resource_container: &ResourceContainer) -> Self {
+        let entity_service = resource_container.require::<EntityService>();
+        let entity_service = entity_service.read();
+
+        entity_service.commands()
+    }
+
+    fn finalize(stored: &Self::StoredType) -> Self {
+        stored.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::component::{ExtensionComponentService, Tag};
+
+    fn new_entity_service() -> EntityService {
+        let resource_container = ResourceContainer::new();
+        resource_container.add::<ExtensionComponentService>(
+            "extension_component_service",
+            Box::new(ExtensionComponentService::new(resource_container.clone())),
+        );
+
+        EntityService::new(resource_container)
+    }
+
+    fn get_tags(entity_service: &EntityService, entity_id: EntityId) -> Vec<String> {
+        let mut tags = entity_service
+            .entity_storage
+            .read()
+            .get_entity_components(entity_id)
+            .unwrap()
+            .into_iter()
+            .filter_map(|component| {
+                component
+                    .as_ref()
+                    .as_any_ref()
+                    .downcast_ref::<Tag>()
+                    .cloned()
+            })
+            .map(|tag| tag.0)
+            .collect::<Vec<_>>();
+        tags.sort();
+
+        tags
+    }
+
+    #[test]
+    fn test_commands_are_merged() {
+        let entity_service = new_entity_service();
+        let commands = entity_service.commands();
+
+        let entity_id = commands.spawn(
+            "entity".to_string(),
+            true,
+            vec![Box::new(Tag::new("a".to_string()))],
+        );
+        commands.insert(entity_id, vec![Box::new(Tag::new("b".to_string()))]);
+        commands.remove::<Tag>(entity_id);
+        commands.insert(entity_id, vec![Box::new(Tag::new("c".to_string()))]);
+
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert_eq!(get_tags(&entity_service, entity_id), vec!["c".to_string()]);
+
+        let spawned_then_despawned = commands.spawn("entity".to_string(), true, vec![]);
+        commands.despawn(spawned_then_despawned);
+        commands.insert(entity_id, vec![Box::new(Tag::new("d".to_string()))]);
+
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert!(entity_service
+            .get_entity_reference(spawned_then_despawned)
+            .is_none());
+        assert_eq!(get_tags(&entity_service, entity_id), vec!["d".to_string()]);
+    }
+
+    #[test]
+    fn test_commands_of_a_dropped_buffer_are_applied() {
+        let entity_service = new_entity_service();
+
+        let entity_id = entity_service.commands().spawn(
+            "entity".to_string(),
+            true,
+            vec![Box::new(Tag::new("a".to_string()))],
+        );
+
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert_eq!(get_tags(&entity_service, entity_id), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_commands_skip_dead_entities() {
+        let entity_service = new_entity_service();
+        let removed_entity_id = entity_service
+            .create_entity("removed".to_string(), true, vec![])
+            .unwrap();
+        let entity_id = entity_service
+            .create_entity("kept".to_string(), true, vec![])
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        entity_service.remove_entity(removed_entity_id).unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let commands = entity_service.commands();
+        commands.despawn(removed_entity_id);
+        commands.insert(removed_entity_id, vec![Box::new(Tag::new("a".to_string()))]);
+        commands.remove::<Tag>(removed_entity_id);
+        commands.insert(entity_id, vec![Box::new(Tag::new("b".to_string()))]);
+
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert!(entity_service
+            .get_entity_reference(removed_entity_id)
+            .is_none());
+        assert_eq!(get_tags(&entity_service, entity_id), vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_commands_are_applied_after_a_failing_one() {
+        let entity_service = new_entity_service();
+        let entity_id = entity_service
+            .create_entity("entity".to_string(), true, vec![])
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        // The first created entity fails
+        let has_failed = Arc::new(Mutex::new(false));
+        let observer_has_failed = has_failed.clone();
+        entity_service.on_created.add_observer(move |_| {
+            let mut has_failed = observer_has_failed.lock();
+            if *has_failed {
+                Ok(())
+            } else {
+                *has_failed = true;
+                Err(fruity_game_engine::FruityError::GenericFailure(
+                    "The entity can't be created".to_string(),
+                ))
+            }
+        });
+
+        let commands = entity_service.commands();
+        commands.spawn("failing".to_string(), true, vec![]);
+        let spawned_entity_id = commands.spawn("spawned".to_string(), true, vec![]);
+        commands.insert(entity_id, vec![Box::new(Tag::new("a".to_string()))]);
+
+        assert!(unsafe { entity_service.apply_pending_mutations() }.is_err());
+        assert!(*has_failed.lock());
+        assert!(entity_service
+            .get_entity_reference(spawned_entity_id)
+            .is_some());
+        assert_eq!(get_tags(&entity_service, entity_id), vec!["a".to_string()]);
+    }
+}
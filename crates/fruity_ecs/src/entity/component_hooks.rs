@@ -0,0 +1,219 @@
+use super::{EntityId, EntityReference, EntityStorage};
+use crate::component::{increment_change_tick, ComponentTypeIndex};
+use fruity_game_engine::{
+    script_value::ScriptObjectType,
+    signal::Signal,
+    sync::{Mutex, RwLock},
+    FruityResult,
+};
+use std::collections::{HashMap, HashSet};
+
+/// The signals notified when the components of a given type are added to, removed from or changed in an entity
+///
+/// A signal is created the first time a hook is requested, the types without signal are not checked
+#[derive(Debug, Default)]
+pub(crate) struct ComponentHooks {
+    added: RwLock<HashMap<ScriptObjectType, Signal<EntityReference>>>,
+    removed: RwLock<HashMap<ScriptObjectType, Signal<EntityReference>>>,
+    changed: RwLock<HashMap<ScriptObjectType, Signal<EntityReference>>>,
+    last_changed_check: Mutex<u64>,
+}
+
+impl ComponentHooks {
+    /// Get the signal notified when a component of a given type is added to an entity
+    pub fn on_added(&self, script_object_type: ScriptObjectType) -> Signal<EntityReference> {
+        get_or_insert(&self.added, script_object_type)
+    }
+
+    /// Get the signal notified when the last component of a given type is removed from an entity
+    pub fn on_removed(&self, script_object_type: ScriptObjectType) -> Signal<EntityReference> {
+        get_or_insert(&self.removed, script_object_type)
+    }
+
+    /// Get the signal notified when a component of a given type is mutably accessed
+    pub fn on_changed(&self, script_object_type: ScriptObjectType) -> Signal<EntityReference> {
+        get_or_insert(&self.changed, script_object_type)
+    }
+
+    /// Returns true if there is an added or a removed hook
+    pub fn has_structural_hooks(&self) -> bool {
+        !self.added.read().is_empty() || !self.removed.read().is_empty()
+    }
+
+    /// Get the types of the added hooks that an entity of a storage has
+    pub fn get_added_types(
+        &self,
+        entity_storage: &EntityStorage,
+        entity_id: EntityId,
+    ) -> HashSet<ScriptObjectType> {
+        get_entity_types(&self.added, entity_storage, entity_id)
+    }
+
+    /// Get the types of the removed hooks that an entity of a storage has
+    pub fn get_removed_types(
+        &self,
+        entity_storage: &EntityStorage,
+        entity_id: EntityId,
+    ) -> HashSet<ScriptObjectType> {
+        get_entity_types(&self.removed, entity_storage, entity_id)
+    }
+
+    /// Notify the added hooks of the components of entities created in batch
+    pub fn notify_batch_created(&self, entity_references: &[EntityReference]) -> FruityResult<()> {
+        if !self.has_structural_hooks() {
+            return Ok(());
+        }
+
+        entity_references.iter().try_for_each(|entity_reference| {
+            let added_types = match entity_reference.inner.read().as_ref() {
+                Some(inner) => self.get_added_types(&inner.entity_storage.read(), inner.entity_id),
+                None => return Ok(()),
+            };
+
+            send(&self.added, entity_reference, &added_types)
+        })
+    }
+
+    /// Notify the hooks of the component types added to and removed from an entity
+    pub fn notify(
+        &self,
+        entity_reference: &EntityReference,
+        added_types: &HashSet<ScriptObjectType>,
+        removed_types: &HashSet<ScriptObjectType>,
+    ) -> FruityResult<()> {
+        send(&self.added, entity_reference, added_types)?;
+        send(&self.removed, entity_reference, removed_types)
+    }
+
+    /// Get the entities whose components of a hooked type were mutably accessed since the last check
+    /// The components added in between are not considered as changed
+    pub fn collect_changed(
+        &self,
+        entity_storage: &EntityStorage,
+    ) -> Vec<(ScriptObjectType, EntityId)> {
+        // Everything stamped from now is seen by the next check, even if it shares this tick
+        let last_check = std::mem::replace(
+            &mut *self.last_changed_check.lock(),
+            increment_change_tick(),
+        );
+
+        let mut result = Vec::new();
+        self.changed.read().keys().for_each(|script_object_type| {
+            // The index is resolved once per hooked type, the type has never been stored if it has none
+            let component_type_index = match ComponentTypeIndex::find(script_object_type) {
+                Some(component_type_index) => component_type_index,
+                None => return,
+            };
+
+            entity_storage
+                .archetypes
+                .iter()
+                .filter(|archetype| {
+                    archetype
+                        .get_component_types()
+                        .contains_index(component_type_index)
+                })
+                .for_each(|archetype| {
+                    (0..archetype.len()).for_each(|entity_index| {
+                        if let Some(ticks) = archetype
+                            .get_component_ticks_by_index(component_type_index, entity_index)
+                        {
+                            if ticks.get_changed() >= last_check && ticks.get_added() < last_check {
+                                result.push((
+                                    script_object_type.clone(),
+                                    archetype.entity_ids[entity_index],
+                                ));
+                            }
+                        }
+                    })
+                });
+
+            if let Some(sparse_set) = entity_storage
+                .sparse_sets
+                .get_by_index(component_type_index)
+            {
+                let sparse_set = sparse_set.read();
+                sparse_set
+                    .entity_ids
+                    .iter()
+                    .zip(sparse_set.component_ticks.iter())
+                    .filter(|(_, ticks)| {
+                        ticks.get_changed() >= last_check && ticks.get_added() < last_check
+                    })
+                    .for_each(|(entity_id, _)| {
+                        result.push((script_object_type.clone(), *entity_id))
+                    });
+            }
+        });
+
+        result
+    }
+
+    /// Notify the changed hook of a component type
+    pub fn notify_changed(
+        &self,
+        script_object_type: &ScriptObjectType,
+        entity_reference: EntityReference,
+    ) -> FruityResult<()> {
+        let signal = self.changed.read().get(script_object_type).cloned();
+        match signal {
+            Some(signal) => signal.send(entity_reference),
+            None => Ok(()),
+        }
+    }
+}
+
+fn get_entity_types(
+    signals: &RwLock<HashMap<ScriptObjectType, Signal<EntityReference>>>,
+    entity_storage: &EntityStorage,
+    entity_id: EntityId,
+) -> HashSet<ScriptObjectType> {
+    let archetype_types = match entity_storage.get_entity_location(entity_id) {
+        Some(location) => entity_storage.archetypes[location.archetype_index]
+            .get_component_types()
+            .clone(),
+        None => return HashSet::new(),
+    };
+
+    signals
+        .read()
+        .keys()
+        .filter(|script_object_type| {
+            archetype_types.contains(script_object_type)
+                || entity_storage
+                    .sparse_sets
+                    .contains(script_object_type, entity_id)
+        })
+        .cloned()
+        .collect()
+}
+
+fn get_or_insert(
+    signals: &RwLock<HashMap<ScriptObjectType, Signal<EntityReference>>>,
+    script_object_type: ScriptObjectType,
+) -> Signal<EntityReference> {
+    signals
+        .write()
+        .entry(script_object_type)
+        .or_default()
+        .clone()
+}
+
+fn send(
+    signals: &RwLock<HashMap<ScriptObjectType, Signal<EntityReference>>>,
+    entity_reference: &EntityReference,
+    script_object_types: &HashSet<ScriptObjectType>,
+) -> FruityResult<()> {
+    // The signals are cloned so an observer can register other hooks
+    let signals = {
+        let signals = signals.read();
+        script_object_types
+            .iter()
+            .filter_map(|script_object_type| signals.get(script_object_type).cloned())
+            .collect::<Vec<_>>()
+    };
+
+    signals
+        .into_iter()
+        .try_for_each(|signal| signal.send(entity_reference.clone()))
+}
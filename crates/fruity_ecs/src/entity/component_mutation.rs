@@ -0,0 +1,171 @@
+use super::{is_sparse_set_component, ArchetypeComponentTypes};
+use crate::component::Component;
+use fruity_game_engine::script_value::ScriptObjectType;
+use std::collections::HashSet;
+
+/// A change over the components of an entity, applied when the entity is moved to its new archetype
+pub(crate) enum ComponentMutation {
+    /// Add components, the existing ones are kept even if they have the same type
+    Add(Vec<Box<dyn Component>>),
+    /// Add a component at an index among the components of the same type
+    AddAt(usize, Box<dyn Component>),
+    /// Add components, the existing ones with the same type are replaced
+    Insert(Vec<Box<dyn Component>>),
+    /// Remove the first component of a type
+    Remove(ScriptObjectType),
+    /// Remove the last component of a type, the last added one
+    RemoveLast(ScriptObjectType),
+    /// Remove the component of a type at an index among the components of the same type
+    RemoveNth(ScriptObjectType, usize),
+    /// Remove all the components of a type
+    RemoveAll(ScriptObjectType),
+    /// Remove a component based on its index in the entity
+    RemoveAt(usize),
+}
+
+impl ComponentMutation {
+    /// Apply the mutation over the components of an entity
+    /// Returns the types of the removed components
+    pub(crate) fn apply(self, components: &mut Vec<Box<dyn Component>>) -> Vec<ScriptObjectType> {
+        match self {
+            ComponentMutation::Add(mut new_components) => {
+                components.append(&mut new_components);
+                Vec::new()
+            }
+            ComponentMutation::AddAt(index, component) => {
+                let script_object_type = component_type(component.as_ref());
+                match Self::nth_position(components, script_object_type.as_ref(), index) {
+                    Some(position) => components.insert(position, component),
+                    None => components.push(component),
+                }
+
+                Vec::new()
+            }
+            ComponentMutation::Insert(mut new_components) => {
+                let new_types = new_components
+                    .iter()
+                    .filter_map(|component| component_type(component.as_ref()))
+                    .collect::<Vec<_>>();
+
+                let removed = Self::remove_where(components, |script_object_type| {
+                    new_types.contains(script_object_type)
+                });
+                components.append(&mut new_components);
+
+                removed
+            }
+            ComponentMutation::Remove(script_object_type) => {
+                match components.iter().position(|component| {
+                    component_type(component.as_ref()).as_ref() == Some(&script_object_type)
+                }) {
+                    Some(index) => {
+                        components.remove(index);
+                        vec![script_object_type]
+                    }
+                    None => Vec::new(),
+                }
+            }
+            ComponentMutation::RemoveLast(script_object_type) => {
+                match components.iter().rposition(|component| {
+                    component_type(component.as_ref()).as_ref() == Some(&script_object_type)
+                }) {
+                    Some(index) => {
+                        components.remove(index);
+                        vec![script_object_type]
+                    }
+                    None => Vec::new(),
+                }
+            }
+            ComponentMutation::RemoveNth(script_object_type, index) => {
+                match Self::nth_position(components, Some(&script_object_type), index) {
+                    Some(position) => {
+                        components.remove(position);
+                        vec![script_object_type]
+                    }
+                    None => Vec::new(),
+                }
+            }
+            ComponentMutation::RemoveAll(script_object_type) => {
+                Self::remove_where(components, |component_type| {
+                    *component_type == script_object_type
+                })
+            }
+            ComponentMutation::RemoveAt(index) => {
+                if index < components.len() {
+                    let removed = components.remove(index);
+                    component_type(removed.as_ref()).into_iter().collect()
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    /// Returns true if the mutation only changes components stored in sparse sets, so an entity
+    /// with the given archetype component types can stay in its archetype
+    pub(crate) fn is_sparse_set_mutation(&self, component_types: &ArchetypeComponentTypes) -> bool {
+        match self {
+            ComponentMutation::Add(components) | ComponentMutation::Insert(components) => {
+                components
+                    .iter()
+                    .all(|component| is_sparse_set_component(component.as_ref()))
+            }
+            ComponentMutation::AddAt(_, component) => is_sparse_set_component(component.as_ref()),
+            ComponentMutation::Remove(script_object_type)
+            | ComponentMutation::RemoveLast(script_object_type)
+            | ComponentMutation::RemoveNth(script_object_type, _)
+            | ComponentMutation::RemoveAll(script_object_type) => {
+                !component_types.contains(script_object_type)
+            }
+            // The index is relative to all the components of the entity
+            ComponentMutation::RemoveAt(_) => false,
+        }
+    }
+
+    /// Returns the position in the entity of the component of a type at an index among the components of the same type
+    fn nth_position(
+        components: &[Box<dyn Component>],
+        script_object_type: Option<&ScriptObjectType>,
+        index: usize,
+    ) -> Option<usize> {
+        components
+            .iter()
+            .enumerate()
+            .filter(|(_, component)| {
+                component_type(component.as_ref()).as_ref() == script_object_type
+            })
+            .map(|(position, _)| position)
+            .nth(index)
+    }
+
+    fn remove_where(
+        components: &mut Vec<Box<dyn Component>>,
+        predicate: impl Fn(&ScriptObjectType) -> bool,
+    ) -> Vec<ScriptObjectType> {
+        let mut removed = Vec::new();
+        components.retain(|component| match component_type(component.as_ref()) {
+            Some(script_object_type) if predicate(&script_object_type) => {
+                removed.push(script_object_type);
+                false
+            }
+            _ => true,
+        });
+
+        removed
+    }
+}
+
+/// Returns the types of the components of an entity
+pub(crate) fn component_types(components: &[Box<dyn Component>]) -> HashSet<ScriptObjectType> {
+    components
+        .iter()
+        .filter_map(|component| component_type(component.as_ref()))
+        .collect()
+}
+
+fn component_type(component: &dyn Component) -> Option<ScriptObjectType> {
+    component
+        .get_component_type_id()
+        .ok()
+        .map(|component_type_id| component_type_id.get_script_object_type())
+}
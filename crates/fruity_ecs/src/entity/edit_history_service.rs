@@ -0,0 +1,886 @@
+use super::{ComponentMutation, EntityId, EntityService, SerializedEntity};
+use crate::{
+    component::{AnyComponentReference, Component},
+    serialization::{Deserialize, Serialize},
+};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{
+    any::FruityAny,
+    console_err,
+    introspect::IntrospectFields,
+    resource::{ResourceContainer, ResourceReference},
+    script_value::{ScriptObjectType, ScriptValue},
+    settings::Settings,
+    sync::Mutex,
+    FruityError, FruityResult,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Debug, Formatter},
+};
+
+/// A reversible edit, the components and entities are kept serialized so they can be created again
+///
+/// The field values are kept as they are returned by the component, they are read again every time they are
+/// set so they can be set several times
+#[derive(Debug)]
+enum EditCommand {
+    SetFieldValue {
+        entity_id: EntityId,
+        script_object_type: ScriptObjectType,
+        component_index: usize,
+        field: String,
+        old_value: ScriptValue,
+        new_value: ScriptValue,
+    },
+    AddComponents {
+        entity_id: EntityId,
+        components: Vec<(ScriptObjectType, Settings)>,
+    },
+    RemoveComponent {
+        entity_id: EntityId,
+        script_object_type: ScriptObjectType,
+        component_index: usize,
+        component: Settings,
+    },
+    CreateEntity {
+        entity_id: EntityId,
+        serialized_entity: SerializedEntity,
+    },
+    RemoveEntity {
+        entity_id: EntityId,
+        serialized_entity: SerializedEntity,
+    },
+}
+
+#[derive(Default)]
+struct EditHistory {
+    undo_stack: VecDeque<Vec<EditCommand>>,
+    redo_stack: Vec<Vec<EditCommand>>,
+    transaction: Option<Vec<EditCommand>>,
+    transaction_depth: usize,
+    max_history: usize,
+    /// The entities created again by an undo or a redo get a new id, the commands keep the old one
+    entity_ids: HashMap<EntityId, EntityId>,
+}
+
+impl EditHistory {
+    fn push(&mut self, command: EditCommand) {
+        self.redo_stack.clear();
+
+        match self.transaction.as_mut() {
+            Some(transaction) => transaction.push(command),
+            None => self.push_entry(vec![command]),
+        }
+    }
+
+    fn push_entry(&mut self, entry: Vec<EditCommand>) {
+        if entry.is_empty() {
+            return;
+        }
+
+        self.undo_stack.push_back(entry);
+        while self.undo_stack.len() > self.max_history {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    fn resolve(&self, mut entity_id: EntityId) -> EntityId {
+        while let Some(new_entity_id) = self.entity_ids.get(&entity_id) {
+            entity_id = *new_entity_id;
+        }
+
+        entity_id
+    }
+}
+
+/// A history of the edits made by the editing tools over the entities, the edits can be undone and redone
+///
+/// The edits should be made through this service instead of the `EntityService` to be recorded, the
+/// consecutive edits can be grouped into a transaction that is undone at once
+#[derive(FruityAny)]
+#[fruity_export]
+pub struct EditHistoryService {
+    history: Mutex<EditHistory>,
+    resource_container: ResourceContainer,
+    entity_service: ResourceReference<EntityService>,
+}
+
+#[fruity_export]
+impl EditHistoryService {
+    /// Returns an EditHistoryService
+    pub fn new(resource_container: ResourceContainer) -> Self {
+        Self {
+            history: Mutex::new(EditHistory {
+                max_history: 100,
+                ..Default::default()
+            }),
+            entity_service: resource_container.require::<EntityService>(),
+            resource_container,
+        }
+    }
+
+    /// Change a field of a component
+    ///
+    /// # Arguments
+    /// * `component` - The component
+    /// * `field` - The field name
+    /// * `value` - The new value
+    ///
+    #[fruity_export]
+    pub fn set_field_value(
+        &self,
+        component: AnyComponentReference,
+        field: String,
+        value: ScriptValue,
+    ) -> FruityResult<()> {
+        let mut component = component;
+        let old_value = component.get_field_value(&field)?;
+        component.set_field_value(&field, value)?;
+        let new_value = component.get_field_value(&field)?;
+
+        self.history.lock().push(EditCommand::SetFieldValue {
+            entity_id: component.get_entity_reference().get_entity_id()?,
+            script_object_type: component.get_script_object_type(),
+            component_index: component.get_component_index(),
+            field,
+            old_value,
+            new_value,
+        });
+
+        Ok(())
+    }
+
+    /// Add components to an entity, the existing components of the same types are kept
+    ///
+    /// # Arguments
+    /// * `entity_id` - The entity id
+    /// * `components` - The added components
+    ///
+    #[fruity_export]
+    pub fn add_components(
+        &self,
+        entity_id: EntityId,
+        components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<()> {
+        let serialized_components = components
+            .iter()
+            .map(|component| {
+                Ok((
+                    component.get_component_type_id()?.get_script_object_type(),
+                    component.serialize(&self.resource_container)?,
+                ))
+            })
+            .try_collect::<Vec<_>>()?;
+
+        self.entity_service
+            .read()
+            .add_components(entity_id, components)?;
+
+        self.history.lock().push(EditCommand::AddComponents {
+            entity_id,
+            components: serialized_components,
+        });
+
+        Ok(())
+    }
+
+    /// Remove the first component of a given type from an entity
+    ///
+    /// # Arguments
+    /// * `entity_id` - The entity id
+    /// * `script_object_type` - The type of the removed component
+    ///
+    #[fruity_export]
+    pub fn remove_component(
+        &self,
+        entity_id: EntityId,
+        script_object_type: ScriptObjectType,
+    ) -> FruityResult<()> {
+        let entity_service = self.entity_service.read();
+        let component = entity_service
+            .get_entity_reference(entity_id)
+            .ok_or_else(|| {
+                FruityError::GenericFailure(format!(
+                    "Entity with id {:?} does not exist",
+                    entity_id
+                ))
+            })?
+            .get_components_by_script_object_type(script_object_type.clone())?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                FruityError::GenericFailure(format!(
+                    "Entity with id {:?} has no component of type {:?}",
+                    entity_id, script_object_type
+                ))
+            })?;
+        let component_index = component.get_component_index();
+        let component = self.serialize_component_reference(&component)?;
+
+        entity_service.mutate_entity(
+            entity_id,
+            ComponentMutation::RemoveNth(script_object_type.clone(), component_index),
+        )?;
+        std::mem::drop(entity_service);
+
+        self.history.lock().push(EditCommand::RemoveComponent {
+            entity_id,
+            script_object_type,
+            component_index,
+            component,
+        });
+
+        Ok(())
+    }
+
+    /// Create an entity
+    /// Returns the newly created entity id
+    ///
+    /// # Arguments
+    /// * `name` - The name of the entity
+    /// * `enabled` - Is the entity active
+    /// * `components` - The components that will be added
+    ///
+    #[fruity_export]
+    pub fn create_entity(
+        &self,
+        name: String,
+        enabled: bool,
+        components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<EntityId> {
+        let entity_service = self.entity_service.read();
+        let serialized_entity = SerializedEntity {
+            local_id: 0,
+            name: name.clone(),
+            enabled,
+            components: components
+                .iter()
+                .map(|component| component.serialize(&self.resource_container))
+                .try_collect()?,
+        };
+
+        let entity_id = entity_service.create_entity(name, enabled, components)?;
+        std::mem::drop(entity_service);
+
+        self.history.lock().push(EditCommand::CreateEntity {
+            entity_id,
+            serialized_entity: SerializedEntity {
+                local_id: entity_id.index() as u64,
+                ..serialized_entity
+            },
+        });
+
+        Ok(entity_id)
+    }
+
+    /// Remove an entity
+    ///
+    /// # Arguments
+    /// * `entity_id` - The entity id
+    ///
+    #[fruity_export]
+    pub fn remove_entity(&self, entity_id: EntityId) -> FruityResult<()> {
+        let entity_service = self.entity_service.read();
+        let components = entity_service.remove_entity(entity_id)?;
+        let serialized_entity = entity_service.serialize_entity(
+            entity_id,
+            components.iter().map(|component| component.as_ref()),
+        )?;
+        std::mem::drop(entity_service);
+
+        self.history.lock().push(EditCommand::RemoveEntity {
+            entity_id,
+            serialized_entity,
+        });
+
+        Ok(())
+    }
+
+    /// Start a transaction, the edits are grouped until the transaction is ended and are undone at once
+    /// The transactions can be nested, the edits are grouped until the outer transaction is ended
+    #[fruity_export]
+    pub fn begin_transaction(&self) {
+        let mut history = self.history.lock();
+        history.transaction_depth += 1;
+        history.transaction.get_or_insert_with(Vec::new);
+    }
+
+    /// End a transaction
+    #[fruity_export]
+    pub fn end_transaction(&self) -> FruityResult<()> {
+        let mut history = self.history.lock();
+        if history.transaction_depth == 0 {
+            return Err(FruityError::GenericFailure(
+                "There is no transaction to end".to_string(),
+            ));
+        }
+
+        history.transaction_depth -= 1;
+        if history.transaction_depth == 0 {
+            if let Some(transaction) = history.transaction.take() {
+                history.push_entry(transaction);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Undo the last edit or transaction
+    /// Returns false if there is nothing to undo
+    ///
+    /// If an edit can't be undone, the edits of the transaction that have already been undone are redone and
+    /// the transaction stays the next one to undo
+    #[fruity_export]
+    pub fn undo(&self) -> FruityResult<bool> {
+        let entry = {
+            let mut history = self.history.lock();
+            if history.transaction.is_some() {
+                return Err(FruityError::GenericFailure(
+                    "Cannot undo during a transaction".to_string(),
+                ));
+            }
+
+            history.undo_stack.pop_back()
+        };
+
+        let mut entry = match entry {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        let result = self.apply_entry(&mut entry, true);
+        let mut history = self.history.lock();
+        match result {
+            Ok(()) => {
+                history.redo_stack.push(entry);
+                Ok(true)
+            }
+            Err(error) => {
+                history.undo_stack.push_back(entry);
+                Err(error)
+            }
+        }
+    }
+
+    /// Redo the last undone edit or transaction
+    /// Returns false if there is nothing to redo
+    ///
+    /// If an edit can't be redone, the edits of the transaction that have already been redone are undone and
+    /// the transaction stays the next one to redo
+    #[fruity_export]
+    pub fn redo(&self) -> FruityResult<bool> {
+        let entry = {
+            let mut history = self.history.lock();
+            if history.transaction.is_some() {
+                return Err(FruityError::GenericFailure(
+                    "Cannot redo during a transaction".to_string(),
+                ));
+            }
+
+            history.redo_stack.pop()
+        };
+
+        let mut entry = match entry {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+
+        let result = self.apply_entry(&mut entry, false);
+        let mut history = self.history.lock();
+        match result {
+            Ok(()) => {
+                history.push_entry(entry);
+                Ok(true)
+            }
+            Err(error) => {
+                history.redo_stack.push(entry);
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns true if there is an edit to undo
+    #[fruity_export]
+    pub fn can_undo(&self) -> bool {
+        !self.history.lock().undo_stack.is_empty()
+    }
+
+    /// Returns true if there is an edit to redo
+    #[fruity_export]
+    pub fn can_redo(&self) -> bool {
+        !self.history.lock().redo_stack.is_empty()
+    }
+
+    /// Forget all the recorded edits
+    #[fruity_export]
+    pub fn clear(&self) {
+        let mut history = self.history.lock();
+        history.undo_stack.clear();
+        history.redo_stack.clear();
+        history.entity_ids.clear();
+    }
+
+    /// Get the maximum number of edits or transactions that can be undone
+    #[fruity_export]
+    pub fn get_max_history(&self) -> usize {
+        self.history.lock().max_history
+    }
+
+    /// Set the maximum number of edits or transactions that can be undone, the oldest ones are forgotten
+    #[fruity_export]
+    pub fn set_max_history(&self, max_history: usize) {
+        let mut history = self.history.lock();
+        history.max_history = max_history;
+        while history.undo_stack.len() > max_history {
+            history.undo_stack.pop_front();
+        }
+    }
+
+    /// Undo or redo the commands of an entry, the commands already applied are reverted if one of them fails
+    fn apply_entry(&self, entry: &mut [EditCommand], undo: bool) -> FruityResult<()> {
+        // The commands are undone in the reverse order
+        let count = entry.len();
+        let command_index = |position: usize| if undo { count - 1 - position } else { position };
+
+        for position in 0..count {
+            if let Err(error) = self.apply_command(&mut entry[command_index(position)], undo) {
+                (0..position).rev().for_each(|position| {
+                    if let Err(revert_error) =
+                        self.apply_command(&mut entry[command_index(position)], !undo)
+                    {
+                        console_err(&format!("Couldn't revert an edit: {:?}", revert_error));
+                    }
+                });
+
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_command(&self, command: &mut EditCommand, undo: bool) -> FruityResult<()> {
+        if undo {
+            self.undo_command(command)
+        } else {
+            self.redo_command(command)
+        }
+    }
+
+    fn undo_command(&self, command: &mut EditCommand) -> FruityResult<()> {
+        match command {
+            EditCommand::SetFieldValue {
+                entity_id,
+                script_object_type,
+                component_index,
+                field,
+                old_value,
+                new_value,
+            } => self.swap_field_value(
+                *entity_id,
+                script_object_type,
+                *component_index,
+                field,
+                old_value,
+                new_value,
+            ),
+            EditCommand::AddComponents {
+                entity_id,
+                components,
+            } => {
+                let entity_id = self.resolve(*entity_id);
+                components
+                    .iter()
+                    .rev()
+                    .try_for_each(|(script_object_type, _)| {
+                        self.entity_service.read().mutate_entity(
+                            entity_id,
+                            ComponentMutation::RemoveLast(script_object_type.clone()),
+                        )
+                    })
+            }
+            EditCommand::RemoveComponent {
+                entity_id,
+                component_index,
+                component,
+                ..
+            } => {
+                // The component is added back at its position, so the recorded component indexes stay valid
+                let component = self.deserialize_component(component)?;
+                self.entity_service.read().mutate_entity(
+                    self.resolve(*entity_id),
+                    ComponentMutation::AddAt(*component_index, component),
+                )
+            }
+            EditCommand::CreateEntity { entity_id, .. } => self
+                .entity_service
+                .read()
+                .remove_entity(self.resolve(*entity_id))
+                .map(|_| ()),
+            EditCommand::RemoveEntity {
+                entity_id,
+                serialized_entity,
+            } => self.restore_entity(*entity_id, serialized_entity),
+        }
+    }
+
+    fn redo_command(&self, command: &mut EditCommand) -> FruityResult<()> {
+        match command {
+            EditCommand::SetFieldValue {
+                entity_id,
+                script_object_type,
+                component_index,
+                field,
+                old_value,
+                new_value,
+            } => self.swap_field_value(
+                *entity_id,
+                script_object_type,
+                *component_index,
+                field,
+                new_value,
+                old_value,
+            ),
+            EditCommand::AddComponents {
+                entity_id,
+                components,
+            } => {
+                let components = components
+                    .iter()
+                    .map(|(_, component)| self.deserialize_component(component))
+                    .try_collect::<Vec<_>>()?;
+
+                self.entity_service
+                    .read()
+                    .add_components(self.resolve(*entity_id), components)
+            }
+            EditCommand::RemoveComponent {
+                entity_id,
+                script_object_type,
+                component_index,
+                ..
+            } => self.entity_service.read().mutate_entity(
+                self.resolve(*entity_id),
+                ComponentMutation::RemoveNth(script_object_type.clone(), *component_index),
+            ),
+            EditCommand::CreateEntity {
+                entity_id,
+                serialized_entity,
+            } => self.restore_entity(*entity_id, serialized_entity),
+            EditCommand::RemoveEntity { entity_id, .. } => self
+                .entity_service
+                .read()
+                .remove_entity(self.resolve(*entity_id))
+                .map(|_| ()),
+        }
+    }
+
+    /// Set a field of a component to a recorded value
+    /// The set value and the replaced one are read again from the component, so they can be set by the next undo or redo
+    fn swap_field_value(
+        &self,
+        entity_id: EntityId,
+        script_object_type: &ScriptObjectType,
+        component_index: usize,
+        field: &str,
+        value: &mut ScriptValue,
+        replaced_value: &mut ScriptValue,
+    ) -> FruityResult<()> {
+        let entity_id = self.resolve(entity_id);
+        let mut component = self
+            .entity_service
+            .read()
+            .get_entity_reference(entity_id)
+            .ok_or_else(|| {
+                FruityError::GenericFailure(format!(
+                    "Entity with id {:?} does not exist",
+                    entity_id
+                ))
+            })?
+            .get_components_by_script_object_type(script_object_type.clone())?
+            .into_iter()
+            .nth(component_index)
+            .ok_or_else(|| {
+                FruityError::GenericFailure(format!(
+                    "Entity with id {:?} has no component of type {:?} at index {}",
+                    entity_id, script_object_type, component_index
+                ))
+            })?;
+
+        *replaced_value = component.get_field_value(field)?;
+        component.set_field_value(field, std::mem::replace(value, ScriptValue::Undefined))?;
+        *value = component.get_field_value(field)?;
+
+        Ok(())
+    }
+
+    /// Create an entity again, the new id is used in place of the old one by the next commands
+    fn restore_entity(
+        &self,
+        entity_id: EntityId,
+        serialized_entity: &SerializedEntity,
+    ) -> FruityResult<()> {
+        let entity_service = self.entity_service.read();
+        let serialized_entities = std::slice::from_ref(serialized_entity);
+        let mut local_id_to_entity_id = entity_service.get_local_id_to_entity_id();
+        local_id_to_entity_id.extend(entity_service.reserve_entity_ids(serialized_entities)?);
+        let new_entity_id =
+            entity_service
+                .restore_entities(serialized_entities, &local_id_to_entity_id, |_| Vec::new())?[0];
+        std::mem::drop(entity_service);
+
+        let mut history = self.history.lock();
+        let entity_id = history.resolve(entity_id);
+        history.entity_ids.insert(entity_id, new_entity_id);
+
+        Ok(())
+    }
+
+    fn resolve(&self, entity_id: EntityId) -> EntityId {
+        self.history.lock().resolve(entity_id)
+    }
+
+    fn serialize_component_reference(
+        &self,
+        component: &AnyComponentReference,
+    ) -> FruityResult<Settings> {
+        let component = component.read_any()?.duplicate();
+        component.serialize(&self.resource_container)
+    }
+
+    fn deserialize_component(&self, serialized: &Settings) -> FruityResult<Box<dyn Component>> {
+        let local_id_to_entity_id = self.entity_service.read().get_local_id_to_entity_id();
+        <Box<dyn Component>>::deserialize(
+            serialized,
+            &self.resource_container,
+            &local_id_to_entity_id,
+        )
+    }
+}
+
+impl Debug for EditHistoryService {
+    fn fmt(&self, _: &mut Formatter) -> Result<(), std::fmt::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        component::ExtensionComponentService, resources::scene_resource::SceneInstance,
+        serialization::SerializationService,
+    };
+
+    fn new_edit_history_service() -> (EditHistoryService, ResourceReference<EntityService>) {
+        let resource_container = ResourceContainer::new();
+        resource_container.add::<ExtensionComponentService>(
+            "extension_component_service",
+            Box::new(ExtensionComponentService::new(resource_container.clone())),
+        );
+        let mut serialization_service = SerializationService::new(resource_container.clone());
+        serialization_service.register_component::<SceneInstance>();
+        resource_container
+            .add::<SerializationService>("serialization_service", Box::new(serialization_service));
+        resource_container.add::<EntityService>(
+            "entity_service",
+            Box::new(EntityService::new(resource_container.clone())),
+        );
+
+        (
+            EditHistoryService::new(resource_container.clone()),
+            resource_container.require::<EntityService>(),
+        )
+    }
+
+    fn get_scene(entity_service: &EntityService, entity_id: EntityId) -> ScriptValue {
+        entity_service
+            .get_entity_reference(entity_id)
+            .unwrap()
+            .get_components_by_script_object_type(ScriptObjectType::of::<SceneInstance>())
+            .unwrap()
+            .remove(0)
+            .get_field_value("scene")
+            .unwrap()
+    }
+
+    fn set_scene(
+        edit_history_service: &EditHistoryService,
+        entity_service: &EntityService,
+        entity_id: EntityId,
+        scene: &str,
+    ) {
+        let component = entity_service
+            .get_entity_reference(entity_id)
+            .unwrap()
+            .get_components_by_script_object_type(ScriptObjectType::of::<SceneInstance>())
+            .unwrap()
+            .remove(0);
+
+        edit_history_service
+            .set_field_value(
+                component,
+                "scene".to_string(),
+                ScriptValue::String(scene.to_string()),
+            )
+            .unwrap();
+    }
+
+    fn create_entity(entity_service: &EntityService) -> EntityId {
+        let entity_id = entity_service
+            .create_entity(
+                "entity".to_string(),
+                true,
+                vec![Box::new(SceneInstance {
+                    scene: "a".to_string(),
+                })],
+            )
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        entity_id
+    }
+
+    #[test]
+    fn test_undo_and_redo_a_field_value() {
+        let (edit_history_service, entity_service) = new_edit_history_service();
+        let entity_service = entity_service.read();
+        let entity_id = create_entity(&entity_service);
+
+        set_scene(&edit_history_service, &entity_service, entity_id, "b");
+        assert!(edit_history_service.can_undo());
+        assert!(!edit_history_service.can_redo());
+
+        // The recorded values can be set several times
+        for _ in 0..2 {
+            assert!(edit_history_service.undo().unwrap());
+            assert!(
+                matches!(get_scene(&entity_service, entity_id), ScriptValue::String(scene) if scene == "a")
+            );
+
+            assert!(edit_history_service.redo().unwrap());
+            assert!(
+                matches!(get_scene(&entity_service, entity_id), ScriptValue::String(scene) if scene == "b")
+            );
+        }
+
+        assert!(edit_history_service.undo().unwrap());
+        assert!(!edit_history_service.undo().unwrap());
+    }
+
+    #[test]
+    fn test_undo_a_transaction_at_once() {
+        let (edit_history_service, entity_service) = new_edit_history_service();
+        let entity_service = entity_service.read();
+        let entity_id = create_entity(&entity_service);
+
+        edit_history_service.begin_transaction();
+        set_scene(&edit_history_service, &entity_service, entity_id, "b");
+        set_scene(&edit_history_service, &entity_service, entity_id, "c");
+        edit_history_service.end_transaction().unwrap();
+
+        assert!(edit_history_service.undo().unwrap());
+        assert!(
+            matches!(get_scene(&entity_service, entity_id), ScriptValue::String(scene) if scene == "a")
+        );
+        assert!(!edit_history_service.can_undo());
+
+        assert!(edit_history_service.redo().unwrap());
+        assert!(
+            matches!(get_scene(&entity_service, entity_id), ScriptValue::String(scene) if scene == "c")
+        );
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_the_history() {
+        let (edit_history_service, entity_service) = new_edit_history_service();
+        let entity_service = entity_service.read();
+        let entity_id = create_entity(&entity_service);
+        let other_entity_id = create_entity(&entity_service);
+
+        edit_history_service.begin_transaction();
+        set_scene(&edit_history_service, &entity_service, other_entity_id, "b");
+        set_scene(&edit_history_service, &entity_service, entity_id, "b");
+        edit_history_service.end_transaction().unwrap();
+
+        // The first edit can't be undone anymore, the second one is redone
+        entity_service.remove_entity(other_entity_id).unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        assert!(edit_history_service.undo().is_err());
+        assert!(edit_history_service.can_undo());
+        assert!(!edit_history_service.can_redo());
+        assert!(
+            matches!(get_scene(&entity_service, entity_id), ScriptValue::String(scene) if scene == "b")
+        );
+    }
+
+    fn get_scenes(entity_service: &EntityService, entity_id: EntityId) -> Vec<String> {
+        entity_service
+            .get_entity_reference(entity_id)
+            .unwrap()
+            .get_components_by_script_object_type(ScriptObjectType::of::<SceneInstance>())
+            .unwrap()
+            .into_iter()
+            .map(
+                |component| match component.get_field_value("scene").unwrap() {
+                    ScriptValue::String(scene) => scene,
+                    _ => unreachable!(),
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn test_undo_a_removed_component_keeps_its_position() {
+        let (edit_history_service, entity_service) = new_edit_history_service();
+        let entity_service = entity_service.read();
+        let entity_id = entity_service
+            .create_entity(
+                "entity".to_string(),
+                true,
+                vec![
+                    Box::new(SceneInstance::new("a".to_string())),
+                    Box::new(SceneInstance::new("b".to_string())),
+                ],
+            )
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        // Edit the second component then remove the first one
+        let second_component = entity_service
+            .get_entity_reference(entity_id)
+            .unwrap()
+            .get_components_by_script_object_type(ScriptObjectType::of::<SceneInstance>())
+            .unwrap()
+            .remove(1);
+        edit_history_service
+            .set_field_value(
+                second_component,
+                "scene".to_string(),
+                ScriptValue::String("c".to_string()),
+            )
+            .unwrap();
+        edit_history_service
+            .remove_component(entity_id, ScriptObjectType::of::<SceneInstance>())
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert_eq!(get_scenes(&entity_service, entity_id), vec!["c"]);
+
+        // The removed component is added back before the edited one
+        assert!(edit_history_service.undo().unwrap());
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert_eq!(get_scenes(&entity_service, entity_id), vec!["a", "c"]);
+
+        assert!(edit_history_service.undo().unwrap());
+        assert_eq!(get_scenes(&entity_service, entity_id), vec!["a", "b"]);
+
+        assert!(edit_history_service.redo().unwrap());
+        assert_eq!(get_scenes(&entity_service, entity_id), vec!["a", "c"]);
+
+        assert!(edit_history_service.redo().unwrap());
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+        assert_eq!(get_scenes(&entity_service, entity_id), vec!["c"]);
+    }
+}
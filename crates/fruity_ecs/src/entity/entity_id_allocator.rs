@@ -0,0 +1,104 @@
+use super::EntityId;
+
+#[derive(Debug)]
+struct EntitySlot {
+    generation: u32,
+    is_alive: bool,
+}
+
+/// Generate the entity ids, the slots of the freed ids are reused with a new generation
+#[derive(Debug, Default)]
+pub(crate) struct EntityIdAllocator {
+    slots: Vec<EntitySlot>,
+    free_indexes: Vec<u32>,
+}
+
+impl EntityIdAllocator {
+    /// Reserve an id for a new entity
+    pub(crate) fn reserve(&mut self) -> EntityId {
+        match self.free_indexes.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.is_alive = true;
+
+                EntityId::new(index, slot.generation)
+            }
+            None => {
+                self.slots.push(EntitySlot {
+                    generation: 0,
+                    is_alive: true,
+                });
+
+                EntityId::new((self.slots.len() - 1) as u32, 0)
+            }
+        }
+    }
+
+    /// Free the id of a removed entity, the stored copies of this id become stale
+    pub(crate) fn free(&mut self, entity_id: EntityId) {
+        if self.is_alive(entity_id) {
+            let slot = &mut self.slots[entity_id.index() as usize];
+            slot.generation = slot.generation.wrapping_add(1);
+            slot.is_alive = false;
+
+            self.free_indexes.push(entity_id.index());
+        }
+    }
+
+    /// Check that an id has been reserved and not freed since
+    pub(crate) fn is_alive(&self, entity_id: EntityId) -> bool {
+        self.slots
+            .get(entity_id.index() as usize)
+            .map(|slot| slot.is_alive && slot.generation == entity_id.generation())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reserve_returns_new_slots() {
+        let mut id_allocator = EntityIdAllocator::default();
+
+        assert_eq!(id_allocator.reserve(), EntityId::new(0, 0));
+        assert_eq!(id_allocator.reserve(), EntityId::new(1, 0));
+        assert!(id_allocator.is_alive(EntityId::new(1, 0)));
+    }
+
+    #[test]
+    fn test_free_reuses_the_slot_with_the_next_generation() {
+        let mut id_allocator = EntityIdAllocator::default();
+        let entity_id = id_allocator.reserve();
+        id_allocator.reserve();
+
+        id_allocator.free(entity_id);
+        assert!(!id_allocator.is_alive(entity_id));
+
+        let reused_id = id_allocator.reserve();
+        assert_eq!(reused_id, EntityId::new(0, 1));
+        assert!(id_allocator.is_alive(reused_id));
+        assert!(!id_allocator.is_alive(entity_id));
+    }
+
+    #[test]
+    fn test_free_ignores_the_stale_ids() {
+        let mut id_allocator = EntityIdAllocator::default();
+        let entity_id = id_allocator.reserve();
+        id_allocator.free(entity_id);
+        let reused_id = id_allocator.reserve();
+
+        // Freeing the stale id again must not free the slot of the new entity
+        id_allocator.free(entity_id);
+        assert!(id_allocator.is_alive(reused_id));
+        assert_eq!(id_allocator.reserve(), EntityId::new(1, 0));
+    }
+
+    #[test]
+    fn test_bits_round_trip() {
+        let entity_id = EntityId::new(u32::MAX - 1, u32::MAX);
+
+        assert_eq!(EntityId::from_bits(entity_id.to_bits()), entity_id);
+    }
+}
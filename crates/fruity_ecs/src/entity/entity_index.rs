@@ -0,0 +1,223 @@
+use super::{EntityId, EntityStorage};
+use crate::component::{increment_change_tick, ComponentTicks, ComponentTypeIndex, Name, Tag};
+use fruity_game_engine::script_value::ScriptObjectType;
+use std::collections::{HashMap, HashSet};
+
+/// An index of the entities by name and by tag, used by the lookups of [`EntityService`]
+///
+/// The index is refreshed at every sync point, an entity renamed or tagged in a system is found once
+/// the next sync point is reached
+///
+/// [`EntityService`]: super::EntityService
+#[derive(Debug, Default)]
+pub(crate) struct EntityIndex {
+    names: HashMap<String, Vec<EntityId>>,
+    tags: HashMap<String, Vec<EntityId>>,
+    entries: HashMap<EntityId, (String, Vec<String>)>,
+    last_refresh: u64,
+}
+
+impl EntityIndex {
+    /// Get the name of an indexed entity
+    pub fn get_name(&self, entity_id: EntityId) -> Option<&str> {
+        self.entries.get(&entity_id).map(|(name, _)| name.as_str())
+    }
+
+    /// Get the entities with a given name
+    pub fn find_all_by_name(&self, name: &str) -> Vec<EntityId> {
+        self.names.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Get the entities with a given tag
+    pub fn find_all_by_tag(&self, tag: &str) -> Vec<EntityId> {
+        self.tags.get(tag).cloned().unwrap_or_default()
+    }
+
+    /// Forget an entity
+    pub fn remove(&mut self, entity_id: EntityId) {
+        if let Some((name, tags)) = self.entries.remove(&entity_id) {
+            remove_from(&mut self.names, &name, entity_id);
+            tags.iter()
+                .for_each(|tag| remove_from(&mut self.tags, tag, entity_id));
+        }
+    }
+
+    /// Index the entities of the storage whose name or tags changed since the last refresh, the
+    /// entities that migrated in between are indexed again too
+    pub fn refresh(
+        &mut self,
+        entity_storage: &EntityStorage,
+        migrated_entities: impl IntoIterator<Item = EntityId>,
+    ) {
+        // Everything stamped from now is seen by the next refresh, even if it shares this tick
+        let last_refresh = std::mem::replace(&mut self.last_refresh, increment_change_tick());
+        let is_newer = |ticks: &ComponentTicks| {
+            ticks.get_added() >= last_refresh || ticks.get_changed() >= last_refresh
+        };
+
+        let name_type = ScriptObjectType::of::<Name>();
+        let name_type_index = ComponentTypeIndex::of::<Name>();
+        let mut entity_ids = migrated_entities.into_iter().collect::<HashSet<_>>();
+        entity_storage.archetypes.iter().for_each(|archetype| {
+            // The name ticks are looked up once per archetype, they are in the same order than the entities
+            if let Some(name_ticks) = archetype.component_ticks.get_by_index(name_type_index) {
+                name_ticks
+                    .iter()
+                    .zip(archetype.entity_ids.iter())
+                    .filter(|(ticks, _)| is_newer(ticks))
+                    .for_each(|(_, entity_id)| {
+                        entity_ids.insert(*entity_id);
+                    });
+            }
+        });
+
+        let tag_sparse_set = entity_storage
+            .sparse_sets
+            .get(&ScriptObjectType::of::<Tag>())
+            .map(|sparse_set| sparse_set.read());
+        if let Some(tag_sparse_set) = &tag_sparse_set {
+            tag_sparse_set
+                .entity_ids
+                .iter()
+                .zip(tag_sparse_set.component_ticks.iter())
+                .filter(|(_, ticks)| is_newer(ticks))
+                .for_each(|(entity_id, _)| {
+                    entity_ids.insert(*entity_id);
+                });
+        }
+
+        entity_ids.into_iter().for_each(|entity_id| {
+            self.remove(entity_id);
+
+            let location = match entity_storage.get_entity_location(entity_id) {
+                Some(location) => location,
+                None => return,
+            };
+
+            let name = entity_storage.archetypes[location.archetype_index]
+                .component_storages
+                .get(&name_type)
+                .and_then(|storage| {
+                    storage
+                        .read()
+                        .get(location.entity_index, 0)
+                        .and_then(|component| component.as_any_ref().downcast_ref::<Name>())
+                        .map(|name| name.0.clone())
+                })
+                .unwrap_or_default();
+
+            let tags = tag_sparse_set
+                .as_ref()
+                .and_then(|tag_sparse_set| tag_sparse_set.iter_entity(entity_id))
+                .map(|components| {
+                    components
+                        .filter_map(|component| component.as_any_ref().downcast_ref::<Tag>())
+                        .map(|tag| tag.0.clone())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            self.names.entry(name.clone()).or_default().push(entity_id);
+            tags.iter().for_each(|tag| {
+                self.tags.entry(tag.clone()).or_default().push(entity_id);
+            });
+            self.entries.insert(entity_id, (name, tags));
+        });
+    }
+
+    /// Forget all the entities
+    pub fn clear(&mut self) {
+        self.names.clear();
+        self.tags.clear();
+        self.entries.clear();
+    }
+}
+
+fn remove_from(index: &mut HashMap<String, Vec<EntityId>>, key: &str, entity_id: EntityId) {
+    if let Some(entity_ids) = index.get_mut(key) {
+        entity_ids.retain(|other| *other != entity_id);
+
+        if entity_ids.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::component::Component;
+
+    fn components(name: &str, tags: &[&str]) -> Vec<Box<dyn Component>> {
+        let mut components = vec![Box::new(Name(name.to_string())) as Box<dyn Component>];
+        tags.iter().for_each(|tag| {
+            components.push(Box::new(Tag(tag.to_string())));
+        });
+
+        components
+    }
+
+    #[test]
+    fn test_refresh_indexes_the_new_entities() {
+        let mut entity_storage = EntityStorage::new();
+        let entity_id = EntityId::new(0, 0);
+        entity_storage
+            .create_entity(entity_id, components("player", &["hero"]), None, None)
+            .unwrap();
+
+        let mut entity_index = EntityIndex::default();
+        entity_index.refresh(&entity_storage, vec![]);
+
+        assert_eq!(entity_index.get_name(entity_id), Some("player"));
+        assert_eq!(entity_index.find_all_by_name("player"), vec![entity_id]);
+        assert_eq!(entity_index.find_all_by_tag("hero"), vec![entity_id]);
+    }
+
+    #[test]
+    fn test_refresh_reindexes_the_renamed_entities() {
+        let mut entity_storage = EntityStorage::new();
+        let entity_ids = (0..2)
+            .map(|index| EntityId::new(index, 0))
+            .collect::<Vec<_>>();
+        entity_storage
+            .create_entity(entity_ids[0], components("player", &["hero"]), None, None)
+            .unwrap();
+        entity_storage
+            .create_entity(entity_ids[1], components("enemy", &[]), None, None)
+            .unwrap();
+
+        let mut entity_index = EntityIndex::default();
+        entity_index.refresh(&entity_storage, vec![]);
+
+        let (_, _, moved_entities) = entity_storage
+            .migrate_entity(entity_ids[0], |components| {
+                *components = self::components("boss", &["villain"]);
+            })
+            .unwrap()
+            .unwrap();
+        entity_index.refresh(&entity_storage, moved_entities);
+
+        assert!(entity_index.find_all_by_name("player").is_empty());
+        assert!(entity_index.find_all_by_tag("hero").is_empty());
+        assert_eq!(entity_index.find_all_by_name("boss"), vec![entity_ids[0]]);
+        assert_eq!(entity_index.find_all_by_tag("villain"), vec![entity_ids[0]]);
+        assert_eq!(entity_index.find_all_by_name("enemy"), vec![entity_ids[1]]);
+    }
+
+    #[test]
+    fn test_remove_forgets_the_entity() {
+        let mut entity_storage = EntityStorage::new();
+        let entity_id = EntityId::new(0, 0);
+        entity_storage
+            .create_entity(entity_id, components("player", &["hero"]), None, None)
+            .unwrap();
+
+        let mut entity_index = EntityIndex::default();
+        entity_index.refresh(&entity_storage, vec![]);
+        entity_index.remove(entity_id);
+
+        assert_eq!(entity_index.get_name(entity_id), None);
+        assert!(entity_index.find_all_by_name("player").is_empty());
+        assert!(entity_index.find_all_by_tag("hero").is_empty());
+    }
+}
@@ -1,13 +1,13 @@
 use super::{Archetype, ArchetypeComponentTypes, EntityId, EntityLocation, EntityStorage};
 use crate::component::{
     AnyComponentReadGuardIterator, AnyComponentReference, AnyComponentWriteGuardIterator,
-    Component, ComponentReadGuard, ComponentReadGuardIterator, ComponentWriteGuard,
-    ComponentWriteGuardIterator, Enabled, Name,
+    Component, ComponentReadGuard, ComponentReadGuardIterator, ComponentTicks, ComponentTypeIndex,
+    ComponentWriteGuard, ComponentWriteGuardIterator, Enabled, Name,
 };
 use either::Either;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
     any::FruityAny,
-    export, export_impl, export_struct,
     script_value::ScriptObjectType,
     signal::{ObserverHandler, Signal},
     sync::{Arc, RwLock},
@@ -86,7 +86,7 @@ impl<'a> EntityReader<'a> {
                 .archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 let storage_reader = storage.read();
                 let slice_len = storage_reader.slice_len(self.entity_index);
@@ -144,7 +144,7 @@ impl<'a> EntityReader<'a> {
             self.archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
                 .map(|storage| {
                     let storage_reader = storage.read();
                     let component_ptr = NonNull::from(
@@ -156,12 +156,21 @@ impl<'a> EntityReader<'a> {
                     );
 
                     ComponentReadGuard {
-                        storage_guard: storage_reader,
+                        storage_guard: storage_reader.into(),
                         component_ptr: component_ptr,
                     }
                 })
         }
     }
+
+    /// Get the change ticks of the components with a given type
+    pub fn get_component_ticks_by_type<T: Component>(&self) -> Option<&ComponentTicks> {
+        unsafe {
+            self.archetype
+                .as_ref()
+                .get_component_ticks_by_index(ComponentTypeIndex::of::<T>(), self.entity_index)
+        }
+    }
 }
 
 /// An entity mut reference
@@ -245,7 +254,7 @@ impl<'a> EntityWriter<'a> {
                 .archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 let storage_reader = storage.read();
                 let slice_len = storage_reader.slice_len(self.entity_index);
@@ -275,7 +284,7 @@ impl<'a> EntityWriter<'a> {
                 .archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 let storage_writer = storage.write();
                 let slice_len = storage_writer.slice_len(self.entity_index);
@@ -287,8 +296,15 @@ impl<'a> EntityWriter<'a> {
                         .unwrap(),
                 );
 
+                let ticks = self
+                    .archetype
+                    .as_ref()
+                    .get_component_ticks_by_index(ComponentTypeIndex::of::<T>(), self.entity_index)
+                    .unwrap();
+
                 Either::Left(ComponentWriteGuardIterator::new(
                     storage_writer,
+                    ticks,
                     slice_begin,
                     slice_len,
                 ))
@@ -333,19 +349,27 @@ impl<'a> EntityWriter<'a> {
         component_identifier: String,
     ) -> impl Iterator<Item = &mut dyn Component> {
         unsafe {
+            let script_object_type = ScriptObjectType::from_identifier(component_identifier);
             if let Some(storage) = self
                 .archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::from_identifier(component_identifier))
+                .get(&script_object_type)
             {
                 let storage_writer = storage.write();
                 let slice_len = storage_writer.slice_len(self.entity_index);
                 let slice_begin = NonNull::from(storage_writer.get_unchecked(self.entity_index, 0));
                 let component_type_size = storage_writer.get_component_type_size();
 
+                let ticks = self
+                    .archetype
+                    .as_ref()
+                    .get_component_ticks(&script_object_type, self.entity_index)
+                    .unwrap();
+
                 Either::Left(AnyComponentWriteGuardIterator::new(
                     storage_writer,
+                    ticks,
                     component_type_size,
                     slice_begin,
                     slice_len,
@@ -362,7 +386,7 @@ impl<'a> EntityWriter<'a> {
             self.archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
                 .map(|storage| {
                     let storage_reader = storage.read();
                     let component_ptr = NonNull::from(
@@ -374,7 +398,7 @@ impl<'a> EntityWriter<'a> {
                     );
 
                     ComponentReadGuard {
-                        storage_guard: storage_reader,
+                        storage_guard: storage_reader.into(),
                         component_ptr: component_ptr,
                     }
                 })
@@ -389,7 +413,7 @@ impl<'a> EntityWriter<'a> {
             self.archetype
                 .as_ref()
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
                 .map(|storage| {
                     let storage_writer = storage.write();
                     let component_ptr = NonNull::from(
@@ -401,8 +425,16 @@ impl<'a> EntityWriter<'a> {
                     );
 
                     ComponentWriteGuard {
-                        storage_guard: storage_writer,
+                        storage_guard: storage_writer.into(),
                         component_ptr: component_ptr,
+                        ticks: self
+                            .archetype
+                            .as_ref()
+                            .get_component_ticks_by_index(
+                                ComponentTypeIndex::of::<T>(),
+                                self.entity_index,
+                            )
+                            .unwrap(),
                     }
                 })
         }
@@ -442,12 +474,12 @@ impl Drop for InnerShareableEntityReference {
 /// A reference to an entity
 /// Update its own state when an entity is moved
 #[derive(Debug, Clone, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct EntityReference {
     pub(crate) inner: Arc<RwLock<Option<InnerShareableEntityReference>>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl EntityReference {
     pub(crate) fn new(
         entity_storage: Arc<RwLock<EntityStorage>>,
@@ -598,39 +630,39 @@ impl EntityReference {
     }
 
     /// Get entity id
-    #[export]
+    #[fruity_export]
     pub fn get_entity_id(&self) -> FruityResult<EntityId> {
         Ok(self.read()?.get_entity_id())
     }
 
     /// Get entity name
-    #[export]
+    #[fruity_export]
     pub fn get_name(&self) -> FruityResult<String> {
         Ok(self.read()?.get_name())
     }
 
     /// Set entity name
-    #[export]
+    #[fruity_export]
     pub fn set_name(&self, name: String) -> FruityResult<()> {
         self.write()?.set_name(name);
         Ok(())
     }
 
     /// Get entity enabled
-    #[export]
+    #[fruity_export]
     pub fn is_enabled(&self) -> FruityResult<bool> {
         Ok(self.read()?.is_enabled())
     }
 
     /// Set entity enabled
-    #[export]
+    #[fruity_export]
     pub fn set_enabled(&self, enabled: bool) -> FruityResult<()> {
         self.write()?.set_enabled(enabled);
         Ok(())
     }
 
     /// Get all components
-    #[export]
+    #[fruity_export]
     pub fn get_all_components(&self) -> FruityResult<Vec<AnyComponentReference>> {
         let inner = self.inner.read();
         if let Some(inner) = inner.as_ref() {
@@ -672,7 +704,7 @@ impl EntityReference {
     }
 
     /// Get components with a given component type id
-    #[export(name = "get_components_by_type")]
+    #[fruity_export(name = "get_components_by_type")]
     pub fn get_components_by_script_object_type(
         &self,
         component_type_id: ScriptObjectType,
@@ -1,13 +1,23 @@
-use super::{ArchetypeComponentTypes, EntityId, EntityLocation, EntityStorage, SerializedEntity};
+use super::{
+    component_types, ArchetypeComponentTypes, Command, Commands, ComponentHooks, ComponentMutation,
+    EntityId, EntityIdAllocator, EntityIndex, EntityLocation, EntityStorage, SerializedEntity,
+};
 use crate::{
-    component::{Component, Enabled, ExtensionComponentService, Name},
+    component::{
+        increment_change_tick, Component, ComponentBundle, Enabled, ExtensionComponentService, Name,
+    },
     entity::EntityReference,
-    query::{Query, QueryParam, ScriptQueryBuilder},
-    serialization::{Deserialize, Serialize},
+    query::{Query, QueryFilter, QueryParam, ScriptQueryBuilder},
+    resources::{
+        prefab_resource::{PrefabInstance, PrefabOverride, PrefabResource},
+        scene_resource::{SceneInstance, SceneResource},
+    },
+    serialization::{binary, Deserialize, Serialize},
 };
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
     any::FruityAny,
-    export, export_impl, export_struct, profile_scope,
+    profile_scope,
     resource::{ResourceContainer, ResourceReference},
     script_value::ScriptObjectType,
     settings::Settings,
@@ -15,22 +25,37 @@ use fruity_game_engine::{
     sync::{Arc, Mutex, RwLock},
     typescript, FruityError, FruityResult,
 };
-use std::{collections::HashMap, fmt::Debug, ops::Deref};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    io::{Read, Write},
+    ops::Deref,
+};
 
 /// A save for the entities stored in an [’EntityService’]
 #[typescript("type EntityServiceSnapshot = SerializedEntity[]")]
 pub type EntityServiceSnapshot = Settings;
 
+/// Get the parent of an entity, it's provided by the module that owns the hierarchy
+/// See [`EntityService::set_parent_resolver`]
+pub type ParentResolver =
+    Arc<dyn Send + Sync + Fn(&EntityReference) -> FruityResult<Option<EntityId>>>;
+
 /// A storage for every entities, use [’Archetypes’] to store entities of different types
 #[derive(FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct EntityService {
-    id_incrementer: Mutex<u64>,
+    id_allocator: Arc<Mutex<EntityIdAllocator>>,
+    command_queues: Mutex<Vec<Arc<Mutex<Vec<Command>>>>>,
     pub(crate) entity_storage: Arc<RwLock<EntityStorage>>,
     pending_entity_storage: Arc<RwLock<EntityStorage>>,
     pending_entity_to_remove: Arc<RwLock<Vec<EntityId>>>,
+    pending_migrations: Mutex<Vec<(EntityId, Vec<ComponentMutation>)>>,
     resource_container: ResourceContainer,
     extension_component_service: ResourceReference<ExtensionComponentService>,
+    entity_index: RwLock<EntityIndex>,
+    parent_resolver: RwLock<Option<ParentResolver>>,
+    component_hooks: Arc<ComponentHooks>,
 
     /// Signal notified when an entity is created
     pub on_created: Signal<EntityReference>,
@@ -38,6 +63,10 @@ pub struct EntityService {
     /// Signal notified when an entity is deleted
     pub on_deleted: Signal<EntityId>,
 
+    /// Signal notified once when a batch of entities is spawned, see [`EntityService::spawn_batch`]
+    /// The entities of the batch are not notified through [`EntityService::on_created`]
+    pub on_batch_created: Signal<Vec<EntityReference>>,
+
     /// Signal notified when an entity archetype or index in archetype is moved
     pub(crate) on_entity_location_moved: Signal<(
         EntityId,
@@ -47,19 +76,35 @@ pub struct EntityService {
     )>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl EntityService {
     /// Returns an EntityService
     pub fn new(resource_container: ResourceContainer) -> EntityService {
+        // The component hooks of the entities spawned in batch are notified from the batch signal
+        let component_hooks = Arc::new(ComponentHooks::default());
+        let on_batch_created = Signal::<Vec<EntityReference>>::new();
+        {
+            let component_hooks = component_hooks.clone();
+            on_batch_created.add_observer(move |entity_references| {
+                component_hooks.notify_batch_created(entity_references)
+            });
+        }
+
         EntityService {
-            id_incrementer: Mutex::new(0),
+            id_allocator: Arc::new(Mutex::new(EntityIdAllocator::default())),
+            command_queues: Mutex::new(Vec::new()),
             entity_storage: Arc::new(RwLock::new(EntityStorage::new())),
             pending_entity_storage: Arc::new(RwLock::new(EntityStorage::new())),
             pending_entity_to_remove: Arc::new(RwLock::new(Vec::new())),
+            pending_migrations: Mutex::new(Vec::new()),
             resource_container: resource_container.clone(),
             extension_component_service: resource_container.require::<ExtensionComponentService>(),
+            entity_index: RwLock::new(EntityIndex::default()),
+            parent_resolver: RwLock::new(None),
+            component_hooks,
             on_created: Signal::new(),
             on_deleted: Signal::new(),
+            on_batch_created,
             on_entity_location_moved: Signal::new(),
         }
     }
@@ -69,8 +114,13 @@ impl EntityService {
     /// # Arguments
     /// * `entity_id` - The entity id
     ///
-    #[export]
+    #[fruity_export]
     pub fn get_entity_reference(&self, entity_id: EntityId) -> Option<EntityReference> {
+        // The id refers to an entity that have been removed, its slot may be used by another entity
+        if !self.id_allocator.lock().is_alive(entity_id) {
+            return None;
+        }
+
         let (entity_storage, location, archetype_types) =
             if let Some(location) = self.entity_storage.read().get_entity_location(entity_id) {
                 (
@@ -105,18 +155,227 @@ impl EntityService {
         ))
     }
 
+    /// Get the first entity with a given name
+    /// The entities are indexed at the sync points, a new or renamed entity is found after the next one
+    ///
+    /// # Arguments
+    /// * `name` - The name of the entity
+    ///
+    #[fruity_export]
+    pub fn find_by_name(&self, name: String) -> Option<EntityId> {
+        self.entity_index
+            .read()
+            .find_all_by_name(&name)
+            .first()
+            .copied()
+    }
+
+    /// Get all the entities with a given tag, see [`Tag`]
+    /// The entities are indexed at the sync points, a new or tagged entity is found after the next one
+    ///
+    /// # Arguments
+    /// * `tag` - The tag
+    ///
+    /// [`Tag`]: crate::component::Tag
+    #[fruity_export]
+    pub fn find_all_by_tag(&self, tag: String) -> Vec<EntityId> {
+        self.entity_index.read().find_all_by_tag(&tag)
+    }
+
+    /// Get an entity by its path into the hierarchy, the path is made of the entity names from a root
+    /// entity separated by slashes, for example "Level/Enemies/Boss"
+    /// Without a parent resolver, every entity is a root
+    ///
+    /// # Arguments
+    /// * `path` - The path of the entity
+    ///
+    #[fruity_export]
+    pub fn find_by_path(&self, path: String) -> FruityResult<Option<EntityId>> {
+        let mut names = path.split('/').filter(|name| !name.is_empty()).rev();
+        let name = match names.next() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        let ancestor_names = names.collect::<Vec<_>>();
+
+        let candidates = self.entity_index.read().find_all_by_name(name);
+        for entity_id in candidates {
+            if self.has_ancestor_names(entity_id, &ancestor_names)? {
+                return Ok(Some(entity_id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Change the way the parent of an entity is found, it's used by [`EntityService::find_by_path`]
+    pub fn set_parent_resolver(
+        &self,
+        parent_resolver: impl Send
+            + Sync
+            + Fn(&EntityReference) -> FruityResult<Option<EntityId>>
+            + 'static,
+    ) {
+        *self.parent_resolver.write() = Some(Arc::new(parent_resolver));
+    }
+
+    /// Check that the ancestors of an entity have the given names, from the parent to a root entity
+    fn has_ancestor_names(
+        &self,
+        entity_id: EntityId,
+        ancestor_names: &[&str],
+    ) -> FruityResult<bool> {
+        let mut current_id = entity_id;
+        for ancestor_name in ancestor_names {
+            match self.get_parent(current_id)? {
+                Some(parent_id)
+                    if self.entity_index.read().get_name(parent_id) == Some(*ancestor_name) =>
+                {
+                    current_id = parent_id
+                }
+                _ => return Ok(false),
+            }
+        }
+
+        Ok(self.get_parent(current_id)?.is_none())
+    }
+
+    /// Get the parent of an entity with the parent resolver
+    fn get_parent(&self, entity_id: EntityId) -> FruityResult<Option<EntityId>> {
+        let parent_resolver = self.parent_resolver.read().clone();
+        match (parent_resolver, self.get_entity_reference(entity_id)) {
+            (Some(parent_resolver), Some(entity_reference)) => parent_resolver(&entity_reference),
+            _ => Ok(None),
+        }
+    }
+
+    /// Get the signal notified when a component of a given type is added to an entity, the entity
+    /// creation included
+    pub fn on_component_added<T: Component + 'static>(&self) -> Signal<EntityReference> {
+        self.script_on_component_added(ScriptObjectType::of::<T>())
+    }
+
+    /// Get the signal notified when a component of a given type is added to an entity, the entity
+    /// creation included
+    #[fruity_export(name = "on_component_added")]
+    pub fn script_on_component_added(
+        &self,
+        script_object_type: ScriptObjectType,
+    ) -> Signal<EntityReference> {
+        self.component_hooks.on_added(script_object_type)
+    }
+
+    /// Get the signal notified when the last component of a given type is removed from an entity,
+    /// the entity deletion included, the entity still has the component when the signal is sent
+    /// for a deletion
+    pub fn on_component_removed<T: Component + 'static>(&self) -> Signal<EntityReference> {
+        self.script_on_component_removed(ScriptObjectType::of::<T>())
+    }
+
+    /// Get the signal notified when the last component of a given type is removed from an entity,
+    /// the entity deletion included, the entity still has the component when the signal is sent
+    /// for a deletion
+    #[fruity_export(name = "on_component_removed")]
+    pub fn script_on_component_removed(
+        &self,
+        script_object_type: ScriptObjectType,
+    ) -> Signal<EntityReference> {
+        self.component_hooks.on_removed(script_object_type)
+    }
+
+    /// Get the signal notified when a component of a given type has been mutably accessed
+    /// The changes are checked at the sync points, the signal is sent once per entity
+    pub fn on_component_changed<T: Component + 'static>(&self) -> Signal<EntityReference> {
+        self.script_on_component_changed(ScriptObjectType::of::<T>())
+    }
+
+    /// Get the signal notified when a component of a given type has been mutably accessed
+    /// The changes are checked at the sync points, the signal is sent once per entity
+    #[fruity_export(name = "on_component_changed")]
+    pub fn script_on_component_changed(
+        &self,
+        script_object_type: ScriptObjectType,
+    ) -> Signal<EntityReference> {
+        self.component_hooks.on_changed(script_object_type)
+    }
+
+    /// Notify the component hooks of the types added to and removed from an entity
+    fn notify_component_hooks(
+        &self,
+        entity_id: EntityId,
+        types_before: &HashSet<ScriptObjectType>,
+        types_after: &HashSet<ScriptObjectType>,
+    ) -> FruityResult<()> {
+        if !self.component_hooks.has_structural_hooks() {
+            return Ok(());
+        }
+
+        let entity_reference = match self.get_entity_reference(entity_id) {
+            Some(entity_reference) => entity_reference,
+            None => return Ok(()),
+        };
+
+        self.component_hooks.notify(
+            &entity_reference,
+            &types_after.difference(types_before).cloned().collect(),
+            &types_before.difference(types_after).cloned().collect(),
+        )
+    }
+
+    /// Notify the removed hooks of the components of an entity that is about to be deleted
+    fn notify_entity_removed(
+        &self,
+        entity_storage: &Arc<RwLock<EntityStorage>>,
+        entity_id: EntityId,
+    ) -> FruityResult<()> {
+        let removed_types = self
+            .component_hooks
+            .get_removed_types(&entity_storage.read(), entity_id);
+        if removed_types.is_empty() {
+            return Ok(());
+        }
+
+        match self.get_entity_reference(entity_id) {
+            Some(entity_reference) => {
+                self.component_hooks
+                    .notify(&entity_reference, &HashSet::new(), &removed_types)
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Create a query over entities
     pub fn query<'a, T: QueryParam<'a> + 'static>(&self) -> Query<T> {
         Query::<T>::new(self)
     }
 
+    /// Create a query over entities with a filter that doesn't fetch any data
+    pub fn query_filtered<'a, D: QueryParam<'a> + 'static, F: QueryFilter + 'static>(
+        &self,
+    ) -> Query<D, F> {
+        Query::<D, F>::new(self)
+    }
+
+    /// Create a buffer of entity mutations that are applied at the next sync point
+    #[fruity_export]
+    pub fn commands(&self) -> Commands {
+        let queue = Arc::new(Mutex::new(Vec::new()));
+        self.command_queues.lock().push(queue.clone());
+
+        Commands {
+            id_allocator: self.id_allocator.clone(),
+            queue,
+        }
+    }
+
     /// Create a query over entities
-    #[export(name = "query")]
+    #[fruity_export(name = "query")]
     pub fn script_query(&self) -> ScriptQueryBuilder {
         ScriptQueryBuilder::new(
             self.entity_storage.clone(),
             self.on_entity_location_moved.clone(),
             self.on_created.clone(),
+            self.on_batch_created.clone(),
             self.on_deleted.clone(),
         )
     }
@@ -130,26 +389,42 @@ impl EntityService {
     /// * `enabled` - Is the entity active
     /// * `components` - The components that will be added
     ///
-    #[export]
+    #[fruity_export]
     pub fn create_entity(
         &self,
         name: String,
         enabled: bool,
         components: Vec<Box<dyn Component>>,
     ) -> FruityResult<EntityId> {
-        // Generate an id for the entity
-        let entity_id = EntityId({
-            let mut id_incrementer = self.id_incrementer.lock();
-            *id_incrementer += 1;
-            *id_incrementer
-        });
+        let entity_id = self.id_allocator.lock().reserve();
+        self.create_entity_with_id(entity_id, name, enabled, components)?;
+
+        Ok(entity_id)
+    }
 
+    /// Add a new entity with an already reserved id in the storage
+    fn create_entity_with_id(
+        &self,
+        entity_id: EntityId,
+        name: String,
+        enabled: bool,
+        components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<()> {
         // Add name and enabled as default components
         let default_components = vec![
             Box::new(Name::new(name)) as Box<dyn Component>,
             Box::new(Enabled::new(enabled)) as Box<dyn Component>,
         ];
 
+        // The component types are only collected for the added hooks
+        let added_types = if self.component_hooks.has_structural_hooks() {
+            let mut added_types = component_types(&components);
+            added_types.extend(component_types(&default_components));
+            added_types
+        } else {
+            HashSet::new()
+        };
+
         // Add the entity to the pending entity storage
         {
             let extension_component_service_reader = self.extension_component_service.read();
@@ -166,18 +441,101 @@ impl EntityService {
         let entity_reference = self.get_entity_reference(entity_id).unwrap();
         self.on_created.send(entity_reference)?;
 
-        Ok(entity_id)
+        self.notify_component_hooks(entity_id, &HashSet::new(), &added_types)
+    }
+
+    /// Add several entities in the storage, it's faster than creating them one by one
+    /// The archetypes are looked up and allocated once for all the entities that share them
+    /// Returns the ids of the created entities
+    ///
+    /// The entities are named with the prefix followed by their index in the batch, they are enabled
+    /// The whole batch is notified once through [`EntityService::on_batch_created`], the entities are not
+    /// notified through [`EntityService::on_created`]
+    ///
+    /// # Arguments
+    /// * `name_prefix` - The prefix of the entity names
+    /// * `entities` - The components of every entity
+    ///
+    #[fruity_export]
+    pub fn spawn_batch(
+        &self,
+        name_prefix: String,
+        entities: Vec<Vec<Box<dyn Component>>>,
+    ) -> FruityResult<Vec<EntityId>> {
+        let entity_ids = {
+            let mut id_allocator = self.id_allocator.lock();
+            entities
+                .iter()
+                .map(|_| id_allocator.reserve())
+                .collect::<Vec<_>>()
+        };
+
+        let entities = entity_ids
+            .iter()
+            .zip(entities)
+            .enumerate()
+            .map(|(index, (entity_id, components))| {
+                let default_components = vec![
+                    Box::new(Name::new(format!("{}{}", name_prefix, index))) as Box<dyn Component>,
+                    Box::new(Enabled::new(true)) as Box<dyn Component>,
+                ];
+
+                (*entity_id, components, Some(default_components))
+            })
+            .collect::<Vec<_>>();
+
+        // Add the entities to the pending entity storage
+        {
+            let extension_component_service_reader = self.extension_component_service.read();
+            self.pending_entity_storage
+                .write()
+                .create_entities(entities, Some(extension_component_service_reader.deref()))?;
+        }
+
+        // Notify that the entities have been created
+        let entity_references = entity_ids
+            .iter()
+            .map(|entity_id| self.get_entity_reference(*entity_id).unwrap())
+            .collect::<Vec<_>>();
+        self.on_batch_created.send(entity_references)?;
+
+        Ok(entity_ids)
+    }
+
+    /// Add several entities in the storage from bundles of typed components, see [`EntityService::spawn_batch`]
+    pub fn spawn_batch_typed<T: ComponentBundle>(
+        &self,
+        name_prefix: &str,
+        bundles: impl IntoIterator<Item = T>,
+    ) -> FruityResult<Vec<EntityId>> {
+        self.spawn_batch(
+            name_prefix.to_string(),
+            bundles
+                .into_iter()
+                .map(|bundle| bundle.into_components())
+                .collect(),
+        )
     }
 
     /// Remove an entity based on its id
+    /// The entity is removed at the next sync point, even if it has been created since the last one
     ///
     /// # Arguments
     /// * `entity_id` - The entity id
     ///
-    #[export]
+    #[fruity_export]
     pub fn remove_entity(&self, entity_id: EntityId) -> FruityResult<Vec<Box<dyn Component>>> {
-        if let Some(entity_components) = self.entity_storage.read().get_entity_components(entity_id)
-        {
+        let entity_components = self
+            .entity_storage
+            .read()
+            .get_entity_components(entity_id)
+            .or_else(|| {
+                self.pending_entity_storage
+                    .read()
+                    .get_entity_components(entity_id)
+            });
+
+        if let Some(entity_components) = entity_components {
             // Add the entity to the pending entities to remove
             self.pending_entity_to_remove.write().push(entity_id);
 
@@ -189,197 +547,1020 @@ impl EntityService {
         }
     }
 
-    /// Add components to an entity
-    #[export]
+    /// Add components to an entity, the existing components of the same types are kept
+    #[fruity_export]
     pub fn add_components(
         &self,
         entity_id: EntityId,
-        mut new_components: Vec<Box<dyn Component>>,
+        new_components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<()> {
+        self.mutate_entity(entity_id, ComponentMutation::Add(new_components))
+    }
+
+    /// Add a component to an entity, replace the existing component of the same type if there is one
+    pub fn insert<T: Component + 'static>(
+        &self,
+        entity_id: EntityId,
+        component: T,
     ) -> FruityResult<()> {
-        let mut components = self.remove_entity(entity_id)?;
+        self.script_insert(entity_id, Box::new(component))
+    }
 
-        components.append(&mut new_components);
+    /// Add a component to an entity, replace the existing component of the same type if there is one
+    #[fruity_export(name = "insert")]
+    pub fn script_insert(
+        &self,
+        entity_id: EntityId,
+        component: Box<dyn Component>,
+    ) -> FruityResult<()> {
+        self.mutate_entity(entity_id, ComponentMutation::Insert(vec![component]))
+    }
 
-        let (location, archetype_types) = self
-            .pending_entity_storage
-            .write()
-            .create_entity(entity_id, components, None, None)?;
+    /// Remove the first component of a given type from an entity
+    pub fn remove<T: Component + 'static>(&self, entity_id: EntityId) -> FruityResult<()> {
+        self.script_remove(entity_id, ScriptObjectType::of::<T>())
+    }
 
-        self.on_entity_location_moved.send((
-            entity_id,
-            self.pending_entity_storage.clone(),
-            location,
-            archetype_types,
-        ))
+    /// Remove the first component of a given type from an entity
+    #[fruity_export(name = "remove")]
+    pub fn script_remove(
+        &self,
+        entity_id: EntityId,
+        script_object_type: ScriptObjectType,
+    ) -> FruityResult<()> {
+        self.mutate_entity(entity_id, ComponentMutation::Remove(script_object_type))
     }
 
-    /// Remove a component from an entity
-    /// TODO: Not easy to use, use component type instead of index
-    #[export]
+    /// Remove all the components of a given type from an entity
+    pub fn remove_all<T: Component + 'static>(&self, entity_id: EntityId) -> FruityResult<()> {
+        self.script_remove_all(entity_id, ScriptObjectType::of::<T>())
+    }
+
+    /// Remove all the components of a given type from an entity
+    #[fruity_export(name = "remove_all")]
+    pub fn script_remove_all(
+        &self,
+        entity_id: EntityId,
+        script_object_type: ScriptObjectType,
+    ) -> FruityResult<()> {
+        self.mutate_entity(entity_id, ComponentMutation::RemoveAll(script_object_type))
+    }
+
+    /// Remove a component from an entity based on its index, prefer [`EntityService::remove`]
+    #[fruity_export]
     pub fn remove_component(
         &self,
         entity_id: EntityId,
         component_index: usize,
     ) -> FruityResult<()> {
-        let components = self.remove_entity(entity_id)?;
+        self.mutate_entity(entity_id, ComponentMutation::RemoveAt(component_index))
+    }
 
-        let new_components = components
-            .into_iter()
-            .enumerate()
-            .filter(|(index, _)| *index != component_index)
-            .map(|(_, component)| component)
-            .collect::<Vec<_>>();
+    /// Change the components of an entity
+    ///
+    /// An entity of the main storage is moved to its new archetype at the next sync point, the mutations
+    /// over the same entity are merged so the entity is moved only once
+    /// An entity that is still in the pending storage is moved immediately
+    pub(crate) fn mutate_entity(
+        &self,
+        entity_id: EntityId,
+        mutation: ComponentMutation,
+    ) -> FruityResult<()> {
+        if self.pending_entity_storage.read().has_entity(entity_id) {
+            // The component types are only collected for the structural hooks
+            let has_structural_hooks = self.component_hooks.has_structural_hooks();
+            let mut types_before = HashSet::new();
+            let mut types_after = HashSet::new();
+            let migration =
+                self.pending_entity_storage
+                    .write()
+                    .migrate_entity(entity_id, |components| {
+                        if has_structural_hooks {
+                            types_before = component_types(components);
+                        }
+                        mutation.apply(components);
+                        if has_structural_hooks {
+                            types_after = component_types(components);
+                        }
+                    })?;
 
-        let (location, archetype_types) = self.pending_entity_storage.write().create_entity(
-            entity_id,
-            new_components,
-            None,
-            None,
-        )?;
+            if let Some((_, _, moved_entities)) = migration {
+                self.notify_migration(&self.pending_entity_storage, entity_id, moved_entities)?;
+                self.notify_component_hooks(entity_id, &types_before, &types_after)?;
+            }
+
+            Ok(())
+        } else if self.entity_storage.read().has_entity(entity_id) {
+            let mut pending_migrations = self.pending_migrations.lock();
+            match pending_migrations
+                .iter_mut()
+                .find(|(pending_entity_id, _)| *pending_entity_id == entity_id)
+            {
+                Some((_, mutations)) => mutations.push(mutation),
+                None => pending_migrations.push((entity_id, vec![mutation])),
+            }
+
+            Ok(())
+        } else {
+            Err(FruityError::GenericFailure(
+                format!("Entity with id {:?} does not exist", entity_id).into(),
+            ))
+        }
+    }
+
+    /// Notify that an entity has been migrated, the next entities of its previous archetype are moved too
+    fn notify_migration(
+        &self,
+        entity_storage: &Arc<RwLock<EntityStorage>>,
+        entity_id: EntityId,
+        moved_entities: Vec<EntityId>,
+    ) -> FruityResult<()> {
+        std::iter::once(entity_id)
+            .chain(moved_entities)
+            .try_for_each(|entity_id| self.notify_location_moved(entity_storage, entity_id))
+    }
+
+    /// Notify the references to an entity of its current location
+    fn notify_location_moved(
+        &self,
+        entity_storage: &Arc<RwLock<EntityStorage>>,
+        entity_id: EntityId,
+    ) -> FruityResult<()> {
+        let (location, archetype_types) = {
+            let entity_storage_reader = entity_storage.read();
+            let location = entity_storage_reader
+                .get_entity_location(entity_id)
+                .unwrap();
+            let archetype_types = entity_storage_reader
+                .get_archetype_types(location.archetype_index)
+                .unwrap();
+
+            (location, archetype_types)
+        };
 
         self.on_entity_location_moved.send((
             entity_id,
-            self.pending_entity_storage.clone(),
+            entity_storage.clone(),
             location,
             archetype_types,
         ))
     }
 
     /// Clear all the entities
-    #[export]
+    /// The ids of the cleared entities become stale, they are not reused as is
+    #[fruity_export]
     pub fn clear(&self) -> FruityResult<()> {
         // Notify that the entity has been deleted
-        self.entity_storage
-            .read()
-            .iter_ids()
-            .try_for_each(|entity_id| self.on_deleted.send(entity_id))?;
-
-        self.pending_entity_storage
-            .read()
-            .iter_ids()
-            .try_for_each(|entity_id| self.on_deleted.send(entity_id))?;
+        for entity_storage in [&self.entity_storage, &self.pending_entity_storage] {
+            let entity_ids = entity_storage.read().iter_ids().collect::<Vec<_>>();
+            entity_ids.into_iter().try_for_each(|entity_id| {
+                self.notify_entity_removed(entity_storage, entity_id)?;
+                self.id_allocator.lock().free(entity_id);
+                self.on_deleted.send(entity_id)
+            })?;
+        }
 
-        *self.id_incrementer.lock() = 0;
+        self.pending_migrations.lock().clear();
+        self.entity_index.write().clear();
         self.entity_storage.write().clear()?;
         self.pending_entity_storage.write().clear()
     }
 
     /// Create a snapshot over all the entities
-    #[export]
+    #[fruity_export]
     pub fn snapshot(&self) -> FruityResult<EntityServiceSnapshot> {
         self.entity_storage
             .read()
             .iter()
-            .map(|(entity_id, components)| {
-                let mut name = String::new();
-                let mut enabled = false;
-
-                let serialized_components = components
-                    .filter(|component| {
-                        if component
-                            .get_component_type_id()
-                            .unwrap()
-                            .get_script_object_type()
-                            == ScriptObjectType::of::<Name>()
-                        {
-                            name = component
-                                .as_any_ref()
-                                .downcast_ref::<Name>()
-                                .unwrap()
-                                .0
-                                .clone();
-
-                            false
-                        } else if component
-                            .get_component_type_id()
-                            .unwrap()
-                            .get_script_object_type()
-                            == ScriptObjectType::of::<Enabled>()
-                        {
-                            enabled = component
-                                .as_any_ref()
-                                .downcast_ref::<Enabled>()
-                                .unwrap()
-                                .0
-                                .clone();
-
-                            false
-                        } else {
-                            true
-                        }
-                    })
-                    .map(|component| {
-                        component
-                            .deref()
-                            .duplicate()
-                            .serialize(&self.resource_container)
-                    })
-                    .try_collect::<Vec<_>>()?;
-
-                FruityResult::Ok(SerializedEntity {
-                    local_id: entity_id.0,
-                    name,
-                    enabled,
-                    components: serialized_components,
-                })
-            })
+            .map(|(entity_id, components)| self.serialize_entity(entity_id, components))
             .try_collect::<Vec<_>>()?
             .serialize(&self.resource_container)
     }
 
+    /// Write a snapshot over all the entities in the binary format
+    ///
+    /// The local ids of all the entities are written first so [`EntityService::read_snapshot`] can reserve
+    /// their ids before restoring any of them, then the entities are written one after the other, the whole
+    /// snapshot is never built in memory
+    /// The written entities are the ones of the snapshot returned by [`EntityService::snapshot`]
+    pub fn write_snapshot(&self, writer: &mut dyn Write) -> FruityResult<()> {
+        let entity_storage_reader = self.entity_storage.read();
+        let local_ids = entity_storage_reader
+            .iter_ids()
+            .map(|entity_id| entity_id.index() as u64)
+            .collect::<Vec<_>>();
+
+        binary::write_array_header(writer, 2)?;
+        local_ids.serialize_binary(&self.resource_container, writer)?;
+        binary::write_array_header(writer, local_ids.len())?;
+
+        entity_storage_reader
+            .iter()
+            .try_for_each(|(entity_id, components)| {
+                self.serialize_entity(entity_id, components)?
+                    .serialize_binary(&self.resource_container, writer)
+            })
+    }
+
+    /// Serialize an entity from its components, the local id is the entity slot index
+    pub(crate) fn serialize_entity<'a>(
+        &self,
+        entity_id: EntityId,
+        components: impl Iterator<Item = &'a dyn Component>,
+    ) -> FruityResult<SerializedEntity> {
+        let mut name = String::new();
+        let mut enabled = false;
+
+        let serialized_components = components
+            .filter(|component| {
+                if component
+                    .get_component_type_id()
+                    .unwrap()
+                    .get_script_object_type()
+                    == ScriptObjectType::of::<Name>()
+                {
+                    name = component
+                        .as_any_ref()
+                        .downcast_ref::<Name>()
+                        .unwrap()
+                        .0
+                        .clone();
+
+                    false
+                } else if component
+                    .get_component_type_id()
+                    .unwrap()
+                    .get_script_object_type()
+                    == ScriptObjectType::of::<Enabled>()
+                {
+                    enabled = component.as_any_ref().downcast_ref::<Enabled>().unwrap().0;
+
+                    false
+                } else {
+                    true
+                }
+            })
+            .map(|component| {
+                component
+                    .deref()
+                    .duplicate()
+                    .serialize(&self.resource_container)
+            })
+            .try_collect::<Vec<_>>()?;
+
+        Ok(SerializedEntity {
+            local_id: entity_id.index() as u64,
+            name,
+            enabled,
+            components: serialized_components,
+        })
+    }
+
     /// Restore an entity snapshot
     ///
     /// # Arguments
     /// * `snapshot` - The snapshot
     ///
-    #[export]
+    #[fruity_export]
     pub fn restore(&self, clear_before: bool, snapshot: EntityServiceSnapshot) -> FruityResult<()> {
         if clear_before {
             self.clear()?;
         }
 
-        let mut local_id_to_entity_id = HashMap::<u64, EntityId>::new();
-        <Vec<SerializedEntity>>::deserialize(
+        let serialized_entities = <Vec<SerializedEntity>>::deserialize(
             &snapshot,
             &self.resource_container,
-            &local_id_to_entity_id,
-        )?
-        .iter()
-        .try_for_each(|serialized_entity| {
-            self.restore_entity(serialized_entity, &mut local_id_to_entity_id)
+            &HashMap::new(),
+        )?;
+        let local_id_to_entity_id = self.reserve_entity_ids(&serialized_entities)?;
+
+        self.restore_entities(&serialized_entities, &local_id_to_entity_id, |_| Vec::new())
+            .map(|_| ())
+    }
+
+    /// Restore an entity snapshot written in the binary format
+    ///
+    /// The ids of all the entities are reserved first, then the entities are read and restored one after
+    /// the other, the whole snapshot is never built in memory
+    /// A component can refer to any entity of the snapshot, whatever their order
+    ///
+    /// # Arguments
+    /// * `clear_before` - Remove all the existing entities before
+    /// * `reader` - The reader over a snapshot written by [`EntityService::write_snapshot`]
+    ///
+    pub fn read_snapshot(&self, clear_before: bool, reader: &mut dyn Read) -> FruityResult<()> {
+        if clear_before {
+            self.clear()?;
+        }
+
+        if binary::read_array_header(reader)? != 2 {
+            return Err(FruityError::ArrayExpected(
+                "A snapshot is made of the local ids of the entities followed by the entities"
+                    .to_string(),
+            ));
+        }
+
+        let local_ids =
+            <Vec<u64>>::deserialize_binary(reader, &self.resource_container, &HashMap::new())?;
+        let local_id_to_entity_id = self.reserve_local_ids(&local_ids)?;
+        let entity_ids = local_ids
+            .iter()
+            .map(|local_id| local_id_to_entity_id[local_id])
+            .collect::<Vec<_>>();
+        self.create_empty_entities(&entity_ids)?;
+
+        // The entities that are not restored yet are removed if the snapshot cannot be read to the end
+        let mut remaining_entity_ids = entity_ids.into_iter().collect::<HashSet<_>>();
+        self.read_snapshot_entities(reader, &local_id_to_entity_id, &mut remaining_entity_ids)
+            .or_else(|error| {
+                self.discard_empty_entities(&remaining_entity_ids.into_iter().collect::<Vec<_>>())?;
+                Err(error)
+            })
+    }
+
+    /// Read the entities of a binary snapshot and restore them in place of the empty entities created for
+    /// their local ids, the restored entities are removed from the remaining ones
+    fn read_snapshot_entities(
+        &self,
+        reader: &mut dyn Read,
+        local_id_to_entity_id: &HashMap<u64, EntityId>,
+        remaining_entity_ids: &mut HashSet<EntityId>,
+    ) -> FruityResult<()> {
+        let len = binary::read_array_header(reader)?;
+        if len != remaining_entity_ids.len() {
+            return Err(FruityError::GenericFailure(format!(
+                "The snapshot has {} local ids but {} entities",
+                remaining_entity_ids.len(),
+                len
+            )));
+        }
+
+        (0..len).try_for_each(|_| {
+            let serialized_entity = SerializedEntity::deserialize_binary(
+                reader,
+                &self.resource_container,
+                local_id_to_entity_id,
+            )?;
+
+            let entity_id = local_id_to_entity_id
+                .get(&serialized_entity.local_id)
+                .filter(|entity_id| remaining_entity_ids.contains(*entity_id))
+                .copied()
+                .ok_or_else(|| {
+                    FruityError::GenericFailure(format!(
+                        "The entity with local id {} is not expected in the snapshot",
+                        serialized_entity.local_id
+                    ))
+                })?;
+
+            self.restore_entity(
+                entity_id,
+                &serialized_entity,
+                local_id_to_entity_id,
+                Vec::new(),
+            )?;
+            remaining_entity_ids.remove(&entity_id);
+
+            Ok(())
+        })
+    }
+
+    /// Reserve an id for every serialized entity, returns the reserved ids by local id
+    ///
+    /// The ids are reserved before any entity is restored, [`EntityService::restore_entities`] then creates
+    /// all the entities before deserializing their components so a component can refer to the entities
+    /// that come after it
+    pub(crate) fn reserve_entity_ids(
+        &self,
+        serialized_entities: &[SerializedEntity],
+    ) -> FruityResult<HashMap<u64, EntityId>> {
+        self.reserve_local_ids(
+            &serialized_entities
+                .iter()
+                .map(|serialized_entity| serialized_entity.local_id)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Reserve an id for every local id, returns the reserved ids by local id
+    fn reserve_local_ids(&self, local_ids: &[u64]) -> FruityResult<HashMap<u64, EntityId>> {
+        let mut unique_local_ids = HashSet::new();
+        if let Some(local_id) = local_ids
+            .iter()
+            .find(|local_id| !unique_local_ids.insert(**local_id))
+        {
+            return Err(FruityError::GenericFailure(format!(
+                "The local id {} is used by several entities",
+                local_id
+            )));
+        }
+
+        let mut id_allocator = self.id_allocator.lock();
+        Ok(local_ids
+            .iter()
+            .map(|local_id| (*local_id, id_allocator.reserve()))
+            .collect())
+    }
+
+    /// Create entities from their serialized form with the ids reserved by [`EntityService::reserve_entity_ids`]
+    /// The additional components of every entity are added to the deserialized ones
+    /// Returns the ids of the created entities
+    ///
+    /// All the entities are created empty before any component is deserialized, so a component can refer to
+    /// any of the restored entities whatever their order
+    /// If an entity cannot be restored, this entity and the next ones are removed and their ids are freed
+    pub(crate) fn restore_entities(
+        &self,
+        serialized_entities: &[SerializedEntity],
+        local_id_to_entity_id: &HashMap<u64, EntityId>,
+        additional_components: impl Fn(&SerializedEntity) -> Vec<Box<dyn Component>>,
+    ) -> FruityResult<Vec<EntityId>> {
+        let entity_ids = serialized_entities
+            .iter()
+            .map(|serialized_entity| local_id_to_entity_id[&serialized_entity.local_id])
+            .collect::<Vec<_>>();
+        self.create_empty_entities(&entity_ids)?;
+
+        serialized_entities
+            .iter()
+            .zip(entity_ids.iter())
+            .enumerate()
+            .try_for_each(|(index, (serialized_entity, entity_id))| {
+                match self.restore_entity(
+                    *entity_id,
+                    serialized_entity,
+                    local_id_to_entity_id,
+                    additional_components(serialized_entity),
+                ) {
+                    Ok(()) => Ok(()),
+                    Err(error) => {
+                        self.discard_empty_entities(&entity_ids[index..])?;
+                        Err(error)
+                    }
+                }
+            })?;
+
+        Ok(entity_ids)
+    }
+
+    /// Create an empty entity for every reserved id, the entities are not notified
+    ///
+    /// It lets the components deserialized by [`EntityService::restore_entity`] refer to the entities that
+    /// are not restored yet, if an entity cannot be created no entity is kept and all the ids are freed
+    fn create_empty_entities(&self, entity_ids: &[EntityId]) -> FruityResult<()> {
+        entity_ids
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, entity_id)| {
+                let result = self.pending_entity_storage.write().create_entity(
+                    *entity_id,
+                    Vec::new(),
+                    None,
+                    Some(vec![
+                        Box::new(Name::new(String::new())) as Box<dyn Component>,
+                        Box::new(Enabled::new(false)) as Box<dyn Component>,
+                    ]),
+                );
+
+                match result {
+                    Ok(_) => Ok(()),
+                    Err(error) => {
+                        self.discard_empty_entities(&entity_ids[..index])?;
+                        let mut id_allocator = self.id_allocator.lock();
+                        entity_ids[index..]
+                            .iter()
+                            .for_each(|entity_id| id_allocator.free(*entity_id));
+
+                        Err(error)
+                    }
+                }
+            })
+    }
+
+    /// Remove the empty entities that have not been restored and free their ids
+    fn discard_empty_entities(&self, entity_ids: &[EntityId]) -> FruityResult<()> {
+        entity_ids.iter().try_for_each(|entity_id| {
+            let moved_entities =
+                Self::remove_pending_entity(&mut self.pending_entity_storage.write(), *entity_id)?;
+            self.id_allocator.lock().free(*entity_id);
+
+            moved_entities
+                .unwrap_or_default()
+                .into_iter()
+                .try_for_each(|entity_id| {
+                    self.notify_location_moved(&self.pending_entity_storage, entity_id)
+                })
         })
     }
 
+    /// Remove an entity from the pending storage without notifying it
+    /// Returns the entities that have been moved in its archetype, None if the entity is not pending
+    fn remove_pending_entity(
+        pending_entity_storage: &mut EntityStorage,
+        entity_id: EntityId,
+    ) -> FruityResult<Option<Vec<EntityId>>> {
+        let location = match pending_entity_storage.get_entity_location(entity_id) {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        pending_entity_storage.remove_entity(entity_id)?;
+        Ok(Some(
+            pending_entity_storage.archetypes[location.archetype_index].entity_ids
+                [location.entity_index..]
+                .to_vec(),
+        ))
+    }
+
+    /// Replace an empty entity created by [`EntityService::create_empty_entities`] by the entity restored
+    /// from its serialized form
+    ///
+    /// The references to the empty entity are moved to the restored one, the restored entity is then notified
+    /// as created
     fn restore_entity(
         &self,
+        entity_id: EntityId,
         serialized_entity: &SerializedEntity,
-        local_id_to_entity_id: &mut HashMap<u64, EntityId>,
+        local_id_to_entity_id: &HashMap<u64, EntityId>,
+        mut additional_components: Vec<Box<dyn Component>>,
     ) -> FruityResult<()> {
-        let entity_id = self.create_entity(
-            serialized_entity.name.clone(),
-            serialized_entity.enabled,
-            serialized_entity
-                .components
+        let mut components = serialized_entity
+            .components
+            .iter()
+            .map(|serialized_component| {
+                <Box<dyn Component>>::deserialize(
+                    serialized_component,
+                    &self.resource_container,
+                    local_id_to_entity_id,
+                )
+            })
+            .try_collect::<Vec<_>>()?;
+        components.append(&mut additional_components);
+
+        let default_components = vec![
+            Box::new(Name::new(serialized_entity.name.clone())) as Box<dyn Component>,
+            Box::new(Enabled::new(serialized_entity.enabled)) as Box<dyn Component>,
+        ];
+
+        // The component types are only collected for the added hooks
+        let added_types = if self.component_hooks.has_structural_hooks() {
+            let mut added_types = component_types(&components);
+            added_types.extend(component_types(&default_components));
+            added_types
+        } else {
+            HashSet::new()
+        };
+
+        // The entity is created again in place of the empty one, like in EntityService::create_entity_with_id
+        let (moved_entities, result) = {
+            let extension_component_service_reader = self.extension_component_service.read();
+            let mut pending_entity_storage_writer = self.pending_entity_storage.write();
+            let moved_entities =
+                Self::remove_pending_entity(&mut pending_entity_storage_writer, entity_id)?
+                    .ok_or_else(|| {
+                        FruityError::GenericFailure(format!(
+                            "Entity with id {:?} has not been reserved",
+                            entity_id
+                        ))
+                    })?;
+            let result = pending_entity_storage_writer.create_entity(
+                entity_id,
+                components,
+                Some(extension_component_service_reader.deref()),
+                Some(default_components),
+            );
+
+            (moved_entities, result)
+        };
+
+        // The entities moved by the removal are notified even if the entity cannot be created again
+        moved_entities.iter().try_for_each(|moved_entity_id| {
+            self.notify_location_moved(&self.pending_entity_storage, *moved_entity_id)
+        })?;
+        result?;
+        self.notify_location_moved(&self.pending_entity_storage, entity_id)?;
+
+        // Notify that the entity has been created
+        let entity_reference = self.get_entity_reference(entity_id).unwrap();
+        self.on_created.send(entity_reference)?;
+
+        self.notify_component_hooks(entity_id, &HashSet::new(), &added_types)
+    }
+
+    /// Load the entities of a scene additively, the existing entities are kept
+    /// Every entity is tagged with a [`SceneInstance`] component so it can be removed with [`EntityService::unload_scene`]
+    /// Returns the ids of the created entities
+    ///
+    /// # Arguments
+    /// * `scene` - The scene resource
+    ///
+    #[fruity_export]
+    pub fn load_scene(
+        &self,
+        scene: ResourceReference<SceneResource>,
+    ) -> FruityResult<Vec<EntityId>> {
+        let serialized_entities = scene.read().get_entities(&self.resource_container)?;
+        let scene_identifier = scene.get_name();
+
+        let local_id_to_entity_id = self.reserve_entity_ids(&serialized_entities)?;
+        self.restore_entities(&serialized_entities, &local_id_to_entity_id, |_| {
+            vec![Box::new(SceneInstance::new(scene_identifier.clone()))]
+        })
+    }
+
+    /// Remove all the entities spawned by a scene, the entities are removed at the next sync point
+    /// Returns the ids of the removed entities
+    ///
+    /// # Arguments
+    /// * `scene_identifier` - The resource identifier of the scene
+    ///
+    #[fruity_export]
+    pub fn unload_scene(&self, scene_identifier: String) -> FruityResult<Vec<EntityId>> {
+        let mut entity_ids = Vec::new();
+        for entity_storage in [&self.entity_storage, &self.pending_entity_storage] {
+            entity_storage
+                .read()
                 .iter()
-                .map(|serialized_component| {
-                    <Box<dyn Component>>::deserialize(
-                        serialized_component,
-                        &self.resource_container,
-                        local_id_to_entity_id,
-                    )
+                .for_each(|(entity_id, mut components)| {
+                    if components.any(|component| {
+                        component
+                            .as_any_ref()
+                            .downcast_ref::<SceneInstance>()
+                            .map(|scene_instance| scene_instance.scene == scene_identifier)
+                            .unwrap_or(false)
+                    }) {
+                        entity_ids.push(entity_id);
+                    }
+                });
+        }
+
+        entity_ids
+            .iter()
+            .try_for_each(|entity_id| self.remove_entity(*entity_id).map(|_| ()))?;
+
+        Ok(entity_ids)
+    }
+
+    /// Get the ids of all the entities by local id, the local id of an entity is its slot index
+    /// It's used to deserialize the references to the existing entities
+    pub(crate) fn get_local_id_to_entity_id(&self) -> HashMap<u64, EntityId> {
+        self.entity_storage
+            .read()
+            .iter_ids()
+            .chain(self.pending_entity_storage.read().iter_ids())
+            .map(|entity_id| (entity_id.index() as u64, entity_id))
+            .collect()
+    }
+
+    /// Spawn the entities of a prefab, every entity keeps a link to the prefab with a [`PrefabInstance`] component
+    /// Returns the ids of the created entities, the first one is the root of the instance
+    ///
+    /// # Arguments
+    /// * `prefab` - The prefab resource
+    /// * `overrides` - The fields changed for this instance
+    ///
+    #[fruity_export]
+    pub fn instantiate_prefab(
+        &self,
+        prefab: ResourceReference<PrefabResource>,
+        overrides: Option<Vec<PrefabOverride>>,
+    ) -> FruityResult<Vec<EntityId>> {
+        let serialized_entities = prefab.read().get_entities(&self.resource_container)?;
+        let root_local_id = match serialized_entities.first() {
+            Some(serialized_entity) => serialized_entity.local_id,
+            None => return Ok(Vec::new()),
+        };
+
+        let overrides = overrides.unwrap_or_default();
+        if let Some(prefab_override) = overrides.iter().find(|prefab_override| {
+            !serialized_entities.iter().any(|serialized_entity| {
+                prefab_override.applies_to(serialized_entity, root_local_id)
+            })
+        }) {
+            return Err(FruityError::GenericFailure(format!(
+                "Cannot override the entity with local id {:?}, the prefab {} has no such entity",
+                prefab_override.local_id,
+                prefab.get_name()
+            )));
+        }
+
+        let entity_overrides = |serialized_entity: &SerializedEntity| {
+            overrides
+                .iter()
+                .filter(|prefab_override| {
+                    prefab_override.applies_to(serialized_entity, root_local_id)
                 })
-                .try_collect()?,
-        )?;
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        // The overrides are applied to the serialized components, before any entity is created
+        let serialized_entities =
+            serialized_entities
+                .iter()
+                .map(|serialized_entity| {
+                    let mut serialized_entity = serialized_entity.clone();
+                    entity_overrides(&serialized_entity).iter().try_for_each(
+                        |prefab_override| prefab_override.apply(&mut serialized_entity.components),
+                    )?;
 
-        local_id_to_entity_id.insert(serialized_entity.local_id, entity_id);
+                    FruityResult::Ok(serialized_entity)
+                })
+                .try_collect::<Vec<_>>()?;
 
-        Ok(())
+        let local_id_to_entity_id = self.reserve_entity_ids(&serialized_entities)?;
+        let root = local_id_to_entity_id[&root_local_id];
+        let prefab_identifier = prefab.get_name();
+        self.restore_entities(
+            &serialized_entities,
+            &local_id_to_entity_id,
+            |serialized_entity| {
+                vec![Box::new(PrefabInstance {
+                    prefab: prefab_identifier.clone(),
+                    local_id: serialized_entity.local_id,
+                    root: (serialized_entity.local_id != root_local_id).then_some(root),
+                    overrides: entity_overrides(serialized_entity),
+                })]
+            },
+        )
+    }
+
+    /// Apply the current state of a prefab to all its instances
+    ///
+    /// The components of the prefab replace the instance components of the same type, the instance overrides
+    /// are applied again
+    /// The entities added or removed from the prefab since the instances have been spawned are ignored
+    ///
+    /// # Arguments
+    /// * `prefab` - The prefab resource
+    ///
+    #[fruity_export]
+    pub fn propagate_prefab(&self, prefab: ResourceReference<PrefabResource>) -> FruityResult<()> {
+        let serialized_entities = prefab.read().get_entities(&self.resource_container)?;
+        let prefab_identifier = prefab.get_name();
+
+        // Group the instance entities by the root of their instance
+        let mut instances = HashMap::<EntityId, Vec<(EntityId, PrefabInstance)>>::new();
+        for entity_storage in [&self.entity_storage, &self.pending_entity_storage] {
+            entity_storage
+                .read()
+                .iter()
+                .for_each(|(entity_id, mut components)| {
+                    let prefab_instance = components.find_map(|component| {
+                        component
+                            .as_any_ref()
+                            .downcast_ref::<PrefabInstance>()
+                            .filter(|prefab_instance| prefab_instance.prefab == prefab_identifier)
+                            .cloned()
+                    });
+
+                    if let Some(prefab_instance) = prefab_instance {
+                        instances
+                            .entry(prefab_instance.root.unwrap_or(entity_id))
+                            .or_default()
+                            .push((entity_id, prefab_instance));
+                    }
+                });
+        }
+
+        instances.into_values().try_for_each(|instance_entities| {
+            let local_id_to_entity_id = instance_entities
+                .iter()
+                .map(|(entity_id, prefab_instance)| (prefab_instance.local_id, *entity_id))
+                .collect::<HashMap<_, _>>();
+
+            instance_entities
+                .into_iter()
+                .try_for_each(|(entity_id, prefab_instance)| {
+                    match serialized_entities.iter().find(|serialized_entity| {
+                        serialized_entity.local_id == prefab_instance.local_id
+                    }) {
+                        Some(serialized_entity) => {
+                            let components = prefab_instance.deserialize_components(
+                                serialized_entity,
+                                &self.resource_container,
+                                &local_id_to_entity_id,
+                            )?;
+
+                            self.mutate_entity(entity_id, ComponentMutation::Insert(components))
+                        }
+                        None => Ok(()),
+                    }
+                })
+        })
     }
 
     /// Apply all the pending mutations, create an entity, add components to an entity, remove components to an entity or delete an entity
+    /// The commands recorded since the last call are applied too
     pub unsafe fn apply_pending_mutations(&self) -> FruityResult<()> {
         profile_scope!("apply_pending_mutations");
 
+        // Apply the direct mutations first so the commands can target the entities created by them
+        self.apply_pending_storage()?;
+
+        let commands = {
+            let mut command_queues = self.command_queues.lock();
+            let commands = command_queues
+                .iter()
+                .flat_map(|queue| queue.lock().drain(..).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+
+            // The queues are kept until drained, so the commands of a dropped buffer are still applied
+            command_queues.retain(|queue| Arc::strong_count(queue) > 1);
+            commands
+        };
+
+        if !commands.is_empty() {
+            // The commands that succeeded are moved to the main storage even if others failed
+            let result = self.apply_commands(commands);
+            self.apply_pending_storage()?;
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply the commands recorded by [`Commands`] into the pending storage
+    /// The commands that target the same entity are merged, so an entity is moved only once
+    /// The commands that target an entity that is no longer alive are skipped
+    /// A failing command doesn't stop the others, the ids reserved by the spawns that failed are freed
+    fn apply_commands(&self, commands: Vec<Command>) -> FruityResult<()> {
+        let mut spawned: Vec<(EntityId, String, bool, Vec<Box<dyn Component>>)> = Vec::new();
+        let mut despawned: Vec<EntityId> = Vec::new();
+
+        // The spawned entities are mutated before their creation, the others are migrated
+        let mutate = |spawned: &mut Vec<(EntityId, String, bool, Vec<Box<dyn Component>>)>,
+                      entity_id: EntityId,
+                      mutation: ComponentMutation| {
+            match spawned.iter_mut().find(|spawned| spawned.0 == entity_id) {
+                Some(spawned) => {
+                    mutation.apply(&mut spawned.3);
+                    Ok(())
+                }
+                None => self.mutate_entity(entity_id, mutation),
+            }
+        };
+
+        let mut errors = Vec::new();
+        commands.into_iter().for_each(|command| {
+            let result = match command {
+                // The entity is already removed or its id is stale, the command is skipped so the
+                // next ones are still applied
+                Command::Despawn(entity_id)
+                | Command::Insert { entity_id, .. }
+                | Command::Remove { entity_id, .. }
+                    if despawned.contains(&entity_id)
+                        || !self.id_allocator.lock().is_alive(entity_id) =>
+                {
+                    Ok(())
+                }
+                Command::Spawn {
+                    entity_id,
+                    name,
+                    enabled,
+                    components,
+                } => {
+                    spawned.push((entity_id, name, enabled, components));
+                    Ok(())
+                }
+                Command::Despawn(entity_id) => {
+                    if let Some(index) = spawned.iter().position(|spawned| spawned.0 == entity_id) {
+                        spawned.remove(index);
+                        self.id_allocator.lock().free(entity_id);
+                        Ok(())
+                    } else {
+                        self.remove_entity(entity_id)
+                            .map(|_| despawned.push(entity_id))
+                    }
+                }
+                Command::Insert {
+                    entity_id,
+                    components,
+                } => mutate(
+                    &mut spawned,
+                    entity_id,
+                    ComponentMutation::Insert(components),
+                ),
+                Command::Remove {
+                    entity_id,
+                    script_object_type,
+                } => mutate(
+                    &mut spawned,
+                    entity_id,
+                    ComponentMutation::RemoveAll(script_object_type),
+                ),
+            };
+
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        });
+
+        spawned
+            .into_iter()
+            .for_each(|(entity_id, name, enabled, components)| {
+                if let Err(error) = self.create_entity_with_id(entity_id, name, enabled, components)
+                {
+                    // The entity was not added, so its reserved id is freed to be reused
+                    if self.get_entity_reference(entity_id).is_none() {
+                        self.id_allocator.lock().free(entity_id);
+                    }
+
+                    errors.push(error);
+                }
+            });
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(FruityError::GenericFailure(
+                errors
+                    .iter()
+                    .map(|error| error.get_message())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+        }
+    }
+
+    /// Move the entities of the pending storage into the main storage
+    fn apply_pending_storage(&self) -> FruityResult<()> {
+        increment_change_tick();
+
+        // Move the entities whose components changed to their new archetype
+        let pending_migrations = std::mem::take(&mut *self.pending_migrations.lock());
+        let migrated_entities = pending_migrations
+            .iter()
+            .map(|(entity_id, _)| *entity_id)
+            .collect::<Vec<_>>();
+
+        // The component types are only collected for the structural hooks
+        let has_structural_hooks = self.component_hooks.has_structural_hooks();
+        pending_migrations
+            .into_iter()
+            .try_for_each(|(entity_id, mutations)| {
+                // The entity stays in its archetype if only its sparse set components change
+                let is_sparse_set_mutation = self
+                    .entity_storage
+                    .read()
+                    .is_sparse_set_mutation(entity_id, &mutations);
+
+                let mut removed_types = Vec::new();
+                let mut types_before = HashSet::new();
+                let mut types_after = HashSet::new();
+                let mutate = |components: &mut Vec<Box<dyn Component>>| {
+                    if has_structural_hooks {
+                        types_before = component_types(components);
+                    }
+                    mutations
+                        .into_iter()
+                        .for_each(|mutation| removed_types.append(&mut mutation.apply(components)));
+                    if has_structural_hooks {
+                        types_after = component_types(components);
+                    }
+                };
+
+                let moved_entities = if is_sparse_set_mutation {
+                    self.entity_storage
+                        .write()
+                        .mutate_sparse_set_components(entity_id, mutate)?
+                        .map(|_| Vec::new())
+                } else {
+                    self.entity_storage
+                        .write()
+                        .migrate_entity(entity_id, mutate)?
+                        .map(|(_, _, moved_entities)| moved_entities)
+                };
+
+                if let Some(moved_entities) = moved_entities {
+                    let entity_storage_reader = self.entity_storage.read();
+                    removed_types.into_iter().for_each(|script_object_type| {
+                        entity_storage_reader
+                            .removed_components
+                            .insert(script_object_type, entity_id)
+                    });
+                    std::mem::drop(entity_storage_reader);
+
+                    if !is_sparse_set_mutation {
+                        self.notify_migration(&self.entity_storage, entity_id, moved_entities)?;
+                    }
+
+                    self.notify_component_hooks(entity_id, &types_before, &types_after)?;
+                }
+
+                FruityResult::Ok(())
+            })?;
+
+        // Remove the entities, the ones that are still pending are removed once they are appended
+        let (pending_entity_to_remove, entity_to_remove) = self
+            .pending_entity_to_remove
+            .write()
+            .drain(..)
+            .partition::<Vec<_>, _>(|entity_id| {
+                self.pending_entity_storage.read().has_entity(*entity_id)
+            });
+        self.remove_entities(entity_to_remove)?;
+
         let new_ids = self
             .entity_storage
             .write()
@@ -406,17 +1587,52 @@ impl EntityService {
             ))
         })?;
 
-        self.pending_entity_to_remove
+        self.remove_entities(pending_entity_to_remove)?;
+
+        // Forget the removed components of the deleted entities
+        let entity_storage_reader = self.entity_storage.read();
+        entity_storage_reader
+            .removed_components
+            .retain(|entity_id| entity_storage_reader.has_entity(entity_id));
+
+        // Index the new, renamed and tagged entities
+        self.entity_index
             .write()
-            .drain(..)
-            .try_for_each(|entity_id| {
-                self.entity_storage.write().remove_entity(entity_id)?;
-                self.on_deleted.send(entity_id)?;
+            .refresh(&entity_storage_reader, migrated_entities);
 
-                FruityResult::Ok(())
-            })?;
+        let changed_components = self.component_hooks.collect_changed(&entity_storage_reader);
+        std::mem::drop(entity_storage_reader);
 
-        Ok(())
+        changed_components
+            .into_iter()
+            .try_for_each(|(script_object_type, entity_id)| {
+                match self.get_entity_reference(entity_id) {
+                    Some(entity_reference) => self
+                        .component_hooks
+                        .notify_changed(&script_object_type, entity_reference),
+                    None => Ok(()),
+                }
+            })
+    }
+
+    /// Remove entities from the main storage and free their ids
+    fn remove_entities(&self, entity_ids: Vec<EntityId>) -> FruityResult<()> {
+        entity_ids.into_iter().try_for_each(|entity_id| {
+            self.notify_entity_removed(&self.entity_storage, entity_id)?;
+
+            if self
+                .entity_storage
+                .write()
+                .remove_entity(entity_id)?
+                .is_some()
+            {
+                self.id_allocator.lock().free(entity_id);
+                self.entity_index.write().remove(entity_id);
+                self.on_deleted.send(entity_id)?;
+            }
+
+            FruityResult::Ok(())
+        })
     }
 }
 
@@ -425,3 +1641,329 @@ impl Debug for EntityService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        component::{SystemTicks, Tag},
+        query::{Added, Changed, WithId},
+        serialization::SerializationService,
+    };
+    use fruity_game_engine::{introspect::IntrospectFields, script_value::TryFromScriptValue};
+    use maplit::hashmap;
+
+    fn new_entity_service() -> EntityService {
+        let resource_container = ResourceContainer::new();
+        resource_container.add::<ExtensionComponentService>(
+            "extension_component_service",
+            Box::new(ExtensionComponentService::new(resource_container.clone())),
+        );
+        let mut serialization_service = SerializationService::new(resource_container.clone());
+        serialization_service.register_component::<Link>();
+        resource_container
+            .add::<SerializationService>("serialization_service", Box::new(serialization_service));
+
+        EntityService::new(resource_container)
+    }
+
+    #[test]
+    fn test_spawn_batch_notifies_the_batch_signal() {
+        let entity_service = new_entity_service();
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let batch_count = Arc::new(Mutex::new(0));
+        let tag_added = Arc::new(Mutex::new(0));
+
+        let query_handler = {
+            let created = created.clone();
+            entity_service
+                .query::<WithId>()
+                .on_created(move |entity_id| {
+                    created.lock().push(entity_id);
+                    Ok(None)
+                })
+        };
+        {
+            let batch_count = batch_count.clone();
+            entity_service.on_batch_created.add_observer(move |_| {
+                *batch_count.lock() += 1;
+                Ok(())
+            });
+        }
+        {
+            let tag_added = tag_added.clone();
+            entity_service
+                .on_component_added::<Tag>()
+                .add_observer(move |_| {
+                    *tag_added.lock() += 1;
+                    Ok(())
+                });
+        }
+
+        let entity_ids = entity_service
+            .spawn_batch(
+                "entity_".to_string(),
+                vec![
+                    vec![Box::new(Tag("a".to_string())) as Box<dyn Component>],
+                    vec![],
+                    vec![Box::new(Tag("b".to_string())) as Box<dyn Component>],
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(*batch_count.lock(), 1);
+        assert_eq!(*tag_added.lock(), 2);
+        assert_eq!(*created.lock(), entity_ids);
+
+        // The handler unregisters the query observers
+        query_handler.dispose();
+        entity_service
+            .spawn_batch("other_".to_string(), vec![vec![]])
+            .unwrap();
+        assert_eq!(created.lock().len(), 3);
+    }
+
+    /// Collect the ids of the entities that match a filter
+    fn find_ids<F: QueryFilter + 'static>(entity_service: &EntityService) -> Vec<EntityId> {
+        let entity_ids = Arc::new(Mutex::new(Vec::new()));
+        entity_service
+            .query_filtered::<WithId, F>()
+            .for_each(|entity_id| {
+                entity_ids.lock().push(entity_id);
+                Ok(())
+            })
+            .unwrap();
+
+        let mut entity_ids = entity_ids.lock().clone();
+        entity_ids.sort();
+        entity_ids
+    }
+
+    #[test]
+    fn test_migration_keeps_the_ticks_of_the_other_components() {
+        let entity_service = new_entity_service();
+        let entity_id = entity_service
+            .create_entity(
+                "entity".to_string(),
+                true,
+                vec![Box::new(Tag("tag".to_string())) as Box<dyn Component>],
+            )
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        // A system that ran once the entity was created
+        let system_ticks = SystemTicks {
+            last_run: increment_change_tick(),
+            this_run: increment_change_tick(),
+        };
+
+        entity_service
+            .insert(entity_id, SceneInstance::new("scene".to_string()))
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        system_ticks.scope(|| {
+            assert_eq!(
+                find_ids::<Added<SceneInstance>>(&entity_service),
+                vec![entity_id]
+            );
+            assert!(find_ids::<Changed<Name>>(&entity_service).is_empty());
+            assert!(find_ids::<Added<Name>>(&entity_service).is_empty());
+            assert!(find_ids::<Changed<Tag>>(&entity_service).is_empty());
+        });
+
+        entity_service.remove::<SceneInstance>(entity_id).unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        system_ticks.scope(|| {
+            assert!(find_ids::<Changed<Name>>(&entity_service).is_empty());
+            assert!(find_ids::<Changed<Enabled>>(&entity_service).is_empty());
+            assert!(find_ids::<Changed<Tag>>(&entity_service).is_empty());
+        });
+    }
+
+    /// A component that refers to another entity
+    #[derive(Debug, Clone, Default, Component, FruityAny)]
+    #[fruity_export]
+    pub struct Link {
+        /// The linked entity
+        pub entity: Option<EntityReference>,
+    }
+
+    #[fruity_export]
+    impl Link {}
+
+    fn serialized_link(local_id: u64) -> Settings {
+        Settings::Object(hashmap! {
+            "class_name".to_string() => Settings::String("Link".to_string()),
+            "fields".to_string() => Settings::Object(hashmap! {
+                "entity".to_string() => Settings::F64(local_id as f64),
+            }),
+        })
+    }
+
+    fn get_linked_entities(
+        entity_service: &EntityService,
+        entity_id: EntityId,
+    ) -> Vec<EntityReference> {
+        entity_service
+            .get_entity_reference(entity_id)
+            .unwrap()
+            .get_components_by_type::<Link>()
+            .unwrap()
+            .into_iter()
+            .map(|link| {
+                let entity = link.get_field_value("entity").unwrap();
+                <Option<EntityReference>>::from_script_value(entity)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn serialized_entity(local_id: u64, components: Vec<Settings>) -> SerializedEntity {
+        SerializedEntity {
+            local_id,
+            name: format!("entity_{}", local_id),
+            enabled: true,
+            components,
+        }
+    }
+
+    #[test]
+    fn test_restore_entities_uses_the_reserved_ids() {
+        let entity_service = new_entity_service();
+        let serialized_entities = vec![serialized_entity(7, vec![]), serialized_entity(3, vec![])];
+
+        let local_id_to_entity_id = entity_service
+            .reserve_entity_ids(&serialized_entities)
+            .unwrap();
+        let entity_ids = entity_service
+            .restore_entities(&serialized_entities, &local_id_to_entity_id, |_| Vec::new())
+            .unwrap();
+
+        assert_eq!(
+            entity_ids,
+            vec![local_id_to_entity_id[&7], local_id_to_entity_id[&3]]
+        );
+        assert_eq!(
+            entity_service
+                .get_entity_reference(entity_ids[1])
+                .unwrap()
+                .get_name()
+                .unwrap(),
+            "entity_3"
+        );
+    }
+
+    #[test]
+    fn test_restore_entities_resolves_the_references_to_the_next_entities() {
+        let entity_service = new_entity_service();
+        let serialized_entities = vec![
+            serialized_entity(0, vec![serialized_link(2)]),
+            serialized_entity(1, vec![]),
+            serialized_entity(2, vec![serialized_link(0)]),
+        ];
+
+        let local_id_to_entity_id = entity_service
+            .reserve_entity_ids(&serialized_entities)
+            .unwrap();
+        let entity_ids = entity_service
+            .restore_entities(&serialized_entities, &local_id_to_entity_id, |_| Vec::new())
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let linked_entity = get_linked_entities(&entity_service, entity_ids[0]).remove(0);
+        assert_eq!(linked_entity.get_entity_id().unwrap(), entity_ids[2]);
+        assert_eq!(linked_entity.get_name().unwrap(), "entity_2");
+
+        let linked_entity = get_linked_entities(&entity_service, entity_ids[2]).remove(0);
+        assert_eq!(linked_entity.get_entity_id().unwrap(), entity_ids[0]);
+        assert_eq!(linked_entity.get_name().unwrap(), "entity_0");
+    }
+
+    #[test]
+    fn test_binary_snapshot_keeps_the_references_to_the_next_archetypes() {
+        let entity_service = new_entity_service();
+
+        // The children are stored in an archetype with one link, it sorts before the archetype of the root
+        // with two links so the children are written before the root they refer to
+        let serialized_entities = vec![
+            serialized_entity(0, vec![serialized_link(1), serialized_link(2)]),
+            serialized_entity(1, vec![serialized_link(0)]),
+            serialized_entity(2, vec![serialized_link(0)]),
+        ];
+        let local_id_to_entity_id = entity_service
+            .reserve_entity_ids(&serialized_entities)
+            .unwrap();
+        let entity_ids = entity_service
+            .restore_entities(&serialized_entities, &local_id_to_entity_id, |_| Vec::new())
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let get_archetype_index = |entity_id| {
+            entity_service
+                .entity_storage
+                .read()
+                .get_entity_location(entity_id)
+                .unwrap()
+                .archetype_index
+        };
+        assert!(get_archetype_index(entity_ids[1]) < get_archetype_index(entity_ids[0]));
+
+        let mut snapshot = Vec::new();
+        entity_service.write_snapshot(&mut snapshot).unwrap();
+        entity_service
+            .read_snapshot(true, &mut snapshot.as_slice())
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let get_linked_names = |name: &str| {
+            let entity_id = entity_service
+                .find_by_path(name.to_string())
+                .unwrap()
+                .unwrap();
+            let mut names = get_linked_entities(&entity_service, entity_id)
+                .iter()
+                .map(|linked_entity| linked_entity.get_name().unwrap())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(get_linked_names("entity_0"), vec!["entity_1", "entity_2"]);
+        assert_eq!(get_linked_names("entity_1"), vec!["entity_0"]);
+        assert_eq!(get_linked_names("entity_2"), vec!["entity_0"]);
+    }
+
+    #[test]
+    fn test_reserve_entity_ids_rejects_the_duplicated_local_ids() {
+        let entity_service = new_entity_service();
+        let serialized_entities = vec![serialized_entity(1, vec![]), serialized_entity(1, vec![])];
+
+        assert!(entity_service
+            .reserve_entity_ids(&serialized_entities)
+            .is_err());
+    }
+
+    #[test]
+    fn test_restore_entities_frees_the_ids_of_the_entities_not_created() {
+        let entity_service = new_entity_service();
+        let serialized_entities = vec![
+            serialized_entity(0, vec![]),
+            serialized_entity(1, vec![Settings::String("not a component".to_string())]),
+            serialized_entity(2, vec![]),
+        ];
+
+        let local_id_to_entity_id = entity_service
+            .reserve_entity_ids(&serialized_entities)
+            .unwrap();
+        assert!(entity_service
+            .restore_entities(&serialized_entities, &local_id_to_entity_id, |_| Vec::new())
+            .is_err());
+
+        let id_allocator = entity_service.id_allocator.lock();
+        assert!(id_allocator.is_alive(local_id_to_entity_id[&0]));
+        assert!(!id_allocator.is_alive(local_id_to_entity_id[&1]));
+        assert!(!id_allocator.is_alive(local_id_to_entity_id[&2]));
+    }
+}
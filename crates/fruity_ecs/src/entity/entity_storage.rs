@@ -1,9 +1,13 @@
-use super::{Archetype, ArchetypeComponentTypes, EntityId, EntityLocation};
+use super::{
+    Archetype, ArchetypeComponentTypes, ComponentMutation, EntityId, EntityLocation, SparseSets,
+};
 use crate::{
-    component::{Component, ExtensionComponentService},
-    query::{EntityStorageQuery, QueryParam},
+    component::{Component, ComponentTicks, ExtensionComponentService, RemovedComponents},
+    query::{EntityStorageQuery, QueryCache, QueryParam},
+};
+use fruity_game_engine::{
+    script_value::ScriptObjectType, signal::Signal, sync::Arc, FruityError, FruityResult,
 };
-use fruity_game_engine::{signal::Signal, FruityError, FruityResult};
 use sorted_vec::SortedVec;
 use std::{collections::HashMap, ptr::NonNull};
 
@@ -22,6 +26,19 @@ pub struct EntityStorage {
     /// Is raised when the archetypes vec is reallocated to increase capacity
     /// The parameter is the gap between the old and the new address of the archetypes vec
     pub on_archetypes_reallocated: Signal<isize>,
+
+    /// The archetypes matched by the queries, memoized by query type
+    pub(crate) query_cache: QueryCache,
+
+    /// The components removed from the entities, used by the [`Removed`] query filter
+    ///
+    /// [`Removed`]: crate::query::Removed
+    pub(crate) removed_components: Arc<RemovedComponents>,
+
+    /// The components stored outside of the archetypes, see [`ComponentStorage::is_sparse_set`]
+    ///
+    /// [`ComponentStorage::is_sparse_set`]: crate::component::ComponentStorage::is_sparse_set
+    pub(crate) sparse_sets: Arc<SparseSets>,
 }
 
 impl EntityStorage {
@@ -33,6 +50,9 @@ impl EntityStorage {
             archetypes: SortedVec::new(),
             on_archetype_created: Signal::new(),
             on_archetypes_reallocated: Signal::new(),
+            query_cache: QueryCache::default(),
+            removed_components: Arc::new(RemovedComponents::default()),
+            sparse_sets: Arc::new(SparseSets::default()),
         }
     }
 
@@ -74,17 +94,18 @@ impl EntityStorage {
         extension_component_service: Option<&ExtensionComponentService>,
         default_components: Option<Vec<Box<dyn Component>>>,
     ) -> FruityResult<(EntityLocation, ArchetypeComponentTypes)> {
-        if self.entity_locations.contains_key(&entity_id) {
-            return Err(FruityError::GenericFailure(
-                format!("Entity with id {:?} already exists", entity_id).into(),
-            ));
-        }
+        self.check_entity_is_new(entity_id)?;
+
+        // The components stored in sparse sets are kept outside of the archetype
+        let (sparse_set_components, components) = components
+            .into_iter()
+            .partition::<Vec<_>, _>(|component| is_sparse_set_component(component.as_ref()));
 
         // Generate the archetype component types
         let component_types = ArchetypeComponentTypes::from_boxed_components(&components)?;
 
         // Insert the entity into the archetype, create the archetype if needed
-        let (entity_location, archetype_types) = match self.archetype_types.get(&component_types) {
+        let entity_location = match self.archetype_types.get(&component_types) {
             Some(archetype_index) => {
                 // Safe cause Archetype::component_types never change
                 let archetype =
@@ -97,79 +118,383 @@ impl EntityStorage {
                     default_components,
                 )?;
 
-                (
-                    EntityLocation {
-                        archetype_index: *archetype_index,
-                        entity_index,
-                    },
-                    archetype.get_component_types().clone(),
-                )
+                EntityLocation {
+                    archetype_index: *archetype_index,
+                    entity_index,
+                }
             }
-            None => {
-                let archetype = Archetype::new(
-                    0,
-                    entity_id,
-                    components,
-                    extension_component_service,
-                    default_components,
-                )?;
+            None => self.create_archetype(
+                component_types,
+                entity_id,
+                components,
+                extension_component_service,
+                default_components,
+            )?,
+        };
+        let archetype_types = self.archetypes[entity_location.archetype_index]
+            .get_component_types()
+            .clone();
 
-                // Check if a reallocation will be occurred on next archetype insert
-                let is_archetypes_about_to_reallocate =
-                    self.archetypes.len() + 1 > self.archetypes.capacity();
-                let archetypes_old_ptr = self.archetypes.as_ptr();
-
-                // Insert the archetype
-                let archetype_index = self.archetypes.push(archetype);
-                self.archetype_types
-                    .insert(component_types, archetype_index);
-
-                // Update the archetypes indexes
-                unsafe { self.archetypes.get_unchecked_mut_vec() }
-                    .iter_mut()
-                    .enumerate()
-                    .for_each(|(index, archetype)| {
-                        archetype.index = index;
-                    });
-
-                // Update the archetype index in entity locations
-                self.entity_locations
-                    .iter_mut()
-                    .for_each(|(_, entity_location)| {
-                        if entity_location.archetype_index >= archetype_index {
-                            entity_location.archetype_index += 1;
-                        }
-                    });
-
-                // Notify memory moves
-                if is_archetypes_about_to_reallocate {
-                    let archetypes_new_ptr = self.archetypes.as_ptr();
-                    let addr_diff =
-                        unsafe { archetypes_new_ptr.byte_offset_from(archetypes_old_ptr) };
-
-                    self.on_archetypes_reallocated.send(addr_diff)?;
+        self.entity_locations
+            .insert(entity_id, entity_location.clone());
+        self.sparse_sets.insert(entity_id, sparse_set_components)?;
+
+        Ok((entity_location, archetype_types))
+    }
+
+    /// Add several entities to the storage
+    /// The entities are grouped by archetype, every archetype is looked up and allocated once for its whole group
+    pub fn create_entities(
+        &mut self,
+        entities: Vec<(
+            EntityId,
+            Vec<Box<dyn Component>>,
+            Option<Vec<Box<dyn Component>>>,
+        )>,
+        extension_component_service: Option<&ExtensionComponentService>,
+    ) -> FruityResult<()> {
+        // Group the entities by the component types stored in the archetypes, keep the creation order
+        let mut group_indexes = HashMap::<ArchetypeComponentTypes, usize>::new();
+        let mut groups = Vec::<(ArchetypeComponentTypes, Vec<_>)>::new();
+        for (entity_id, components, default_components) in entities {
+            self.check_entity_is_new(entity_id)?;
+
+            let (sparse_set_components, components) = components
+                .into_iter()
+                .partition::<Vec<_>, _>(|component| is_sparse_set_component(component.as_ref()));
+            let component_types = ArchetypeComponentTypes::from_boxed_components(&components)?;
+
+            let group_index = match group_indexes.get(&component_types) {
+                Some(group_index) => *group_index,
+                None => {
+                    group_indexes.insert(component_types.clone(), groups.len());
+                    groups.push((component_types, Vec::new()));
+                    groups.len() - 1
                 }
+            };
 
-                let archetype = &self.archetypes[archetype_index];
+            groups[group_index].1.push((
+                entity_id,
+                components,
+                default_components,
+                sparse_set_components,
+            ));
+        }
 
-                self.on_archetype_created.send(unsafe {
-                    NonNull::new_unchecked(archetype as *const Archetype as *mut Archetype)
-                })?;
+        groups.into_iter().try_for_each(|(component_types, group)| {
+            let mut group = group.into_iter();
 
-                (
-                    EntityLocation {
-                        archetype_index,
-                        entity_index: 0,
-                    },
-                    archetype.get_component_types().clone(),
-                )
+            // The first entity of the group creates the archetype if needed
+            let archetype_index = match self.archetype_types.get(&component_types) {
+                Some(archetype_index) => *archetype_index,
+                None => {
+                    let (entity_id, components, default_components, sparse_set_components) =
+                        group.next().unwrap();
+                    let entity_location = self.create_archetype(
+                        component_types,
+                        entity_id,
+                        components,
+                        extension_component_service,
+                        default_components,
+                    )?;
+                    self.entity_locations
+                        .insert(entity_id, entity_location.clone());
+                    self.sparse_sets.insert(entity_id, sparse_set_components)?;
+
+                    entity_location.archetype_index
+                }
+            };
+
+            // Safe cause Archetype::component_types is not modified
+            let archetype =
+                unsafe { &mut self.archetypes.get_unchecked_mut_vec()[archetype_index] };
+            archetype.reserve(group.len());
+
+            group.try_for_each(
+                |(entity_id, components, default_components, sparse_set_components)| {
+                    let entity_index = archetype.len();
+                    archetype.add_entity(
+                        entity_id,
+                        components,
+                        extension_component_service,
+                        default_components,
+                    )?;
+
+                    self.entity_locations.insert(
+                        entity_id,
+                        EntityLocation {
+                            archetype_index,
+                            entity_index,
+                        },
+                    );
+                    self.sparse_sets.insert(entity_id, sparse_set_components)
+                },
+            )
+        })
+    }
+
+    fn check_entity_is_new(&self, entity_id: EntityId) -> FruityResult<()> {
+        if self.entity_locations.contains_key(&entity_id) {
+            Err(FruityError::GenericFailure(
+                format!("Entity with id {:?} already exists", entity_id).into(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Create the archetype of an entity, the entity is its first one
+    fn create_archetype(
+        &mut self,
+        component_types: ArchetypeComponentTypes,
+        entity_id: EntityId,
+        components: Vec<Box<dyn Component>>,
+        extension_component_service: Option<&ExtensionComponentService>,
+        default_components: Option<Vec<Box<dyn Component>>>,
+    ) -> FruityResult<EntityLocation> {
+        let archetype = Archetype::new(
+            0,
+            entity_id,
+            components,
+            extension_component_service,
+            default_components,
+            self.sparse_sets.clone(),
+        )?;
+
+        // Check if a reallocation will be occurred on next archetype insert
+        let is_archetypes_about_to_reallocate =
+            self.archetypes.len() + 1 > self.archetypes.capacity();
+        let archetypes_old_ptr = self.archetypes.as_ptr();
+
+        // Insert the archetype
+        let archetype_index = self.archetypes.push(archetype);
+        self.shift_archetype_indexes(archetype_index);
+        self.archetype_types
+            .insert(component_types, archetype_index);
+
+        // Notify memory moves
+        if is_archetypes_about_to_reallocate {
+            let archetypes_new_ptr = self.archetypes.as_ptr();
+            let addr_diff = unsafe { archetypes_new_ptr.byte_offset_from(archetypes_old_ptr) };
+
+            self.on_archetypes_reallocated.send(addr_diff)?;
+        }
+
+        let archetype = &self.archetypes[archetype_index];
+
+        self.on_archetype_created.send(unsafe {
+            NonNull::new_unchecked(archetype as *const Archetype as *mut Archetype)
+        })?;
+
+        Ok(EntityLocation {
+            archetype_index,
+            entity_index: 0,
+        })
+    }
+
+    /// Update the indexes after an archetype has been inserted, the next archetypes are moved by one slot
+    fn shift_archetype_indexes(&mut self, archetype_index: usize) {
+        unsafe { self.archetypes.get_unchecked_mut_vec() }
+            .iter_mut()
+            .enumerate()
+            .for_each(|(index, archetype)| {
+                archetype.index = index;
+            });
+
+        self.archetype_types.values_mut().for_each(|index| {
+            if *index >= archetype_index {
+                *index += 1;
             }
-        };
+        });
 
         self.entity_locations
-            .insert(entity_id, entity_location.clone());
+            .values_mut()
+            .for_each(|entity_location| {
+                if entity_location.archetype_index >= archetype_index {
+                    entity_location.archetype_index += 1;
+                }
+            });
+    }
 
-        Ok((entity_location, archetype_types))
+    /// Move an entity to the archetype that matches its new components
+    /// The components kept by the mutation are moved to the new archetype with their change ticks, the
+    /// sparse sets of the component types that the mutation doesn't change are not touched
+    ///
+    /// Returns the new location of the entity and the entities that have been moved into the previous archetype,
+    /// or None if the entity doesn't exist
+    pub fn migrate_entity(
+        &mut self,
+        entity_id: EntityId,
+        mutate: impl FnOnce(&mut Vec<Box<dyn Component>>),
+    ) -> FruityResult<Option<(EntityLocation, ArchetypeComponentTypes, Vec<EntityId>)>> {
+        let previous_location = match self.get_entity_location(entity_id) {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        let taken_components = self.take_entity(entity_id).unwrap_or_default();
+        let moved_entities = self.archetypes[previous_location.archetype_index].entity_ids
+            [previous_location.entity_index..]
+            .to_vec();
+
+        let mut components = Vec::new();
+        let mut previous_addresses = ComponentAddresses::new();
+        let mut previous_ticks = HashMap::new();
+        taken_components.into_iter().for_each(
+            |(script_object_type, mut type_components, ticks)| {
+                previous_addresses.insert(
+                    script_object_type.clone(),
+                    component_addresses(&type_components),
+                );
+                previous_ticks.insert(script_object_type, ticks);
+                components.append(&mut type_components);
+            },
+        );
+
+        // The sparse set components are lent as copies, see EntityStorage::replace_sparse_set_components
+        let mut sparse_set_components = self.sparse_sets.get_entity_components(entity_id);
+        let previous_sparse_set_addresses = group_component_addresses(&sparse_set_components)?;
+        components.append(&mut sparse_set_components);
+
+        mutate(&mut components);
+
+        let (sparse_set_components, components) = components
+            .into_iter()
+            .partition::<Vec<_>, _>(|component| is_sparse_set_component(component.as_ref()));
+        self.replace_sparse_set_components(
+            entity_id,
+            previous_sparse_set_addresses,
+            sparse_set_components,
+        )?;
+
+        let addresses = group_component_addresses(&components)?;
+        let (location, archetype_types) = self.create_entity(entity_id, components, None, None)?;
+
+        // The component types that the mutation doesn't add or replace keep their ticks
+        // Safe cause Archetype::component_types is not modified
+        let archetype =
+            unsafe { &mut self.archetypes.get_unchecked_mut_vec()[location.archetype_index] };
+        addresses
+            .into_iter()
+            .for_each(|(script_object_type, addresses)| {
+                let ticks = match previous_ticks.remove(&script_object_type) {
+                    Some(ticks) => ticks,
+                    None => return,
+                };
+
+                match component_type_change(&previous_addresses[&script_object_type], &addresses) {
+                    ComponentTypeChange::Unchanged => {}
+                    ComponentTypeChange::Changed => ticks.set_changed(),
+                    ComponentTypeChange::Added => return,
+                }
+
+                archetype.set_component_ticks(&script_object_type, location.entity_index, ticks);
+            });
+
+        Ok(Some((location, archetype_types, moved_entities)))
+    }
+
+    /// Returns true if mutations only change the components stored in sparse sets, the entity can then
+    /// stay in its archetype, see [`EntityStorage::mutate_sparse_set_components`]
+    pub(crate) fn is_sparse_set_mutation(
+        &self,
+        entity_id: EntityId,
+        mutations: &[ComponentMutation],
+    ) -> bool {
+        self.get_entity_location(entity_id)
+            .map(|location| {
+                let component_types =
+                    self.archetypes[location.archetype_index].get_component_types();
+                mutations
+                    .iter()
+                    .all(|mutation| mutation.is_sparse_set_mutation(component_types))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Change the components of an entity that are stored in sparse sets, the entity is not moved
+    ///
+    /// Returns the location of the entity or None if the entity doesn't exist
+    pub fn mutate_sparse_set_components(
+        &mut self,
+        entity_id: EntityId,
+        mutate: impl FnOnce(&mut Vec<Box<dyn Component>>),
+    ) -> FruityResult<Option<EntityLocation>> {
+        let location = match self.get_entity_location(entity_id) {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        // The sparse set components are lent as copies, see EntityStorage::replace_sparse_set_components
+        let mut components = self.sparse_sets.get_entity_components(entity_id);
+        let previous_addresses = group_component_addresses(&components)?;
+        mutate(&mut components);
+
+        if let Some(component) = components
+            .iter()
+            .find(|component| !is_sparse_set_component(component.as_ref()))
+        {
+            return Err(FruityError::GenericFailure(format!(
+                "The component {:?} is not stored in a sparse set, the entity {:?} should be migrated",
+                component, entity_id
+            )));
+        }
+
+        self.replace_sparse_set_components(entity_id, previous_addresses, components)?;
+
+        Ok(Some(location))
+    }
+
+    /// Write the sparse set components of an entity after a mutation
+    ///
+    /// The mutation is applied over copies of the components, the sets of the types whose copies are
+    /// all kept as they are are not touched. The types that only lost components keep their ticks and
+    /// are marked as changed, the other changed types are marked as added
+    fn replace_sparse_set_components(
+        &self,
+        entity_id: EntityId,
+        previous_addresses: ComponentAddresses,
+        components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<()> {
+        let mut grouped_components = HashMap::<ScriptObjectType, Vec<Box<dyn Component>>>::new();
+        components.into_iter().try_for_each(|component| {
+            grouped_components
+                .entry(component.get_component_type_id()?.get_script_object_type())
+                .or_default()
+                .push(component);
+
+            FruityResult::Ok(())
+        })?;
+
+        // The types whose components have all been removed
+        previous_addresses.keys().for_each(|script_object_type| {
+            grouped_components
+                .entry(script_object_type.clone())
+                .or_default();
+        });
+
+        grouped_components
+            .into_iter()
+            .try_for_each(|(script_object_type, components)| {
+                let change = match previous_addresses.get(&script_object_type) {
+                    Some(previous_addresses) => {
+                        component_type_change(previous_addresses, &component_addresses(&components))
+                    }
+                    None => ComponentTypeChange::Added,
+                };
+
+                match change {
+                    ComponentTypeChange::Unchanged => Ok(()),
+                    ComponentTypeChange::Changed => {
+                        self.sparse_sets
+                            .replace(script_object_type, entity_id, components, true)
+                    }
+                    ComponentTypeChange::Added => {
+                        self.sparse_sets
+                            .replace(script_object_type, entity_id, components, false)
+                    }
+                }
+            })
     }
 
     /// Remove an entity from the storage
@@ -177,20 +502,42 @@ impl EntityStorage {
         &mut self,
         entity_id: EntityId,
     ) -> FruityResult<Option<Vec<Box<dyn Component>>>> {
-        let entity_location =
-            if let Some(entity_location) = self.entity_locations.remove(&entity_id) {
-                entity_location
-            } else {
-                return Ok(None);
-            };
+        let mut result = match self.take_entity(entity_id) {
+            Some(components) => components
+                .into_iter()
+                .flat_map(|(_, components, _)| components)
+                .collect::<Vec<_>>(),
+            None => return Ok(None),
+        };
+        result.append(&mut self.sparse_sets.remove_entity(entity_id));
+
+        Ok(Some(result))
+    }
+
+    /// Take an entity out of its archetype, its components are returned grouped by type with their change ticks
+    /// The sparse set components are not taken
+    fn take_entity(
+        &mut self,
+        entity_id: EntityId,
+    ) -> Option<Vec<(ScriptObjectType, Vec<Box<dyn Component>>, ComponentTicks)>> {
+        let entity_location = self.entity_locations.remove(&entity_id)?;
 
         // Safe cause Archetype::component_types never change
         let archetype = unsafe {
             &mut self.archetypes.get_unchecked_mut_vec()[entity_location.archetype_index]
         };
-        let result = archetype.remove_entity(entity_location.entity_index)?;
+        let components = archetype.take_entity(entity_location.entity_index);
 
-        Ok(Some(result))
+        // The next entities of the archetype are moved by one slot
+        archetype.entity_ids[entity_location.entity_index..]
+            .iter()
+            .for_each(|entity_id| {
+                if let Some(location) = self.entity_locations.get_mut(entity_id) {
+                    location.entity_index -= 1;
+                }
+            });
+
+        Some(components)
     }
 
     /// Iterate over all entities
@@ -214,6 +561,7 @@ impl EntityStorage {
     /// Clear the storage
     pub fn clear(&mut self) -> FruityResult<()> {
         self.entity_locations.clear();
+        self.sparse_sets.clear();
         unsafe {
             self.archetypes
                 .get_unchecked_mut_vec()
@@ -226,7 +574,12 @@ impl EntityStorage {
 
     /// Append the entities of another storage to this one
     pub fn append(&mut self, other: &mut Self) -> FruityResult<Vec<EntityId>> {
+        self.sparse_sets.append(&other.sparse_sets)?;
+
         other.archetypes.drain(..).try_for_each(|mut archetype| {
+            // The entities are visible to the queries from now
+            archetype.mark_as_added();
+
             let component_types = archetype.get_component_types().clone();
 
             let archetype = match self.archetype_types.get(&component_types) {
@@ -244,8 +597,10 @@ impl EntityStorage {
                         self.archetypes.len() + 1 > self.archetypes.capacity();
                     let archetypes_old_ptr = self.archetypes.as_ptr();
 
-                    // Insert the archetype
+                    // Insert the archetype, it now refers to the sparse sets of this storage
+                    archetype.sparse_sets = self.sparse_sets.clone();
                     let archetype_index = self.archetypes.insert(archetype);
+                    self.shift_archetype_indexes(archetype_index);
                     self.archetype_types
                         .insert(component_types.clone(), archetype_index);
 
@@ -286,6 +641,9 @@ impl EntityStorage {
             FruityResult::Ok(())
         })?;
 
+        // The archetypes of the other storage have all been moved
+        other.archetype_types.clear();
+
         // Update the archetypes indexes
         unsafe { self.archetypes.get_unchecked_mut_vec() }
             .iter_mut()
@@ -303,3 +661,57 @@ impl EntityStorage {
         Ok(entity_ids)
     }
 }
+
+/// Returns true if a component is stored in a sparse set instead of the archetype of its entity
+pub(crate) fn is_sparse_set_component(component: &dyn Component) -> bool {
+    component.is_in_sparse_set()
+}
+
+/// The addresses of the components of an entity grouped by type, they tell which components a mutation kept
+/// The zero sized components share their address, replacing one by another of the same type is not detected
+type ComponentAddresses = HashMap<ScriptObjectType, Vec<*const ()>>;
+
+fn component_addresses(components: &[Box<dyn Component>]) -> Vec<*const ()> {
+    components
+        .iter()
+        .map(|component| component.as_ref() as *const dyn Component as *const ())
+        .collect()
+}
+
+fn group_component_addresses(
+    components: &[Box<dyn Component>],
+) -> FruityResult<ComponentAddresses> {
+    let mut addresses = ComponentAddresses::new();
+    components.iter().try_for_each(|component| {
+        addresses
+            .entry(component.get_component_type_id()?.get_script_object_type())
+            .or_default()
+            .push(component.as_ref() as *const dyn Component as *const ());
+
+        FruityResult::Ok(())
+    })?;
+
+    Ok(addresses)
+}
+
+/// How a mutation changed the components of a type in an entity
+enum ComponentTypeChange {
+    /// The components are the same, in the same order
+    Unchanged,
+    /// Some components have been removed or reordered, the remaining ones are the same
+    Changed,
+    /// Some components have been added or replaced
+    Added,
+}
+
+fn component_type_change(previous: &[*const ()], current: &[*const ()]) -> ComponentTypeChange {
+    if previous == current {
+        ComponentTypeChange::Unchanged
+    } else if current.len() <= previous.len()
+        && current.iter().all(|address| previous.contains(address))
+    {
+        ComponentTypeChange::Changed
+    } else {
+        ComponentTypeChange::Added
+    }
+}
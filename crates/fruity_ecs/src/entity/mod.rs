@@ -2,7 +2,7 @@ use crate::serialization::{Deserialize, Serialize};
 use fruity_game_engine::{
     script_value::{ScriptValue, TryFromScriptValue, TryIntoScriptValue},
     settings::Settings,
-    FruityError, FruityResult,
+    typescript, FruityError, FruityResult,
 };
 
 mod archetype;
@@ -14,34 +14,99 @@ pub use entity_service::*;
 mod entity_reference;
 pub use entity_reference::*;
 
+mod edit_history_service;
+pub use edit_history_service::*;
+
 mod entity_storage;
 pub use entity_storage::*;
 
+mod sparse_set;
+pub use sparse_set::*;
+
+mod commands;
+pub use commands::*;
+
+mod entity_id_allocator;
+pub(crate) use entity_id_allocator::*;
+
+mod entity_index;
+pub(crate) use entity_index::*;
+
+mod component_mutation;
+pub(crate) use component_mutation::*;
+
+mod component_hooks;
+pub(crate) use component_hooks::*;
+
 /// An entity is a unique identifier for an object in the world
+///
+/// The id is made of a slot index and a generation, the slot of a removed entity is reused with the next
+/// generation so a stored id can't refer to another entity
+///
+/// The scripts get the id packed with [`EntityId::to_bits`] into a bigint, a number would lose the high bits
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct EntityId(pub u64);
+#[typescript("type EntityId = bigint")]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+impl EntityId {
+    /// Returns an EntityId
+    pub fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    /// The slot of the entity
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The number of times the slot has been reused
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Pack the id into a single number, the generation is stored in the high bits
+    pub fn to_bits(&self) -> u64 {
+        ((self.generation as u64) << 32) | self.index as u64
+    }
+
+    /// Unpack an id created with [`EntityId::to_bits`]
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            index: bits as u32,
+            generation: (bits >> 32) as u32,
+        }
+    }
+}
 
 impl TryIntoScriptValue for EntityId {
     fn into_script_value(self) -> FruityResult<ScriptValue> {
-        Ok(ScriptValue::U64(self.0))
+        Ok(ScriptValue::U64(self.to_bits()))
     }
 }
 
 impl TryFromScriptValue for EntityId {
     fn from_script_value(value: ScriptValue) -> FruityResult<Self> {
         match value {
-            ScriptValue::I8(value) => Ok(EntityId(value as u64)),
-            ScriptValue::I16(value) => Ok(EntityId(value as u64)),
-            ScriptValue::I32(value) => Ok(EntityId(value as u64)),
-            ScriptValue::I64(value) => Ok(EntityId(value as u64)),
-            ScriptValue::ISize(value) => Ok(EntityId(value as u64)),
-            ScriptValue::U8(value) => Ok(EntityId(value as u64)),
-            ScriptValue::U16(value) => Ok(EntityId(value as u64)),
-            ScriptValue::U32(value) => Ok(EntityId(value as u64)),
-            ScriptValue::U64(value) => Ok(EntityId(value as u64)),
-            ScriptValue::USize(value) => Ok(EntityId(value as u64)),
-            ScriptValue::F32(value) => Ok(EntityId(value as u64)),
-            ScriptValue::F64(value) => Ok(EntityId(value as u64)),
+            ScriptValue::I8(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::I16(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::I32(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::I64(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::ISize(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::U8(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::U16(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::U32(value) => Ok(EntityId::from_bits(value as u64)),
+            ScriptValue::U64(value) => Ok(EntityId::from_bits(value)),
+            ScriptValue::USize(value) => Ok(EntityId::from_bits(value as u64)),
+            // A number is accepted only if it holds the packed id exactly
+            ScriptValue::F32(value) if is_exact_bits(value as f64) => {
+                Ok(EntityId::from_bits(value as u64))
+            }
+            ScriptValue::F64(value) if is_exact_bits(value) => {
+                Ok(EntityId::from_bits(value as u64))
+            }
             _ => Err(FruityError::NumberExpected(format!(
                 "Couldn't convert {:?} to EntityId",
                 value
@@ -50,6 +115,13 @@ impl TryFromScriptValue for EntityId {
     }
 }
 
+fn is_exact_bits(value: f64) -> bool {
+    // Number.MAX_SAFE_INTEGER, the bigger integers can't be represented exactly by a number
+    const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+    value.fract() == 0.0 && (0.0..=MAX_SAFE_INTEGER).contains(&value)
+}
+
 /// The location of an entity in the world
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EntityLocation {
@@ -63,7 +135,7 @@ pub struct EntityLocation {
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
 pub struct SerializedEntity {
     /// Entity id, it will not be the definitively used id but is internal to the serialization
-    /// so the same id can be found in different serializations
+    /// so the same id can be found in different serializations, the entity slot index is used
     pub local_id: u64,
     /// Name
     pub name: String,
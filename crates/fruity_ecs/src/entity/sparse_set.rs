@@ -0,0 +1,423 @@
+use super::EntityId;
+use crate::component::{
+    Component, ComponentStorage, ComponentTicks, ComponentTypeIndex, ComponentTypeMap,
+};
+use fruity_game_engine::{
+    script_value::ScriptObjectType,
+    sync::{RwLock, RwLockReadGuard},
+    FruityResult,
+};
+use std::collections::HashMap;
+
+/// The components of a type that are stored outside of the archetypes
+///
+/// The components are packed into a storage with one slice per entity and every entity knows the position
+/// of its slice, so adding or removing a component doesn't move the entity to another archetype
+#[derive(Debug)]
+pub struct SparseSet {
+    /// The components, one slice per entity
+    pub(crate) storage: Box<dyn ComponentStorage>,
+
+    /// The entity ids, in the same order than the slices of the storage
+    pub(crate) entity_ids: Vec<EntityId>,
+
+    /// The change ticks of the components, one per entity
+    pub(crate) component_ticks: Vec<ComponentTicks>,
+
+    indexes: HashMap<EntityId, usize>,
+}
+
+impl SparseSet {
+    /// Create a sparse set that stores its components into a given storage
+    pub fn new(storage: Box<dyn ComponentStorage>) -> Self {
+        Self {
+            storage,
+            entity_ids: Vec::new(),
+            component_ticks: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+
+    /// Returns the number of entities that have a component in the set
+    pub fn len(&self) -> usize {
+        self.entity_ids.len()
+    }
+
+    /// Returns true if no entity has a component in the set
+    pub fn is_empty(&self) -> bool {
+        self.entity_ids.is_empty()
+    }
+
+    /// Returns true if an entity has a component in the set
+    pub fn contains(&self, entity_id: EntityId) -> bool {
+        self.indexes.contains_key(&entity_id)
+    }
+
+    /// Returns the position of the components of an entity into the storage
+    pub fn get_index(&self, entity_id: EntityId) -> Option<usize> {
+        self.indexes.get(&entity_id).copied()
+    }
+
+    /// Returns the change ticks of the components of an entity
+    pub fn get_component_ticks(&self, entity_id: EntityId) -> Option<&ComponentTicks> {
+        self.component_ticks.get(self.get_index(entity_id)?)
+    }
+
+    /// Iterate over the components of an entity
+    pub fn iter_entity(
+        &self,
+        entity_id: EntityId,
+    ) -> Option<Box<dyn Iterator<Item = &dyn Component> + '_>> {
+        self.storage.iter_slice(self.get_index(entity_id)?)
+    }
+
+    /// Add components to an entity, they are stored after the existing ones of the entity
+    /// An entity that already has components in the set keeps its ticks
+    pub fn insert(
+        &mut self,
+        entity_id: EntityId,
+        mut components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<()> {
+        if components.is_empty() {
+            return Ok(());
+        }
+
+        // The slice of the entity is rebuilt at the end of the storage
+        let (mut existing_components, ticks) = self
+            .swap_remove(entity_id)
+            .unwrap_or_else(|| (Vec::new(), ComponentTicks::new()));
+        existing_components.append(&mut components);
+        self.storage.push_slice(existing_components)?;
+
+        self.indexes.insert(entity_id, self.entity_ids.len());
+        self.entity_ids.push(entity_id);
+        self.component_ticks.push(ticks);
+
+        Ok(())
+    }
+
+    /// Replace the components of an entity, an entity without components is removed from the set
+    /// The entity keeps its ticks if `keep_ticks` is true, the components are then marked as changed,
+    /// otherwise they are marked as added right now
+    pub fn replace(
+        &mut self,
+        entity_id: EntityId,
+        components: Vec<Box<dyn Component>>,
+        keep_ticks: bool,
+    ) -> FruityResult<()> {
+        let previous_ticks = self.swap_remove(entity_id).map(|(_, ticks)| ticks);
+        if components.is_empty() {
+            return Ok(());
+        }
+
+        let ticks = match previous_ticks {
+            Some(ticks) if keep_ticks => {
+                ticks.set_changed();
+                ticks
+            }
+            _ => ComponentTicks::new(),
+        };
+        self.storage.push_slice(components)?;
+
+        self.indexes.insert(entity_id, self.entity_ids.len());
+        self.entity_ids.push(entity_id);
+        self.component_ticks.push(ticks);
+
+        Ok(())
+    }
+
+    /// Remove the components of an entity, the last entity of the set takes its place
+    pub fn remove(&mut self, entity_id: EntityId) -> Vec<Box<dyn Component>> {
+        self.swap_remove(entity_id)
+            .map(|(components, _)| components)
+            .unwrap_or_default()
+    }
+
+    fn swap_remove(
+        &mut self,
+        entity_id: EntityId,
+    ) -> Option<(Vec<Box<dyn Component>>, ComponentTicks)> {
+        let index = self.indexes.remove(&entity_id)?;
+
+        self.entity_ids.swap_remove(index);
+        let ticks = self.component_ticks.swap_remove(index);
+        if let Some(moved_entity_id) = self.entity_ids.get(index) {
+            self.indexes.insert(*moved_entity_id, index);
+        }
+
+        Some((self.storage.swap_remove_slice(index), ticks))
+    }
+
+    /// Move the components of another set at the end of this one, they are marked as added right now
+    pub fn append(&mut self, other: &mut SparseSet) -> FruityResult<()> {
+        self.storage.append(other.storage.as_mut())?;
+
+        other.entity_ids.drain(..).for_each(|entity_id| {
+            self.indexes.insert(entity_id, self.entity_ids.len());
+            self.entity_ids.push(entity_id);
+            self.component_ticks.push(ComponentTicks::new());
+        });
+        other.component_ticks.clear();
+        other.indexes.clear();
+
+        Ok(())
+    }
+
+    /// Clear the set
+    pub fn clear(&mut self) {
+        self.storage.clear();
+        self.entity_ids.clear();
+        self.component_ticks.clear();
+        self.indexes.clear();
+    }
+}
+
+/// The sparse sets of an entity storage, one per component type
+///
+/// They are shared with the archetypes of the storage so the queries can reach them, a set is never removed
+/// once it is created
+#[derive(Debug, Default)]
+pub struct SparseSets(RwLock<ComponentTypeMap<Box<RwLock<SparseSet>>>>);
+
+impl SparseSets {
+    /// Get the sparse set of a component type
+    pub fn get(&self, script_object_type: &ScriptObjectType) -> Option<&RwLock<SparseSet>> {
+        self.get_by_index(ComponentTypeIndex::find(script_object_type)?)
+    }
+
+    /// Get the sparse set of a component type from its index, doesn't access the component type registry
+    pub fn get_by_index(&self, index: ComponentTypeIndex) -> Option<&RwLock<SparseSet>> {
+        let sparse_sets = self.0.read();
+        let sparse_set = sparse_sets.get_by_index(index)?;
+
+        // Safe cause the sets are boxed and never removed, they live as long as self
+        Some(unsafe { &*(sparse_set.as_ref() as *const RwLock<SparseSet>) })
+    }
+
+    /// Returns true if an entity has a component of a given type in the sparse sets
+    pub fn contains(&self, script_object_type: &ScriptObjectType, entity_id: EntityId) -> bool {
+        ComponentTypeIndex::find(script_object_type)
+            .map(|index| self.contains_index(index, entity_id))
+            .unwrap_or(false)
+    }
+
+    /// Returns true if an entity has a component of a given type in the sparse sets, see [`SparseSets::get_by_index`]
+    pub fn contains_index(&self, index: ComponentTypeIndex, entity_id: EntityId) -> bool {
+        self.get_by_index(index)
+            .map(|sparse_set| sparse_set.read().contains(entity_id))
+            .unwrap_or(false)
+    }
+
+    /// Add components to an entity, the components should be stored in sparse sets
+    pub fn insert(
+        &self,
+        entity_id: EntityId,
+        components: Vec<Box<dyn Component>>,
+    ) -> FruityResult<()> {
+        let mut grouped_components = HashMap::<ScriptObjectType, Vec<Box<dyn Component>>>::new();
+        components.into_iter().try_for_each(|component| {
+            grouped_components
+                .entry(component.get_component_type_id()?.get_script_object_type())
+                .or_default()
+                .push(component);
+
+            FruityResult::Ok(())
+        })?;
+
+        grouped_components
+            .into_iter()
+            .try_for_each(|(script_object_type, components)| {
+                let storage = components.first().unwrap().get_storage();
+                self.get_or_insert(script_object_type, storage)
+                    .write()
+                    .insert(entity_id, components)
+            })
+    }
+
+    /// Replace the components of an entity in the sparse set of a type, see [`SparseSet::replace`]
+    pub fn replace(
+        &self,
+        script_object_type: ScriptObjectType,
+        entity_id: EntityId,
+        components: Vec<Box<dyn Component>>,
+        keep_ticks: bool,
+    ) -> FruityResult<()> {
+        let sparse_set = match components.first() {
+            Some(component) => {
+                Some(self.get_or_insert(script_object_type, component.get_storage()))
+            }
+            None => self.get(&script_object_type),
+        };
+
+        match sparse_set {
+            Some(sparse_set) => sparse_set
+                .write()
+                .replace(entity_id, components, keep_ticks),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove the components of an entity from all the sparse sets
+    pub fn remove_entity(&self, entity_id: EntityId) -> Vec<Box<dyn Component>> {
+        self.0
+            .read()
+            .values()
+            .flat_map(|sparse_set| sparse_set.write().remove(entity_id))
+            .collect()
+    }
+
+    /// Get the components of an entity stored in the sparse sets, clone them
+    pub fn get_entity_components(&self, entity_id: EntityId) -> Vec<Box<dyn Component>> {
+        self.0
+            .read()
+            .values()
+            .flat_map(|sparse_set| {
+                sparse_set
+                    .read()
+                    .iter_entity(entity_id)
+                    .map(|components| {
+                        components
+                            .map(|component| component.duplicate())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Lock all the sparse sets with a shared access
+    pub fn read_all(&self) -> Vec<RwLockReadGuard<'_, SparseSet>> {
+        self.0
+            .read()
+            .values()
+            // Safe cause the sets are boxed and never removed, they live as long as self
+            .map(|sparse_set| unsafe { &*(sparse_set.as_ref() as *const RwLock<SparseSet>) }.read())
+            .collect()
+    }
+
+    /// Move the components of other sparse sets into these ones
+    pub fn append(&self, other: &SparseSets) -> FruityResult<()> {
+        other
+            .0
+            .read()
+            .iter()
+            .try_for_each(|(script_object_type, other_sparse_set)| {
+                let mut other_sparse_set = other_sparse_set.write();
+
+                // An empty storage of the same type is created from the first component
+                let storage = match other_sparse_set.storage.get(0, 0) {
+                    Some(component) => component.get_storage(),
+                    None => return Ok(()),
+                };
+
+                self.get_or_insert(script_object_type.clone(), storage)
+                    .write()
+                    .append(&mut other_sparse_set)
+            })
+    }
+
+    /// Clear all the sparse sets
+    pub fn clear(&self) {
+        self.0
+            .read()
+            .values()
+            .for_each(|sparse_set| sparse_set.write().clear());
+    }
+
+    fn get_or_insert(
+        &self,
+        script_object_type: ScriptObjectType,
+        storage: Box<dyn ComponentStorage>,
+    ) -> &RwLock<SparseSet> {
+        if let Some(sparse_set) = self.get(&script_object_type) {
+            return sparse_set;
+        }
+
+        let mut sparse_sets = self.0.write();
+
+        // The set may have been created between the two locks
+        if sparse_sets.get(&script_object_type).is_none() {
+            ComponentTypeIndex::register(&script_object_type).set_sparse_set();
+            sparse_sets.insert(
+                script_object_type.clone(),
+                Box::new(RwLock::new(SparseSet::new(storage))),
+            );
+        }
+        std::mem::drop(sparse_sets);
+
+        self.get(&script_object_type).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::component::{increment_change_tick, Tag};
+
+    fn new_sparse_set() -> SparseSet {
+        SparseSet::new(Tag::default().get_storage())
+    }
+
+    fn get_tags(sparse_set: &SparseSet, entity_id: EntityId) -> Vec<String> {
+        sparse_set
+            .iter_entity(entity_id)
+            .unwrap()
+            .map(|component| {
+                component
+                    .as_any_ref()
+                    .downcast_ref::<Tag>()
+                    .unwrap()
+                    .0
+                    .clone()
+            })
+            .collect()
+    }
+
+    fn tags(tags: &[&str]) -> Vec<Box<dyn Component>> {
+        tags.iter()
+            .map(|tag| Box::new(Tag(tag.to_string())) as Box<dyn Component>)
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_keeps_existing_ticks() {
+        let mut sparse_set = new_sparse_set();
+        let entity_id = EntityId::new(0, 0);
+
+        sparse_set.insert(entity_id, tags(&["a"])).unwrap();
+        let added = sparse_set.component_ticks[0].get_added();
+
+        increment_change_tick();
+        sparse_set.insert(entity_id, tags(&["b"])).unwrap();
+
+        assert_eq!(sparse_set.len(), 1);
+        assert_eq!(sparse_set.component_ticks[0].get_added(), added);
+        assert_eq!(get_tags(&sparse_set, entity_id), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_remove_moves_the_last_entity() {
+        let mut sparse_set = new_sparse_set();
+        let entity_ids = (0..3)
+            .map(|index| EntityId::new(index, 0))
+            .collect::<Vec<_>>();
+
+        sparse_set.insert(entity_ids[0], tags(&["a"])).unwrap();
+        sparse_set.insert(entity_ids[1], tags(&["b", "c"])).unwrap();
+        sparse_set
+            .insert(entity_ids[2], tags(&["d", "e", "f"]))
+            .unwrap();
+        let last_added = sparse_set.component_ticks[2].get_added();
+
+        increment_change_tick();
+        let removed = sparse_set.remove(entity_ids[0]);
+
+        assert_eq!(removed.len(), 1);
+        assert!(!sparse_set.contains(entity_ids[0]));
+        assert_eq!(sparse_set.get_index(entity_ids[2]), Some(0));
+        assert_eq!(sparse_set.get_index(entity_ids[1]), Some(1));
+        assert_eq!(sparse_set.component_ticks[0].get_added(), last_added);
+        assert_eq!(get_tags(&sparse_set, entity_ids[1]), vec!["b", "c"]);
+        assert_eq!(get_tags(&sparse_set, entity_ids[2]), vec!["d", "e", "f"]);
+    }
+}
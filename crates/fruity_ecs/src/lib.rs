@@ -13,11 +13,16 @@
 //! - Entities represent any object stored in the ecs, entities are composed of components, in a game engine, a game object for example
 //! - Components are structure where the datas are stored
 
-use component::ExtensionComponentService;
-use entity::{EntityId, EntityService};
-use fruity_game_engine::{export_function, module::Module, sync::Arc, typescript_import};
+use component::{ExtensionComponentService, Tag};
+use entity::{EditHistoryService, EntityId, EntityService};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{module::Module, sync::Arc, typescript_import};
+use resources::{
+    prefab_resource::{load_prefab, PrefabInstance, PrefabOverride},
+    scene_resource::{load_scene, SceneInstance},
+};
 use serialization::SerializationService;
-use system::SystemService;
+use system::{StateService, SystemService};
 
 /// Components module
 pub mod component;
@@ -28,6 +33,9 @@ pub mod entity;
 /// Queries module
 pub mod query;
 
+/// Resources module
+pub mod resources;
+
 /// Serialization module
 pub mod serialization;
 
@@ -37,10 +45,10 @@ pub mod storage;
 /// Systems module
 pub mod system;
 
-#[typescript_import({Signal, ObserverHandler, Module, ScriptValue} from "fruity_game_engine")]
+#[typescript_import({Signal, ObserverHandler, Module, ScriptValue, ResourceReference, Settings} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_ecs_module() -> Module {
     Module {
         name: "fruity_ecs".to_string(),
@@ -54,6 +62,9 @@ pub fn create_fruity_ecs_module() -> Module {
                 Box::new(serialization_service),
             );
 
+            let state_service = StateService::new(resource_container.clone());
+            resource_container.add::<StateService>("state_service", Box::new(state_service));
+
             let system_service = SystemService::new(resource_container.clone());
             resource_container.add::<SystemService>("system_service", Box::new(system_service));
 
@@ -67,10 +78,21 @@ pub fn create_fruity_ecs_module() -> Module {
             let entity_service = EntityService::new(resource_container.clone());
             resource_container.add::<EntityService>("entity_service", Box::new(entity_service));
 
+            let edit_history_service = EditHistoryService::new(resource_container.clone());
+            resource_container
+                .add::<EditHistoryService>("edit_history_service", Box::new(edit_history_service));
+
             let serialization_service = resource_container.require::<SerializationService>();
             let mut serialization_service = serialization_service.write();
 
             serialization_service.register::<EntityId>();
+            serialization_service.register::<PrefabOverride>();
+            serialization_service.register_component::<PrefabInstance>();
+            serialization_service.register_component::<SceneInstance>();
+            serialization_service.register_component::<Tag>();
+
+            resource_container.add_resource_loader("prefab", load_prefab);
+            resource_container.add_resource_loader("scene", load_scene);
 
             // Register system middleware
             let system_service = resource_container.require::<SystemService>();
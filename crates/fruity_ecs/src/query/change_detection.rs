@@ -0,0 +1,180 @@
+use super::{
+    EntityFilterContext, InfiniteEntityIterator, LockedStorages, QueryParam, SingleEntityIterator,
+};
+use crate::{
+    component::{Component, ComponentTicks, ComponentTypeIndex},
+    entity::{Archetype, ArchetypeComponentTypes, EntityReference},
+};
+use std::{marker::PhantomData, ops::Range};
+
+/// Keep only the entities where a component has been added since the last run of the system
+pub struct Added<T: Component + 'static> {
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T: Component + 'static> QueryParam<'a> for Added<T> {
+    type Item = ();
+    type Iterator = InfiniteEntityIterator<Self::Item>;
+    type FromEntityReferenceIterator = SingleEntityIterator<Self::Item>;
+
+    const HAS_ENTITY_FILTER: bool = true;
+
+    fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+        // The sparse set components are checked entity by entity
+        T::is_sparse_set() || component_types.contains_index(ComponentTypeIndex::of::<T>())
+    }
+
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        get_component_tick::<T>(archetype, entity_index, ComponentTicks::get_added)
+            .map(|tick| context.system_ticks.is_newer(tick))
+            .unwrap_or(false)
+    }
+
+    fn iter(_archetype: &'a Archetype) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        _entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
+    fn from_entity_reference(
+        _entity_reference: &EntityReference,
+    ) -> Self::FromEntityReferenceIterator {
+        SingleEntityIterator::default()
+    }
+}
+
+/// Keep only the entities where a component has been added or mutably accessed since the last run of the system
+pub struct Changed<T: Component + 'static> {
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T: Component + 'static> QueryParam<'a> for Changed<T> {
+    type Item = ();
+    type Iterator = InfiniteEntityIterator<Self::Item>;
+    type FromEntityReferenceIterator = SingleEntityIterator<Self::Item>;
+
+    const HAS_ENTITY_FILTER: bool = true;
+
+    fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+        // The sparse set components are checked entity by entity
+        T::is_sparse_set() || component_types.contains_index(ComponentTypeIndex::of::<T>())
+    }
+
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        get_component_tick::<T>(archetype, entity_index, ComponentTicks::get_changed)
+            .map(|tick| context.system_ticks.is_newer(tick))
+            .unwrap_or(false)
+    }
+
+    fn iter(_archetype: &'a Archetype) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        _entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
+    fn from_entity_reference(
+        _entity_reference: &EntityReference,
+    ) -> Self::FromEntityReferenceIterator {
+        SingleEntityIterator::default()
+    }
+}
+
+/// Keep only the entities where a component has been removed since the last run of the system
+pub struct Removed<T: Component + 'static> {
+    _phantom: PhantomData<T>,
+}
+
+impl<'a, T: Component + 'static> QueryParam<'a> for Removed<T> {
+    type Item = ();
+    type Iterator = InfiniteEntityIterator<Self::Item>;
+    type FromEntityReferenceIterator = SingleEntityIterator<Self::Item>;
+
+    const HAS_ENTITY_FILTER: bool = true;
+
+    fn filter_archetype(_component_types: &ArchetypeComponentTypes) -> bool {
+        true
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        context
+            .removed_components
+            .get(
+                ComponentTypeIndex::of::<T>(),
+                archetype.entity_ids[entity_index],
+            )
+            .map(|tick| context.system_ticks.is_newer(tick))
+            .unwrap_or(false)
+    }
+
+    fn iter(_archetype: &'a Archetype) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        _entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
+    fn from_entity_reference(
+        _entity_reference: &EntityReference,
+    ) -> Self::FromEntityReferenceIterator {
+        SingleEntityIterator::default()
+    }
+}
+
+/// Get a change tick of the components of an entity, they can be stored in the archetype or in a sparse set
+fn get_component_tick<T: Component + 'static>(
+    archetype: &Archetype,
+    entity_index: usize,
+    get_tick: impl Fn(&ComponentTicks) -> u64,
+) -> Option<u64> {
+    if T::is_sparse_set() {
+        let sparse_set = archetype
+            .sparse_sets
+            .get_by_index(ComponentTypeIndex::of::<T>())?
+            .read();
+
+        sparse_set
+            .get_component_ticks(archetype.entity_ids[entity_index])
+            .map(get_tick)
+    } else {
+        archetype
+            .get_component_ticks_by_index(ComponentTypeIndex::of::<T>(), entity_index)
+            .map(get_tick)
+    }
+}
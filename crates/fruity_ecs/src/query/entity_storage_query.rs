@@ -1,11 +1,23 @@
-use crate::entity::{Archetype, ArchetypeComponentTypes, EntityReference, EntityStorage};
+use super::WithEntityIndex;
+use crate::{
+    component::{ComponentStorage, ComponentTypeIndex, RemovedComponents, SystemTicks},
+    entity::{Archetype, ArchetypeComponentTypes, EntityReference, EntityStorage, SparseSet},
+};
 use fruity_game_engine::{
     signal::ObserverHandler,
-    sync::{Arc, RwLock},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     FruityResult,
 };
 use sorted_vec::SortedVec;
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Deref, Range},
+    ptr::NonNull,
+    sync::OnceLock,
+};
 
 /// An iterator over entities elements
 pub trait EntityIterator: Iterator {
@@ -96,13 +108,48 @@ pub trait QueryParam<'a> {
     /// The type of the iterator for from_entity_reference
     type FromEntityReferenceIterator: Iterator<Item = Self::Item> + EntityIterator + 'a;
 
+    /// True if the param filters the entities one by one, see [`QueryParam::filter_entity`]
+    const HAS_ENTITY_FILTER: bool = false;
+
     /// A filter over the archetypes
     fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool;
 
+    /// True if the param reads components stored in sparse sets, see [`Component::is_sparse_set`]
+    ///
+    /// The entities of the matching archetypes are then filtered with [`QueryParam::filter_entity`] and
+    /// iterated one by one with [`QueryParam::iter_batch`]
+    ///
+    /// [`Component::is_sparse_set`]: crate::component::Component::is_sparse_set
+    fn has_sparse_set() -> bool {
+        false
+    }
+
+    /// A filter over the entities of a matching archetype, used by the change detection filters
+    /// Only called if [`QueryParam::HAS_ENTITY_FILTER`] or [`QueryParam::has_sparse_set`] is true
+    fn filter_entity(
+        _archetype: &Archetype,
+        _entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        true
+    }
+
     /// Iter over the queried components into a given archetype
     /// The iterator should not lock the entity guard, the query will take care of it
     fn iter(archetype: &'a Archetype) -> Self::Iterator;
 
+    /// Lock the storages read or written by the param, they stay locked while the batches of the archetype
+    /// are iterated with [`QueryParam::iter_batch`]
+    fn lock_storages(_archetype: &'a Archetype, _locked_storages: &mut LockedStorages<'a>) {}
+
+    /// Iter over the queried components of a range of entities into a given archetype
+    /// The iterator doesn't lock anything, the storages are locked once for all the batches by [`QueryParam::lock_storages`]
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator;
+
     /// Iter over the queried components into a given entity
     /// It should not lock the entity guard, the query will take care of it
     fn from_entity_reference(
@@ -110,8 +157,279 @@ pub trait QueryParam<'a> {
     ) -> Self::FromEntityReferenceIterator;
 }
 
+/// The informations needed to filter the entities of a query
+pub struct EntityFilterContext<'a> {
+    /// The ticks of the system that runs the query
+    pub system_ticks: SystemTicks,
+
+    /// The components removed from the entities of the storage
+    pub removed_components: &'a RemovedComponents,
+}
+
+enum StorageGuard<'a> {
+    Read(RwLockReadGuard<'a, Box<dyn ComponentStorage>>),
+    Write(RwLockWriteGuard<'a, Box<dyn ComponentStorage>>),
+    SparseSetRead(RwLockReadGuard<'a, SparseSet>),
+    SparseSetWrite(RwLockWriteGuard<'a, SparseSet>),
+}
+
+/// A storage locked by a query param, the storages are never moved while their archetype lives
+#[derive(Clone)]
+enum StorageBorrow {
+    Read(NonNull<RwLock<Box<dyn ComponentStorage>>>),
+    Write(NonNull<RwLock<Box<dyn ComponentStorage>>>),
+    SparseSetRead(NonNull<RwLock<SparseSet>>),
+    SparseSetWrite(NonNull<RwLock<SparseSet>>),
+}
+
+/// The storages that a query param locks into an archetype, they are looked up once and locked
+/// again at every iteration, see [`LockedStorages::from_borrows`]
+#[derive(Clone, Default)]
+pub(crate) struct StorageBorrows(Vec<(ComponentTypeIndex, StorageBorrow)>);
+
+/// The component storages of an archetype locked for a batched iteration, see [`QueryParam::iter_batch`]
+#[derive(Default)]
+pub struct LockedStorages<'a> {
+    guards: Vec<(ComponentTypeIndex, StorageGuard<'a>)>,
+    borrows: StorageBorrows,
+}
+
+impl<'a> LockedStorages<'a> {
+    /// Lock the storages recorded by other locked storages, in the same order, without looking them up
+    ///
+    /// The borrows should come from the same archetype and the archetype should outlive the locks
+    pub(crate) fn from_borrows(borrows: &StorageBorrows) -> Self {
+        let guards = borrows
+            .0
+            .iter()
+            .map(|(component_type_index, borrow)| {
+                // Safe cause the storages live as long as their archetype
+                let guard = unsafe {
+                    match borrow {
+                        StorageBorrow::Read(storage) => StorageGuard::Read(storage.as_ref().read()),
+                        StorageBorrow::Write(storage) => {
+                            StorageGuard::Write(storage.as_ref().write())
+                        }
+                        StorageBorrow::SparseSetRead(sparse_set) => {
+                            StorageGuard::SparseSetRead(sparse_set.as_ref().read())
+                        }
+                        StorageBorrow::SparseSetWrite(sparse_set) => {
+                            StorageGuard::SparseSetWrite(sparse_set.as_ref().write())
+                        }
+                    }
+                };
+
+                (*component_type_index, guard)
+            })
+            .collect();
+
+        Self {
+            guards,
+            borrows: borrows.clone(),
+        }
+    }
+
+    /// Push a guard and remember the storage it locks
+    fn push(
+        &mut self,
+        component_type_index: ComponentTypeIndex,
+        guard: StorageGuard<'a>,
+        borrow: StorageBorrow,
+    ) {
+        self.guards.push((component_type_index, guard));
+        self.borrows.0.push((component_type_index, borrow));
+    }
+
+    /// Release a guard
+    fn remove(&mut self, index: usize) {
+        self.guards.remove(index);
+        self.borrows.0.remove(index);
+    }
+
+    /// Lock a storage of the archetype with a shared access, do nothing if the storage is already locked
+    pub fn read(&mut self, archetype: &'a Archetype, component_type_index: ComponentTypeIndex) {
+        if self.position(component_type_index).is_some() {
+            return;
+        }
+
+        if let Some(storage) = archetype
+            .component_storages
+            .get_by_index(component_type_index)
+        {
+            self.push(
+                component_type_index,
+                StorageGuard::Read(storage.read()),
+                StorageBorrow::Read(NonNull::from(storage)),
+            );
+        }
+    }
+
+    /// Lock a storage of the archetype with an exclusive access, a storage already locked with a shared access is locked again
+    pub fn write(&mut self, archetype: &'a Archetype, component_type_index: ComponentTypeIndex) {
+        let storage = match archetype
+            .component_storages
+            .get_by_index(component_type_index)
+        {
+            Some(storage) => storage,
+            None => return,
+        };
+
+        match self.position(component_type_index) {
+            Some(index) => {
+                if let StorageGuard::Read(_) = self.guards[index].1 {
+                    // The shared lock is released before taking the exclusive one
+                    self.remove(index);
+                    self.push(
+                        component_type_index,
+                        StorageGuard::Write(storage.write()),
+                        StorageBorrow::Write(NonNull::from(storage)),
+                    );
+                }
+            }
+            None => self.push(
+                component_type_index,
+                StorageGuard::Write(storage.write()),
+                StorageBorrow::Write(NonNull::from(storage)),
+            ),
+        }
+    }
+
+    /// Lock the sparse set of a component type with a shared access, do nothing if the set is already locked
+    pub fn read_sparse_set(
+        &mut self,
+        archetype: &'a Archetype,
+        component_type_index: ComponentTypeIndex,
+    ) {
+        if self.position(component_type_index).is_some() {
+            return;
+        }
+
+        if let Some(sparse_set) = archetype.sparse_sets.get_by_index(component_type_index) {
+            self.push(
+                component_type_index,
+                StorageGuard::SparseSetRead(sparse_set.read()),
+                StorageBorrow::SparseSetRead(NonNull::from(sparse_set)),
+            );
+        }
+    }
+
+    /// Lock the sparse set of a component type with an exclusive access, a set already locked with a shared access is locked again
+    pub fn write_sparse_set(
+        &mut self,
+        archetype: &'a Archetype,
+        component_type_index: ComponentTypeIndex,
+    ) {
+        let sparse_set = match archetype.sparse_sets.get_by_index(component_type_index) {
+            Some(sparse_set) => sparse_set,
+            None => return,
+        };
+
+        match self.position(component_type_index) {
+            Some(index) => {
+                if let StorageGuard::SparseSetRead(_) = self.guards[index].1 {
+                    // The shared lock is released before taking the exclusive one
+                    self.remove(index);
+                    self.push(
+                        component_type_index,
+                        StorageGuard::SparseSetWrite(sparse_set.write()),
+                        StorageBorrow::SparseSetWrite(NonNull::from(sparse_set)),
+                    );
+                }
+            }
+            None => self.push(
+                component_type_index,
+                StorageGuard::SparseSetWrite(sparse_set.write()),
+                StorageBorrow::SparseSetWrite(NonNull::from(sparse_set)),
+            ),
+        }
+    }
+
+    /// Get a locked storage, returns None if the archetype doesn't have it
+    ///
+    /// The returned storage should not be used after the locked storages are dropped
+    pub fn get(
+        &self,
+        component_type_index: ComponentTypeIndex,
+    ) -> Option<&'a dyn ComponentStorage> {
+        self.guards
+            .iter()
+            .find(|(guard_type, _)| *guard_type == component_type_index)
+            .map(|(_, guard)| {
+                let storage: &dyn ComponentStorage = match guard {
+                    StorageGuard::Read(guard) => guard.deref().deref(),
+                    StorageGuard::Write(guard) => guard.deref().deref(),
+                    StorageGuard::SparseSetRead(guard) => guard.storage.deref(),
+                    StorageGuard::SparseSetWrite(guard) => guard.storage.deref(),
+                };
+
+                // Safe cause the storages are boxed, they are not moved while the guards are alive
+                unsafe { &*(storage as *const dyn ComponentStorage) }
+            })
+    }
+
+    /// Get a locked sparse set, returns None if it is not locked
+    ///
+    /// The returned set should not be used after the locked storages are dropped
+    pub fn get_sparse_set(
+        &self,
+        component_type_index: ComponentTypeIndex,
+    ) -> Option<&'a SparseSet> {
+        self.guards
+            .iter()
+            .find(|(guard_type, _)| *guard_type == component_type_index)
+            .and_then(|(_, guard)| {
+                let sparse_set: &SparseSet = match guard {
+                    StorageGuard::SparseSetRead(guard) => guard.deref(),
+                    StorageGuard::SparseSetWrite(guard) => guard.deref(),
+                    _ => return None,
+                };
+
+                // Safe cause the sparse sets are boxed, they are not moved while the guards are alive
+                Some(unsafe { &*(sparse_set as *const SparseSet) })
+            })
+    }
+
+    fn position(&self, component_type_index: ComponentTypeIndex) -> Option<usize> {
+        self.guards
+            .iter()
+            .position(|(guard_type, _)| *guard_type == component_type_index)
+    }
+}
+
+// SAFETY: the guards are only read to get the storages and they are released by the thread that took them.
+// A storage locked with an exclusive access is written from several threads only by
+// [`EntityStorageQuery::par_for_each`], on entity ranges that never overlap, see the comment over its batches
+unsafe impl<'a> Sync for LockedStorages<'a> {}
+
 #[derive(Clone)]
-pub(crate) struct ArchetypePtr(pub(crate) NonNull<Archetype>);
+pub(crate) struct ArchetypePtr(
+    pub(crate) NonNull<Archetype>,
+    /// The storages locked by the query into the archetype, recorded at the first batched iteration
+    pub(crate) OnceLock<StorageBorrows>,
+);
+
+impl ArchetypePtr {
+    pub(crate) fn new(archetype: NonNull<Archetype>) -> Self {
+        Self(archetype, OnceLock::new())
+    }
+
+    /// Lock the storages read or written by a query param into the archetype
+    /// The storages are looked up at the first call, the next ones lock the same storages again
+    ///
+    /// The locks should be released before the archetype is moved, like the references to the archetype
+    pub(crate) fn lock_storages<'a, T: QueryParam<'a>>(&self) -> LockedStorages<'a> {
+        match self.1.get() {
+            Some(borrows) => LockedStorages::from_borrows(borrows),
+            None => {
+                let mut locked_storages = LockedStorages::default();
+                T::lock_storages(unsafe { self.0.as_ref() }, &mut locked_storages);
+                let _ = self.1.set(locked_storages.borrows.clone());
+
+                locked_storages
+            }
+        }
+    }
+}
 
 impl PartialEq for ArchetypePtr {
     fn eq(&self, other: &Self) -> bool {
@@ -137,18 +455,111 @@ impl Ord for ArchetypePtr {
     }
 }
 
-// Safe cause archetypes are updated when an archetype is moved trough memory
+// SAFETY: the pointers are updated when the archetypes are moved trough memory, and the archetypes are only
+// accessed through shared references. The components are mutated through the storage locks, from several
+// threads only on entity ranges that never overlap, see [`EntityStorageQuery::par_for_each`]
 unsafe impl Sync for ArchetypePtr {}
 
-// Safe cause archetypes are updated when an archetype is moved trough memory
+// SAFETY: see the Sync implementation
 unsafe impl Send for ArchetypePtr {}
 
 pub(crate) struct InnerEntityStorageQuery {
     pub(crate) archetypes: SortedVec<ArchetypePtr>,
+    pub(crate) removed_components: Arc<RemovedComponents>,
     pub(crate) on_archetype_created_handle: Option<ObserverHandler<NonNull<Archetype>>>,
     pub(crate) on_archetypes_reallocated_handle: Option<ObserverHandler<isize>>,
 }
 
+impl InnerEntityStorageQuery {
+    /// Match all the existing archetypes against a filter and keep the matching list up to date
+    /// The filter is only called again for the archetypes created afterward
+    pub(crate) fn new(
+        entity_storage: &EntityStorage,
+        filter: impl Fn(&ArchetypeComponentTypes) -> bool + Send + Sync + 'static,
+    ) -> Arc<RwLock<Self>> {
+        // Filter existing archetypes
+        let inner = Arc::new(RwLock::new(InnerEntityStorageQuery {
+            archetypes: SortedVec::from(
+                entity_storage
+                    .archetypes
+                    .iter()
+                    .filter(|archetype| filter(archetype.get_component_types()))
+                    .map(|archetype| unsafe {
+                        ArchetypePtr::new(NonNull::new_unchecked(
+                            archetype as *const Archetype as *mut Archetype,
+                        ))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            removed_components: entity_storage.removed_components.clone(),
+            on_archetype_created_handle: None,
+            on_archetypes_reallocated_handle: None,
+        }));
+
+        // Listen to entity storage archetype create event
+        // The observers only keep a weak reference, so the query and its observers are released with its last owner
+        let on_archetype_created_handle = {
+            let inner_2 = Arc::downgrade(&inner);
+            entity_storage
+                .on_archetype_created
+                .add_observer(move |archetype_ptr| {
+                    let inner_2 = match inner_2.upgrade() {
+                        Some(inner_2) => inner_2,
+                        None => return Ok(()),
+                    };
+                    let mut inner_writer = inner_2.write();
+
+                    // The archetypes stored after this one are moved by one slot cause archetypes are ordered,
+                    // the order between the matching archetypes is kept so they are shifted in place
+                    unsafe { inner_writer.archetypes.get_unchecked_mut_vec() }
+                        .iter_mut()
+                        .filter(|archetype| archetype.0 >= *archetype_ptr)
+                        .for_each(|archetype| unsafe {
+                            archetype.0 = NonNull::new_unchecked(archetype.0.as_ptr().add(1));
+                        });
+
+                    // Add the archetype to the query list if it match the filter
+                    if filter(unsafe { archetype_ptr.as_ref().get_component_types() }) {
+                        inner_writer
+                            .archetypes
+                            .push(ArchetypePtr::new(*archetype_ptr));
+                    }
+
+                    Ok(())
+                })
+        };
+
+        // Listen to entity storage archetype reallocated event
+        let on_archetypes_reallocated_handle = {
+            let inner_2 = Arc::downgrade(&inner);
+            entity_storage
+                .on_archetypes_reallocated
+                .add_observer(move |addr_diff| {
+                    let inner_2 = match inner_2.upgrade() {
+                        Some(inner_2) => inner_2,
+                        None => return Ok(()),
+                    };
+                    let mut inner_writer = inner_2.write();
+
+                    unsafe { inner_writer.archetypes.get_unchecked_mut_vec() }
+                        .iter_mut()
+                        .for_each(|archetype| unsafe {
+                            archetype.0 = NonNull::new_unchecked(
+                                archetype.0.as_ptr().byte_offset(*addr_diff),
+                            );
+                        });
+
+                    Ok(())
+                })
+        };
+
+        inner.write().on_archetype_created_handle = Some(on_archetype_created_handle);
+        inner.write().on_archetypes_reallocated_handle = Some(on_archetypes_reallocated_handle);
+
+        inner
+    }
+}
+
 impl Drop for InnerEntityStorageQuery {
     fn drop(&mut self) {
         if let Some(on_archetype_created_handle) = self.on_archetype_created_handle.take() {
@@ -162,6 +573,42 @@ impl Drop for InnerEntityStorageQuery {
     }
 }
 
+/// The archetypes matched by the queries of an entity storage, memoized by query type
+///
+/// Every query with the same params shares the same matching list, so creating a query
+/// doesn't go trough all the archetypes again and only one set of observers is updated
+/// when an archetype is created
+///
+/// The observers only hold weak references to the matching lists, so the lists and their observers
+/// are released with the cache
+#[derive(Default)]
+pub(crate) struct QueryCache(RwLock<HashMap<TypeId, Arc<RwLock<InnerEntityStorageQuery>>>>);
+
+impl QueryCache {
+    /// Get the matching list of a query type, create it if needed
+    pub(crate) fn get_or_insert(
+        &self,
+        query_type: TypeId,
+        create: impl FnOnce() -> Arc<RwLock<InnerEntityStorageQuery>>,
+    ) -> Arc<RwLock<InnerEntityStorageQuery>> {
+        if let Some(inner) = self.0.read().get(&query_type) {
+            return inner.clone();
+        }
+
+        self.0
+            .write()
+            .entry(query_type)
+            .or_insert_with(create)
+            .clone()
+    }
+}
+
+impl Debug for QueryCache {
+    fn fmt(&self, _: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        Ok(())
+    }
+}
+
 /// A query over entities
 pub struct EntityStorageQuery<T> {
     inner: Arc<RwLock<InnerEntityStorageQuery>>,
@@ -185,26 +632,15 @@ unsafe impl<T> Send for EntityStorageQuery<T> {}
 
 impl<'a, T: QueryParam<'a> + 'static> EntityStorageQuery<T> {
     /// Create the entity query
+    /// The matching archetypes are memoized into the storage, so only the first query of a type filters the archetypes
     pub fn new(entity_storage: &EntityStorage) -> Self {
-        // Filter existing archetypes
-        let inner = Arc::new(RwLock::new(InnerEntityStorageQuery {
-            archetypes: SortedVec::from(
-                entity_storage
-                    .archetypes
-                    .iter()
-                    .filter(|archetype| T::filter_archetype(archetype.get_component_types()))
-                    .map(|archetype| unsafe {
-                        ArchetypePtr(NonNull::new_unchecked(
-                            archetype as *const Archetype as *mut Archetype,
-                        ))
-                    })
-                    .collect::<Vec<_>>(),
-            ),
-            on_archetype_created_handle: None,
-            on_archetypes_reallocated_handle: None,
-        }));
-
-        Self::generate_storage_observers(&inner, entity_storage);
+        let inner = entity_storage
+            .query_cache
+            .get_or_insert(TypeId::of::<T>(), || {
+                InnerEntityStorageQuery::new(entity_storage, |component_types| {
+                    T::filter_archetype(component_types)
+                })
+            });
 
         // Returns the query
         Self {
@@ -220,82 +656,144 @@ impl<'a, T: QueryParam<'a> + 'static> EntityStorageQuery<T> {
     ) -> FruityResult<()> {
         let inner_reader = self.inner.read();
 
-        #[cfg(target_arch = "wasm32")]
-        let mut iterator = inner_reader.archetypes.iter();
-
-        #[cfg(not(target_arch = "wasm32"))]
-        let mut iterator = inner_reader.archetypes.iter();
-
-        iterator.try_for_each(|archetype| {
-            let archetype = unsafe { archetype.0.as_ref() };
-            T::iter(archetype).try_for_each(|item| callback(item))
-        })
-    }
-
-    pub(crate) fn generate_storage_observers(
-        inner: &Arc<RwLock<InnerEntityStorageQuery>>,
-        entity_storage: &EntityStorage,
-    ) {
-        // Listen to entity storage archetype create event
-        let on_archetype_created_handle = {
-            let inner_2 = inner.clone();
-            entity_storage
-                .on_archetype_created
-                .add_observer(move |archetype_ptr| {
-                    let mut inner_writer = inner_2.write();
-
-                    // The archetypes after this one are moved cause archetypes are ordered
-                    inner_writer.archetypes = SortedVec::from(
-                        inner_writer
-                            .archetypes
-                            .iter()
-                            .map(|archetype| unsafe {
-                                if archetype.0.as_ref() <= archetype_ptr.as_ref() {
-                                    archetype.clone()
-                                } else {
-                                    ArchetypePtr(NonNull::new_unchecked(
-                                        archetype.0.as_ptr().add(1) as *mut Archetype,
-                                    ))
-                                }
-                            })
-                            .collect::<Vec<_>>(),
-                    );
-
-                    // Add the archetype to the query list if it match the filter
-                    if T::filter_archetype(unsafe { archetype_ptr.as_ref().get_component_types() })
-                    {
-                        inner_writer.archetypes.push(ArchetypePtr(*archetype_ptr));
+        if T::has_sparse_set() {
+            let context = EntityFilterContext {
+                system_ticks: SystemTicks::current(),
+                removed_components: &inner_reader.removed_components,
+            };
+
+            inner_reader
+                .archetypes
+                .iter()
+                .try_for_each(|archetype_ptr| {
+                    let archetype = unsafe { archetype_ptr.0.as_ref() };
+
+                    // The entities are filtered before the storages are locked cause the filter reads the sparse sets
+                    let entity_indexes = (0..archetype.len())
+                        .filter(|entity_index| T::filter_entity(archetype, *entity_index, &context))
+                        .collect::<Vec<_>>();
+
+                    let locked_storages = archetype_ptr.lock_storages::<T>();
+
+                    entity_indexes.into_iter().try_for_each(|entity_index| {
+                        T::iter_batch(
+                            archetype,
+                            &locked_storages,
+                            entity_index..(entity_index + 1),
+                        )
+                        .try_for_each(|item| callback(item))
+                    })
+                })
+        } else if T::HAS_ENTITY_FILTER {
+            let context = EntityFilterContext {
+                system_ticks: SystemTicks::current(),
+                removed_components: &inner_reader.removed_components,
+            };
+
+            inner_reader.archetypes.iter().try_for_each(|archetype| {
+                let archetype = unsafe { archetype.0.as_ref() };
+                <(WithEntityIndex, T)>::iter(archetype).try_for_each(|(entity_index, item)| {
+                    if T::filter_entity(archetype, entity_index, &context) {
+                        callback(item)
+                    } else {
+                        Ok(())
                     }
-
-                    Ok(())
                 })
-        };
+            })
+        } else {
+            inner_reader.archetypes.iter().try_for_each(|archetype| {
+                let archetype = unsafe { archetype.0.as_ref() };
+                T::iter(archetype).try_for_each(|item| callback(item))
+            })
+        }
+    }
 
-        // Listen to entity storage archetype reallocated event
-        let on_archetypes_reallocated_handle = {
-            let inner_2 = inner.clone();
-            entity_storage
-                .on_archetypes_reallocated
-                .add_observer(move |addr_diff| {
-                    let mut inner_writer = inner_2.write();
+    /// Call a function for every entities of an query, the entities are processed in parallel
+    ///
+    /// The archetypes are iterated one after the other, the entities of an archetype are split into
+    /// batches of `batch_size` entities that are processed on the rayon pool
+    /// The iteration is sequential on wasm
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn par_for_each(
+        &self,
+        batch_size: usize,
+        callback: impl Fn(T::Item) -> FruityResult<()> + Send + Sync,
+    ) -> FruityResult<()> {
+        use rayon::prelude::*;
 
-                    inner_writer.archetypes = SortedVec::from(
-                        inner_writer
-                            .archetypes
-                            .iter()
-                            .map(|archetype| unsafe {
-                                ArchetypePtr(NonNull::new_unchecked(
-                                    archetype.0.as_ptr().byte_offset(*addr_diff) as *mut Archetype,
-                                ))
-                            })
-                            .collect::<Vec<_>>(),
-                    );
+        // The entities with sparse set components are iterated one by one
+        if T::has_sparse_set() {
+            return self.for_each(callback);
+        }
 
-                    Ok(())
-                })
-        };
+        let inner_reader = self.inner.read();
+        let system_ticks = SystemTicks::current();
+        let removed_components = &inner_reader.removed_components;
+        let batch_size = batch_size.max(1);
+
+        inner_reader
+            .archetypes
+            .iter()
+            .try_for_each(|archetype_ptr| {
+                let archetype = unsafe { archetype_ptr.0.as_ref() };
+                let locked_storages = archetype_ptr.lock_storages::<T>();
+
+                // SAFETY: every batch builds its `Mut` items from the same exclusively locked storages, so
+                // the batches must never reach the same component. The batch `i` only covers the entities
+                // `i * batch_size..(i + 1) * batch_size`, so the entity ranges of two batches are disjoint.
+                // The components of an entity are a slice of the sliced vec that no other entity shares, so
+                // the data ranges of two disjoint entity ranges are disjoint too, see `SlicedVec::data_range`.
+                // The ticks are indexed by entity the same way and are atomics. The storages stay locked until
+                // every batch ended, so no other thread reaches the components meanwhile
+                let batch_count = (archetype.len() + batch_size - 1) / batch_size;
+                (0..batch_count)
+                    .into_par_iter()
+                    .try_for_each(|batch_index| {
+                        // The archetype pointer is shared instead of the reference cause it can be sent to the pool threads
+                        let archetype = unsafe { archetype_ptr.0.as_ref() };
+                        let entity_range = (batch_index * batch_size)
+                            ..((batch_index + 1) * batch_size).min(archetype.len());
+
+                        // The ticks of the system are local to the thread running it
+                        system_ticks.scope(|| {
+                            if T::HAS_ENTITY_FILTER {
+                                let context = EntityFilterContext {
+                                    system_ticks,
+                                    removed_components,
+                                };
+
+                                <(WithEntityIndex, T)>::iter_batch(
+                                    archetype,
+                                    &locked_storages,
+                                    entity_range,
+                                )
+                                .try_for_each(
+                                    |(entity_index, item)| {
+                                        if T::filter_entity(archetype, entity_index, &context) {
+                                            callback(item)
+                                        } else {
+                                            Ok(())
+                                        }
+                                    },
+                                )
+                            } else {
+                                T::iter_batch(archetype, &locked_storages, entity_range)
+                                    .try_for_each(|item| callback(item))
+                            }
+                        })
+                    })
+            })
+    }
 
-        inner.write().on_archetype_created_handle = Some(on_archetype_created_handle);
-        inner.write().on_archetypes_reallocated_handle = Some(on_archetypes_reallocated_handle);
+    /// Call a function for every entities of an query, the entities are processed in parallel
+    ///
+    /// The iteration is sequential on wasm
+    #[cfg(target_arch = "wasm32")]
+    pub fn par_for_each(
+        &self,
+        _batch_size: usize,
+        callback: impl Fn(T::Item) -> FruityResult<()> + Send + Sync,
+    ) -> FruityResult<()> {
+        self.for_each(callback)
     }
 }
@@ -0,0 +1,293 @@
+use super::{
+    Added, Changed, EntityFilterContext, LockedStorages, QueryParam, Removed, With, WithOptional,
+    Without,
+};
+use crate::{
+    component::{has_tag, Component, TypedTag},
+    entity::{Archetype, ArchetypeComponentTypes, EntityReference},
+};
+use std::{marker::PhantomData, ops::Range};
+
+/// A trait that should be implement for everything that can filter a [`Query`] without fetching data
+///
+/// [`Query`]: super::Query
+pub trait QueryFilter {
+    /// True if the filter checks the entities one by one, see [`QueryFilter::filter_entity`]
+    const HAS_ENTITY_FILTER: bool = false;
+
+    /// A filter over the archetypes
+    fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool;
+
+    /// True if the filter checks components stored in sparse sets, see [`QueryParam::has_sparse_set`]
+    fn has_sparse_set() -> bool {
+        false
+    }
+
+    /// A filter over the entities of a matching archetype
+    /// Only called if [`QueryFilter::HAS_ENTITY_FILTER`] or [`QueryFilter::has_sparse_set`] is true
+    fn filter_entity(
+        _archetype: &Archetype,
+        _entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        true
+    }
+}
+
+/// No filter, every entities are kept
+impl QueryFilter for () {
+    fn filter_archetype(_component_types: &ArchetypeComponentTypes) -> bool {
+        true
+    }
+}
+
+/// Keep only the entities that have a component, the component is not fetched
+impl<T: Component + 'static> QueryFilter for With<T> {
+    fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+        <Self as QueryParam<'static>>::filter_archetype(component_types)
+    }
+
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        <Self as QueryParam<'static>>::filter_entity(archetype, entity_index, context)
+    }
+}
+
+/// Keep only the entities that have a given [`Tag`], the tag is not fetched
+///
+/// The tags are checked entity by entity, each check locks the tag sparse set and hashes the entity id,
+/// [`EntityService::find_all_by_tag`] is cheaper to find a few entities in a large world
+///
+/// [`Tag`]: crate::component::Tag
+/// [`EntityService::find_all_by_tag`]: crate::entity::EntityService::find_all_by_tag
+pub struct WithTag<T> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T: TypedTag> QueryFilter for WithTag<T> {
+    fn filter_archetype(_component_types: &ArchetypeComponentTypes) -> bool {
+        true
+    }
+
+    fn has_sparse_set() -> bool {
+        true
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        has_tag(archetype, entity_index, T::NAME)
+    }
+}
+
+/// Delegate the filter to the query param implementation, the param should not fetch any data
+macro_rules! impl_query_filter_from_param {
+    ($name:ident) => {
+        impl<T: Component + 'static> QueryFilter for $name<T> {
+            const HAS_ENTITY_FILTER: bool = <Self as QueryParam<'static>>::HAS_ENTITY_FILTER;
+
+            fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+                <Self as QueryParam<'static>>::filter_archetype(component_types)
+            }
+
+            fn has_sparse_set() -> bool {
+                <Self as QueryParam<'static>>::has_sparse_set()
+            }
+
+            fn filter_entity(
+                archetype: &Archetype,
+                entity_index: usize,
+                context: &EntityFilterContext,
+            ) -> bool {
+                <Self as QueryParam<'static>>::filter_entity(archetype, entity_index, context)
+            }
+        }
+    };
+}
+
+impl_query_filter_from_param!(Without);
+impl_query_filter_from_param!(Added);
+impl_query_filter_from_param!(Changed);
+impl_query_filter_from_param!(Removed);
+
+/// Keep only the entities that match at least one of the filters
+pub struct Or<T> {
+    _phantom: PhantomData<T>,
+}
+
+/// Fetch the components an entity have between several types, the entity should have at least one of them
+pub struct AnyOf<T> {
+    _phantom: PhantomData<T>,
+}
+
+macro_rules! tuple_impl_filters {
+    ($($tn:ident),+) => {
+        /// Keep only the entities that match all the filters
+        impl<$($tn: QueryFilter),+> QueryFilter for ($($tn,)+) {
+            const HAS_ENTITY_FILTER: bool = $($tn::HAS_ENTITY_FILTER) || +;
+
+            fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+                $($tn::filter_archetype(component_types)) && +
+            }
+
+            fn has_sparse_set() -> bool {
+                $($tn::has_sparse_set()) || +
+            }
+
+            fn filter_entity(
+                archetype: &Archetype,
+                entity_index: usize,
+                context: &EntityFilterContext,
+            ) -> bool {
+                $($tn::filter_entity(archetype, entity_index, context)) && +
+            }
+        }
+
+        impl<$($tn: QueryFilter),+> QueryFilter for Or<($($tn,)+)> {
+            const HAS_ENTITY_FILTER: bool = $($tn::HAS_ENTITY_FILTER) || +;
+
+            fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+                $($tn::filter_archetype(component_types)) || +
+            }
+
+            fn has_sparse_set() -> bool {
+                $($tn::has_sparse_set()) || +
+            }
+
+            fn filter_entity(
+                archetype: &Archetype,
+                entity_index: usize,
+                context: &EntityFilterContext,
+            ) -> bool {
+                // The archetype matches at least one of the filters, the entity should match one of those
+                let component_types = archetype.get_component_types();
+                $(
+                    ($tn::filter_archetype(component_types)
+                        && (!($tn::HAS_ENTITY_FILTER || $tn::has_sparse_set())
+                            || $tn::filter_entity(archetype, entity_index, context)))
+                ) || +
+            }
+        }
+
+        impl<'a, $($tn: Component + 'static),+> QueryParam<'a> for AnyOf<($($tn,)+)> {
+            type Item = ($(Option<&'a $tn>,)+);
+            type Iterator = <($(WithOptional<$tn>,)+) as QueryParam<'a>>::Iterator;
+            type FromEntityReferenceIterator =
+                <($(WithOptional<$tn>,)+) as QueryParam<'a>>::FromEntityReferenceIterator;
+
+            fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+                // The sparse set components are checked entity by entity, like with With
+                $(<With<$tn> as QueryParam<'static>>::filter_archetype(component_types)) || +
+            }
+
+            fn has_sparse_set() -> bool {
+                $($tn::is_sparse_set()) || +
+            }
+
+            fn filter_entity(
+                archetype: &Archetype,
+                entity_index: usize,
+                context: &EntityFilterContext,
+            ) -> bool {
+                let component_types = archetype.get_component_types();
+                $(
+                    (<With<$tn> as QueryParam<'static>>::filter_archetype(component_types)
+                        && <With<$tn> as QueryParam<'static>>::filter_entity(archetype, entity_index, context))
+                ) || +
+            }
+
+            fn iter(archetype: &'a Archetype) -> Self::Iterator {
+                <($(WithOptional<$tn>,)+)>::iter(archetype)
+            }
+
+            fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+                <($(WithOptional<$tn>,)+)>::lock_storages(archetype, locked_storages)
+            }
+
+            fn iter_batch(
+                archetype: &'a Archetype,
+                locked_storages: &LockedStorages<'a>,
+                entity_range: Range<usize>,
+            ) -> Self::Iterator {
+                <($(WithOptional<$tn>,)+)>::iter_batch(archetype, locked_storages, entity_range)
+            }
+
+            fn from_entity_reference(
+                entity_reference: &EntityReference,
+            ) -> Self::FromEntityReferenceIterator {
+                <($(WithOptional<$tn>,)+)>::from_entity_reference(entity_reference)
+            }
+        }
+    };
+}
+
+tuple_impl_filters!(T1, T2);
+tuple_impl_filters!(T1, T2, T3);
+tuple_impl_filters!(T1, T2, T3, T4);
+tuple_impl_filters!(T1, T2, T3, T4, T5);
+tuple_impl_filters!(T1, T2, T3, T4, T5, T6);
+tuple_impl_filters!(T1, T2, T3, T4, T5, T6, T7);
+tuple_impl_filters!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// A query param with an additional filter, used by [`Query`] to separate the data from the filters
+///
+/// [`Query`]: super::Query
+pub struct Filtered<D, F> {
+    _phantom: PhantomData<(D, F)>,
+}
+
+impl<'a, D: QueryParam<'a> + 'static, F: QueryFilter + 'static> QueryParam<'a> for Filtered<D, F> {
+    type Item = D::Item;
+    type Iterator = D::Iterator;
+    type FromEntityReferenceIterator = D::FromEntityReferenceIterator;
+
+    const HAS_ENTITY_FILTER: bool = D::HAS_ENTITY_FILTER || F::HAS_ENTITY_FILTER;
+
+    fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
+        D::filter_archetype(component_types) && F::filter_archetype(component_types)
+    }
+
+    fn has_sparse_set() -> bool {
+        D::has_sparse_set() || F::has_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        D::filter_entity(archetype, entity_index, context)
+            && F::filter_entity(archetype, entity_index, context)
+    }
+
+    fn iter(archetype: &'a Archetype) -> Self::Iterator {
+        D::iter(archetype)
+    }
+
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        D::lock_storages(archetype, locked_storages)
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        D::iter_batch(archetype, locked_storages, entity_range)
+    }
+
+    fn from_entity_reference(
+        entity_reference: &EntityReference,
+    ) -> Self::FromEntityReferenceIterator {
+        D::from_entity_reference(entity_reference)
+    }
+}
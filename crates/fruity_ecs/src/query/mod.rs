@@ -1,10 +1,14 @@
+use crate::component::SystemTicks;
 use crate::entity::EntityId;
 use crate::entity::EntityReference;
 use crate::entity::EntityService;
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::inject::Injectable;
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::signal::ObserverHandler;
 use fruity_game_engine::signal::Signal;
+use fruity_game_engine::sync::Arc;
 use fruity_game_engine::FruityResult;
 
 /// Queries for scripting languages
@@ -23,27 +27,40 @@ pub use with::*;
 mod without;
 pub use without::*;
 
+/// Queries filters over the components changes
+mod change_detection;
+pub use change_detection::*;
+
+/// Queries filters that don't fetch any data
+mod filter;
+pub use filter::*;
+
 mod entity_storage_query;
 pub use entity_storage_query::*;
 
 /// A query over entities
-pub struct Query<T> {
-    entity_storage_query: EntityStorageQuery<T>,
+///
+/// The first param is the data fetched for every entity, the second one is an optional
+/// [`QueryFilter`] that doesn't fetch anything, for example `Query<WithMut<Transform2D>, (With<Parent>, Without<Camera>)>`
+pub struct Query<D, F = ()> {
+    entity_storage_query: EntityStorageQuery<Filtered<D, F>>,
     on_created: Signal<EntityReference>,
+    on_batch_created: Signal<Vec<EntityReference>>,
     on_deleted: Signal<EntityId>,
 }
 
-impl<T> Clone for Query<T> {
+impl<D, F> Clone for Query<D, F> {
     fn clone(&self) -> Self {
         Self {
             entity_storage_query: self.entity_storage_query.clone(),
             on_created: self.on_created.clone(),
+            on_batch_created: self.on_batch_created.clone(),
             on_deleted: self.on_deleted.clone(),
         }
     }
 }
 
-impl<'a, T: QueryParam<'a> + 'static> Query<T> {
+impl<'a, D: QueryParam<'a> + 'static, F: QueryFilter + 'static> Query<D, F> {
     /// Create the entity query
     pub fn new(entity_service: &EntityService) -> Self {
         let entity_storage_reader = entity_service.entity_storage.read();
@@ -55,6 +72,7 @@ impl<'a, T: QueryParam<'a> + 'static> Query<T> {
         Self {
             entity_storage_query,
             on_created: entity_service.on_created.clone(),
+            on_batch_created: entity_service.on_batch_created.clone(),
             on_deleted: entity_service.on_deleted.clone(),
         }
     }
@@ -62,33 +80,61 @@ impl<'a, T: QueryParam<'a> + 'static> Query<T> {
     /// Call a function for every entities of an query
     pub fn for_each(
         &self,
-        callback: impl Fn(T::Item) -> FruityResult<()> + Send + Sync,
+        callback: impl Fn(D::Item) -> FruityResult<()> + Send + Sync,
     ) -> FruityResult<()> {
         self.entity_storage_query.for_each(callback)
     }
 
+    /// Call a function for every entities of an query, the entities are split into batches of `batch_size`
+    /// entities processed in parallel
+    ///
+    /// The iteration is sequential on wasm
+    pub fn par_for_each(
+        &self,
+        batch_size: usize,
+        callback: impl Fn(D::Item) -> FruityResult<()> + Send + Sync,
+    ) -> FruityResult<()> {
+        self.entity_storage_query.par_for_each(batch_size, callback)
+    }
+
     /// Call a function for every entities of an query
+    ///
+    /// The entities spawned in batch are notified through [`EntityService::on_batch_created`]
     pub fn on_created(
         &self,
-        callback: impl Fn(T::Item) -> FruityResult<Option<Box<dyn Fn() + Send + Sync>>>
+        callback: impl Fn(D::Item) -> FruityResult<Option<Box<dyn Fn() + Send + Sync>>>
             + Send
             + Sync
             + 'static,
-    ) -> ObserverHandler<EntityReference> {
+    ) -> QueryObserverHandler {
         let on_deleted = self.on_deleted.clone();
-        self.on_created.add_observer(move |entity_reference| {
+        let observer = Arc::new(move |entity_reference: &EntityReference| {
             if let Some(entity_reference_inner) = entity_reference.inner.read().as_ref() {
                 let matches_query = {
                     let entity_storage_inner = entity_reference_inner.entity_storage.read();
                     let archetype = &entity_storage_inner.archetypes
                         [entity_reference_inner.location.archetype_index];
 
-                    T::filter_archetype(archetype.get_component_types())
+                    Filtered::<D, F>::filter_archetype(archetype.get_component_types())
+                        && (!(Filtered::<D, F>::HAS_ENTITY_FILTER
+                            || Filtered::<D, F>::has_sparse_set())
+                            || {
+                                let context = EntityFilterContext {
+                                    system_ticks: SystemTicks::current(),
+                                    removed_components: &entity_storage_inner.removed_components,
+                                };
+
+                                Filtered::<D, F>::filter_entity(
+                                    archetype,
+                                    entity_reference_inner.location.entity_index,
+                                    &context,
+                                )
+                            })
                 };
 
                 if matches_query {
                     let entity_id = entity_reference.get_entity_id()?;
-                    let mut iterator = T::from_entity_reference(&entity_reference);
+                    let mut iterator = D::from_entity_reference(entity_reference);
 
                     iterator.try_for_each(|item| {
                         let dispose_callback = callback(item)?;
@@ -114,21 +160,399 @@ impl<'a, T: QueryParam<'a> + 'static> Query<T> {
             } else {
                 Ok(())
             }
-        })
+        });
+
+        QueryObserverHandler::new(&self.on_created, &self.on_batch_created, observer)
+    }
+}
+
+/// The observers registered by [`Query::on_created`] and [`ScriptQuery::on_created`]
+#[derive(FruityAny, Clone, Debug)]
+#[fruity_export]
+pub struct QueryObserverHandler {
+    on_created_handler: ObserverHandler<EntityReference>,
+    on_batch_created_handler: ObserverHandler<Vec<EntityReference>>,
+}
+
+#[fruity_export]
+impl QueryObserverHandler {
+    /// Register an observer of the created entities, the entities spawned in batch are notified one by one
+    pub(crate) fn new(
+        on_created: &Signal<EntityReference>,
+        on_batch_created: &Signal<Vec<EntityReference>>,
+        observer: Arc<dyn Fn(&EntityReference) -> FruityResult<()> + Send + Sync>,
+    ) -> Self {
+        let batch_observer = observer.clone();
+
+        Self {
+            on_created_handler: on_created
+                .add_observer(move |entity_reference| observer(entity_reference)),
+            on_batch_created_handler: on_batch_created.add_observer(move |entity_references| {
+                entity_references
+                    .iter()
+                    .try_for_each(|entity_reference| batch_observer(entity_reference))
+            }),
+        }
+    }
+
+    /// Remove the observers from the signals
+    #[fruity_export]
+    pub fn dispose(&self) {
+        self.on_created_handler.dispose_by_ref();
+        self.on_batch_created_handler.dispose_by_ref();
     }
 }
 
-impl<'a, T: QueryParam<'a> + 'static> Injectable for Query<T> {
-    type StoredType = Query<T>;
+impl<'a, D: QueryParam<'a> + 'static, F: QueryFilter + 'static> Injectable for Query<D, F> {
+    type StoredType = Query<D, F>;
 
     fn from_resource_container(resource_container: &ResourceContainer) -> Self {
         let entity_service = resource_container.require::<EntityService>();
         let entity_service = entity_service.read();
 
-        entity_service.query::<T>()
+        Query::new(&entity_service)
     }
 
     fn finalize(stored: &Self::StoredType) -> Self {
         stored.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        component::{increment_change_tick, Component, ExtensionComponentService, Tag},
+        resources::scene_resource::SceneInstance,
+    };
+    use fruity_game_engine::script_value::{ScriptObjectType, ScriptValue};
+    use fruity_game_engine::sync::Mutex;
+
+    fn new_entity_service() -> EntityService {
+        let resource_container = ResourceContainer::new();
+        resource_container.add::<ExtensionComponentService>(
+            "extension_component_service",
+            Box::new(ExtensionComponentService::new(resource_container.clone())),
+        );
+
+        EntityService::new(resource_container)
+    }
+
+    #[test]
+    fn test_par_for_each_with_mut() {
+        let entity_service = new_entity_service();
+        (0..100).for_each(|index| {
+            entity_service
+                .create_entity(
+                    format!("entity_{}", index),
+                    true,
+                    vec![Box::new(SceneInstance::new(index.to_string())) as Box<dyn Component>],
+                )
+                .unwrap();
+        });
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        // Several batches mutate the same archetype in parallel, the last batch is not full
+        let written_ids = Arc::new(Mutex::new(Vec::new()));
+        entity_service
+            .query::<(WithId, WithMut<SceneInstance>)>()
+            .par_for_each(7, |(entity_id, mut scene_instance)| {
+                written_ids.lock().push(entity_id);
+                scene_instance.scene.push('!');
+                Ok(())
+            })
+            .unwrap();
+
+        // Every entity has been written exactly once
+        let mut written_ids = written_ids.lock().clone();
+        written_ids.sort();
+        let written_count = written_ids.len();
+        written_ids.dedup();
+        assert_eq!(written_count, 100);
+        assert_eq!(written_ids.len(), 100);
+
+        let scenes = Arc::new(Mutex::new(Vec::new()));
+        entity_service
+            .query::<With<SceneInstance>>()
+            .for_each(|scene_instance| {
+                scenes.lock().push(scene_instance.scene.clone());
+                Ok(())
+            })
+            .unwrap();
+
+        let mut scenes = scenes.lock().clone();
+        scenes.sort();
+        let mut expected = (0..100)
+            .map(|index| format!("{}!", index))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(scenes, expected);
+    }
+
+    #[test]
+    fn test_any_of_finds_the_sparse_set_components() {
+        let entity_service = new_entity_service();
+        entity_service
+            .create_entity(
+                "tagged".to_string(),
+                true,
+                vec![Box::new(Tag::new("tag".to_string())) as Box<dyn Component>],
+            )
+            .unwrap();
+        entity_service
+            .create_entity(
+                "scene".to_string(),
+                true,
+                vec![Box::new(SceneInstance::new("scene".to_string())) as Box<dyn Component>],
+            )
+            .unwrap();
+        entity_service
+            .create_entity("empty".to_string(), true, vec![])
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        // The tag is stored in a sparse set, the tagged entity shares the archetype of the empty one
+        let found = Arc::new(Mutex::new(Vec::new()));
+        entity_service
+            .query::<(WithName, AnyOf<(Tag, SceneInstance)>)>()
+            .for_each(|(name, (tag, scene_instance))| {
+                found.lock().push((
+                    name.clone(),
+                    tag.map(|tag| tag.0.clone()),
+                    scene_instance.map(|scene_instance| scene_instance.scene.clone()),
+                ));
+                Ok(())
+            })
+            .unwrap();
+
+        let mut found = found.lock().clone();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("scene".to_string(), None, Some("scene".to_string())),
+                ("tagged".to_string(), Some("tag".to_string()), None),
+            ]
+        );
+    }
+
+    /// Collect the names of the entities that match a filter
+    fn find_names<F: QueryFilter + 'static>(entity_service: &EntityService) -> Vec<String> {
+        let names = Arc::new(Mutex::new(Vec::new()));
+        entity_service
+            .query_filtered::<WithName, F>()
+            .for_each(|name| {
+                names.lock().push(name.clone());
+                Ok(())
+            })
+            .unwrap();
+
+        let mut names = names.lock().clone();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_added_finds_the_sparse_set_components() {
+        let entity_service = new_entity_service();
+        entity_service
+            .create_entity(
+                "tagged".to_string(),
+                true,
+                vec![Box::new(Tag::new("tag".to_string())) as Box<dyn Component>],
+            )
+            .unwrap();
+        entity_service
+            .create_entity("empty".to_string(), true, vec![])
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        // Outside of a system everything is added, the empty entity shares the archetype of the tagged one
+        assert_eq!(
+            find_names::<Added<Tag>>(&entity_service),
+            vec!["tagged".to_string()]
+        );
+
+        let system_ticks = SystemTicks {
+            last_run: increment_change_tick(),
+            this_run: increment_change_tick(),
+        };
+        system_ticks.scope(|| {
+            assert!(find_names::<Added<Tag>>(&entity_service).is_empty());
+
+            entity_service
+                .create_entity(
+                    "late".to_string(),
+                    true,
+                    vec![Box::new(Tag::new("tag".to_string())) as Box<dyn Component>],
+                )
+                .unwrap();
+            unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+            assert_eq!(
+                find_names::<Added<Tag>>(&entity_service),
+                vec!["late".to_string()]
+            );
+        });
+    }
+
+    #[test]
+    fn test_changed_finds_the_sparse_set_components() {
+        let entity_service = new_entity_service();
+        ["first", "second"].iter().for_each(|name| {
+            entity_service
+                .create_entity(
+                    name.to_string(),
+                    true,
+                    vec![Box::new(Tag::new("tag".to_string())) as Box<dyn Component>],
+                )
+                .unwrap();
+        });
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let system_ticks = SystemTicks {
+            last_run: increment_change_tick(),
+            this_run: increment_change_tick(),
+        };
+        system_ticks.scope(|| {
+            assert!(find_names::<Changed<Tag>>(&entity_service).is_empty());
+
+            entity_service
+                .query::<(WithName, WithMut<Tag>)>()
+                .for_each(|(name, mut tag)| {
+                    if name == "first" {
+                        tag.0.push('!');
+                    }
+
+                    Ok(())
+                })
+                .unwrap();
+
+            assert_eq!(
+                find_names::<Changed<Tag>>(&entity_service),
+                vec!["first".to_string()]
+            );
+        });
+    }
+
+    #[test]
+    fn test_on_created_filters_the_sparse_set_entities() {
+        let entity_service = new_entity_service();
+        let created = Arc::new(Mutex::new(Vec::new()));
+
+        let observer_created = created.clone();
+        let _handler = entity_service
+            .query_filtered::<With<SceneInstance>, Without<Tag>>()
+            .on_created(move |scene_instance| {
+                observer_created.lock().push(scene_instance.scene.clone());
+                Ok(None)
+            });
+
+        // The tag is stored in a sparse set, so both entities share the same archetype
+        entity_service
+            .create_entity(
+                "untagged".to_string(),
+                true,
+                vec![Box::new(SceneInstance::new("untagged".to_string()))],
+            )
+            .unwrap();
+        entity_service
+            .create_entity(
+                "tagged".to_string(),
+                true,
+                vec![
+                    Box::new(SceneInstance::new("tagged".to_string())),
+                    Box::new(Tag::new("tag".to_string())),
+                ],
+            )
+            .unwrap();
+        entity_service
+            .spawn_batch(
+                "batch_".to_string(),
+                vec![
+                    vec![Box::new(SceneInstance::new("batch_untagged".to_string()))],
+                    vec![
+                        Box::new(SceneInstance::new("batch_tagged".to_string())),
+                        Box::new(Tag::new("tag".to_string())),
+                    ],
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            created.lock().clone(),
+            vec!["untagged".to_string(), "batch_untagged".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_script_on_created_filters_the_sparse_set_entities() {
+        let entity_service = new_entity_service();
+        let created = Arc::new(Mutex::new(Vec::new()));
+
+        let observer_created = created.clone();
+        let _handler = entity_service
+            .script_query()
+            .with_name()
+            .without(ScriptObjectType::of::<Tag>())
+            .build()
+            .on_created(Box::new(move |item| {
+                if let ScriptValue::Array(values) = item {
+                    if let Some(ScriptValue::String(name)) = values.into_iter().next() {
+                        observer_created.lock().push(name);
+                    }
+                }
+
+                Ok(None)
+            }));
+
+        entity_service
+            .create_entity("untagged".to_string(), true, vec![])
+            .unwrap();
+        entity_service
+            .create_entity(
+                "tagged".to_string(),
+                true,
+                vec![Box::new(Tag::new("tag".to_string()))],
+            )
+            .unwrap();
+
+        assert_eq!(created.lock().clone(), vec!["untagged".to_string()]);
+    }
+
+    #[test]
+    fn test_script_any_of_finds_the_sparse_set_components() {
+        let entity_service = new_entity_service();
+        entity_service
+            .create_entity(
+                "tagged".to_string(),
+                true,
+                vec![Box::new(Tag::new("tag".to_string()))],
+            )
+            .unwrap();
+        entity_service
+            .create_entity("empty".to_string(), true, vec![])
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let found = Arc::new(Mutex::new(Vec::new()));
+        let found_2 = found.clone();
+        entity_service
+            .script_query()
+            .with_name()
+            .any_of(vec![ScriptObjectType::of::<Tag>()])
+            .build()
+            .for_each(Arc::new(move |item| {
+                if let ScriptValue::Array(values) = item {
+                    if let Some(ScriptValue::String(name)) = values.into_iter().next() {
+                        found_2.lock().push(name);
+                    }
+                }
+
+                Ok(ScriptValue::Undefined)
+            }))
+            .unwrap();
+
+        assert_eq!(found.lock().clone(), vec!["tagged".to_string()]);
+    }
+}
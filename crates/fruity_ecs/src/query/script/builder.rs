@@ -1,13 +1,16 @@
 use super::{
-    ScriptQuery, ScriptQueryParam, ScriptTuple, ScriptWith, ScriptWithEnabled,
-    ScriptWithEntityReference, ScriptWithId, ScriptWithName, ScriptWithOptional, ScriptWithout,
+    ScriptAnyOf, ScriptChangeDetection, ScriptChangeDetectionKind, ScriptOr, ScriptQuery,
+    ScriptQueryParam, ScriptTuple, ScriptWith, ScriptWithEnabled, ScriptWithEntityReference,
+    ScriptWithFilter, ScriptWithId, ScriptWithName, ScriptWithOptional, ScriptWithTag,
+    ScriptWithout,
 };
+use crate::component::ComponentTypeIndex;
 use crate::entity::{
     ArchetypeComponentTypes, EntityId, EntityLocation, EntityReference, EntityStorage,
 };
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
     any::FruityAny,
-    export, export_impl, export_struct,
     script_value::ScriptObjectType,
     signal::Signal,
     sync::{Arc, RwLock},
@@ -16,7 +19,7 @@ use std::fmt::Debug;
 
 /// Query builder for script queries
 #[derive(FruityAny)]
-#[export_struct(typescript = "class ScriptQueryBuilder<Args extends any[] = []> {
+#[fruity_export(typescript = "class ScriptQueryBuilder<Args extends any[] = []> {
   withEntity(): ScriptQueryBuilder<[...Args, EntityReference]>;
   withId(): ScriptQueryBuilder<[...Args, EntityId]>;
   withName(): ScriptQueryBuilder<[...Args, string]>;
@@ -24,6 +27,13 @@ use std::fmt::Debug;
   with<T>(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, T]>;
   withOptional<T>(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, T | null]>;
   without(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  withFilter(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  withTag(tag: string): ScriptQueryBuilder<[...Args, null]>;
+  or(filters: ScriptQueryBuilder<any[]>[]): ScriptQueryBuilder<[...Args, null]>;
+  anyOf(constructors: (new (...args) => any)[]): ScriptQueryBuilder<[...Args, any[]]>;
+  added(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  changed(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
+  removed(constructor: new (...args) => T): ScriptQueryBuilder<[...Args, null]>;
   build(): ScriptQuery<[...Args]>
 }")]
 pub struct ScriptQueryBuilder {
@@ -35,11 +45,12 @@ pub struct ScriptQueryBuilder {
         ArchetypeComponentTypes,
     )>,
     on_created: Signal<EntityReference>,
+    on_batch_created: Signal<Vec<EntityReference>>,
     on_deleted: Signal<EntityId>,
     params: Vec<Box<dyn ScriptQueryParam>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ScriptQueryBuilder {
     /// Create the entity query
     pub fn new(
@@ -51,19 +62,21 @@ impl ScriptQueryBuilder {
             ArchetypeComponentTypes,
         )>,
         on_created: Signal<EntityReference>,
+        on_batch_created: Signal<Vec<EntityReference>>,
         on_deleted: Signal<EntityId>,
     ) -> Self {
         ScriptQueryBuilder {
             entity_storage,
             on_entity_location_moved,
             on_created,
+            on_batch_created,
             on_deleted,
             params: Vec::default(),
         }
     }
 
     /// Inject the entity reference as next item of the query
-    #[export]
+    #[fruity_export]
     pub fn with_entity(&self) -> Self {
         let mut query = self.clone();
         query.params.push(Box::new(ScriptWithEntityReference {
@@ -74,7 +87,7 @@ impl ScriptQueryBuilder {
     }
 
     /// Inject the entity id as next item of the query
-    #[export]
+    #[fruity_export]
     pub fn with_id(&self) -> Self {
         let mut query = self.clone();
         query.params.push(Box::new(ScriptWithId {}));
@@ -82,7 +95,7 @@ impl ScriptQueryBuilder {
     }
 
     /// Inject the entity name as next item of the query
-    #[export]
+    #[fruity_export]
     pub fn with_name(&self) -> Self {
         let mut query = self.clone();
         query.params.push(Box::new(ScriptWithName {}));
@@ -90,7 +103,7 @@ impl ScriptQueryBuilder {
     }
 
     /// Inject the entity enabled state as next item of the query
-    #[export]
+    #[fruity_export]
     pub fn with_enabled(&self) -> Self {
         let mut query = self.clone();
         query.params.push(Box::new(ScriptWithEnabled {}));
@@ -98,41 +111,166 @@ impl ScriptQueryBuilder {
     }
 
     /// Inject a component as next item of the query
-    #[export]
+    #[fruity_export]
     pub fn with(&self, script_object_type: ScriptObjectType) -> Self {
         let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
         query.params.push(Box::new(ScriptWith {
             entity_storage: self.entity_storage.clone(),
             on_entity_location_moved: self.on_entity_location_moved.clone(),
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
             script_object_type,
         }));
         query
     }
 
     /// Inject an optional component as next item of the query
-    #[export]
+    #[fruity_export]
     pub fn with_optional(&self, script_object_type: ScriptObjectType) -> Self {
         let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
         query.params.push(Box::new(ScriptWithOptional {
             entity_storage: self.entity_storage.clone(),
             on_entity_location_moved: self.on_entity_location_moved.clone(),
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
             script_object_type,
         }));
         query
     }
 
     /// Filter out entities that have a component
-    #[export]
+    #[fruity_export]
     pub fn without(&self, script_object_type: ScriptObjectType) -> Self {
         let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
+        query.params.push(Box::new(ScriptWithout {
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
+        }));
+        query
+    }
+
+    /// Filter out entities that don't have a component, the component is not injected
+    #[fruity_export]
+    pub fn with_filter(&self, script_object_type: ScriptObjectType) -> Self {
+        let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
+        query.params.push(Box::new(ScriptWithFilter {
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
+        }));
+        query
+    }
+
+    /// Filter out entities that don't have a tag, see [`Tag`]
+    ///
+    /// [`Tag`]: crate::component::Tag
+    #[fruity_export]
+    pub fn with_tag(&self, tag: String) -> Self {
+        let mut query = self.clone();
+        query.params.push(Box::new(ScriptWithTag { tag }));
         query
-            .params
-            .push(Box::new(ScriptWithout { script_object_type }));
+    }
+
+    /// Filter out entities that don't match any of the given queries, their items are not injected
+    #[fruity_export]
+    pub fn or(&self, filters: Vec<ScriptQueryBuilder>) -> Self {
+        let mut query = self.clone();
+        query.params.push(Box::new(ScriptOr {
+            filters: filters
+                .into_iter()
+                .map(|filter| {
+                    Box::new(ScriptTuple {
+                        params: filter.params,
+                    }) as Box<dyn ScriptQueryParam>
+                })
+                .collect(),
+        }));
+        query
+    }
+
+    /// Inject an array with the optional components of the given types as next item of the query,
+    /// entities that don't have any of them are filtered out
+    #[fruity_export]
+    pub fn any_of(&self, script_object_types: Vec<ScriptObjectType>) -> Self {
+        let mut query = self.clone();
+        let component_type_indexes = script_object_types
+            .iter()
+            .map(ComponentTypeIndex::register)
+            .collect::<Vec<_>>();
+
+        // The sparse set components are checked entity by entity
+        let (sparse_set_indexes, archetype_indexes) = component_type_indexes
+            .iter()
+            .copied()
+            .partition::<Vec<_>, _>(|component_type_index| component_type_index.is_sparse_set());
+
+        let optionals = script_object_types
+            .iter()
+            .zip(component_type_indexes.iter())
+            .map(|(script_object_type, component_type_index)| {
+                let component_type_index = *component_type_index;
+                Box::new(ScriptWithOptional {
+                    entity_storage: self.entity_storage.clone(),
+                    on_entity_location_moved: self.on_entity_location_moved.clone(),
+                    component_type_index,
+                    is_sparse_set: component_type_index.is_sparse_set(),
+                    script_object_type: script_object_type.clone(),
+                }) as Box<dyn ScriptQueryParam>
+            })
+            .collect();
+
+        query.params.push(Box::new(ScriptAnyOf {
+            component_type_indexes: archetype_indexes,
+            sparse_set_indexes,
+            optionals: Box::new(ScriptTuple { params: optionals }),
+        }));
+        query
+    }
+
+    /// Filter out entities where a component has not been added since the last run of the system
+    #[fruity_export]
+    pub fn added(&self, script_object_type: ScriptObjectType) -> Self {
+        let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
+        query.params.push(Box::new(ScriptChangeDetection {
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
+            kind: ScriptChangeDetectionKind::Added,
+        }));
+        query
+    }
+
+    /// Filter out entities where a component has not been changed since the last run of the system
+    #[fruity_export]
+    pub fn changed(&self, script_object_type: ScriptObjectType) -> Self {
+        let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
+        query.params.push(Box::new(ScriptChangeDetection {
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
+            kind: ScriptChangeDetectionKind::Changed,
+        }));
+        query
+    }
+
+    /// Filter out entities where a component has not been removed since the last run of the system
+    #[fruity_export]
+    pub fn removed(&self, script_object_type: ScriptObjectType) -> Self {
+        let mut query = self.clone();
+        let component_type_index = ComponentTypeIndex::register(&script_object_type);
+        query.params.push(Box::new(ScriptChangeDetection {
+            component_type_index,
+            is_sparse_set: component_type_index.is_sparse_set(),
+            kind: ScriptChangeDetectionKind::Removed,
+        }));
         query
     }
 
     /// Build the query
-    #[export]
+    #[fruity_export]
     pub fn build(&self) -> ScriptQuery {
         ScriptQuery::new(
             Box::new(ScriptTuple {
@@ -140,6 +278,7 @@ impl ScriptQueryBuilder {
             }),
             &self.entity_storage.read(),
             self.on_created.clone(),
+            self.on_batch_created.clone(),
             self.on_deleted.clone(),
         )
     }
@@ -151,6 +290,7 @@ impl Clone for ScriptQueryBuilder {
             entity_storage: self.entity_storage.clone(),
             on_entity_location_moved: self.on_entity_location_moved.clone(),
             on_created: self.on_created.clone(),
+            on_batch_created: self.on_batch_created.clone(),
             on_deleted: self.on_deleted.clone(),
             params: self.params.iter().map(|param| param.duplicate()).collect(),
         }
@@ -1,15 +1,15 @@
-use super::{ArchetypePtr, EntityIterator, InnerEntityStorageQuery};
+use super::{EntityFilterContext, EntityIterator, InnerEntityStorageQuery, QueryObserverHandler};
+use crate::component::SystemTicks;
 use crate::entity::{Archetype, ArchetypeComponentTypes, EntityId, EntityReference, EntityStorage};
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
     any::FruityAny,
-    export, export_impl, export_struct,
     script_value::ScriptValue,
-    signal::{ObserverHandler, Signal},
+    signal::Signal,
     sync::{Arc, RwLock},
     FruityError, FruityResult,
 };
-use sorted_vec::SortedVec;
-use std::{fmt::Debug, ptr::NonNull};
+use std::fmt::Debug;
 
 mod builder;
 pub use builder::*;
@@ -22,6 +22,22 @@ pub trait ScriptQueryParam: Send + Sync {
     /// A filter over the archetypes
     fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool;
 
+    /// True if the param filters the entities one by one, see [`ScriptQueryParam::filter_entity`]
+    fn has_entity_filter(&self) -> bool {
+        false
+    }
+
+    /// A filter over the entities of a matching archetype, used by the change detection filters
+    /// Only called if [`ScriptQueryParam::has_entity_filter`] returns true
+    fn filter_entity(
+        &self,
+        _archetype: &Archetype,
+        _entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        true
+    }
+
     /// Iter over the queried components into a given archetype
     /// The iterator should not lock the entity guard, the query will take care of it
     fn iter<'a>(
@@ -42,61 +58,46 @@ pub trait ScriptQueryParam: Send + Sync {
 
 /// A query over entities
 #[derive(FruityAny)]
-#[export_struct(typescript = "class ScriptQuery<Args extends any[] = []> {
+#[fruity_export(typescript = "class ScriptQuery<Args extends any[] = []> {
   forEach(callback: (args: Args) => void);
-  onCreated(callback: (args: Args) => undefined | (() => void)): ObserverHandler;
+  onCreated(callback: (args: Args) => undefined | (() => void)): QueryObserverHandler;
 }")]
 pub struct ScriptQuery {
     inner: Arc<RwLock<InnerEntityStorageQuery>>,
     on_created: Signal<EntityReference>,
+    on_batch_created: Signal<Vec<EntityReference>>,
     on_deleted: Signal<EntityId>,
     params: Box<dyn ScriptQueryParam>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ScriptQuery {
     /// Create the entity query
     pub fn new(
         params: Box<dyn ScriptQueryParam>,
         entity_storage: &EntityStorage,
         on_created: Signal<EntityReference>,
+        on_batch_created: Signal<Vec<EntityReference>>,
         on_deleted: Signal<EntityId>,
     ) -> Self {
-        let params_2 = params.duplicate();
-
         // Filter existing archetypes
-        let inner = Arc::new(RwLock::new(InnerEntityStorageQuery {
-            archetypes: SortedVec::from(
-                entity_storage
-                    .archetypes
-                    .iter()
-                    .filter(move |archetype| {
-                        params_2.filter_archetype(archetype.get_component_types())
-                    })
-                    .map(|archetype| unsafe {
-                        ArchetypePtr(NonNull::new_unchecked(
-                            archetype as *const Archetype as *mut Archetype,
-                        ))
-                    })
-                    .collect::<Vec<_>>(),
-            ),
-            on_archetype_created_handle: None,
-            on_archetypes_reallocated_handle: None,
-        }));
-
-        Self::generate_storage_observers(params.duplicate(), &inner, entity_storage);
+        let filter_params = params.duplicate();
+        let inner = InnerEntityStorageQuery::new(entity_storage, move |component_types| {
+            filter_params.filter_archetype(component_types)
+        });
 
         // Returns the query
         Self {
             inner,
-            on_created: on_created.clone(),
-            on_deleted: on_deleted.clone(),
+            on_created,
+            on_batch_created,
+            on_deleted,
             params,
         }
     }
 
     /// Call a function for every entities of an query
-    #[export]
+    #[fruity_export]
     pub fn for_each(
         &self,
         callback: Arc<dyn Send + Sync + Fn(ScriptValue) -> FruityResult<ScriptValue>>,
@@ -104,17 +105,48 @@ impl ScriptQuery {
         let inner_reader = self.inner.read();
         let mut iterator = inner_reader.archetypes.iter();
 
-        iterator.try_for_each(|archetype| {
-            let archetype = unsafe { archetype.0.as_ref() };
-            self.params.iter(archetype).try_for_each(|item| {
-                callback(item)?;
-                Result::<(), FruityError>::Ok(())
+        if self.params.has_entity_filter() {
+            let context = EntityFilterContext {
+                system_ticks: SystemTicks::current(),
+                removed_components: &inner_reader.removed_components,
+            };
+            let params = ScriptTuple {
+                params: vec![Box::new(ScriptWithEntityIndex), self.params.duplicate()],
+            };
+
+            iterator.try_for_each(|archetype| {
+                let archetype = unsafe { archetype.0.as_ref() };
+                params.iter(archetype).try_for_each(|item| {
+                    if let ScriptValue::Array(mut item) = item {
+                        let params_item = item.pop().unwrap();
+
+                        if let Some(ScriptValue::USize(entity_index)) = item.pop() {
+                            if self.params.filter_entity(archetype, entity_index, &context) {
+                                callback(params_item)?;
+                            }
+                        }
+                    }
+
+                    Result::<(), FruityError>::Ok(())
+                })
+            })
+        } else {
+            iterator.try_for_each(|archetype| {
+                let archetype = unsafe { archetype.0.as_ref() };
+                self.params.iter(archetype).try_for_each(|item| {
+                    callback(item)?;
+                    Result::<(), FruityError>::Ok(())
+                })
             })
-        })
+        }
     }
 
     /// Call a function for every entities of an query
-    #[export]
+    ///
+    /// The entities spawned in batch are notified through [`EntityService::on_batch_created`]
+    ///
+    /// [`EntityService::on_batch_created`]: crate::entity::EntityService::on_batch_created
+    #[fruity_export]
     pub fn on_created(
         &self,
         callback: Box<
@@ -125,10 +157,11 @@ impl ScriptQuery {
                 )
                     -> FruityResult<Option<Box<dyn Send + Sync + Fn() -> FruityResult<()>>>>,
         >,
-    ) -> ObserverHandler<EntityReference> {
+    ) -> QueryObserverHandler {
+        let inner = self.inner.clone();
         let params = self.params.duplicate();
         let on_deleted = self.on_deleted.clone();
-        self.on_created.add_observer(move |entity_reference| {
+        let observer = Arc::new(move |entity_reference: &EntityReference| {
             if let Some(entity_reference_inner) = entity_reference.inner.read().as_ref() {
                 let matches_query = {
                     let entity_storage_inner = entity_reference_inner.entity_storage.read();
@@ -136,11 +169,24 @@ impl ScriptQuery {
                         [entity_reference_inner.location.archetype_index];
 
                     params.filter_archetype(archetype.get_component_types())
+                        && (!params.has_entity_filter() || {
+                            let inner_reader = inner.read();
+                            let context = EntityFilterContext {
+                                system_ticks: SystemTicks::current(),
+                                removed_components: &inner_reader.removed_components,
+                            };
+
+                            params.filter_entity(
+                                archetype,
+                                entity_reference_inner.location.entity_index,
+                                &context,
+                            )
+                        })
                 };
 
                 if matches_query {
                     let entity_id = entity_reference.get_entity_id()?;
-                    let mut iterator = params.from_entity_reference(&entity_reference);
+                    let mut iterator = params.from_entity_reference(entity_reference);
 
                     iterator.try_for_each(|item| {
                         let dispose_callback = callback(item)?;
@@ -166,76 +212,9 @@ impl ScriptQuery {
             } else {
                 Ok(())
             }
-        })
-    }
-
-    pub(crate) fn generate_storage_observers(
-        params: Box<dyn ScriptQueryParam>,
-        inner: &Arc<RwLock<InnerEntityStorageQuery>>,
-        entity_storage: &EntityStorage,
-    ) {
-        // Listen to entity storage archetype create event
-        let on_archetype_created_handle = {
-            let inner_2 = inner.clone();
-            entity_storage
-                .on_archetype_created
-                .add_observer(move |archetype_ptr| {
-                    let mut inner_writer = inner_2.write();
-
-                    // The archetypes after this one are moved cause archetypes are ordered
-                    inner_writer.archetypes = SortedVec::from(
-                        inner_writer
-                            .archetypes
-                            .iter()
-                            .map(|archetype| unsafe {
-                                if archetype.0.as_ref() <= archetype_ptr.as_ref() {
-                                    archetype.clone()
-                                } else {
-                                    ArchetypePtr(NonNull::new_unchecked(
-                                        archetype.0.as_ptr().add(1) as *mut Archetype,
-                                    ))
-                                }
-                            })
-                            .collect::<Vec<_>>(),
-                    );
-
-                    // Add the archetype to the query list if it match the filter
-                    if params
-                        .filter_archetype(unsafe { archetype_ptr.as_ref().get_component_types() })
-                    {
-                        inner_writer.archetypes.push(ArchetypePtr(*archetype_ptr));
-                    }
-
-                    Ok(())
-                })
-        };
-
-        // Listen to entity storage archetype reallocated event
-        let on_archetypes_reallocated_handle = {
-            let inner_2 = inner.clone();
-            entity_storage
-                .on_archetypes_reallocated
-                .add_observer(move |addr_diff| {
-                    let mut inner_writer = inner_2.write();
-
-                    inner_writer.archetypes = SortedVec::from(
-                        inner_writer
-                            .archetypes
-                            .iter()
-                            .map(|archetype| unsafe {
-                                ArchetypePtr(NonNull::new_unchecked(
-                                    archetype.0.as_ptr().byte_offset(*addr_diff) as *mut Archetype,
-                                ))
-                            })
-                            .collect::<Vec<_>>(),
-                    );
-
-                    Ok(())
-                })
-        };
+        });
 
-        inner.write().on_archetype_created_handle = Some(on_archetype_created_handle);
-        inner.write().on_archetypes_reallocated_handle = Some(on_archetypes_reallocated_handle);
+        QueryObserverHandler::new(&self.on_created, &self.on_batch_created, observer)
     }
 }
 
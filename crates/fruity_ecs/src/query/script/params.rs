@@ -1,5 +1,8 @@
+use crate::component::has_tag;
 use crate::component::AnyComponentReference;
 use crate::component::ComponentStorage;
+use crate::component::ComponentTicks;
+use crate::component::ComponentTypeIndex;
 use crate::component::Name;
 use crate::entity::Archetype;
 use crate::entity::ArchetypeComponentTypes;
@@ -8,12 +11,14 @@ use crate::entity::EntityLocation;
 use crate::entity::EntityReference;
 use crate::entity::EntityStorage;
 use crate::entity::InnerShareableEntityReference;
+use crate::query::EntityFilterContext;
 use crate::query::EntityIterator;
 use crate::query::InfiniteEntityIterator;
 use crate::query::QueryParam;
 use crate::query::SingleEntityIterator;
 use crate::query::With;
 use crate::query::WithEnabled;
+use crate::query::WithEntityIndex;
 use crate::query::WithId;
 use crate::query::WithIdIterator;
 use fruity_game_engine::any::FruityAny;
@@ -128,6 +133,32 @@ impl<'a> EntityIterator for WithEntityReferenceIterator<'a> {
     }
 }
 
+impl<'a> WithEntityReferenceIterator<'a> {
+    fn new(
+        archetype: &'a Archetype,
+        entity_storage: Arc<RwLock<EntityStorage>>,
+        on_entity_location_moved: Signal<(
+            EntityId,
+            Arc<RwLock<EntityStorage>>,
+            EntityLocation,
+            ArchetypeComponentTypes,
+        )>,
+    ) -> Self {
+        let begin = archetype.entity_ids.as_ptr() as *mut EntityId;
+        WithEntityReferenceIterator {
+            id_iterator: WithIdIterator {
+                current: unsafe { NonNull::new_unchecked(begin) },
+                end: unsafe { NonNull::new_unchecked(begin.add(archetype.len())) },
+                _marker: Default::default(),
+            },
+            entity_storage,
+            archetype_index: archetype.index,
+            current_entity_index: 0,
+            on_entity_location_moved,
+        }
+    }
+}
+
 #[derive(FruityAny, Clone)]
 pub(crate) struct ScriptWithEntityReference {
     pub(crate) entity_storage: Arc<RwLock<EntityStorage>>,
@@ -424,6 +455,114 @@ impl EntityIterator for ScriptFromEntityWithIterator {
     }
 }
 
+/// An iterator over entity components with a given type stored in a sparse set
+///
+/// An entity without component yields a single null value, it's skipped by the entity filter of
+/// [`ScriptWith`] and kept as a missing component by [`ScriptWithOptional`]
+pub struct ScriptSparseSetWithIterator<'a> {
+    entity_reference_iterator: WithEntityReferenceIterator<'a>,
+    component_counts: Vec<usize>,
+    current_entity_index: usize,
+    current_component_index: usize,
+    script_object_type: ScriptObjectType,
+}
+
+impl<'a> ScriptSparseSetWithIterator<'a> {
+    fn new(
+        archetype: &'a Archetype,
+        entity_reference_iterator: WithEntityReferenceIterator<'a>,
+        script_object_type: ScriptObjectType,
+    ) -> Self {
+        let component_counts = match archetype.sparse_sets.get(&script_object_type) {
+            Some(sparse_set) => {
+                let sparse_set = sparse_set.read();
+                archetype
+                    .entity_ids
+                    .iter()
+                    .map(|entity_id| {
+                        sparse_set
+                            .get_index(*entity_id)
+                            .map(|index| sparse_set.storage.slice_len(index))
+                            .unwrap_or(0)
+                    })
+                    .collect()
+            }
+            None => vec![0; archetype.len()],
+        };
+
+        ScriptSparseSetWithIterator {
+            entity_reference_iterator,
+            component_counts,
+            current_entity_index: 0,
+            current_component_index: 0,
+            script_object_type,
+        }
+    }
+}
+
+impl<'a> Iterator for ScriptSparseSetWithIterator<'a> {
+    type Item = ScriptValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_entity_index < self.component_counts.len() {
+            let result = self.current();
+
+            if self.has_reach_entity_end() {
+                self.entity_reference_iterator.next();
+                self.current_entity_index += 1;
+                self.current_component_index = 0;
+            } else {
+                self.current_component_index += 1;
+            }
+
+            Some(result)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> EntityIterator for ScriptSparseSetWithIterator<'a> {
+    fn current(&mut self) -> Self::Item {
+        if self.component_counts[self.current_entity_index] == 0 {
+            return ScriptValue::Null;
+        }
+
+        ScriptValue::Object(Box::new(AnyComponentReference::new(
+            self.entity_reference_iterator.current(),
+            self.script_object_type.clone(),
+            self.current_component_index,
+        )))
+    }
+
+    fn has_reach_entity_end(&self) -> bool {
+        self.current_component_index + 1 >= self.component_counts[self.current_entity_index]
+    }
+
+    fn reset_current_entity(&mut self) {
+        self.current_component_index = 0;
+    }
+}
+
+/// Returns the number of components of a given type that an entity stores in a sparse set
+/// The sparse set is looked up by index, so the component type registry is not accessed
+fn sparse_set_slice_len(
+    archetype: &Archetype,
+    entity_index: usize,
+    component_type_index: ComponentTypeIndex,
+) -> usize {
+    archetype
+        .sparse_sets
+        .get_by_index(component_type_index)
+        .and_then(|sparse_set| {
+            let sparse_set = sparse_set.read();
+            sparse_set
+                .get_index(archetype.entity_ids[entity_index])
+                .map(|index| sparse_set.storage.slice_len(index))
+        })
+        .unwrap_or(0)
+}
+
 #[derive(FruityAny, Clone)]
 pub(crate) struct ScriptWith {
     pub(crate) entity_storage: Arc<RwLock<EntityStorage>>,
@@ -434,32 +573,50 @@ pub(crate) struct ScriptWith {
         ArchetypeComponentTypes,
     )>,
     pub(crate) script_object_type: ScriptObjectType,
+    pub(crate) component_type_index: ComponentTypeIndex,
+    pub(crate) is_sparse_set: bool,
 }
 
 impl ScriptQueryParam for ScriptWith {
     fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
-        component_types.contains(&self.script_object_type)
+        // The sparse set components are checked entity by entity
+        self.is_sparse_set || component_types.contains_index(self.component_type_index)
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        self.is_sparse_set
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        !self.is_sparse_set
+            || sparse_set_slice_len(archetype, entity_index, self.component_type_index) > 0
     }
 
     fn iter<'a>(
         &self,
         archetype: &'a Archetype,
     ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
-        let begin = archetype.entity_ids.as_ptr() as *mut EntityId;
-        let entity_reference_iterator = WithEntityReferenceIterator {
-            id_iterator: WithIdIterator {
-                current: unsafe { NonNull::new_unchecked(begin) },
-                end: unsafe { NonNull::new_unchecked(begin.add(archetype.len())) },
-                _marker: Default::default(),
-            },
-            entity_storage: self.entity_storage.clone(),
-            archetype_index: archetype.index,
-            current_entity_index: 0,
-            on_entity_location_moved: self.on_entity_location_moved.clone(),
-        };
+        let entity_reference_iterator = WithEntityReferenceIterator::new(
+            archetype,
+            self.entity_storage.clone(),
+            self.on_entity_location_moved.clone(),
+        );
+
+        if self.is_sparse_set {
+            return Box::new(ScriptSparseSetWithIterator::new(
+                archetype,
+                entity_reference_iterator,
+                self.script_object_type.clone(),
+            ));
+        }
 
         Box::new(ScriptValueIterator::new(ScriptWithIterator::new(
-            archetype.component_storages[&self.script_object_type].read(),
+            archetype.component_storages[self.component_type_index].read(),
             entity_reference_iterator,
             self.script_object_type.clone(),
         )))
@@ -484,12 +641,17 @@ impl ScriptQueryParam for ScriptWith {
                 .unwrap()
             };
 
-            let component_storage_lock =
-                archetype.component_storages[&self.script_object_type].read();
+            let component_count = if self.is_sparse_set {
+                sparse_set_slice_len(archetype, location.entity_index, self.component_type_index)
+            } else {
+                archetype.component_storages[self.component_type_index]
+                    .read()
+                    .slice_len(location.entity_index)
+            };
 
             Box::new(ScriptValueIterator::new(ScriptFromEntityWithIterator::new(
                 entity_reference.clone(),
-                component_storage_lock.slice_len(location.entity_index),
+                component_count,
                 self.script_object_type.clone(),
             )))
         } else {
@@ -512,6 +674,8 @@ pub(crate) struct ScriptWithOptional {
         ArchetypeComponentTypes,
     )>,
     pub(crate) script_object_type: ScriptObjectType,
+    pub(crate) component_type_index: ComponentTypeIndex,
+    pub(crate) is_sparse_set: bool,
 }
 
 impl ScriptQueryParam for ScriptWithOptional {
@@ -523,21 +687,24 @@ impl ScriptQueryParam for ScriptWithOptional {
         &self,
         archetype: &'a Archetype,
     ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
-        if let Some(component_storage) = archetype.component_storages.get(&self.script_object_type)
-        {
-            let begin = archetype.entity_ids.as_ptr() as *mut EntityId;
-            let entity_reference_iterator = WithEntityReferenceIterator {
-                id_iterator: WithIdIterator {
-                    current: unsafe { NonNull::new_unchecked(begin) },
-                    end: unsafe { NonNull::new_unchecked(begin.add(archetype.len())) },
-                    _marker: Default::default(),
-                },
-                entity_storage: self.entity_storage.clone(),
-                archetype_index: archetype.index,
-                current_entity_index: 0,
-                on_entity_location_moved: self.on_entity_location_moved.clone(),
-            };
+        let entity_reference_iterator = WithEntityReferenceIterator::new(
+            archetype,
+            self.entity_storage.clone(),
+            self.on_entity_location_moved.clone(),
+        );
 
+        if self.is_sparse_set {
+            return Box::new(ScriptSparseSetWithIterator::new(
+                archetype,
+                entity_reference_iterator,
+                self.script_object_type.clone(),
+            ));
+        }
+
+        if let Some(component_storage) = archetype
+            .component_storages
+            .get_by_index(self.component_type_index)
+        {
             Box::new(ScriptValueIterator::new(ScriptWithIterator::new(
                 component_storage.read(),
                 entity_reference_iterator,
@@ -569,13 +736,22 @@ impl ScriptQueryParam for ScriptWithOptional {
                 .unwrap()
             };
 
-            if let Some(component_storage) =
-                archetype.component_storages.get(&self.script_object_type)
-            {
-                let component_storage_lock = component_storage.read();
+            let component_count = if self.is_sparse_set {
+                sparse_set_slice_len(archetype, location.entity_index, self.component_type_index)
+            } else {
+                archetype
+                    .component_storages
+                    .get_by_index(self.component_type_index)
+                    .map(|component_storage| {
+                        component_storage.read().slice_len(location.entity_index)
+                    })
+                    .unwrap_or(0)
+            };
+
+            if component_count > 0 {
                 Box::new(ScriptValueIterator::new(ScriptFromEntityWithIterator::new(
                     entity_reference.clone(),
-                    component_storage_lock.slice_len(location.entity_index),
+                    component_count,
                     self.script_object_type.clone(),
                 )))
             } else {
@@ -595,12 +771,28 @@ impl ScriptQueryParam for ScriptWithOptional {
 
 #[derive(FruityAny, Clone)]
 pub(crate) struct ScriptWithout {
-    pub(crate) script_object_type: ScriptObjectType,
+    pub(crate) component_type_index: ComponentTypeIndex,
+    pub(crate) is_sparse_set: bool,
 }
 
 impl ScriptQueryParam for ScriptWithout {
     fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
-        !component_types.contains(&self.script_object_type)
+        // The sparse set components are checked entity by entity
+        self.is_sparse_set || !component_types.contains_index(self.component_type_index)
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        self.is_sparse_set
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        !self.is_sparse_set
+            || sparse_set_slice_len(archetype, entity_index, self.component_type_index) == 0
     }
 
     fn iter<'a>(
@@ -626,6 +818,355 @@ impl ScriptQueryParam for ScriptWithout {
     }
 }
 
+/// Keep only the entities that have a component, the component is not fetched
+#[derive(FruityAny, Clone)]
+pub(crate) struct ScriptWithFilter {
+    pub(crate) component_type_index: ComponentTypeIndex,
+    pub(crate) is_sparse_set: bool,
+}
+
+impl ScriptQueryParam for ScriptWithFilter {
+    fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
+        // The sparse set components are checked entity by entity
+        self.is_sparse_set || component_types.contains_index(self.component_type_index)
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        self.is_sparse_set
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        !self.is_sparse_set
+            || sparse_set_slice_len(archetype, entity_index, self.component_type_index) > 0
+    }
+
+    fn iter<'a>(
+        &self,
+        _archetype: &'a Archetype,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(InfiniteEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn from_entity_reference<'a>(
+        &self,
+        _entity_reference: &'a EntityReference,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(SingleEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn duplicate(&self) -> Box<dyn ScriptQueryParam> {
+        Box::new(self.clone())
+    }
+}
+
+/// Keep only the entities that have a tag, the tag is not fetched
+///
+/// The tags are checked entity by entity, each check locks the tag sparse set and hashes the entity id
+#[derive(FruityAny, Clone)]
+pub(crate) struct ScriptWithTag {
+    pub(crate) tag: String,
+}
+
+impl ScriptQueryParam for ScriptWithTag {
+    fn filter_archetype(&self, _component_types: &ArchetypeComponentTypes) -> bool {
+        true
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        true
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        has_tag(archetype, entity_index, &self.tag)
+    }
+
+    fn iter<'a>(
+        &self,
+        _archetype: &'a Archetype,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(InfiniteEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn from_entity_reference<'a>(
+        &self,
+        _entity_reference: &'a EntityReference,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(SingleEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn duplicate(&self) -> Box<dyn ScriptQueryParam> {
+        Box::new(self.clone())
+    }
+}
+
+/// Keep only the entities that match at least one of the filters, the filters data are not fetched
+#[derive(FruityAny)]
+pub(crate) struct ScriptOr {
+    pub(crate) filters: Vec<Box<dyn ScriptQueryParam>>,
+}
+
+impl ScriptQueryParam for ScriptOr {
+    fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
+        self.filters
+            .iter()
+            .any(|filter| filter.filter_archetype(component_types))
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        self.filters.iter().any(|filter| filter.has_entity_filter())
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        // The archetype matches at least one of the filters, the entity should match one of those
+        self.filters.iter().any(|filter| {
+            filter.filter_archetype(archetype.get_component_types())
+                && (!filter.has_entity_filter()
+                    || filter.filter_entity(archetype, entity_index, context))
+        })
+    }
+
+    fn iter<'a>(
+        &self,
+        _archetype: &'a Archetype,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(InfiniteEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn from_entity_reference<'a>(
+        &self,
+        _entity_reference: &'a EntityReference,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(SingleEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn duplicate(&self) -> Box<dyn ScriptQueryParam> {
+        Box::new(Self {
+            filters: self
+                .filters
+                .iter()
+                .map(|filter| filter.duplicate())
+                .collect(),
+        })
+    }
+}
+
+/// Fetch the components an entity have between several types, the entity should have at least one of them
+#[derive(FruityAny)]
+pub(crate) struct ScriptAnyOf {
+    pub(crate) component_type_indexes: Vec<ComponentTypeIndex>,
+    pub(crate) sparse_set_indexes: Vec<ComponentTypeIndex>,
+    pub(crate) optionals: Box<dyn ScriptQueryParam>,
+}
+
+impl ScriptQueryParam for ScriptAnyOf {
+    fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
+        // The sparse set components are checked entity by entity
+        !self.sparse_set_indexes.is_empty()
+            || self
+                .component_type_indexes
+                .iter()
+                .any(|component_type_index| component_types.contains_index(*component_type_index))
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        !self.sparse_set_indexes.is_empty()
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        let component_types = archetype.get_component_types();
+        self.component_type_indexes
+            .iter()
+            .any(|component_type_index| component_types.contains_index(*component_type_index))
+            || self.sparse_set_indexes.iter().any(|component_type_index| {
+                sparse_set_slice_len(archetype, entity_index, *component_type_index) > 0
+            })
+    }
+
+    fn iter<'a>(
+        &self,
+        archetype: &'a Archetype,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        self.optionals.iter(archetype)
+    }
+
+    fn from_entity_reference<'a>(
+        &self,
+        entity_reference: &'a EntityReference,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        self.optionals.from_entity_reference(entity_reference)
+    }
+
+    fn duplicate(&self) -> Box<dyn ScriptQueryParam> {
+        Box::new(Self {
+            component_type_indexes: self.component_type_indexes.clone(),
+            sparse_set_indexes: self.sparse_set_indexes.clone(),
+            optionals: self.optionals.duplicate(),
+        })
+    }
+}
+
+/// The index of the entity into its archetype, used to filter the entities of a query
+#[derive(FruityAny, Clone)]
+pub(crate) struct ScriptWithEntityIndex;
+
+impl ScriptQueryParam for ScriptWithEntityIndex {
+    fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
+        WithEntityIndex::filter_archetype(component_types)
+    }
+
+    fn iter<'a>(
+        &self,
+        archetype: &'a Archetype,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(WithEntityIndex::iter(archetype)))
+    }
+
+    fn from_entity_reference<'a>(
+        &self,
+        entity_reference: &'a EntityReference,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(
+            WithEntityIndex::from_entity_reference(entity_reference),
+        ))
+    }
+
+    fn duplicate(&self) -> Box<dyn ScriptQueryParam> {
+        Box::new(self.clone())
+    }
+}
+
+/// The change detection filters that can be used in a script query
+#[derive(Clone, Copy)]
+pub(crate) enum ScriptChangeDetectionKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+/// Keep only the entities where a component has been added, changed or removed since the last run of the system
+#[derive(FruityAny, Clone)]
+pub(crate) struct ScriptChangeDetection {
+    pub(crate) component_type_index: ComponentTypeIndex,
+    pub(crate) is_sparse_set: bool,
+    pub(crate) kind: ScriptChangeDetectionKind,
+}
+
+impl ScriptChangeDetection {
+    /// Get a change tick of the components of an entity, they can be stored in the archetype or in a sparse set
+    fn get_tick(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        get_tick: impl Fn(&ComponentTicks) -> u64,
+    ) -> Option<u64> {
+        if self.is_sparse_set {
+            let sparse_set = archetype
+                .sparse_sets
+                .get_by_index(self.component_type_index)?
+                .read();
+
+            sparse_set
+                .get_component_ticks(archetype.entity_ids[entity_index])
+                .map(get_tick)
+        } else {
+            archetype
+                .get_component_ticks_by_index(self.component_type_index, entity_index)
+                .map(get_tick)
+        }
+    }
+}
+
+impl ScriptQueryParam for ScriptChangeDetection {
+    fn filter_archetype(&self, component_types: &ArchetypeComponentTypes) -> bool {
+        match self.kind {
+            ScriptChangeDetectionKind::Removed => true,
+            // The sparse set components are checked entity by entity
+            _ => self.is_sparse_set || component_types.contains_index(self.component_type_index),
+        }
+    }
+
+    fn has_entity_filter(&self) -> bool {
+        true
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        let tick = match self.kind {
+            ScriptChangeDetectionKind::Added => {
+                self.get_tick(archetype, entity_index, ComponentTicks::get_added)
+            }
+            ScriptChangeDetectionKind::Changed => {
+                self.get_tick(archetype, entity_index, ComponentTicks::get_changed)
+            }
+            ScriptChangeDetectionKind::Removed => context.removed_components.get(
+                self.component_type_index,
+                archetype.entity_ids[entity_index],
+            ),
+        };
+
+        tick.map(|tick| context.system_ticks.is_newer(tick))
+            .unwrap_or(false)
+    }
+
+    fn iter<'a>(
+        &self,
+        _archetype: &'a Archetype,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(InfiniteEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn from_entity_reference<'a>(
+        &self,
+        _entity_reference: &'a EntityReference,
+    ) -> Box<dyn EntityIterator<Item = ScriptValue> + 'a> {
+        Box::new(ScriptValueIterator::new(SingleEntityIterator::new(
+            Option::<()>::None,
+        )))
+    }
+
+    fn duplicate(&self) -> Box<dyn ScriptQueryParam> {
+        Box::new(self.clone())
+    }
+}
+
 /// An iterator over entity enabled state
 pub struct ScriptTupleIterator<'a> {
     iterators: Vec<Box<dyn EntityIterator<Item = ScriptValue> + 'a>>,
@@ -712,6 +1253,21 @@ impl ScriptQueryParam for ScriptTuple {
             .all(|param| param.filter_archetype(component_types))
     }
 
+    fn has_entity_filter(&self) -> bool {
+        self.params.iter().any(|param| param.has_entity_filter())
+    }
+
+    fn filter_entity(
+        &self,
+        archetype: &Archetype,
+        entity_index: usize,
+        context: &EntityFilterContext,
+    ) -> bool {
+        self.params
+            .iter()
+            .all(|param| param.filter_entity(archetype, entity_index, context))
+    }
+
     fn iter<'a>(
         &self,
         archetype: &'a Archetype,
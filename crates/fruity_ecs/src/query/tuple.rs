@@ -1,5 +1,6 @@
-use super::{EntityIterator, QueryParam};
+use super::{EntityFilterContext, EntityIterator, LockedStorages, QueryParam};
 use crate::entity::{Archetype, ArchetypeComponentTypes, EntityReference};
+use std::ops::Range;
 
 macro_rules! struct_iterator {
     (
@@ -154,10 +155,24 @@ macro_rules! impl_query_param {
             type Iterator = $iterator_ident<'a, $($tn),+>;
             type FromEntityReferenceIterator = $from_entity_reference_iterator_ident<'a, $($tn),+>;
 
+            const HAS_ENTITY_FILTER: bool = $($tn::HAS_ENTITY_FILTER) || +;
+
             fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
                 $($tn::filter_archetype(component_types)) && +
             }
 
+            fn has_sparse_set() -> bool {
+                $($tn::has_sparse_set()) || +
+            }
+
+            fn filter_entity(
+                archetype: &Archetype,
+                entity_index: usize,
+                context: &EntityFilterContext,
+            ) -> bool {
+                $($tn::filter_entity(archetype, entity_index, context)) && +
+            }
+
             fn iter(archetype: &'a Archetype) -> Self::Iterator {
                 $iterator_ident {
                     $(
@@ -166,6 +181,22 @@ macro_rules! impl_query_param {
                 }
             }
 
+            fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+                $($tn::lock_storages(archetype, locked_storages);)*
+            }
+
+            fn iter_batch(
+                archetype: &'a Archetype,
+                locked_storages: &LockedStorages<'a>,
+                entity_range: Range<usize>,
+            ) -> Self::Iterator {
+                $iterator_ident {
+                    $(
+                        $tn: $tn::iter_batch(archetype, locked_storages, entity_range.clone()),
+                    )*
+                }
+            }
+
             fn from_entity_reference(
                 entity_reference: &EntityReference,
             ) -> Self::FromEntityReferenceIterator {
@@ -1,16 +1,22 @@
-use super::{EntityIterator, QueryParam, SingleEntityIterator};
+use super::{
+    EntityFilterContext, EntityIterator, LockedStorages, QueryParam, SingleEntityIterator,
+};
 use crate::{
-    component::{Component, ComponentStorage, Enabled, Name, VecComponentStorage},
+    component::{
+        Component, ComponentStorage, ComponentTicks, ComponentTypeIndex, Enabled, Mut, Name,
+        VecComponentStorage,
+    },
     entity::{
         Archetype, ArchetypeComponentTypes, EntityId, EntityLocation, EntityReader,
-        EntityReference, EntityWriter, InnerShareableEntityReference,
+        EntityReference, EntityWriter, InnerShareableEntityReference, SparseSet,
     },
 };
-use fruity_game_engine::{
-    script_value::ScriptObjectType,
-    sync::{RwLockReadGuard, RwLockWriteGuard},
+use fruity_game_engine::sync::{RwLockReadGuard, RwLockWriteGuard};
+use std::{
+    marker::PhantomData,
+    ops::{Deref, Range},
+    ptr::NonNull,
 };
-use std::{marker::PhantomData, ops::Deref, ptr::NonNull};
 
 /// The entity
 pub struct WithEntity;
@@ -73,6 +79,18 @@ impl<'a> QueryParam<'a> for WithEntity {
         }
     }
 
+    fn iter_batch(
+        archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        WithEntityIterator {
+            current_entity_index: entity_range.start,
+            end_entity_index: entity_range.end,
+            archetype,
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -96,6 +114,85 @@ impl<'a> QueryParam<'a> for WithEntity {
     }
 }
 
+/// The index of the entity into its archetype, used to filter the entities of a query
+pub(crate) struct WithEntityIndex;
+
+/// An iterator over the entity indexes of an archetype
+pub(crate) struct WithEntityIndexIterator {
+    current_entity_index: usize,
+    next_entity_index: usize,
+    end_entity_index: usize,
+}
+
+impl Iterator for WithEntityIndexIterator {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_entity_index < self.end_entity_index {
+            self.current_entity_index = self.next_entity_index;
+            self.next_entity_index += 1;
+
+            Some(self.current_entity_index)
+        } else {
+            None
+        }
+    }
+}
+
+impl EntityIterator for WithEntityIndexIterator {
+    fn current(&mut self) -> Self::Item {
+        self.current_entity_index
+    }
+
+    fn has_reach_entity_end(&self) -> bool {
+        true
+    }
+
+    fn reset_current_entity(&mut self) {}
+}
+
+impl<'a> QueryParam<'a> for WithEntityIndex {
+    type Item = usize;
+    type Iterator = WithEntityIndexIterator;
+    type FromEntityReferenceIterator = SingleEntityIterator<Self::Item>;
+
+    fn filter_archetype(_component_types: &ArchetypeComponentTypes) -> bool {
+        true
+    }
+
+    fn iter(archetype: &'a Archetype) -> Self::Iterator {
+        WithEntityIndexIterator {
+            current_entity_index: 0,
+            next_entity_index: 0,
+            end_entity_index: archetype.len(),
+        }
+    }
+
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        WithEntityIndexIterator {
+            current_entity_index: entity_range.start,
+            next_entity_index: entity_range.start,
+            end_entity_index: entity_range.end,
+        }
+    }
+
+    fn from_entity_reference(
+        entity_reference: &EntityReference,
+    ) -> Self::FromEntityReferenceIterator {
+        let inner_entity_reference = entity_reference.inner.read();
+        if let Some(InnerShareableEntityReference { location, .. }) = inner_entity_reference.deref()
+        {
+            SingleEntityIterator::new(location.entity_index)
+        } else {
+            unreachable!()
+        }
+    }
+}
+
 /// The entity with mutability
 pub struct WithEntityMut;
 
@@ -157,6 +254,18 @@ impl<'a> QueryParam<'a> for WithEntityMut {
         }
     }
 
+    fn iter_batch(
+        archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        WithEntityMutIterator {
+            current_entity_index: entity_range.start,
+            end_entity_index: entity_range.end,
+            archetype,
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -237,6 +346,19 @@ impl<'a> QueryParam<'a> for WithId {
         }
     }
 
+    fn iter_batch(
+        archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        let begin = archetype.entity_ids.as_ptr() as *mut EntityId;
+        WithIdIterator {
+            current: unsafe { NonNull::new_unchecked(begin.add(entity_range.start)) },
+            end: unsafe { NonNull::new_unchecked(begin.add(entity_range.end)) },
+            _marker: Default::default(),
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -307,6 +429,20 @@ impl<'a> QueryParam<'a> for WithName {
         }
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        With::<Name>::lock_storages(archetype, locked_storages)
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        WithNameIterator {
+            with_iterator: With::iter_batch(archetype, locked_storages, entity_range),
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -363,6 +499,20 @@ impl<'a> QueryParam<'a> for WithEnabled {
         }
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        With::<Enabled>::lock_storages(archetype, locked_storages)
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        WithEnabledIterator {
+            with_iterator: With::iter_batch(archetype, locked_storages, entity_range),
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -372,32 +522,96 @@ impl<'a> QueryParam<'a> for WithEnabled {
     }
 }
 
+/// Get the pointers over the components of a range of entities, returns the beginning of the components,
+/// the beginning of the entity lengths and the end of the components
+fn storage_pointers<T: Component + 'static>(
+    component_storage: &dyn ComponentStorage,
+    entity_range: Range<usize>,
+) -> (NonNull<T>, NonNull<usize>, NonNull<T>) {
+    let component_storage = component_storage
+        .as_any_ref()
+        .downcast_ref::<VecComponentStorage<T>>()
+        .unwrap();
+
+    let data_range = component_storage.data.data_range(entity_range.clone());
+    let begin = component_storage.data.data.as_ptr() as *mut T;
+    let begin_entity_length = component_storage.data.lengths.as_ptr() as *mut usize;
+
+    unsafe {
+        (
+            NonNull::new_unchecked(begin.add(data_range.start)),
+            NonNull::new_unchecked(begin_entity_length.add(entity_range.start)),
+            NonNull::new_unchecked(begin.add(data_range.end)),
+        )
+    }
+}
+
 /// An iterator over entity components with a given type
 pub struct WithIterator<'a, T: Component + 'static> {
-    _component_storage_lock: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
+    _component_storage_lock: Option<RwLockReadGuard<'a, Box<dyn ComponentStorage>>>,
     current: NonNull<T>,
     current_entity_length: NonNull<usize>,
     current_entity_index: usize,
     end: NonNull<T>,
 }
 
+/// The slice length used by the empty iterators, so they are at the end of their entity
+static EMPTY_ENTITY_LENGTH: usize = 1;
+
+/// Returns true if an entity of an archetype has a component stored in a sparse set
+fn has_sparse_set_component<T: Component + 'static>(
+    archetype: &Archetype,
+    entity_index: usize,
+) -> bool {
+    archetype.sparse_sets.contains_index(
+        ComponentTypeIndex::of::<T>(),
+        archetype.entity_ids[entity_index],
+    )
+}
+
+/// Get the locked sparse set of a component type and the position of the components of an entity into it
+fn locked_sparse_set<'a, T: Component + 'static>(
+    archetype: &'a Archetype,
+    locked_storages: &LockedStorages<'a>,
+    entity_index: usize,
+) -> Option<(&'a SparseSet, usize)> {
+    let sparse_set = locked_storages.get_sparse_set(ComponentTypeIndex::of::<T>())?;
+    let index = sparse_set.get_index(archetype.entity_ids[entity_index])?;
+
+    Some((sparse_set, index))
+}
+
 impl<'a, T: Component + 'static> WithIterator<'a, T> {
+    /// Returns an iterator without any component
+    fn empty() -> Self {
+        WithIterator {
+            _component_storage_lock: None,
+            current: NonNull::dangling(),
+            current_entity_length: NonNull::from(&EMPTY_ENTITY_LENGTH),
+            current_entity_index: 0,
+            end: NonNull::dangling(),
+        }
+    }
+
     fn new(component_storage_lock: RwLockReadGuard<'a, Box<dyn ComponentStorage>>) -> Self {
-        let component_storage = component_storage_lock
-            .as_any_ref()
-            .downcast_ref::<VecComponentStorage<T>>()
-            .unwrap();
+        let entity_count = component_storage_lock.slice_count();
+        let mut iterator = Self::new_batch(&**component_storage_lock, 0..entity_count);
+        iterator._component_storage_lock = Some(component_storage_lock);
 
-        let begin = component_storage.data.data.as_ptr() as *mut T;
-        let begin_entity_length = component_storage.data.lengths.as_ptr() as *mut usize;
-        let end = unsafe { begin.add(component_storage.data.data.len()) };
+        iterator
+    }
+
+    /// Iter over a range of entities, the storage should stay locked while the iterator is used
+    fn new_batch(component_storage: &dyn ComponentStorage, entity_range: Range<usize>) -> Self {
+        let (begin, begin_entity_length, end) =
+            storage_pointers::<T>(component_storage, entity_range);
 
         WithIterator {
-            _component_storage_lock: component_storage_lock,
-            current: unsafe { NonNull::new_unchecked(begin) },
-            current_entity_length: unsafe { NonNull::new_unchecked(begin_entity_length) },
+            _component_storage_lock: None,
+            current: begin,
+            current_entity_length: begin_entity_length,
             current_entity_index: 0,
-            end: unsafe { NonNull::new_unchecked(end) },
+            end,
         }
     }
 }
@@ -443,7 +657,8 @@ impl<'a, T: Component + 'static> EntityIterator for WithIterator<'a, T> {
 
 /// An iterator over entity components of a single entity
 pub struct FromEntityWithIterator<'a, T: Component + 'static> {
-    _component_storage_lock: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
+    _component_storage_lock: Option<RwLockReadGuard<'a, Box<dyn ComponentStorage>>>,
+    _sparse_set_lock: Option<RwLockReadGuard<'a, SparseSet>>,
     begin: NonNull<T>,
     current: NonNull<T>,
     end: NonNull<T>,
@@ -466,7 +681,46 @@ impl<'a, T: Component + 'static> FromEntityWithIterator<'a, T> {
             .as_ptr_range();
 
         Self {
-            _component_storage_lock: component_storage_lock,
+            _component_storage_lock: Some(component_storage_lock),
+            _sparse_set_lock: None,
+            begin: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
+            current: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
+            end: unsafe { NonNull::new_unchecked(slice_range.end as *mut T) },
+        }
+    }
+
+    /// Returns an iterator without any component
+    fn empty() -> Self {
+        Self {
+            _component_storage_lock: None,
+            _sparse_set_lock: None,
+            begin: NonNull::dangling(),
+            current: NonNull::dangling(),
+            end: NonNull::dangling(),
+        }
+    }
+
+    /// Iter over the components of an entity stored in a sparse set, nothing if the entity has none
+    fn from_sparse_set(
+        sparse_set_lock: RwLockReadGuard<'a, SparseSet>,
+        entity_id: EntityId,
+    ) -> Self {
+        let slice_range = match sparse_set_lock.get_index(entity_id).and_then(|index| {
+            sparse_set_lock
+                .storage
+                .as_any_ref()
+                .downcast_ref::<VecComponentStorage<T>>()
+                .unwrap()
+                .data
+                .get_slice(index)
+        }) {
+            Some(slice) => slice.as_ptr_range(),
+            None => return Self::empty(),
+        };
+
+        Self {
+            _component_storage_lock: None,
+            _sparse_set_lock: Some(sparse_set_lock),
             begin: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
             current: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
             end: unsafe { NonNull::new_unchecked(slice_range.end as *mut T) },
@@ -514,15 +768,60 @@ impl<'a, T: Component + 'static> QueryParam<'a> for With<T> {
     type FromEntityReferenceIterator = FromEntityWithIterator<'a, T>;
 
     fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
-        component_types.contains(&ScriptObjectType::of::<T>())
+        // The sparse set components are checked entity by entity
+        T::is_sparse_set() || component_types.contains_index(ComponentTypeIndex::of::<T>())
+    }
+
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        !T::is_sparse_set() || has_sparse_set_component::<T>(archetype, entity_index)
     }
 
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
+        // The sparse set components are only iterated entity by entity, see QueryParam::has_sparse_set
+        if T::is_sparse_set() {
+            return WithIterator::empty();
+        }
+
         let component_storage_lock =
-            archetype.component_storages[&ScriptObjectType::of::<T>()].read();
+            archetype.component_storages[ComponentTypeIndex::of::<T>()].read();
         WithIterator::new(component_storage_lock)
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        if T::is_sparse_set() {
+            locked_storages.read_sparse_set(archetype, ComponentTypeIndex::of::<T>());
+        } else {
+            locked_storages.read(archetype, ComponentTypeIndex::of::<T>());
+        }
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        if T::is_sparse_set() {
+            return locked_sparse_set::<T>(archetype, locked_storages, entity_range.start)
+                .map(|(sparse_set, index)| {
+                    WithIterator::new_batch(sparse_set.storage.as_ref(), index..(index + 1))
+                })
+                .unwrap_or_else(WithIterator::empty);
+        }
+
+        WithIterator::new_batch(
+            locked_storages.get(ComponentTypeIndex::of::<T>()).unwrap(),
+            entity_range,
+        )
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -541,8 +840,21 @@ impl<'a, T: Component + 'static> QueryParam<'a> for With<T> {
                 .unwrap()
             };
 
+            if T::is_sparse_set() {
+                return match archetype
+                    .sparse_sets
+                    .get_by_index(ComponentTypeIndex::of::<T>())
+                {
+                    Some(sparse_set) => FromEntityWithIterator::from_sparse_set(
+                        sparse_set.read(),
+                        archetype.entity_ids[location.entity_index],
+                    ),
+                    None => FromEntityWithIterator::empty(),
+                };
+            }
+
             let component_storage_lock =
-                archetype.component_storages[&ScriptObjectType::of::<T>()].read();
+                archetype.component_storages[ComponentTypeIndex::of::<T>()].read();
 
             FromEntityWithIterator::new(component_storage_lock, &location)
         } else {
@@ -553,45 +865,74 @@ impl<'a, T: Component + 'static> QueryParam<'a> for With<T> {
 
 /// An iterator over entity components with a given type with mutability
 pub struct WithMutIterator<'a, T: Component + 'static> {
-    _component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    _component_storage_lock: Option<RwLockWriteGuard<'a, Box<dyn ComponentStorage>>>,
     current: NonNull<T>,
     current_entity_length: NonNull<usize>,
     current_entity_index: usize,
+    current_ticks: NonNull<ComponentTicks>,
     end: NonNull<T>,
 }
 
 impl<'a, T: Component + 'static> WithMutIterator<'a, T> {
-    fn new(component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>) -> Self {
-        let component_storage = component_storage_lock
-            .as_any_ref()
-            .downcast_ref::<VecComponentStorage<T>>()
-            .unwrap();
+    /// Returns an iterator without any component
+    fn empty() -> Self {
+        Self {
+            _component_storage_lock: None,
+            current: NonNull::dangling(),
+            current_entity_length: NonNull::from(&EMPTY_ENTITY_LENGTH),
+            current_entity_index: 0,
+            current_ticks: NonNull::dangling(),
+            end: NonNull::dangling(),
+        }
+    }
+
+    fn new(
+        component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+        component_ticks: &'a [ComponentTicks],
+    ) -> Self {
+        let entity_count = component_storage_lock.slice_count();
+        let mut iterator =
+            Self::new_batch(&**component_storage_lock, component_ticks, 0..entity_count);
+        iterator._component_storage_lock = Some(component_storage_lock);
 
-        let begin = component_storage.data.data.as_ptr() as *mut T;
-        let begin_entity_length = component_storage.data.lengths.as_ptr() as *mut usize;
-        let end = unsafe { begin.add(component_storage.data.data.len()) };
+        iterator
+    }
+
+    /// Iter over a range of entities, the storage should stay locked while the iterator is used
+    fn new_batch(
+        component_storage: &dyn ComponentStorage,
+        component_ticks: &'a [ComponentTicks],
+        entity_range: Range<usize>,
+    ) -> Self {
+        let begin_ticks = component_ticks.as_ptr() as *mut ComponentTicks;
+        let current_ticks = unsafe { begin_ticks.add(entity_range.start) };
+        let (begin, begin_entity_length, end) =
+            storage_pointers::<T>(component_storage, entity_range);
 
         Self {
-            _component_storage_lock: component_storage_lock,
-            current: unsafe { NonNull::new_unchecked(begin) },
-            current_entity_length: unsafe { NonNull::new_unchecked(begin_entity_length) },
+            _component_storage_lock: None,
+            current: begin,
+            current_entity_length: begin_entity_length,
             current_entity_index: 0,
-            end: unsafe { NonNull::new_unchecked(end) },
+            current_ticks: unsafe { NonNull::new_unchecked(current_ticks) },
+            end,
         }
     }
 }
 
 impl<'a, T: Component + 'static> Iterator for WithMutIterator<'a, T> {
-    type Item = &'a mut T;
+    type Item = Mut<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current < self.end {
-            let result = unsafe { self.current.as_mut() };
+            let result = self.current();
             self.current = unsafe { NonNull::new_unchecked(self.current.as_ptr().add(1)) };
 
             if self.current_entity_index + 1 == *unsafe { self.current_entity_length.as_ref() } {
                 self.current_entity_length =
                     unsafe { NonNull::new_unchecked(self.current_entity_length.as_ptr().add(1)) };
+                self.current_ticks =
+                    unsafe { NonNull::new_unchecked(self.current_ticks.as_ptr().add(1)) };
                 self.current_entity_index = 0;
             } else {
                 self.current_entity_index += 1;
@@ -606,7 +947,10 @@ impl<'a, T: Component + 'static> Iterator for WithMutIterator<'a, T> {
 
 impl<'a, T: Component + 'static> EntityIterator for WithMutIterator<'a, T> {
     fn current(&mut self) -> Self::Item {
-        unsafe { self.current.as_mut() }
+        Mut {
+            value: unsafe { self.current.as_mut() },
+            ticks: unsafe { self.current_ticks.as_ref() },
+        }
     }
 
     fn has_reach_entity_end(&self) -> bool {
@@ -622,15 +966,18 @@ impl<'a, T: Component + 'static> EntityIterator for WithMutIterator<'a, T> {
 
 /// An iterator over entity components with a given type with mutability
 pub struct FromEntityWithMutIterator<'a, T: Component + 'static> {
-    _component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    _component_storage_lock: Option<RwLockWriteGuard<'a, Box<dyn ComponentStorage>>>,
+    _sparse_set_lock: Option<RwLockWriteGuard<'a, SparseSet>>,
     begin: NonNull<T>,
     current: NonNull<T>,
     end: NonNull<T>,
+    ticks: NonNull<ComponentTicks>,
 }
 
 impl<'a, T: Component + 'static> FromEntityWithMutIterator<'a, T> {
     fn new(
         component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+        ticks: &'a ComponentTicks,
         location: &EntityLocation,
     ) -> Self {
         let vec_component_storage = component_storage_lock
@@ -645,20 +992,65 @@ impl<'a, T: Component + 'static> FromEntityWithMutIterator<'a, T> {
             .as_ptr_range();
 
         Self {
-            _component_storage_lock: component_storage_lock,
+            _component_storage_lock: Some(component_storage_lock),
+            _sparse_set_lock: None,
+            begin: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
+            current: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
+            end: unsafe { NonNull::new_unchecked(slice_range.end as *mut T) },
+            ticks: NonNull::from(ticks),
+        }
+    }
+
+    /// Returns an iterator without any component
+    fn empty() -> Self {
+        Self {
+            _component_storage_lock: None,
+            _sparse_set_lock: None,
+            begin: NonNull::dangling(),
+            current: NonNull::dangling(),
+            end: NonNull::dangling(),
+            ticks: NonNull::dangling(),
+        }
+    }
+
+    /// Iter over the components of an entity stored in a sparse set, nothing if the entity has none
+    fn from_sparse_set(
+        sparse_set_lock: RwLockWriteGuard<'a, SparseSet>,
+        entity_id: EntityId,
+    ) -> Self {
+        let index = match sparse_set_lock.get_index(entity_id) {
+            Some(index) => index,
+            None => return Self::empty(),
+        };
+
+        let slice_range = sparse_set_lock
+            .storage
+            .as_any_ref()
+            .downcast_ref::<VecComponentStorage<T>>()
+            .unwrap()
+            .data
+            .get_slice(index)
+            .unwrap()
+            .as_ptr_range();
+        let ticks = NonNull::from(&sparse_set_lock.component_ticks[index]);
+
+        Self {
+            _component_storage_lock: None,
+            _sparse_set_lock: Some(sparse_set_lock),
             begin: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
             current: unsafe { NonNull::new_unchecked(slice_range.start as *mut T) },
             end: unsafe { NonNull::new_unchecked(slice_range.end as *mut T) },
+            ticks,
         }
     }
 }
 
 impl<'a, T: Component + 'static> Iterator for FromEntityWithMutIterator<'a, T> {
-    type Item = &'a mut T;
+    type Item = Mut<'a, T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current < self.end {
-            let result = unsafe { self.current.as_mut() };
+            let result = self.current();
             self.current = unsafe { NonNull::new_unchecked(self.current.as_ptr().add(1)) };
 
             Some(result)
@@ -670,7 +1062,10 @@ impl<'a, T: Component + 'static> Iterator for FromEntityWithMutIterator<'a, T> {
 
 impl<'a, T: Component + 'static> EntityIterator for FromEntityWithMutIterator<'a, T> {
     fn current(&mut self) -> Self::Item {
-        unsafe { self.current.as_mut() }
+        Mut {
+            value: unsafe { self.current.as_mut() },
+            ticks: unsafe { self.ticks.as_ref() },
+        }
     }
 
     fn has_reach_entity_end(&self) -> bool {
@@ -688,18 +1083,71 @@ pub struct WithMut<T> {
 }
 
 impl<'a, T: Component + 'static> QueryParam<'a> for WithMut<T> {
-    type Item = &'a mut T;
+    type Item = Mut<'a, T>;
     type Iterator = WithMutIterator<'a, T>;
     type FromEntityReferenceIterator = FromEntityWithMutIterator<'a, T>;
 
     fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
-        component_types.contains(&ScriptObjectType::of::<T>())
+        // The sparse set components are checked entity by entity
+        T::is_sparse_set() || component_types.contains_index(ComponentTypeIndex::of::<T>())
+    }
+
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        !T::is_sparse_set() || has_sparse_set_component::<T>(archetype, entity_index)
     }
 
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
+        // The sparse set components are only iterated entity by entity, see QueryParam::has_sparse_set
+        if T::is_sparse_set() {
+            return WithMutIterator::empty();
+        }
+
         let component_storage_lock =
-            archetype.component_storages[&ScriptObjectType::of::<T>()].write();
-        WithMutIterator::new(component_storage_lock)
+            archetype.component_storages[ComponentTypeIndex::of::<T>()].write();
+        WithMutIterator::new(
+            component_storage_lock,
+            &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+        )
+    }
+
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        if T::is_sparse_set() {
+            locked_storages.write_sparse_set(archetype, ComponentTypeIndex::of::<T>());
+        } else {
+            locked_storages.write(archetype, ComponentTypeIndex::of::<T>());
+        }
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        if T::is_sparse_set() {
+            return locked_sparse_set::<T>(archetype, locked_storages, entity_range.start)
+                .map(|(sparse_set, index)| {
+                    WithMutIterator::new_batch(
+                        sparse_set.storage.as_ref(),
+                        &sparse_set.component_ticks,
+                        index..(index + 1),
+                    )
+                })
+                .unwrap_or_else(WithMutIterator::empty);
+        }
+
+        WithMutIterator::new_batch(
+            locked_storages.get(ComponentTypeIndex::of::<T>()).unwrap(),
+            &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+            entity_range,
+        )
     }
 
     fn from_entity_reference(
@@ -720,10 +1168,26 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithMut<T> {
                 .unwrap()
             };
 
+            if T::is_sparse_set() {
+                return match archetype
+                    .sparse_sets
+                    .get_by_index(ComponentTypeIndex::of::<T>())
+                {
+                    Some(sparse_set) => FromEntityWithMutIterator::from_sparse_set(
+                        sparse_set.write(),
+                        archetype.entity_ids[location.entity_index],
+                    ),
+                    None => FromEntityWithMutIterator::empty(),
+                };
+            }
+
             let component_storage_lock =
-                archetype.component_storages[&ScriptObjectType::of::<T>()].write();
+                archetype.component_storages[ComponentTypeIndex::of::<T>()].write();
+            let ticks = archetype
+                .get_component_ticks_by_index(ComponentTypeIndex::of::<T>(), location.entity_index)
+                .unwrap();
 
-            FromEntityWithMutIterator::new(component_storage_lock, &location)
+            FromEntityWithMutIterator::new(component_storage_lock, ticks, location)
         } else {
             unreachable!()
         }
@@ -793,10 +1257,19 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithOptional<T> {
         true
     }
 
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
+        // The sparse set components are only iterated entity by entity, see QueryParam::has_sparse_set
+        if T::is_sparse_set() {
+            return WithOptionalIterator::empty();
+        }
+
         if let Some(component_storage) = archetype
             .component_storages
-            .get(&ScriptObjectType::of::<T>())
+            .get_by_index(ComponentTypeIndex::of::<T>())
         {
             WithOptionalIterator::new(WithIterator::new(component_storage.read()))
         } else {
@@ -804,6 +1277,37 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithOptional<T> {
         }
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        if T::is_sparse_set() {
+            locked_storages.read_sparse_set(archetype, ComponentTypeIndex::of::<T>());
+        } else {
+            locked_storages.read(archetype, ComponentTypeIndex::of::<T>());
+        }
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        if T::is_sparse_set() {
+            return locked_sparse_set::<T>(archetype, locked_storages, entity_range.start)
+                .map(|(sparse_set, index)| {
+                    WithOptionalIterator::new(WithIterator::new_batch(
+                        sparse_set.storage.as_ref(),
+                        index..(index + 1),
+                    ))
+                })
+                .unwrap_or_else(WithOptionalIterator::empty);
+        }
+
+        if let Some(component_storage) = locked_storages.get(ComponentTypeIndex::of::<T>()) {
+            WithOptionalIterator::new(WithIterator::new_batch(component_storage, entity_range))
+        } else {
+            WithOptionalIterator::empty()
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -822,9 +1326,26 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithOptional<T> {
                 .unwrap()
             };
 
+            if T::is_sparse_set() {
+                let entity_id = archetype.entity_ids[location.entity_index];
+                return match archetype
+                    .sparse_sets
+                    .get_by_index(ComponentTypeIndex::of::<T>())
+                    .map(|sparse_set| sparse_set.read())
+                {
+                    Some(sparse_set_lock) if sparse_set_lock.get_index(entity_id).is_some() => {
+                        WithOptionalIterator::new(FromEntityWithIterator::from_sparse_set(
+                            sparse_set_lock,
+                            entity_id,
+                        ))
+                    }
+                    _ => WithOptionalIterator::empty(),
+                };
+            }
+
             if let Some(component_storage) = archetype
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 let component_storage_lock = component_storage.read();
                 WithOptionalIterator::new(FromEntityWithIterator::new(
@@ -846,7 +1367,7 @@ pub struct WithOptionalMut<T> {
 }
 
 impl<'a, T: Component + 'static> QueryParam<'a> for WithOptionalMut<T> {
-    type Item = Option<&'a mut T>;
+    type Item = Option<Mut<'a, T>>;
     type Iterator = WithOptionalIterator<WithMutIterator<'a, T>>;
     type FromEntityReferenceIterator = WithOptionalIterator<FromEntityWithMutIterator<'a, T>>;
 
@@ -857,9 +1378,32 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithOptionalMut<T> {
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
         if let Some(component_storage) = archetype
             .component_storages
-            .get(&ScriptObjectType::of::<T>())
+            .get_by_index(ComponentTypeIndex::of::<T>())
         {
-            WithOptionalIterator::new(WithMutIterator::new(component_storage.write()))
+            WithOptionalIterator::new(WithMutIterator::new(
+                component_storage.write(),
+                &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+            ))
+        } else {
+            WithOptionalIterator::empty()
+        }
+    }
+
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        locked_storages.write(archetype, ComponentTypeIndex::of::<T>());
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        if let Some(component_storage) = locked_storages.get(ComponentTypeIndex::of::<T>()) {
+            WithOptionalIterator::new(WithMutIterator::new_batch(
+                component_storage,
+                &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+                entity_range,
+            ))
         } else {
             WithOptionalIterator::empty()
         }
@@ -885,11 +1429,18 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithOptionalMut<T> {
 
             if let Some(component_storage) = archetype
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 let component_storage_lock = component_storage.write();
+                let ticks = archetype
+                    .get_component_ticks_by_index(
+                        ComponentTypeIndex::of::<T>(),
+                        location.entity_index,
+                    )
+                    .unwrap();
                 WithOptionalIterator::new(FromEntityWithMutIterator::new(
                     component_storage_lock,
+                    ticks,
                     &location,
                 ))
             } else {
@@ -903,7 +1454,7 @@ impl<'a, T: Component + 'static> QueryParam<'a> for WithOptionalMut<T> {
 
 /// An iterator over entity components with a given type and its associated extension
 pub struct WithExtensionIterator<'a, T: Component + 'static, E: Component + 'static> {
-    _extension_storage_lock: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
+    _extension_storage_lock: Option<RwLockReadGuard<'a, Box<dyn ComponentStorage>>>,
     with_iterator: WithIterator<'a, T>,
     current_extension: NonNull<E>,
 }
@@ -913,17 +1464,31 @@ impl<'a, T: Component + 'static, E: Component + 'static> WithExtensionIterator<'
         component_storage_lock: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
         extension_storage_lock: RwLockReadGuard<'a, Box<dyn ComponentStorage>>,
     ) -> Self {
-        let extension_component_storage = extension_storage_lock
-            .as_any_ref()
-            .downcast_ref::<VecComponentStorage<E>>()
-            .unwrap();
-
-        let begin_extension = extension_component_storage.data.data.as_ptr() as *mut E;
+        let entity_count = component_storage_lock.slice_count();
+        let mut iterator = Self::new_batch(
+            &**component_storage_lock,
+            &**extension_storage_lock,
+            0..entity_count,
+        );
+        iterator._extension_storage_lock = Some(extension_storage_lock);
+        iterator.with_iterator._component_storage_lock = Some(component_storage_lock);
+
+        iterator
+    }
+
+    /// Iter over a range of entities, the storages should stay locked while the iterator is used
+    fn new_batch(
+        component_storage: &dyn ComponentStorage,
+        extension_storage: &dyn ComponentStorage,
+        entity_range: Range<usize>,
+    ) -> Self {
+        let (begin_extension, _, _) =
+            storage_pointers::<E>(extension_storage, entity_range.clone());
 
         Self {
-            _extension_storage_lock: extension_storage_lock,
-            with_iterator: WithIterator::new(component_storage_lock),
-            current_extension: unsafe { NonNull::new_unchecked(begin_extension) },
+            _extension_storage_lock: None,
+            with_iterator: WithIterator::new_batch(component_storage, entity_range),
+            current_extension: begin_extension,
         }
     }
 }
@@ -1049,19 +1614,36 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a> for With
     type FromEntityReferenceIterator = FromEntityWithExtensionIterator<'a, T, E>;
 
     fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
-        component_types.contains(&ScriptObjectType::of::<T>())
+        component_types.contains_index(ComponentTypeIndex::of::<T>())
     }
 
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
         let component_storage_lock =
-            archetype.component_storages[&ScriptObjectType::of::<T>()].read();
+            archetype.component_storages[ComponentTypeIndex::of::<T>()].read();
 
         let extension_component_storage_lock =
-            archetype.component_storages[&ScriptObjectType::of::<E>()].read();
+            archetype.component_storages[ComponentTypeIndex::of::<E>()].read();
 
         Self::Iterator::new(component_storage_lock, extension_component_storage_lock)
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        locked_storages.read(archetype, ComponentTypeIndex::of::<T>());
+        locked_storages.read(archetype, ComponentTypeIndex::of::<E>());
+    }
+
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        Self::Iterator::new_batch(
+            locked_storages.get(ComponentTypeIndex::of::<T>()).unwrap(),
+            locked_storages.get(ComponentTypeIndex::of::<E>()).unwrap(),
+            entity_range,
+        )
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -1081,10 +1663,10 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a> for With
             };
 
             let component_storage_lock =
-                archetype.component_storages[&ScriptObjectType::of::<T>()].read();
+                archetype.component_storages[ComponentTypeIndex::of::<T>()].read();
 
             let extension_component_storage_lock =
-                archetype.component_storages[&ScriptObjectType::of::<E>()].read();
+                archetype.component_storages[ComponentTypeIndex::of::<E>()].read();
 
             FromEntityWithExtensionIterator::new(
                 component_storage_lock,
@@ -1099,39 +1681,88 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a> for With
 
 /// An iterator over entity components with a given type and its associated extension with mutability
 pub struct WithExtensionMutIterator<'a, T: Component + 'static, E: Component + 'static> {
-    _extension_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+    _extension_storage_lock: Option<RwLockWriteGuard<'a, Box<dyn ComponentStorage>>>,
     with_iterator: WithMutIterator<'a, T>,
     current_extension: NonNull<E>,
+    begin_component_ticks: NonNull<ComponentTicks>,
+    extension_ticks: &'a [ComponentTicks],
 }
 
 impl<'a, T: Component + 'static, E: Component + 'static> WithExtensionMutIterator<'a, T, E> {
     fn new(
         component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
         extension_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+        component_ticks: &'a [ComponentTicks],
+        extension_ticks: &'a [ComponentTicks],
     ) -> Self {
-        let extension_component_storage = extension_storage_lock
-            .as_any_ref()
-            .downcast_ref::<VecComponentStorage<E>>()
-            .unwrap();
-
-        let begin_extension = extension_component_storage.data.data.as_ptr() as *mut E;
+        let entity_count = component_storage_lock.slice_count();
+        let mut iterator = Self::new_batch(
+            &**component_storage_lock,
+            &**extension_storage_lock,
+            component_ticks,
+            extension_ticks,
+            0..entity_count,
+        );
+        iterator._extension_storage_lock = Some(extension_storage_lock);
+        iterator.with_iterator._component_storage_lock = Some(component_storage_lock);
+
+        iterator
+    }
+
+    /// Iter over a range of entities, the storages should stay locked while the iterator is used
+    fn new_batch(
+        component_storage: &dyn ComponentStorage,
+        extension_storage: &dyn ComponentStorage,
+        component_ticks: &'a [ComponentTicks],
+        extension_ticks: &'a [ComponentTicks],
+        entity_range: Range<usize>,
+    ) -> Self {
+        let (begin_extension, _, _) =
+            storage_pointers::<E>(extension_storage, entity_range.clone());
 
         Self {
-            _extension_storage_lock: extension_storage_lock,
-            with_iterator: WithMutIterator::new(component_storage_lock),
-            current_extension: unsafe { NonNull::new_unchecked(begin_extension) },
+            _extension_storage_lock: None,
+            with_iterator: WithMutIterator::new_batch(
+                component_storage,
+                component_ticks,
+                entity_range,
+            ),
+            current_extension: begin_extension,
+            begin_component_ticks: unsafe {
+                NonNull::new_unchecked(component_ticks.as_ptr() as *mut ComponentTicks)
+            },
+            extension_ticks,
         }
     }
+
+    /// The extension ticks of the entity the iterator is on
+    fn current_extension_ticks(&self) -> &'a ComponentTicks {
+        let entity_index = unsafe {
+            self.with_iterator
+                .current_ticks
+                .as_ptr()
+                .offset_from(self.begin_component_ticks.as_ptr())
+        };
+
+        &self.extension_ticks[entity_index as usize]
+    }
 }
 
 impl<'a, T: Component + 'static, E: Component + 'static> Iterator
     for WithExtensionMutIterator<'a, T, E>
 {
-    type Item = (&'a mut T, &'a mut E);
+    type Item = (Mut<'a, T>, Mut<'a, E>);
 
     fn next(&mut self) -> Option<Self::Item> {
+        let extension_ticks = self.current_extension_ticks();
         self.with_iterator.next().map(|component| {
-            let result = (component, unsafe { self.current_extension.as_mut() });
+            let result = (
+                component,
+                Mut {
+                    value: unsafe { self.current_extension.as_mut() },
+                    ticks: extension_ticks,
+                },
+            );
             self.current_extension =
                 unsafe { NonNull::new_unchecked(self.current_extension.as_ptr().add(1)) };
 
@@ -1144,9 +1775,14 @@ impl<'a, T: Component + 'static, E: Component + 'static> EntityIterator
     for WithExtensionMutIterator<'a, T, E>
 {
     fn current(&mut self) -> Self::Item {
-        (unsafe { self.with_iterator.current.as_mut() }, unsafe {
-            self.current_extension.as_mut()
-        })
+        let extension_ticks = self.current_extension_ticks();
+        (
+            self.with_iterator.current(),
+            Mut {
+                value: unsafe { self.current_extension.as_mut() },
+                ticks: extension_ticks,
+            },
+        )
     }
 
     fn has_reach_entity_end(&self) -> bool {
@@ -1171,6 +1807,7 @@ pub struct FromEntityWithExtensionMutIterator<'a, T: Component + 'static, E: Com
     with_iterator: FromEntityWithMutIterator<'a, T>,
     current_extension: NonNull<E>,
     begin_extension: NonNull<E>,
+    extension_ticks: &'a ComponentTicks,
 }
 
 impl<'a, T: Component + 'static, E: Component + 'static>
@@ -1179,6 +1816,8 @@ impl<'a, T: Component + 'static, E: Component + 'static>
     fn new(
         component_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
         extension_storage_lock: RwLockWriteGuard<'a, Box<dyn ComponentStorage>>,
+        component_ticks: &'a ComponentTicks,
+        extension_ticks: &'a ComponentTicks,
         location: &EntityLocation,
     ) -> Self {
         let extension_component_storage = extension_storage_lock
@@ -1194,9 +1833,14 @@ impl<'a, T: Component + 'static, E: Component + 'static>
 
         Self {
             _extension_storage_lock: extension_storage_lock,
-            with_iterator: FromEntityWithMutIterator::new(component_storage_lock, location),
+            with_iterator: FromEntityWithMutIterator::new(
+                component_storage_lock,
+                component_ticks,
+                location,
+            ),
             current_extension: unsafe { NonNull::new_unchecked(begin_extension) },
             begin_extension: unsafe { NonNull::new_unchecked(begin_extension) },
+            extension_ticks,
         }
     }
 }
@@ -1204,11 +1848,17 @@ impl<'a, T: Component + 'static, E: Component + 'static>
 impl<'a, T: Component + 'static, E: Component + 'static> Iterator
     for FromEntityWithExtensionMutIterator<'a, T, E>
 {
-    type Item = (&'a mut T, &'a mut E);
+    type Item = (Mut<'a, T>, Mut<'a, E>);
 
     fn next(&mut self) -> Option<Self::Item> {
         self.with_iterator.next().map(|component| {
-            let result = (component, unsafe { self.current_extension.as_mut() });
+            let result = (
+                component,
+                Mut {
+                    value: unsafe { self.current_extension.as_mut() },
+                    ticks: self.extension_ticks,
+                },
+            );
             self.current_extension =
                 unsafe { NonNull::new_unchecked(self.current_extension.as_ptr().add(1)) };
 
@@ -1221,9 +1871,13 @@ impl<'a, T: Component + 'static, E: Component + 'static> EntityIterator
     for FromEntityWithExtensionMutIterator<'a, T, E>
 {
     fn current(&mut self) -> Self::Item {
-        (unsafe { self.with_iterator.current.as_mut() }, unsafe {
-            self.current_extension.as_mut()
-        })
+        (
+            self.with_iterator.current(),
+            Mut {
+                value: unsafe { self.current_extension.as_mut() },
+                ticks: self.extension_ticks,
+            },
+        )
     }
 
     fn has_reach_entity_end(&self) -> bool {
@@ -1242,22 +1896,46 @@ pub struct WithExtensionMut<T, E> {
 }
 
 impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a> for WithExtensionMut<T, E> {
-    type Item = (&'a mut T, &'a mut E);
+    type Item = (Mut<'a, T>, Mut<'a, E>);
     type Iterator = WithExtensionMutIterator<'a, T, E>;
     type FromEntityReferenceIterator = FromEntityWithExtensionMutIterator<'a, T, E>;
 
     fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
-        component_types.contains(&ScriptObjectType::of::<T>())
+        component_types.contains_index(ComponentTypeIndex::of::<T>())
     }
 
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
         let component_storage_lock =
-            archetype.component_storages[&ScriptObjectType::of::<T>()].write();
+            archetype.component_storages[ComponentTypeIndex::of::<T>()].write();
 
         let extension_component_storage_lock =
-            archetype.component_storages[&ScriptObjectType::of::<E>()].write();
+            archetype.component_storages[ComponentTypeIndex::of::<E>()].write();
 
-        Self::Iterator::new(component_storage_lock, extension_component_storage_lock)
+        Self::Iterator::new(
+            component_storage_lock,
+            extension_component_storage_lock,
+            &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+            &archetype.component_ticks[ComponentTypeIndex::of::<E>()],
+        )
+    }
+
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        locked_storages.write(archetype, ComponentTypeIndex::of::<T>());
+        locked_storages.write(archetype, ComponentTypeIndex::of::<E>());
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        Self::Iterator::new_batch(
+            locked_storages.get(ComponentTypeIndex::of::<T>()).unwrap(),
+            locked_storages.get(ComponentTypeIndex::of::<E>()).unwrap(),
+            &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+            &archetype.component_ticks[ComponentTypeIndex::of::<E>()],
+            entity_range,
+        )
     }
 
     fn from_entity_reference(
@@ -1279,14 +1957,26 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a> for With
             };
 
             let component_storage_lock =
-                archetype.component_storages[&ScriptObjectType::of::<T>()].write();
+                archetype.component_storages[ComponentTypeIndex::of::<T>()].write();
 
             let extension_component_storage_lock =
-                archetype.component_storages[&ScriptObjectType::of::<E>()].write();
+                archetype.component_storages[ComponentTypeIndex::of::<E>()].write();
 
             FromEntityWithExtensionMutIterator::new(
                 component_storage_lock,
                 extension_component_storage_lock,
+                archetype
+                    .get_component_ticks_by_index(
+                        ComponentTypeIndex::of::<T>(),
+                        location.entity_index,
+                    )
+                    .unwrap(),
+                archetype
+                    .get_component_ticks_by_index(
+                        ComponentTypeIndex::of::<E>(),
+                        location.entity_index,
+                    )
+                    .unwrap(),
                 &location,
             )
         } else {
@@ -1315,11 +2005,11 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
         if let Some(component_storage) = archetype
             .component_storages
-            .get(&ScriptObjectType::of::<T>())
+            .get_by_index(ComponentTypeIndex::of::<T>())
         {
             if let Some(extension_storage) = archetype
                 .component_storages
-                .get(&ScriptObjectType::of::<E>())
+                .get_by_index(ComponentTypeIndex::of::<E>())
             {
                 WithOptionalIterator::new(WithExtensionIterator::new(
                     component_storage.read(),
@@ -1333,6 +2023,31 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
         }
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        locked_storages.read(archetype, ComponentTypeIndex::of::<T>());
+        locked_storages.read(archetype, ComponentTypeIndex::of::<E>());
+    }
+
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        match (
+            locked_storages.get(ComponentTypeIndex::of::<T>()),
+            locked_storages.get(ComponentTypeIndex::of::<E>()),
+        ) {
+            (Some(component_storage), Some(extension_storage)) => {
+                WithOptionalIterator::new(WithExtensionIterator::new_batch(
+                    component_storage,
+                    extension_storage,
+                    entity_range,
+                ))
+            }
+            _ => WithOptionalIterator::empty(),
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -1353,11 +2068,11 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
 
             if let Some(component_storage) = archetype
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 if let Some(extension_storage) = archetype
                     .component_storages
-                    .get(&ScriptObjectType::of::<E>())
+                    .get_by_index(ComponentTypeIndex::of::<E>())
                 {
                     let component_storage_lock = component_storage.read();
                     let extension_storage_lock = extension_storage.read();
@@ -1386,7 +2101,7 @@ pub struct WithExtensionOptionalMut<T, E> {
 impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
     for WithExtensionOptionalMut<T, E>
 {
-    type Item = Option<(&'a mut T, &'a mut E)>;
+    type Item = Option<(Mut<'a, T>, Mut<'a, E>)>;
     type Iterator = WithOptionalIterator<WithExtensionMutIterator<'a, T, E>>;
     type FromEntityReferenceIterator =
         WithOptionalIterator<FromEntityWithExtensionMutIterator<'a, T, E>>;
@@ -1398,15 +2113,17 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
     fn iter(archetype: &'a Archetype) -> Self::Iterator {
         if let Some(component_storage) = archetype
             .component_storages
-            .get(&ScriptObjectType::of::<T>())
+            .get_by_index(ComponentTypeIndex::of::<T>())
         {
             if let Some(extension_storage) = archetype
                 .component_storages
-                .get(&ScriptObjectType::of::<E>())
+                .get_by_index(ComponentTypeIndex::of::<E>())
             {
                 WithOptionalIterator::new(WithExtensionMutIterator::new(
                     component_storage.write(),
                     extension_storage.write(),
+                    &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+                    &archetype.component_ticks[ComponentTypeIndex::of::<E>()],
                 ))
             } else {
                 WithOptionalIterator::empty()
@@ -1416,6 +2133,33 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
         }
     }
 
+    fn lock_storages(archetype: &'a Archetype, locked_storages: &mut LockedStorages<'a>) {
+        locked_storages.write(archetype, ComponentTypeIndex::of::<T>());
+        locked_storages.write(archetype, ComponentTypeIndex::of::<E>());
+    }
+
+    fn iter_batch(
+        archetype: &'a Archetype,
+        locked_storages: &LockedStorages<'a>,
+        entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        match (
+            locked_storages.get(ComponentTypeIndex::of::<T>()),
+            locked_storages.get(ComponentTypeIndex::of::<E>()),
+        ) {
+            (Some(component_storage), Some(extension_storage)) => {
+                WithOptionalIterator::new(WithExtensionMutIterator::new_batch(
+                    component_storage,
+                    extension_storage,
+                    &archetype.component_ticks[ComponentTypeIndex::of::<T>()],
+                    &archetype.component_ticks[ComponentTypeIndex::of::<E>()],
+                    entity_range,
+                ))
+            }
+            _ => WithOptionalIterator::empty(),
+        }
+    }
+
     fn from_entity_reference(
         entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
@@ -1436,17 +2180,29 @@ impl<'a, T: Component + 'static, E: Component + 'static> QueryParam<'a>
 
             if let Some(component_storage) = archetype
                 .component_storages
-                .get(&ScriptObjectType::of::<T>())
+                .get_by_index(ComponentTypeIndex::of::<T>())
             {
                 if let Some(extension_storage) = archetype
                     .component_storages
-                    .get(&ScriptObjectType::of::<E>())
+                    .get_by_index(ComponentTypeIndex::of::<E>())
                 {
                     let component_storage_lock = component_storage.write();
                     let extension_storage_lock = extension_storage.write();
                     WithOptionalIterator::new(FromEntityWithExtensionMutIterator::new(
                         component_storage_lock,
                         extension_storage_lock,
+                        archetype
+                            .get_component_ticks_by_index(
+                                ComponentTypeIndex::of::<T>(),
+                                location.entity_index,
+                            )
+                            .unwrap(),
+                        archetype
+                            .get_component_ticks_by_index(
+                                ComponentTypeIndex::of::<E>(),
+                                location.entity_index,
+                            )
+                            .unwrap(),
                         &location,
                     ))
                 } else {
@@ -1,10 +1,11 @@
-use super::{InfiniteEntityIterator, QueryParam, SingleEntityIterator};
+use super::{
+    EntityFilterContext, InfiniteEntityIterator, LockedStorages, QueryParam, SingleEntityIterator,
+};
 use crate::{
-    component::Component,
+    component::{Component, ComponentTypeIndex},
     entity::{Archetype, ArchetypeComponentTypes, EntityReference},
 };
-use fruity_game_engine::script_value::ScriptObjectType;
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::Range};
 
 /// Exclude a component from a query
 pub struct Without<T: Component + 'static> {
@@ -17,13 +18,38 @@ impl<'a, T: Component + 'static> QueryParam<'a> for Without<T> {
     type FromEntityReferenceIterator = SingleEntityIterator<Self::Item>;
 
     fn filter_archetype(component_types: &ArchetypeComponentTypes) -> bool {
-        !component_types.contains(&ScriptObjectType::of::<T>())
+        // The sparse set components are checked entity by entity
+        T::is_sparse_set() || !component_types.contains_index(ComponentTypeIndex::of::<T>())
+    }
+
+    fn has_sparse_set() -> bool {
+        T::is_sparse_set()
+    }
+
+    fn filter_entity(
+        archetype: &Archetype,
+        entity_index: usize,
+        _context: &EntityFilterContext,
+    ) -> bool {
+        !T::is_sparse_set()
+            || !archetype.sparse_sets.contains_index(
+                ComponentTypeIndex::of::<T>(),
+                archetype.entity_ids[entity_index],
+            )
     }
 
     fn iter(_archetype: &'a Archetype) -> Self::Iterator {
         InfiniteEntityIterator::default()
     }
 
+    fn iter_batch(
+        _archetype: &'a Archetype,
+        _locked_storages: &LockedStorages<'a>,
+        _entity_range: Range<usize>,
+    ) -> Self::Iterator {
+        InfiniteEntityIterator::default()
+    }
+
     fn from_entity_reference(
         _entity_reference: &EntityReference,
     ) -> Self::FromEntityReferenceIterator {
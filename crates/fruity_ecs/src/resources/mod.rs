@@ -0,0 +1,7 @@
+/// Prefab resources, reusable templates of entities
+pub mod prefab_resource;
+
+/// Scene resources, sets of entities loaded additively into the world
+pub mod scene_resource;
+
+mod snapshot_resource;
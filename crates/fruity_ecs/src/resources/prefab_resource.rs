@@ -0,0 +1,183 @@
+use super::snapshot_resource::{get_snapshot_entities, load_snapshot_resource};
+use crate::{
+    component::Component,
+    entity::{EntityId, EntityServiceSnapshot, SerializedEntity},
+    serialization::{Deserialize, Serialize},
+};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{
+    any::FruityAny, json_schema, resource::ResourceContainer, settings::Settings, FruityError,
+    FruityResult,
+};
+use std::{collections::HashMap, future::Future, pin::Pin};
+
+/// A reusable template of entities, described in the same format as an [`EntityServiceSnapshot`]
+///
+/// The first entity is the root of the prefab, the hierarchy between the entities is kept with the
+/// entity references of the components, they refer to the local ids of the prefab
+#[derive(FruityAny, Debug)]
+#[fruity_export]
+pub struct PrefabResource {
+    identifier: String,
+    snapshot: EntityServiceSnapshot,
+}
+
+#[fruity_export]
+impl PrefabResource {
+    /// Returns a PrefabResource
+    pub fn new(identifier: &str, snapshot: EntityServiceSnapshot) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            snapshot,
+        }
+    }
+
+    /// Get the resource identifier of the prefab
+    #[fruity_export]
+    pub fn get_identifier(&self) -> String {
+        self.identifier.clone()
+    }
+
+    /// Get the entities of the prefab
+    #[fruity_export]
+    pub fn get_snapshot(&self) -> EntityServiceSnapshot {
+        self.snapshot.clone()
+    }
+
+    /// Change the entities of the prefab
+    /// The existing instances are not modified, call `EntityService.propagatePrefab` to update them
+    #[fruity_export]
+    pub fn set_snapshot(&mut self, snapshot: EntityServiceSnapshot) {
+        self.snapshot = snapshot;
+    }
+
+    /// Get the serialized entities of the prefab, the components are kept serialized
+    pub(crate) fn get_entities(
+        &self,
+        resource_container: &ResourceContainer,
+    ) -> FruityResult<Vec<SerializedEntity>> {
+        get_snapshot_entities(&self.snapshot, resource_container)
+    }
+}
+
+/// Load a prefab resource, the settings contain the entities of the prefab
+#[json_schema(resource = "prefab", path = "schemas/prefab.json")]
+pub fn load_prefab(
+    identifier: &str,
+    settings: Settings,
+    resource_container: ResourceContainer,
+) -> Pin<Box<dyn Send + Future<Output = FruityResult<()>>>> {
+    load_snapshot_resource(
+        identifier,
+        settings,
+        resource_container,
+        PrefabResource::new,
+    )
+}
+
+/// A field of a prefab component that is changed for a single instance
+#[derive(Debug, Clone, Default, FruityAny, Serialize, Deserialize)]
+#[fruity_export(from_raw_js_object = true)]
+pub struct PrefabOverride {
+    /// The local id of the overridden entity into the prefab, the root entity if none
+    pub local_id: Option<u64>,
+
+    /// The class name of the overridden component
+    pub component: String,
+
+    /// The overridden field
+    pub field: String,
+
+    /// The serialized value of the field
+    pub value: Settings,
+}
+
+#[fruity_export]
+impl PrefabOverride {
+    /// Returns true if the override applies to an entity of the prefab
+    pub(crate) fn applies_to(
+        &self,
+        serialized_entity: &SerializedEntity,
+        root_local_id: u64,
+    ) -> bool {
+        self.local_id.unwrap_or(root_local_id) == serialized_entity.local_id
+    }
+
+    /// Change the field of the first serialized component with the overridden class name
+    pub(crate) fn apply(&self, serialized_components: &mut Vec<Settings>) -> FruityResult<()> {
+        let fields = serialized_components
+            .iter_mut()
+            .filter_map(|serialized_component| match serialized_component {
+                Settings::Object(serialized_component) => Some(serialized_component),
+                _ => None,
+            })
+            .find(|serialized_component| {
+                serialized_component.get("class_name")
+                    == Some(&Settings::String(self.component.clone()))
+            })
+            .ok_or_else(|| {
+                FruityError::GenericFailure(format!(
+                    "Cannot override the field {} of the component {}, the entity has no such component",
+                    self.field, self.component
+                ))
+            })?
+            .entry("fields".to_string())
+            .or_insert_with(|| Settings::Object(HashMap::new()));
+
+        match fields {
+            Settings::Object(fields) => {
+                fields.insert(self.field.clone(), self.value.clone());
+                Ok(())
+            }
+            _ => Err(FruityError::GenericFailure(format!(
+                "Cannot override the field {} of the component {}, the fields are not an object",
+                self.field, self.component
+            ))),
+        }
+    }
+}
+
+/// A component for an entity that is spawned from a prefab, keep the link with the prefab so the
+/// prefab changes can be propagated to the instance
+#[derive(Debug, Clone, Default, Component, FruityAny)]
+#[fruity_export]
+pub struct PrefabInstance {
+    /// The resource identifier of the prefab
+    pub prefab: String,
+
+    /// The local id of the entity into the prefab
+    pub local_id: u64,
+
+    /// The root entity of the instance, none if the entity is the root
+    pub root: Option<EntityId>,
+
+    /// The overrides applied to this entity
+    pub overrides: Vec<PrefabOverride>,
+}
+
+#[fruity_export]
+impl PrefabInstance {
+    /// Deserialize the components of a prefab entity for this instance, the overrides are applied before
+    pub(crate) fn deserialize_components(
+        &self,
+        serialized_entity: &SerializedEntity,
+        resource_container: &ResourceContainer,
+        local_id_to_entity_id: &HashMap<u64, EntityId>,
+    ) -> FruityResult<Vec<Box<dyn Component>>> {
+        let mut serialized_components = serialized_entity.components.clone();
+        self.overrides
+            .iter()
+            .try_for_each(|prefab_override| prefab_override.apply(&mut serialized_components))?;
+
+        serialized_components
+            .iter()
+            .map(|serialized_component| {
+                <Box<dyn Component>>::deserialize(
+                    serialized_component,
+                    resource_container,
+                    local_id_to_entity_id,
+                )
+            })
+            .try_collect()
+    }
+}
@@ -0,0 +1,78 @@
+use super::snapshot_resource::{get_snapshot_entities, load_snapshot_resource};
+use crate::{
+    component::Component,
+    entity::{EntityServiceSnapshot, SerializedEntity},
+};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{
+    any::FruityAny, json_schema, resource::ResourceContainer, settings::Settings, FruityResult,
+};
+use std::{future::Future, pin::Pin};
+
+/// A set of entities loaded additively into the world, described in the same format as an [`EntityServiceSnapshot`]
+///
+/// Several scenes can be loaded at the same time, use `EntityService.loadScene` and `EntityService.unloadScene`
+#[derive(FruityAny, Debug)]
+#[fruity_export]
+pub struct SceneResource {
+    identifier: String,
+    snapshot: EntityServiceSnapshot,
+}
+
+#[fruity_export]
+impl SceneResource {
+    /// Returns a SceneResource
+    pub fn new(identifier: &str, snapshot: EntityServiceSnapshot) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            snapshot,
+        }
+    }
+
+    /// Get the resource identifier of the scene
+    #[fruity_export]
+    pub fn get_identifier(&self) -> String {
+        self.identifier.clone()
+    }
+
+    /// Get the entities of the scene
+    #[fruity_export]
+    pub fn get_snapshot(&self) -> EntityServiceSnapshot {
+        self.snapshot.clone()
+    }
+
+    /// Get the serialized entities of the scene, the components are kept serialized
+    pub(crate) fn get_entities(
+        &self,
+        resource_container: &ResourceContainer,
+    ) -> FruityResult<Vec<SerializedEntity>> {
+        get_snapshot_entities(&self.snapshot, resource_container)
+    }
+}
+
+/// Load a scene resource, the settings contain the entities of the scene
+#[json_schema(resource = "scene", path = "schemas/scene.json")]
+pub fn load_scene(
+    identifier: &str,
+    settings: Settings,
+    resource_container: ResourceContainer,
+) -> Pin<Box<dyn Send + Future<Output = FruityResult<()>>>> {
+    load_snapshot_resource(identifier, settings, resource_container, SceneResource::new)
+}
+
+/// A component for an entity that is spawned by a scene, the entity is removed when the scene is unloaded
+#[derive(Debug, Clone, Default, Component, FruityAny)]
+#[fruity_export]
+pub struct SceneInstance {
+    /// The resource identifier of the scene
+    pub scene: String,
+}
+
+#[fruity_export]
+impl SceneInstance {
+    /// Returns a new SceneInstance
+    #[fruity_export(constructor)]
+    pub fn new(scene: String) -> SceneInstance {
+        Self { scene }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    entity::{EntityServiceSnapshot, SerializedEntity},
+    serialization::Deserialize,
+};
+use fruity_game_engine::{
+    introspect::{IntrospectFields, IntrospectMethods},
+    resource::ResourceContainer,
+    settings::Settings,
+    FruityResult,
+};
+use std::{collections::HashMap, future::Future, pin::Pin};
+
+/// Get the serialized entities of a snapshot, the components are kept serialized
+pub(crate) fn get_snapshot_entities(
+    snapshot: &EntityServiceSnapshot,
+    resource_container: &ResourceContainer,
+) -> FruityResult<Vec<SerializedEntity>> {
+    <Vec<SerializedEntity>>::deserialize(snapshot, resource_container, &HashMap::new())
+}
+
+/// Load a resource made of entities, like a scene or a prefab, the settings contain the entities
+pub(crate) fn load_snapshot_resource<T>(
+    identifier: &str,
+    settings: Settings,
+    resource_container: ResourceContainer,
+    new_resource: fn(&str, EntityServiceSnapshot) -> T,
+) -> Pin<Box<dyn Send + Future<Output = FruityResult<()>>>>
+where
+    T: IntrospectFields + IntrospectMethods + Send + Sync + 'static,
+{
+    let identifier = identifier.to_string();
+    Box::pin(async move {
+        let snapshot = settings.get_settings("entities");
+
+        // Check that the entities are well formed before adding the resource
+        get_snapshot_entities(&snapshot, &resource_container)?;
+
+        resource_container.add::<T>(&identifier, Box::new(new_resource(&identifier, snapshot)));
+
+        Ok(())
+    })
+}
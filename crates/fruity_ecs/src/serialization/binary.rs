@@ -0,0 +1,421 @@
+use fruity_game_engine::{settings::Settings, FruityError, FruityResult};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+/// Write a serialized object in the binary format
+///
+/// The binary format is a subset of MessagePack, the numbers without fractional part are stored as integers
+/// and the object keys are sorted so the same object is always written the same way
+pub fn write_settings(writer: &mut dyn Write, settings: &Settings) -> FruityResult<()> {
+    match settings {
+        Settings::Null => write_bytes(writer, &[0xc0]),
+        Settings::Bool(false) => write_bytes(writer, &[0xc2]),
+        Settings::Bool(true) => write_bytes(writer, &[0xc3]),
+        Settings::F64(value) => write_number(writer, *value),
+        Settings::String(value) => write_string(writer, value),
+        Settings::Array(values) => {
+            write_array_header(writer, values.len())?;
+            values
+                .iter()
+                .try_for_each(|value| write_settings(writer, value))
+        }
+        Settings::Object(fields) => {
+            write_header(writer, fields.len(), 0x80, 0x0f, 0xde, 0xdf)?;
+
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_by(|(key, _), (other_key, _)| key.cmp(other_key));
+            fields.into_iter().try_for_each(|(key, value)| {
+                write_string(writer, key)?;
+                write_settings(writer, value)
+            })
+        }
+    }
+}
+
+/// The maximum nesting of arrays and objects that can be read
+///
+/// A corrupted or malicious file could otherwise nest enough arrays to overflow the stack
+const MAX_DEPTH: usize = 128;
+
+/// Read a serialized object written in the binary format
+pub fn read_settings(reader: &mut dyn Read) -> FruityResult<Settings> {
+    read_settings_with_depth(reader, 0)
+}
+
+fn read_settings_with_depth(reader: &mut dyn Read, depth: usize) -> FruityResult<Settings> {
+    let marker = read_u8(reader)?;
+    match marker {
+        0x00..=0x7f => Ok(Settings::F64(marker as f64)),
+        0x80..=0x8f => read_object(reader, (marker & 0x0f) as usize, depth),
+        0x90..=0x9f => read_array(reader, (marker & 0x0f) as usize, depth),
+        0xa0..=0xbf => read_string(reader, (marker & 0x1f) as usize).map(Settings::String),
+        0xc0 => Ok(Settings::Null),
+        0xc2 => Ok(Settings::Bool(false)),
+        0xc3 => Ok(Settings::Bool(true)),
+        0xca => Ok(Settings::F64(
+            f32::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xcb => Ok(Settings::F64(f64::from_be_bytes(read_array_bytes(reader)?))),
+        0xcc => Ok(Settings::F64(read_u8(reader)? as f64)),
+        0xcd => Ok(Settings::F64(
+            u16::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xce => Ok(Settings::F64(
+            u32::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xcf => Ok(Settings::F64(
+            u64::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xd0 => Ok(Settings::F64(
+            i8::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xd1 => Ok(Settings::F64(
+            i16::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xd2 => Ok(Settings::F64(
+            i32::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xd3 => Ok(Settings::F64(
+            i64::from_be_bytes(read_array_bytes(reader)?) as f64
+        )),
+        0xd9 => {
+            let len = read_u8(reader)? as usize;
+            read_string(reader, len).map(Settings::String)
+        }
+        0xda => {
+            let len = u16::from_be_bytes(read_array_bytes(reader)?) as usize;
+            read_string(reader, len).map(Settings::String)
+        }
+        0xdb => {
+            let len = u32::from_be_bytes(read_array_bytes(reader)?) as usize;
+            read_string(reader, len).map(Settings::String)
+        }
+        0xdc => {
+            let len = u16::from_be_bytes(read_array_bytes(reader)?) as usize;
+            read_array(reader, len, depth)
+        }
+        0xdd => {
+            let len = u32::from_be_bytes(read_array_bytes(reader)?) as usize;
+            read_array(reader, len, depth)
+        }
+        0xde => {
+            let len = u16::from_be_bytes(read_array_bytes(reader)?) as usize;
+            read_object(reader, len, depth)
+        }
+        0xdf => {
+            let len = u32::from_be_bytes(read_array_bytes(reader)?) as usize;
+            read_object(reader, len, depth)
+        }
+        0xe0..=0xff => Ok(Settings::F64((marker as i8) as f64)),
+        marker => Err(FruityError::GenericFailure(format!(
+            "Unsupported binary marker {:#04x}",
+            marker
+        ))),
+    }
+}
+
+/// Write the header of an array, the elements should be written one after the other just after
+///
+/// It's used to stream a large array without building it in memory
+pub fn write_array_header(writer: &mut dyn Write, len: usize) -> FruityResult<()> {
+    write_header(writer, len, 0x90, 0x0f, 0xdc, 0xdd)
+}
+
+/// Read the header of an array, returns the number of elements that follow
+pub fn read_array_header(reader: &mut dyn Read) -> FruityResult<usize> {
+    let marker = read_u8(reader)?;
+    match marker {
+        0x90..=0x9f => Ok((marker & 0x0f) as usize),
+        0xdc => Ok(u16::from_be_bytes(read_array_bytes(reader)?) as usize),
+        0xdd => Ok(u32::from_be_bytes(read_array_bytes(reader)?) as usize),
+        marker => Err(FruityError::ArrayExpected(format!(
+            "Expected an array header, found the binary marker {:#04x}",
+            marker
+        ))),
+    }
+}
+
+fn write_number(writer: &mut dyn Write, value: f64) -> FruityResult<()> {
+    let is_integer = value.fract() == 0.0
+        && value >= i64::MIN as f64
+        && value < i64::MAX as f64
+        && !(value == 0.0 && value.is_sign_negative());
+
+    if is_integer {
+        let value = value as i64;
+        match value {
+            0..=0x7f => write_bytes(writer, &[value as u8]),
+            -32..=-1 => write_bytes(writer, &[value as i8 as u8]),
+            0x80..=0xff => write_bytes(writer, &[0xcc, value as u8]),
+            0x100..=0xffff => write_prefixed(writer, 0xcd, &(value as u16).to_be_bytes()),
+            0x10000..=0xffffffff => write_prefixed(writer, 0xce, &(value as u32).to_be_bytes()),
+            -0x80..=-33 => write_bytes(writer, &[0xd0, value as i8 as u8]),
+            -0x8000..=-0x81 => write_prefixed(writer, 0xd1, &(value as i16).to_be_bytes()),
+            -0x80000000..=-0x8001 => write_prefixed(writer, 0xd2, &(value as i32).to_be_bytes()),
+            _ => write_prefixed(writer, 0xd3, &value.to_be_bytes()),
+        }
+    } else if (value as f32) as f64 == value {
+        write_prefixed(writer, 0xca, &(value as f32).to_be_bytes())
+    } else {
+        write_prefixed(writer, 0xcb, &value.to_be_bytes())
+    }
+}
+
+fn write_string(writer: &mut dyn Write, value: &str) -> FruityResult<()> {
+    let len = value.len();
+    if len < 32 {
+        write_bytes(writer, &[0xa0 | len as u8])?;
+    } else if len <= u8::MAX as usize {
+        write_bytes(writer, &[0xd9, len as u8])?;
+    } else if len <= u16::MAX as usize {
+        write_prefixed(writer, 0xda, &(len as u16).to_be_bytes())?;
+    } else {
+        write_prefixed(writer, 0xdb, &(len as u32).to_be_bytes())?;
+    }
+
+    write_bytes(writer, value.as_bytes())
+}
+
+fn write_header(
+    writer: &mut dyn Write,
+    len: usize,
+    fix_marker: u8,
+    fix_max: usize,
+    marker_16: u8,
+    marker_32: u8,
+) -> FruityResult<()> {
+    if len <= fix_max {
+        write_bytes(writer, &[fix_marker | len as u8])
+    } else if len <= u16::MAX as usize {
+        write_prefixed(writer, marker_16, &(len as u16).to_be_bytes())
+    } else {
+        write_prefixed(writer, marker_32, &(len as u32).to_be_bytes())
+    }
+}
+
+fn write_prefixed(writer: &mut dyn Write, marker: u8, bytes: &[u8]) -> FruityResult<()> {
+    write_bytes(writer, &[marker])?;
+    write_bytes(writer, bytes)
+}
+
+fn write_bytes(writer: &mut dyn Write, bytes: &[u8]) -> FruityResult<()> {
+    writer
+        .write_all(bytes)
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))
+}
+
+fn check_depth(depth: usize) -> FruityResult<usize> {
+    if depth < MAX_DEPTH {
+        Ok(depth + 1)
+    } else {
+        Err(FruityError::GenericFailure(format!(
+            "Binary settings are nested deeper than {} levels",
+            MAX_DEPTH
+        )))
+    }
+}
+
+fn read_array(reader: &mut dyn Read, len: usize, depth: usize) -> FruityResult<Settings> {
+    let depth = check_depth(depth)?;
+
+    (0..len)
+        .map(|_| read_settings_with_depth(reader, depth))
+        .try_collect::<Vec<_>>()
+        .map(Settings::Array)
+}
+
+fn read_object(reader: &mut dyn Read, len: usize, depth: usize) -> FruityResult<Settings> {
+    let depth = check_depth(depth)?;
+
+    (0..len)
+        .map(|_| {
+            let key = match read_settings_with_depth(reader, depth)? {
+                Settings::String(key) => key,
+                key => {
+                    return Err(FruityError::StringExpected(format!(
+                        "Object keys must be strings, found {:?}",
+                        key
+                    )))
+                }
+            };
+
+            Ok((key, read_settings_with_depth(reader, depth)?))
+        })
+        .try_collect::<HashMap<_, _>>()
+        .map(Settings::Object)
+}
+
+fn read_string(reader: &mut dyn Read, len: usize) -> FruityResult<String> {
+    // The length comes from the file, the buffer grows with the bytes actually read so a corrupted
+    // length cannot allocate more than the reader holds
+    let mut bytes = Vec::new();
+    reader
+        .take(len as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))?;
+
+    if bytes.len() != len {
+        return Err(FruityError::GenericFailure(format!(
+            "Expected a string of {} bytes, found only {} bytes",
+            len,
+            bytes.len()
+        )));
+    }
+
+    String::from_utf8(bytes).map_err(|error| FruityError::StringExpected(error.to_string()))
+}
+
+fn read_u8(reader: &mut dyn Read) -> FruityResult<u8> {
+    Ok(read_array_bytes::<1>(reader)?[0])
+}
+
+fn read_array_bytes<const N: usize>(reader: &mut dyn Read) -> FruityResult<[u8; N]> {
+    let mut bytes = [0; N];
+    read_bytes(reader, &mut bytes)?;
+
+    Ok(bytes)
+}
+
+fn read_bytes(reader: &mut dyn Read, bytes: &mut [u8]) -> FruityResult<()> {
+    reader
+        .read_exact(bytes)
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maplit::hashmap;
+
+    fn round_trip(settings: &Settings) -> Settings {
+        let mut buffer = Vec::new();
+        write_settings(&mut buffer, settings).unwrap();
+
+        let mut reader = buffer.as_slice();
+        let result = read_settings(&mut reader).unwrap();
+        assert!(reader.is_empty());
+
+        result
+    }
+
+    fn read_f64(settings: Settings) -> f64 {
+        match settings {
+            Settings::F64(value) => value,
+            settings => panic!("Expected a number, found {:?}", settings),
+        }
+    }
+
+    #[test]
+    fn test_numbers_round_trip() {
+        [
+            0.0,
+            1.0,
+            127.0,
+            128.0,
+            255.0,
+            256.0,
+            65535.0,
+            65536.0,
+            4294967295.0,
+            4294967296.0,
+            -1.0,
+            -32.0,
+            -33.0,
+            -128.0,
+            -129.0,
+            -32768.0,
+            -32769.0,
+            -2147483648.0,
+            -2147483649.0,
+            i64::MIN as f64,
+            i64::MAX as f64,
+            1.5,
+            0.1,
+            -2.75,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ]
+        .into_iter()
+        .for_each(|value| {
+            let result = read_f64(round_trip(&Settings::F64(value)));
+            assert_eq!(
+                result.to_bits(),
+                value.to_bits(),
+                "{} was read as {}",
+                value,
+                result
+            );
+        });
+    }
+
+    #[test]
+    fn test_negative_zero_keeps_its_sign() {
+        let result = read_f64(round_trip(&Settings::F64(-0.0)));
+        assert!(result == 0.0 && result.is_sign_negative());
+    }
+
+    #[test]
+    fn test_nan_round_trip() {
+        assert!(read_f64(round_trip(&Settings::F64(f64::NAN))).is_nan());
+    }
+
+    #[test]
+    fn test_strings_round_trip() {
+        [0, 1, 31, 32, 255, 256, 65535, 65536]
+            .into_iter()
+            .map(|len| "é".repeat(len / 2) + &"a".repeat(len % 2))
+            .for_each(|value| {
+                assert_eq!(
+                    round_trip(&Settings::String(value.clone())),
+                    Settings::String(value)
+                );
+            });
+    }
+
+    #[test]
+    fn test_nested_objects_round_trip() {
+        let settings = Settings::Object(hashmap! {
+            "class_name".to_string() => Settings::String("Translate2D".to_string()),
+            "fields".to_string() => Settings::Object(hashmap! {
+                "vec".to_string() => Settings::Array(vec![Settings::F64(-1.5), Settings::F64(3.0)]),
+                "enabled".to_string() => Settings::Bool(true),
+                "parent".to_string() => Settings::Null,
+                "children".to_string() => Settings::Array(
+                    (0..20).map(|index| Settings::Object(hashmap! {
+                        "index".to_string() => Settings::F64(index as f64),
+                    })).collect()
+                ),
+            }),
+        });
+
+        assert_eq!(round_trip(&settings), settings);
+    }
+
+    #[test]
+    fn test_truncated_string_is_an_error() {
+        // A string announcing 4 GiB of data followed by only a few bytes
+        let mut reader = [0xdb, 0xff, 0xff, 0xff, 0xff, b'a', b'b'].as_slice();
+        assert!(read_settings(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_deeply_nested_arrays_are_an_error() {
+        // Arrays of one element nested far deeper than the stack could handle
+        let buffer = vec![0x91; 1_000_000];
+        let mut reader = buffer.as_slice();
+        assert!(read_settings(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_nested_arrays_up_to_the_max_depth_round_trip() {
+        let settings = (0..MAX_DEPTH).fold(Settings::Null, |settings, _| {
+            Settings::Array(vec![settings])
+        });
+
+        assert_eq!(round_trip(&settings), settings);
+    }
+}
@@ -114,7 +114,7 @@ impl<T: Deserialize + Send + Sync + Clone + 'static> Deserialize for SignalPrope
 
 impl Serialize for EntityReference {
     fn serialize(&self, _resource_container: &ResourceContainer) -> FruityResult<Settings> {
-        Ok(Settings::F64(self.get_entity_id()?.0 as f64))
+        Ok(Settings::F64(self.get_entity_id()?.index() as f64))
     }
 }
 
@@ -222,7 +222,7 @@ impl<T: IntrospectFields + IntrospectMethods + Send + Sync + ?Sized> Deserialize
 
 impl Serialize for EntityId {
     fn serialize(&self, _resource_container: &ResourceContainer) -> FruityResult<Settings> {
-        Ok(Settings::F64(self.0 as f64))
+        Ok(Settings::F64(self.index() as f64))
     }
 }
 
@@ -1,6 +1,9 @@
 use crate::entity::EntityId;
 use fruity_game_engine::{resource::ResourceContainer, settings::Settings, FruityResult};
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
 
 pub use fruity_ecs_macro::Deserialize;
 pub use fruity_ecs_macro::Serialize;
@@ -17,6 +20,12 @@ pub mod impl_containers;
 /// Implementation of script value conversions for tuples
 pub mod impl_tuples;
 
+/// Compact binary format for the serialized objects
+pub mod binary;
+
+/// JSON and YAML formats for the serialized objects, the formats of the settings files
+pub mod text;
+
 mod serialization_service;
 pub use serialization_service::*;
 
@@ -24,6 +33,15 @@ pub use serialization_service::*;
 pub trait Serialize {
     /// Serialize an object
     fn serialize(&self, resource_container: &ResourceContainer) -> FruityResult<Settings>;
+
+    /// Serialize an object and write it in the binary format
+    fn serialize_binary(
+        &self,
+        resource_container: &ResourceContainer,
+        writer: &mut dyn Write,
+    ) -> FruityResult<()> {
+        binary::write_settings(writer, &self.serialize(resource_container)?)
+    }
 }
 
 /// Trait to implement a generic constructor from a ScriptValue
@@ -38,4 +56,17 @@ pub trait Deserialize: Sized {
         resource_container: &ResourceContainer,
         local_id_to_entity_id: &HashMap<u64, EntityId>,
     ) -> FruityResult<Self>;
+
+    /// Read an object written in the binary format and deserialize it
+    fn deserialize_binary(
+        reader: &mut dyn Read,
+        resource_container: &ResourceContainer,
+        local_id_to_entity_id: &HashMap<u64, EntityId>,
+    ) -> FruityResult<Self> {
+        Self::deserialize(
+            &binary::read_settings(reader)?,
+            resource_container,
+            local_id_to_entity_id,
+        )
+    }
 }
@@ -1,20 +1,31 @@
-use super::Deserialize;
-use crate::{component::Component, entity::EntityId};
+use super::{binary, text, Deserialize};
+use crate::{
+    component::{Component, ComponentTypeIndex},
+    entity::EntityId,
+};
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
     any::FruityAny,
-    export_impl, export_struct,
     introspect::IntrospectFields,
     javascript::JsIntrospectObject,
     resource::ResourceContainer,
-    script_value::{ScriptValue, TryIntoScriptValue},
+    script_value::{ScriptObjectType, ScriptValue, TryIntoScriptValue},
     settings::Settings,
     FruityError, FruityResult,
 };
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// A function that upgrades the serialized fields of an object from a version to the next one
+pub type SerializedMigration = dyn Fn(Settings) -> FruityResult<Settings> + Send + Sync + 'static;
 
 /// Utility used to deserialize objects, mostly used to restore snapshot
 #[derive(FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct SerializationService {
     resource_container: ResourceContainer,
     factories: HashMap<
@@ -29,15 +40,17 @@ pub struct SerializationService {
                 + Sync,
         >,
     >,
+    migrations: HashMap<String, BTreeMap<u32, Box<SerializedMigration>>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl SerializationService {
     /// Returns an SerializationService
     pub fn new(resource_container: ResourceContainer) -> SerializationService {
         SerializationService {
             resource_container,
             factories: HashMap::new(),
+            migrations: HashMap::new(),
         }
     }
 
@@ -76,6 +89,11 @@ impl SerializationService {
     where
         T: Deserialize + Component + TryIntoScriptValue,
     {
+        // Let the script queries know where to look for the components before any is stored
+        if T::is_sparse_set() {
+            ComponentTypeIndex::register(&ScriptObjectType::of::<T>()).set_sparse_set();
+        }
+
         self.factories.insert(
             T::get_identifier(),
             Box::new(|script_value, resource_container, local_id_to_entity_id| {
@@ -109,6 +127,194 @@ impl SerializationService {
             .insert(object_type.to_string(), Box::new(instantiate));
     }
 
+    /// Register a function that upgrades the serialized fields of an object from a version to the next one
+    ///
+    /// The current version of an object type is the last upgraded version plus one, the objects serialized
+    /// with an older version are upgraded before being instantiated
+    ///
+    /// # Arguments
+    /// * `class_name` - The class name of the serialized object
+    /// * `from_version` - The version upgraded by the migration
+    /// * `migration` - A function that takes the old fields and returns the upgraded ones
+    ///
+    pub fn register_migration(
+        &mut self,
+        class_name: &str,
+        from_version: u32,
+        migration: impl Fn(Settings) -> FruityResult<Settings> + Send + Sync + 'static,
+    ) {
+        self.script_register_migration(class_name.to_string(), from_version, Box::new(migration))
+    }
+
+    /// Register a function that upgrades the serialized fields of an object from a version to the next one
+    ///
+    /// The current version of an object type is the last upgraded version plus one, the objects serialized
+    /// with an older version are upgraded before being instantiated
+    ///
+    /// # Arguments
+    /// * `class_name` - The class name of the serialized object
+    /// * `from_version` - The version upgraded by the migration
+    /// * `migration` - A function that takes the old fields and returns the upgraded ones
+    ///
+    #[fruity_export(name = "register_migration")]
+    pub fn script_register_migration(
+        &mut self,
+        class_name: String,
+        from_version: u32,
+        migration: Box<dyn Send + Sync + Fn(Settings) -> FruityResult<Settings>>,
+    ) {
+        self.migrations
+            .entry(class_name)
+            .or_default()
+            .insert(from_version, migration);
+    }
+
+    /// Get the current serialization version of an object type, 0 if it has no migration
+    #[fruity_export]
+    pub fn get_version(&self, class_name: String) -> u32 {
+        self.migrations
+            .get(&class_name)
+            .and_then(|migrations| migrations.keys().next_back())
+            .map(|from_version| from_version + 1)
+            .unwrap_or(0)
+    }
+
+    /// Upgrade the serialized fields of an object to the current version
+    ///
+    /// # Arguments
+    /// * `class_name` - The class name of the serialized object
+    /// * `version` - The version used to serialize the object
+    /// * `fields` - The serialized fields
+    ///
+    pub fn migrate_fields(
+        &self,
+        class_name: &str,
+        version: u32,
+        fields: Settings,
+    ) -> FruityResult<Settings> {
+        let current_version = self.get_version(class_name.to_string());
+        if version > current_version {
+            return Err(FruityError::GenericFailure(format!(
+                "The object {} has been serialized with the version {} but the current version is {}",
+                class_name, version, current_version
+            )));
+        }
+
+        (version..current_version).try_fold(fields, |fields, from_version| {
+            let migration = self
+                .migrations
+                .get(class_name)
+                .and_then(|migrations| migrations.get(&from_version))
+                .ok_or_else(|| {
+                    FruityError::GenericFailure(format!(
+                        "Missing a migration of the object {} from the version {}",
+                        class_name, from_version
+                    ))
+                })?;
+
+            migration(fields)
+        })
+    }
+
+    /// Upgrade all the serialized objects of a tree to their current version, for example a whole snapshot or scene
+    ///
+    /// # Arguments
+    /// * `serialized` - The serialized tree
+    ///
+    #[fruity_export]
+    pub fn migrate(&self, serialized: Settings) -> FruityResult<Settings> {
+        match serialized {
+            Settings::Array(items) => items
+                .into_iter()
+                .map(|item| self.migrate(item))
+                .try_collect::<Vec<_>>()
+                .map(Settings::Array),
+            Settings::Object(mut value) => {
+                if let (Some(Settings::String(class_name)), Some(fields)) = (
+                    value.get("class_name").cloned(),
+                    value.get("fields").cloned(),
+                ) {
+                    let version = get_serialized_version(&value)?;
+                    let fields = self.migrate_fields(&class_name, version, fields)?;
+
+                    value.insert("fields".to_string(), fields);
+                    value.insert(
+                        "version".to_string(),
+                        Settings::F64(self.get_version(class_name) as f64),
+                    );
+                }
+
+                value
+                    .into_iter()
+                    .map(|(key, value)| Ok((key, self.migrate(value)?)))
+                    .try_collect::<HashMap<_, _>>()
+                    .map(Settings::Object)
+            }
+            serialized => Ok(serialized),
+        }
+    }
+
+    /// Upgrade in place a file that contains serialized objects, for example a scene or a snapshot written by
+    /// `EntityService::write_snapshot`
+    ///
+    /// The format is chosen from the file extension, json, yaml or yml, the other files are read in the binary format
+    /// The upgraded file is written next to the original one before replacing it, so it is not lost if the upgrade fails
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file
+    ///
+    #[fruity_export]
+    pub fn upgrade_file(&self, path: String) -> FruityResult<()> {
+        let path = Path::new(&path);
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        let serialized = {
+            let file =
+                File::open(path).map_err(|error| FruityError::GenericFailure(error.to_string()))?;
+            let mut reader = BufReader::new(file);
+
+            match extension.as_deref() {
+                Some("json") => text::read_json_settings(&mut reader)?,
+                Some("yaml" | "yml") => text::read_yaml_settings(&mut reader)?,
+                _ => binary::read_settings(&mut reader)?,
+            }
+        };
+
+        let serialized = self.migrate(serialized)?;
+
+        let mut temporary_path = path.as_os_str().to_owned();
+        temporary_path.push(".upgrade");
+        let temporary_path = PathBuf::from(temporary_path);
+
+        let result = File::create(&temporary_path)
+            .map_err(|error| FruityError::GenericFailure(error.to_string()))
+            .and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                match extension.as_deref() {
+                    Some("json") => text::write_json_settings(&mut writer, &serialized)?,
+                    Some("yaml" | "yml") => text::write_yaml_settings(&mut writer, &serialized)?,
+                    _ => binary::write_settings(&mut writer, &serialized)?,
+                };
+
+                writer
+                    .flush()
+                    .map_err(|error| FruityError::GenericFailure(error.to_string()))
+            })
+            .and_then(|_| {
+                fs::rename(&temporary_path, path)
+                    .map_err(|error| FruityError::GenericFailure(error.to_string()))
+            });
+
+        if result.is_err() {
+            fs::remove_file(&temporary_path).ok();
+        }
+
+        result
+    }
+
     /// Instantiate an object from it's factory
     ///
     /// # Arguments
@@ -147,6 +353,13 @@ impl SerializationService {
                     "Missing fields in object".to_string(),
                 ))?;
 
+                // Upgrade the fields serialized with an older version
+                let fields = &self.migrate_fields(
+                    &class_name,
+                    get_serialized_version(&value)?,
+                    fields.clone(),
+                )?;
+
                 if let Some(factory) = self.factories.get(&class_name) {
                     factory(&fields, &self.resource_container, local_id_to_entity_id)?
                 } else {
@@ -172,8 +385,100 @@ impl SerializationService {
     }
 }
 
+/// Get the version of a serialized object, the objects serialized before the versioning have the version 0
+fn get_serialized_version(serialized: &HashMap<String, Settings>) -> FruityResult<u32> {
+    match serialized.get("version") {
+        Some(version) => u32::try_from(version.clone()),
+        None => Ok(0),
+    }
+}
+
 impl std::fmt::Debug for SerializationService {
     fn fmt(&self, _: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maplit::hashmap;
+
+    /// A service where the field "x" of Position has been renamed "left", then multiplied by 2
+    fn new_serialization_service() -> SerializationService {
+        let mut serialization_service = SerializationService::new(ResourceContainer::new());
+        serialization_service.register_migration("Position", 0, |fields| {
+            Ok(Settings::Object(hashmap! {
+                "left".to_string() => fields.get_settings("x"),
+            }))
+        });
+        serialization_service.register_migration("Position", 1, |fields| {
+            Ok(Settings::Object(hashmap! {
+                "left".to_string() => Settings::F64(fields.get::<f64>("left", 0.0) * 2.0),
+            }))
+        });
+
+        serialization_service
+    }
+
+    fn serialized_position(version: u32, field: &str, value: f64) -> Settings {
+        Settings::Object(hashmap! {
+            "class_name".to_string() => Settings::String("Position".to_string()),
+            "version".to_string() => Settings::F64(version as f64),
+            "fields".to_string() => Settings::Object(hashmap! {
+                field.to_string() => Settings::F64(value),
+            }),
+        })
+    }
+
+    #[test]
+    fn test_migrate_upgrades_from_the_serialized_version() {
+        let serialization_service = new_serialization_service();
+        assert_eq!(serialization_service.get_version("Position".to_string()), 2);
+
+        // The objects are upgraded in nested trees
+        let migrated = serialization_service
+            .migrate(Settings::Array(vec![
+                serialized_position(0, "x", 1.0),
+                serialized_position(1, "left", 1.0),
+                serialized_position(2, "left", 1.0),
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            migrated,
+            Settings::Array(vec![
+                serialized_position(2, "left", 2.0),
+                serialized_position(2, "left", 2.0),
+                serialized_position(2, "left", 1.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_versions() {
+        let serialization_service = new_serialization_service();
+        assert!(serialization_service
+            .migrate(serialized_position(3, "left", 1.0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_upgrade_json_file() {
+        let serialization_service = new_serialization_service();
+        let path = std::env::temp_dir().join(format!("upgrade_{}.json", std::process::id()));
+        text::write_json_settings(
+            &mut File::create(&path).unwrap(),
+            &serialized_position(0, "x", 1.5),
+        )
+        .unwrap();
+
+        serialization_service
+            .upgrade_file(path.to_string_lossy().to_string())
+            .unwrap();
+
+        let upgraded = text::read_json_settings(&mut File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(upgraded, serialized_position(2, "left", 3.0));
+    }
+}
@@ -0,0 +1,123 @@
+use fruity_game_engine::{settings::Settings, FruityError, FruityResult};
+use serde_json::{Map, Number, Value};
+use std::io::{Read, Write};
+
+/// Write a serialized object in the JSON format, the same format as the settings files
+pub fn write_json_settings(writer: &mut dyn Write, settings: &Settings) -> FruityResult<()> {
+    serde_json::to_writer_pretty(writer, &settings_to_value(settings))
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))
+}
+
+/// Read a serialized object written in the JSON format
+pub fn read_json_settings(reader: &mut dyn Read) -> FruityResult<Settings> {
+    serde_json::from_reader::<_, Value>(reader)
+        .map(value_to_settings)
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))
+}
+
+/// Write a serialized object in the YAML format
+pub fn write_yaml_settings(writer: &mut dyn Write, settings: &Settings) -> FruityResult<()> {
+    serde_yaml::to_writer(writer, &settings_to_value(settings))
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))
+}
+
+/// Read a serialized object written in the YAML format
+pub fn read_yaml_settings(reader: &mut dyn Read) -> FruityResult<Settings> {
+    serde_yaml::from_reader::<_, Value>(reader)
+        .map(value_to_settings)
+        .map_err(|error| FruityError::GenericFailure(error.to_string()))
+}
+
+/// The numbers without fractional part are written as integers, the numbers that are not finite are written as null
+fn settings_to_value(settings: &Settings) -> Value {
+    match settings {
+        Settings::Null => Value::Null,
+        Settings::Bool(value) => Value::Bool(*value),
+        Settings::F64(value) => {
+            // -0.0 is kept as a float so its sign is not lost
+            let is_negative_zero = *value == 0.0 && value.is_sign_negative();
+            if value.fract() == 0.0 && value.abs() < i64::MAX as f64 && !is_negative_zero {
+                Value::Number(Number::from(*value as i64))
+            } else {
+                Number::from_f64(*value)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null)
+            }
+        }
+        Settings::String(value) => Value::String(value.clone()),
+        Settings::Array(values) => Value::Array(values.iter().map(settings_to_value).collect()),
+        Settings::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), settings_to_value(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+fn value_to_settings(value: Value) -> Settings {
+    match value {
+        Value::Null => Settings::Null,
+        Value::Bool(value) => Settings::Bool(value),
+        Value::Number(value) => Settings::F64(value.as_f64().unwrap_or_default()),
+        Value::String(value) => Settings::String(value),
+        Value::Array(values) => {
+            Settings::Array(values.into_iter().map(value_to_settings).collect())
+        }
+        Value::Object(fields) => Settings::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, value_to_settings(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use maplit::hashmap;
+
+    fn serialized_object() -> Settings {
+        Settings::Object(hashmap! {
+            "class_name".to_string() => Settings::String("Translate2D".to_string()),
+            "version".to_string() => Settings::F64(2.0),
+            "fields".to_string() => Settings::Object(hashmap! {
+                "vec".to_string() => Settings::Array(vec![Settings::F64(-1.5), Settings::F64(-0.0)]),
+                "enabled".to_string() => Settings::Bool(true),
+                "parent".to_string() => Settings::Null,
+            }),
+        })
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut buffer = Vec::new();
+        write_json_settings(&mut buffer, &serialized_object()).unwrap();
+
+        let settings = read_json_settings(&mut buffer.as_slice()).unwrap();
+        assert_eq!(settings, serialized_object());
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let mut buffer = Vec::new();
+        write_yaml_settings(&mut buffer, &serialized_object()).unwrap();
+
+        let settings = read_yaml_settings(&mut buffer.as_slice()).unwrap();
+        assert_eq!(settings, serialized_object());
+    }
+
+    #[test]
+    fn test_integers_are_written_without_fraction() {
+        let mut buffer = Vec::new();
+        write_json_settings(&mut buffer, &Settings::Array(vec![Settings::F64(3.0)])).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer)
+                .unwrap()
+                .replace(char::is_whitespace, ""),
+            "[3]"
+        );
+    }
+}
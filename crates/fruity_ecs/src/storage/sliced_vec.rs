@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 /// A sliced vector
 #[derive(Debug, Clone, Default)]
 pub struct SlicedVec<T> {
@@ -80,7 +82,7 @@ impl<T> SlicedVec<T> {
 
         let slice = self.data.drain(start..end).collect();
 
-        for i in slice_index + 1..self.len() {
+        for i in slice_index + 1..self.indices.len() {
             self.indices[i] -= self.lengths[slice_index];
         }
 
@@ -90,6 +92,49 @@ impl<T> SlicedVec<T> {
         slice
     }
 
+    /// Removes the slice at the given index, the last slice takes its place
+    ///
+    /// Only the items of the two slices are moved if they have the same length, which is the case of a
+    /// storage with one item per slice
+    pub fn swap_remove_slice(&mut self, slice_index: usize) -> Vec<T> {
+        let last_index = self.lengths.len() - 1;
+        if slice_index == last_index {
+            let slice = self.data.split_off(self.indices[slice_index]);
+            self.indices.pop();
+            self.lengths.pop();
+
+            return slice;
+        }
+
+        let start = self.indices[slice_index];
+        let len = self.lengths[slice_index];
+        let last_start = self.indices[last_index];
+        let last_len = self.lengths[last_index];
+
+        if len == last_len {
+            (0..len).for_each(|i| self.data.swap(start + i, last_start + i));
+            let slice = self.data.split_off(last_start);
+            self.indices.pop();
+            self.lengths.pop();
+
+            return slice;
+        }
+
+        // The last slice is moved where the removed one was, the slices in between are shifted
+        let slice = self.data.drain(start..(start + len)).collect();
+        self.data[start..].rotate_right(last_len);
+
+        for i in slice_index + 1..last_index {
+            self.indices[i] = self.indices[i] + last_len - len;
+        }
+
+        self.lengths[slice_index] = last_len;
+        self.indices.pop();
+        self.lengths.pop();
+
+        slice
+    }
+
     /// Insert a new item at the given index in a slice
     /// This will move all the items after the given index
     pub fn insert_in_slice(&mut self, slice_index: usize, item: T) {
@@ -99,15 +144,18 @@ impl<T> SlicedVec<T> {
         self.data.insert(end, item);
         self.lengths[slice_index] += 1;
 
-        for i in slice_index + 1..self.len() {
+        for i in slice_index + 1..self.indices.len() {
             self.indices[i] += 1;
         }
     }
 
     /// Append the contents of another SlicedVec to this one
     pub fn append(&mut self, other: &mut Self) {
+        // The slices of the other vector are moved after the data of this one
+        let offset = self.data.len();
+        self.indices
+            .extend(other.indices.drain(..).map(|index| index + offset));
         self.data.append(&mut other.data);
-        self.indices.append(&mut other.indices);
         self.lengths.append(&mut other.lengths);
     }
 
@@ -119,13 +167,29 @@ impl<T> SlicedVec<T> {
         let item = self.data.remove(start + index);
         self.lengths[slice_index] -= 1;
 
-        for i in slice_index + 1..self.len() {
+        for i in slice_index + 1..self.indices.len() {
             self.indices[i] -= 1;
         }
 
         item
     }
 
+    /// Returns the range of the data covered by a range of slices
+    pub fn data_range(&self, slice_range: Range<usize>) -> Range<usize> {
+        let start = self
+            .indices
+            .get(slice_range.start)
+            .copied()
+            .unwrap_or(self.data.len());
+        let end = self
+            .indices
+            .get(slice_range.end)
+            .copied()
+            .unwrap_or(self.data.len());
+
+        start..end
+    }
+
     /// Clears the SlicedVec
     pub fn clear(&mut self) {
         self.data.clear();
@@ -138,6 +202,13 @@ impl<T> SlicedVec<T> {
         self.data.reserve(additional);
     }
 
+    /// Reserve capacity for at least `additional` more slices of `slice_len` elements
+    pub fn reserve_slices(&mut self, additional: usize, slice_len: usize) {
+        self.data.reserve(additional * slice_len);
+        self.indices.reserve(additional);
+        self.lengths.reserve(additional);
+    }
+
     /// Returns a raw pointer to the data
     pub fn as_ptr(&self) -> *mut T {
         self.data.as_ptr() as *mut T
@@ -153,3 +224,82 @@ impl<T> SlicedVec<T> {
         self.data.capacity()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn new_sliced_vec(slices: Vec<Vec<u32>>) -> SlicedVec<u32> {
+        let mut sliced_vec = SlicedVec::new();
+        slices
+            .into_iter()
+            .for_each(|slice| sliced_vec.push_slice(slice));
+
+        sliced_vec
+    }
+
+    fn get_slices(sliced_vec: &SlicedVec<u32>) -> Vec<Vec<u32>> {
+        (0..sliced_vec.slice_count())
+            .map(|slice_index| sliced_vec.get_slice(slice_index).unwrap().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_slice_indexes_are_shifted() {
+        let mut sliced_vec = new_sliced_vec(vec![vec![1, 2], vec![3, 4], vec![5, 6]]);
+
+        assert_eq!(sliced_vec.remove_slice(0), vec![1, 2]);
+        assert_eq!(get_slices(&sliced_vec), vec![vec![3, 4], vec![5, 6]]);
+
+        sliced_vec.insert_in_slice(0, 7);
+        assert_eq!(get_slices(&sliced_vec), vec![vec![3, 4, 7], vec![5, 6]]);
+
+        assert_eq!(sliced_vec.remove_in_slice(0, 0), 3);
+        assert_eq!(get_slices(&sliced_vec), vec![vec![4, 7], vec![5, 6]]);
+    }
+
+    #[test]
+    fn test_append_offsets_the_slices() {
+        let mut sliced_vec = new_sliced_vec(vec![vec![1, 2], vec![3]]);
+        let mut other = new_sliced_vec(vec![vec![4], vec![5, 6]]);
+
+        sliced_vec.append(&mut other);
+
+        assert_eq!(
+            get_slices(&sliced_vec),
+            vec![vec![1, 2], vec![3], vec![4], vec![5, 6]]
+        );
+        assert_eq!(other.slice_count(), 0);
+    }
+
+    #[test]
+    fn test_swap_remove_slice_with_same_lengths() {
+        let mut sliced_vec = new_sliced_vec(vec![vec![1], vec![2], vec![3]]);
+
+        assert_eq!(sliced_vec.swap_remove_slice(0), vec![1]);
+        assert_eq!(get_slices(&sliced_vec), vec![vec![3], vec![2]]);
+
+        assert_eq!(sliced_vec.swap_remove_slice(1), vec![2]);
+        assert_eq!(get_slices(&sliced_vec), vec![vec![3]]);
+        assert_eq!(sliced_vec.len(), 1);
+    }
+
+    #[test]
+    fn test_swap_remove_slice_with_different_lengths() {
+        let mut sliced_vec =
+            new_sliced_vec(vec![vec![1, 2], vec![3], vec![4, 5, 6], vec![7, 8, 9, 10]]);
+
+        assert_eq!(sliced_vec.swap_remove_slice(1), vec![3]);
+        assert_eq!(
+            get_slices(&sliced_vec),
+            vec![vec![1, 2], vec![7, 8, 9, 10], vec![4, 5, 6]]
+        );
+
+        assert_eq!(sliced_vec.swap_remove_slice(0), vec![1, 2]);
+        assert_eq!(
+            get_slices(&sliced_vec),
+            vec![vec![4, 5, 6], vec![7, 8, 9, 10]]
+        );
+        assert_eq!(sliced_vec.len(), 7);
+    }
+}
@@ -1,14 +1,29 @@
+use crate::component::increment_change_tick;
+use crate::component::SystemTicks;
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::console_err;
+use fruity_game_engine::error::catch_panic;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::inject::Inject;
 use fruity_game_engine::profile_scope;
 use fruity_game_engine::resource::ResourceContainer;
+use fruity_game_engine::resource::ResourceReference;
 use fruity_game_engine::sync::Arc;
 use fruity_game_engine::sync::Mutex;
+use fruity_game_engine::sync::RwLock;
+use fruity_game_engine::utils::spawn;
+use fruity_game_engine::utils::SpawnedFuture;
+use fruity_game_engine::FruityError;
 use fruity_game_engine::FruityResult;
-use fruity_game_engine::{export, export_impl, export_struct};
+
+use futures::Future;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fmt::Debug;
+use std::pin::Pin;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -20,9 +35,21 @@ use rayon::prelude::*;
 mod system_pool;
 pub use system_pool::*;
 
+mod system_schedule;
+use system_schedule::*;
+
+mod state_service;
+pub use state_service::*;
+
 /// A callback for a system called every frame
 pub type SystemCallback = dyn Fn() -> FruityResult<()> + Send + Sync + 'static;
 
+/// A future returned by an async system
+pub type SystemFuture = Pin<Box<dyn Send + Future<Output = FruityResult<()>>>>;
+
+/// A callback for an async system called every frame
+pub type AsyncSystemCallback = dyn Fn() -> SystemFuture + Send + Sync + 'static;
+
 /// A callback for a startup system dispose callback
 pub type StartupDisposeSystemCallback =
     Option<Box<dyn FnOnce() -> FruityResult<()> + Send + Sync + 'static>>;
@@ -33,37 +60,86 @@ pub type StartupSystemCallback =
 
 /// Params for a system
 #[derive(Debug, Clone, FruityAny, Default)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct SystemParams {
-    /// The pool index
+    /// The pool index, the pools run one after the other
+    /// Prefer the labels and the before/after constraints to order the systems of a pool
     pub pool_index: Option<usize>,
 
+    /// Labels used by the other systems to be ordered relatively to this one, the identifier is always a label
+    pub labels: Option<Vec<String>>,
+
+    /// Labels of the systems that should run after this one
+    pub before: Option<Vec<String>>,
+
+    /// Labels of the systems that should run before this one
+    pub after: Option<Vec<String>>,
+
+    /// Resources and components read by the system, it doesn't run in parallel with a system that writes them
+    pub reads: Option<Vec<String>>,
+
+    /// Resources and components written by the system, it doesn't run in parallel with a system that accesses them
+    pub writes: Option<Vec<String>>,
+
     /// If true, the system is still running while pause
     pub ignore_pause: Option<bool>,
 
     /// If true, the system will be executed in the main thread
     pub execute_in_main_thread: Option<bool>,
+
+    /// What to do when an async system is still running at the next frame, default is skip
+    pub async_policy: Option<AsyncSystemPolicy>,
+
+    /// If true, an error in the system is logged and the system is disabled instead of stopping the frame
+    /// A panic is turned into an error too, except on wasm32 where a panic aborts the application
+    pub disable_on_error: Option<bool>,
+
+    /// If set, the system runs only while the application is in one of those states, see [`StateService`]
+    pub states: Option<Vec<String>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl SystemParams {}
 
+/// Define how an async system behaves when it's future is still running at the next frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[fruity_export]
+pub enum AsyncSystemPolicy {
+    /// The frame waits for the future to complete
+    /// Wasm can't block the main thread, so it behaves like queue in wasm
+    /// Not available for the script systems, their promises are resolved by the javascript event loop that the frame would block
+    Block,
+
+    /// The system is not called again until the running future is completed
+    #[default]
+    Skip,
+
+    /// The system is called every frame, the futures are queued and run one after the other
+    Queue,
+}
+
 /// Params for a system
 #[derive(Debug, Clone, FruityAny, Default)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct StartupSystemParams {
     /// If true, the system is still running while pause
     pub ignore_pause: Option<bool>,
 
     /// If true, the system will be executed in the main thread
     pub execute_in_main_thread: Option<bool>,
+
+    /// If set, the system runs when the application enters this state and its dispose callback runs
+    /// when the application exits it, see [`StateService`]
+    /// The pause is ignored by the state startup systems
+    pub state: Option<String>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl StartupSystemParams {}
 
 struct StartupDisposeSystem {
     identifier: String,
+    module: Option<String>,
     callback: Box<dyn FnOnce() -> FruityResult<()> + Send + Sync + 'static>,
     execute_in_main_thread: bool,
 }
@@ -71,7 +147,7 @@ struct StartupDisposeSystem {
 /// System service
 ///
 #[derive(FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct SystemService {
     pause: Arc<AtomicBool>,
     system_pools: BTreeMap<usize, FrameSystemPool>,
@@ -79,6 +155,10 @@ pub struct SystemService {
     startup_pause_systems: StartupSystemPool,
     startup_dispose_callbacks: Arc<Mutex<StartupDisposeSystemPool>>,
     startup_pause_dispose_callbacks: Arc<Mutex<StartupDisposeSystemPool>>,
+    pool_orderings_checked: AtomicBool,
+    state: Arc<RwLock<Option<String>>>,
+    state_startup_systems: HashMap<String, StartupSystemPool>,
+    state_service: Option<ResourceReference<StateService>>,
     resource_container: ResourceContainer,
 }
 
@@ -88,7 +168,7 @@ impl Debug for SystemService {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl SystemService {
     /// Returns a SystemService
     pub fn new(resource_container: ResourceContainer) -> SystemService {
@@ -99,6 +179,12 @@ impl SystemService {
         let startup_pause_dispose_callbacks = Arc::new(Mutex::new(StartupDisposeSystemPool {
             systems: Vec::new(),
         }));
+        // Without a state service, the application never enters a state
+        let state_service = resource_container.get::<StateService>("state_service");
+        let state = match &state_service {
+            Some(state_service) => state_service.read().get_shared_state(),
+            None => Arc::new(RwLock::new(None)),
+        };
 
         SystemService {
             pause,
@@ -113,6 +199,10 @@ impl SystemService {
             },
             startup_dispose_callbacks,
             startup_pause_dispose_callbacks,
+            pool_orderings_checked: AtomicBool::new(false),
+            state,
+            state_startup_systems: HashMap::new(),
+            state_service,
             resource_container,
         }
     }
@@ -142,7 +232,7 @@ impl SystemService {
     /// * `system` - A function that will compute the world
     /// * `pool_index` - A pool identifier, all the systems of the same pool will be processed together in parallel
     ///
-    #[export(name = "add_system")]
+    #[fruity_export(name = "add_system")]
     pub fn add_script_system(
         &mut self,
         identifier: String,
@@ -179,23 +269,136 @@ impl SystemService {
         params: Option<SystemParams>,
     ) {
         let params = params.unwrap_or_default();
-        let system = FrameSystem {
-            identifier: identifier.to_string(),
-            system,
-            ignore_pause: params.ignore_pause.unwrap_or(false),
-            execute_in_main_thread: params.execute_in_main_thread.unwrap_or(false),
-        };
+        self.insert_frame_system(
+            FrameSystem {
+                identifier: identifier.to_string(),
+                module: self.resource_container.get_current_module_name(),
+                ordering: SystemOrdering::new(identifier, &params),
+                system: FrameSystemCallback::Sync(system),
+                ignore_pause: params.ignore_pause.unwrap_or(false),
+                execute_in_main_thread: params.execute_in_main_thread.unwrap_or(false),
+                disable_on_error: params.disable_on_error.unwrap_or(false),
+                states: params.states,
+                disabled: AtomicBool::new(false),
+                last_run_tick: AtomicU64::new(0),
+            },
+            params.pool_index.unwrap_or(50),
+        );
+    }
 
-        if let Some(pool) = self.system_pools.get_mut(&params.pool_index.unwrap_or(50)) {
+    /// Add an async system to the collection
+    /// The returned future runs in the background, see [`AsyncSystemPolicy`] for the frames where it is still running
+    ///
+    /// # Arguments
+    /// * `system` - A function that will compute the world
+    /// * `pool_index` - A pool identifier, all the systems of the same pool will be processed together in parallel
+    ///
+    pub fn add_async_system<T: Inject<SystemFuture>>(
+        &mut self,
+        identifier: &str,
+        callback: T,
+        params: Option<SystemParams>,
+    ) {
+        self.add_boxed_async_system(
+            identifier,
+            callback.inject(&self.resource_container).into(),
+            params,
+        )
+    }
+
+    /// Add an async system to the collection
+    /// The returned promise is awaited across the frames, the block policy is rejected
+    ///
+    /// # Arguments
+    /// * `system` - A function that will compute the world
+    /// * `pool_index` - A pool identifier, all the systems of the same pool will be processed together in parallel
+    ///
+    #[fruity_export(name = "add_async_system")]
+    pub fn add_script_async_system(
+        &mut self,
+        identifier: String,
+        callback: Box<
+            dyn Send + Sync + Fn() -> Pin<Box<dyn Send + Future<Output = FruityResult<()>>>>,
+        >,
+        params: Option<SystemParams>,
+    ) -> FruityResult<()> {
+        if let Some(AsyncSystemPolicy::Block) =
+            params.as_ref().and_then(|params| params.async_policy)
+        {
+            return Err(FruityError::GenericFailure(format!(
+                "The async system {} can't use the block policy, a script promise is resolved by the javascript event loop that the frame would block",
+                identifier
+            )));
+        }
+
+        self.add_boxed_async_system(
+            identifier.as_str(),
+            callback,
+            Some(
+                params
+                    .map(|params| SystemParams {
+                        execute_in_main_thread: Some(true),
+                        ..params
+                    })
+                    .unwrap_or(SystemParams {
+                        execute_in_main_thread: Some(true),
+                        ..Default::default()
+                    }),
+            ),
+        );
+
+        Ok(())
+    }
+
+    /// Add an async system to the collection
+    ///
+    /// # Arguments
+    /// * `system` - A function that will compute the world
+    /// * `pool_index` - A pool identifier, all the systems of the same pool will be processed together in parallel
+    ///
+    pub fn add_boxed_async_system(
+        &mut self,
+        identifier: &str,
+        system: Box<AsyncSystemCallback>,
+        params: Option<SystemParams>,
+    ) {
+        let params = params.unwrap_or_default();
+        self.insert_frame_system(
+            FrameSystem {
+                identifier: identifier.to_string(),
+                module: self.resource_container.get_current_module_name(),
+                ordering: SystemOrdering::new(identifier, &params),
+                system: FrameSystemCallback::Async {
+                    callback: system,
+                    policy: params.async_policy.unwrap_or_default(),
+                    running_futures: Mutex::new(RunningFutures::default()),
+                },
+                ignore_pause: params.ignore_pause.unwrap_or(false),
+                execute_in_main_thread: params.execute_in_main_thread.unwrap_or(false),
+                disable_on_error: params.disable_on_error.unwrap_or(false),
+                states: params.states,
+                disabled: AtomicBool::new(false),
+                last_run_tick: AtomicU64::new(0),
+            },
+            params.pool_index.unwrap_or(50),
+        );
+    }
+
+    fn insert_frame_system(&mut self, system: FrameSystem, pool_index: usize) {
+        self.pool_orderings_checked.store(false, Ordering::Relaxed);
+
+        if let Some(pool) = self.system_pools.get_mut(&pool_index) {
             pool.add_system(system)
         } else {
             // If the pool not exists, we create it
             let systems = vec![system];
             self.system_pools.insert(
-                params.pool_index.unwrap_or(50),
+                pool_index,
                 FrameSystemPool {
                     pause: self.pause.clone(),
+                    state: self.state.clone(),
                     systems,
+                    schedule: Mutex::new(None),
                     enabled: true,
                 },
             );
@@ -227,7 +430,7 @@ impl SystemService {
     /// * `system` - A function that will compute the world
     /// * `pool_index` - A pool identifier, all the systems of the same pool will be processed together in parallel
     ///
-    #[export(name = "add_startup_system")]
+    #[fruity_export(name = "add_startup_system")]
     pub fn add_script_startup_system(
         &mut self,
         identifier: String,
@@ -277,15 +480,32 @@ impl SystemService {
     ) {
         let params = params.unwrap_or_default();
 
-        if params.ignore_pause.unwrap_or(false) {
+        if let Some(state) = params.state {
+            self.state_startup_systems
+                .entry(state)
+                .or_insert_with(|| StartupSystemPool {
+                    systems: Default::default(),
+                    dispose_callbacks: Arc::new(Mutex::new(StartupDisposeSystemPool {
+                        systems: Vec::new(),
+                    })),
+                })
+                .add_system(StartupSystem {
+                    identifier: identifier.to_string(),
+                    module: self.resource_container.get_current_module_name(),
+                    system: callback,
+                    execute_in_main_thread: params.execute_in_main_thread.unwrap_or(false),
+                });
+        } else if params.ignore_pause.unwrap_or(false) {
             self.startup_systems.add_system(StartupSystem {
                 identifier: identifier.to_string(),
+                module: self.resource_container.get_current_module_name(),
                 system: callback,
                 execute_in_main_thread: params.execute_in_main_thread.unwrap_or(false),
             });
         } else {
             self.startup_pause_systems.add_system(StartupSystem {
                 identifier: identifier.to_string(),
+                module: self.resource_container.get_current_module_name(),
                 system: callback,
                 execute_in_main_thread: params.execute_in_main_thread.unwrap_or(false),
             });
@@ -298,6 +518,13 @@ impl SystemService {
 
         let was_paused = self.is_paused();
 
+        if !self.pool_orderings_checked.load(Ordering::Relaxed) {
+            self.check_pool_orderings()?;
+            self.pool_orderings_checked.store(true, Ordering::Relaxed);
+        }
+
+        self.apply_state_transition()?;
+
         self.system_pools.iter().try_for_each(|(_, pool)| {
             if pool.enabled {
                 (pool as &dyn SystemPool<FrameSystem>).run_systems()?;
@@ -318,6 +545,62 @@ impl SystemService {
         Ok(())
     }
 
+    /// Check that the ordering constraints refer to existing systems and that the constraints between
+    /// systems of different pools match the pools order
+    fn check_pool_orderings(&self) -> FruityResult<()> {
+        let systems = self
+            .system_pools
+            .iter()
+            .flat_map(|(pool_index, pool)| {
+                pool.systems.iter().map(move |system| (*pool_index, system))
+            })
+            .collect::<Vec<_>>();
+
+        systems.iter().try_for_each(|(_, system)| {
+            system
+                .ordering
+                .before
+                .iter()
+                .chain(system.ordering.after.iter())
+                .try_for_each(|label| {
+                    if systems
+                        .iter()
+                        .any(|(_, other_system)| other_system.ordering.has_label(label))
+                    {
+                        Ok(())
+                    } else {
+                        Err(FruityError::GenericFailure(format!(
+                            "The {} is ordered relatively to the label {} but no system has this label",
+                            system_context(&system.identifier, &system.module),
+                            label
+                        )))
+                    }
+                })
+        })?;
+
+        systems.iter().try_for_each(|(pool_index, system)| {
+            systems
+                .iter()
+                .try_for_each(|(other_pool_index, other_system)| {
+                    if (other_pool_index < pool_index
+                        && system.ordering.is_before(&other_system.ordering))
+                        || (other_pool_index > pool_index
+                            && other_system.ordering.is_before(&system.ordering))
+                    {
+                        Err(FruityError::GenericFailure(format!(
+                            "The {} is ordered in the wrong way relatively to the {}, they are in the pools {} and {}",
+                            system_context(&system.identifier, &system.module),
+                            system_context(&other_system.identifier, &other_system.module),
+                            pool_index,
+                            other_pool_index
+                        )))
+                    } else {
+                        Ok(())
+                    }
+                })
+        })
+    }
+
     /// Run all the startup systems
     pub fn run_start(&self) -> FruityResult<()> {
         profile_scope!("start_systems");
@@ -328,6 +611,8 @@ impl SystemService {
             (&self.startup_pause_systems as &dyn SystemPool<StartupSystem>).run_systems()?;
         }
 
+        self.apply_state_transition()?;
+
         Result::Ok(())
     }
 
@@ -339,6 +624,16 @@ impl SystemService {
             self.run_unpause_end()?;
         }
 
+        // The state is left, it's entered again at the next start so its startup systems run again
+        let state = self.state.read().clone();
+        if let Some(state) = state {
+            self.run_state_exit(&state)?;
+            *self.state.write() = None;
+            if let Some(state_service) = &self.state_service {
+                state_service.read().reenter_state(state);
+            }
+        }
+
         let mut startup_dispose_callbacks = self.startup_dispose_callbacks.lock();
         startup_dispose_callbacks.run_systems()
     }
@@ -354,6 +649,39 @@ impl SystemService {
         startup_dispose_callbacks.run_systems()
     }
 
+    /// Exit the current state and enter the next one if a state change has been requested
+    fn apply_state_transition(&self) -> FruityResult<()> {
+        let next_state = match &self.state_service {
+            Some(state_service) => state_service.read().take_next_state(),
+            None => None,
+        };
+
+        if let Some(next_state) = next_state {
+            let previous_state = self.state.read().clone();
+            if let Some(previous_state) = previous_state {
+                self.run_state_exit(&previous_state)?;
+            }
+
+            *self.state.write() = Some(next_state.clone());
+
+            if let Some(pool) = self.state_startup_systems.get(&next_state) {
+                (pool as &dyn SystemPool<StartupSystem>).run_systems()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run the dispose callbacks of the startup systems of a state
+    fn run_state_exit(&self, state: &str) -> FruityResult<()> {
+        if let Some(pool) = self.state_startup_systems.get(state) {
+            let mut dispose_callbacks = pool.dispose_callbacks.lock();
+            dispose_callbacks.run_systems()?;
+        }
+
+        Ok(())
+    }
+
     /// Enable a pool
     pub fn enable_pool(&mut self, index: usize) {
         if let Some(pool) = self.system_pools.get_mut(&index) {
@@ -369,7 +697,7 @@ impl SystemService {
     }
 
     /// Is systems paused
-    #[export]
+    #[fruity_export]
     pub fn is_paused(&self) -> bool {
         self.pause.load(Ordering::Relaxed)
     }
@@ -379,42 +707,135 @@ impl SystemService {
     /// # Arguments
     /// * `paused` - The paused value
     ///
-    #[export]
+    #[fruity_export]
     pub fn set_paused(&self, paused: bool) -> FruityResult<()> {
         self.pause.store(paused, Ordering::Relaxed);
         Ok(())
     }
 }
 
+/// Describe where a system comes from, used to trace the errors
+fn system_context(identifier: &str, module: &Option<String>) -> String {
+    match module {
+        Some(module) => format!("system {} (module {})", identifier, module),
+        None => format!("system {}", identifier),
+    }
+}
+
+enum FrameSystemCallback {
+    Sync(Box<SystemCallback>),
+    Async {
+        callback: Box<AsyncSystemCallback>,
+        policy: AsyncSystemPolicy,
+        running_futures: Mutex<RunningFutures>,
+    },
+}
+
+/// The futures of an async system, they run one after the other in the background
+#[derive(Default)]
+struct RunningFutures {
+    running: Option<SpawnedFuture<FruityResult<()>>>,
+    queued: VecDeque<SystemFuture>,
+}
+
+impl RunningFutures {
+    fn is_empty(&self) -> bool {
+        self.running.is_none() && self.queued.is_empty()
+    }
+
+    /// Take the result of the running future if it is completed and start the next queued one
+    fn update(&mut self) -> FruityResult<()> {
+        let result = self
+            .running
+            .as_ref()
+            .and_then(|running| running.take_result());
+
+        if result.is_some() {
+            self.running = None;
+        }
+
+        if self.running.is_none() {
+            self.running = self.queued.pop_front().map(spawn);
+        }
+
+        result.unwrap_or(Ok(()))
+    }
+
+    fn push(&mut self, future: SystemFuture) -> FruityResult<()> {
+        self.queued.push_back(future);
+        self.update()
+    }
+}
+
 struct FrameSystem {
     identifier: String,
-    system: Box<SystemCallback>,
+    module: Option<String>,
+    ordering: SystemOrdering,
+    system: FrameSystemCallback,
     ignore_pause: bool,
     execute_in_main_thread: bool,
+    disable_on_error: bool,
+    states: Option<Vec<String>>,
+    disabled: AtomicBool,
+    /// The change tick of the previous run, used by the change detection query filters
+    last_run_tick: AtomicU64,
 }
 
 /// A system pool, see [‘SystemService‘] for more informations
 struct FrameSystemPool {
     pause: Arc<AtomicBool>,
+    state: Arc<RwLock<Option<String>>>,
 
     /// Systems of the pool
     systems: Vec<FrameSystem>,
 
+    /// The stages of the systems, built at the first run after a system is added
+    schedule: Mutex<Option<SystemSchedule>>,
+
     /// Is the pool enabled
     enabled: bool,
 }
 
 impl SystemPool<FrameSystem> for FrameSystemPool {
     fn add_system(&mut self, system: FrameSystem) {
-        self.systems.push(system)
+        self.systems.push(system);
+        *self.schedule.lock() = None;
     }
 
     fn iter(&self) -> Box<dyn Iterator<Item = &FrameSystem> + '_> {
-        if self.pause.load(Ordering::Relaxed) {
-            Box::new(self.systems.iter().filter(|system| system.ignore_pause))
-        } else {
-            Box::new(self.systems.iter())
+        Box::new(
+            self.systems
+                .iter()
+                .filter(|system| self.is_runnable_system(system)),
+        )
+    }
+
+    fn stages(&self) -> FruityResult<Vec<Vec<&FrameSystem>>> {
+        let mut schedule = self.schedule.lock();
+        if schedule.is_none() {
+            let orderings = self
+                .systems
+                .iter()
+                .map(|system| &system.ordering)
+                .collect::<Vec<_>>();
+
+            *schedule = Some(SystemSchedule::build(&orderings)?);
         }
+
+        Ok(schedule
+            .as_ref()
+            .unwrap()
+            .stages
+            .iter()
+            .map(|stage| {
+                stage
+                    .iter()
+                    .map(|system_index| &self.systems[*system_index])
+                    .filter(|system| self.is_runnable_system(system))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|stage| !stage.is_empty())
+            .collect())
     }
 
     fn is_main_thread_system(&self, system: &FrameSystem) -> bool {
@@ -423,12 +844,82 @@ impl SystemPool<FrameSystem> for FrameSystemPool {
 
     fn execute_system(&self, system: &FrameSystem) -> FruityResult<()> {
         profile_scope!(&system.identifier);
-        (system.system)()
+
+        let system_ticks = SystemTicks {
+            last_run: system.last_run_tick.load(Ordering::Relaxed),
+            this_run: increment_change_tick(),
+        };
+
+        let result = system_ticks
+            .scope(|| catch_panic(|| Self::execute_system_callback(system)))
+            .map_err(|err| err.with_context(&system_context(&system.identifier, &system.module)));
+
+        system
+            .last_run_tick
+            .store(system_ticks.this_run, Ordering::Relaxed);
+
+        match result {
+            Err(err) if system.disable_on_error => {
+                console_err(&format!(
+                    "The system {} has been disabled cause it failed: {}",
+                    system.identifier,
+                    err.to_string()
+                ));
+                system.disabled.store(true, Ordering::Relaxed);
+
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+impl FrameSystemPool {
+    /// Returns false if the system is disabled, paused or restricted to other states
+    fn is_runnable_system(&self, system: &FrameSystem) -> bool {
+        !system.disabled.load(Ordering::Relaxed)
+            && (system.ignore_pause || !self.pause.load(Ordering::Relaxed))
+            && system.states.as_ref().map_or(true, |states| {
+                self.state
+                    .read()
+                    .as_ref()
+                    .map_or(false, |state| states.contains(state))
+            })
+    }
+
+    fn execute_system_callback(system: &FrameSystem) -> FruityResult<()> {
+        match &system.system {
+            FrameSystemCallback::Sync(callback) => callback(),
+            FrameSystemCallback::Async {
+                callback,
+                policy,
+                running_futures,
+            } => match policy {
+                #[cfg(not(target_arch = "wasm32"))]
+                AsyncSystemPolicy::Block => fruity_game_engine::utils::block_on(callback()),
+                AsyncSystemPolicy::Skip => {
+                    let mut running_futures = running_futures.lock();
+                    running_futures.update()?;
+
+                    if running_futures.is_empty() {
+                        running_futures.push(callback())?;
+                    }
+
+                    Ok(())
+                }
+                _ => {
+                    let mut running_futures = running_futures.lock();
+                    running_futures.update()?;
+                    running_futures.push(callback())
+                }
+            },
+        }
     }
 }
 
 struct StartupSystem {
     identifier: String,
+    module: Option<String>,
     system: Box<StartupSystemCallback>,
     execute_in_main_thread: bool,
 }
@@ -453,12 +944,14 @@ impl SystemPool<StartupSystem> for StartupSystemPool {
 
     fn execute_system(&self, system: &StartupSystem) -> FruityResult<()> {
         profile_scope!(&system.identifier);
-        let dispose = (system.system)()?;
+        let dispose = catch_panic(|| (system.system)())
+            .map_err(|err| err.with_context(&system_context(&system.identifier, &system.module)))?;
 
         if let Some(dispose) = dispose {
             let mut dispose_callbacks = self.dispose_callbacks.lock();
             dispose_callbacks.add_system(StartupDisposeSystem {
                 identifier: system.identifier.clone(),
+                module: system.module.clone(),
                 callback: dispose,
                 execute_in_main_thread: system.execute_in_main_thread,
             });
@@ -479,7 +972,8 @@ impl StartupDisposeSystemPool {
 
     fn execute_system(system: StartupDisposeSystem) -> FruityResult<()> {
         profile_scope!(&system.identifier);
-        (system.callback)()
+        let context = system_context(&system.identifier, &system.module);
+        catch_panic(|| (system.callback)()).map_err(|err| err.with_context(&context))
     }
 
     /// Run all the systems in the system pool
@@ -538,3 +1032,296 @@ impl StartupDisposeSystemPool {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::channel::oneshot;
+    use std::{sync::atomic::AtomicUsize, thread, time::Duration};
+
+    #[derive(Default)]
+    struct AsyncSystemCounters {
+        calls: AtomicUsize,
+        started: AtomicUsize,
+        completed: AtomicUsize,
+        releases: Mutex<Vec<oneshot::Sender<()>>>,
+    }
+
+    impl AsyncSystemCounters {
+        fn release(&self) {
+            self.releases.lock().drain(..).for_each(|release| {
+                release.send(()).unwrap();
+            });
+        }
+
+        fn wait_started(&self, count: usize) {
+            wait_until(|| self.started.load(Ordering::Relaxed) >= count);
+        }
+
+        fn wait_completed(&self, count: usize) {
+            wait_until(|| self.completed.load(Ordering::Relaxed) >= count);
+        }
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        for _ in 0..1000 {
+            if condition() {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        panic!("The system futures are not completed in time");
+    }
+
+    fn new_system_service() -> SystemService {
+        let resource_container = ResourceContainer::new();
+        resource_container.add::<StateService>(
+            "state_service",
+            Box::new(StateService::new(resource_container.clone())),
+        );
+
+        SystemService::new(resource_container)
+    }
+
+    /// Create a system whose futures wait to be released by the test, like a promise waits for the javascript event loop
+    fn released_system(counters: Arc<AsyncSystemCounters>) -> Box<AsyncSystemCallback> {
+        Box::new(move || {
+            counters.calls.fetch_add(1, Ordering::Relaxed);
+            let (release, released) = oneshot::channel();
+            counters.releases.lock().push(release);
+
+            let counters = counters.clone();
+            Box::pin(async move {
+                counters.started.fetch_add(1, Ordering::Relaxed);
+                released.await.unwrap();
+                counters.completed.fetch_add(1, Ordering::Relaxed);
+
+                Ok(())
+            })
+        })
+    }
+
+    /// Add a system whose futures wait to be released by the test
+    fn add_released_system(
+        system_service: &mut SystemService,
+        policy: AsyncSystemPolicy,
+    ) -> Arc<AsyncSystemCounters> {
+        let counters = Arc::new(AsyncSystemCounters::default());
+
+        system_service.add_boxed_async_system(
+            "released_system",
+            released_system(counters.clone()),
+            Some(SystemParams {
+                async_policy: Some(policy),
+                ..Default::default()
+            }),
+        );
+
+        counters
+    }
+
+    #[test]
+    fn test_stop_and_start_enters_the_state_again() {
+        let mut system_service = new_system_service();
+        let enters = Arc::new(AtomicUsize::new(0));
+        let exits = Arc::new(AtomicUsize::new(0));
+
+        {
+            let enters = enters.clone();
+            let exits = exits.clone();
+            system_service.add_arc_startup_system(
+                "playing_system",
+                Box::new(move || {
+                    enters.fetch_add(1, Ordering::Relaxed);
+
+                    let exits = exits.clone();
+                    Ok(Some(Box::new(move || {
+                        exits.fetch_add(1, Ordering::Relaxed);
+                        Ok(())
+                    })))
+                }),
+                Some(StartupSystemParams {
+                    state: Some("playing".to_string()),
+                    ..Default::default()
+                }),
+            );
+        }
+
+        let state_service = system_service.state_service.clone().unwrap();
+        state_service
+            .read()
+            .set_state("playing".to_string())
+            .unwrap();
+
+        system_service.run_start().unwrap();
+        assert_eq!(enters.load(Ordering::Relaxed), 1);
+
+        system_service.run_end().unwrap();
+        assert_eq!(exits.load(Ordering::Relaxed), 1);
+        assert_eq!(state_service.read().get_state(), None);
+
+        system_service.run_start().unwrap();
+        assert_eq!(enters.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            state_service.read().get_state(),
+            Some("playing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_system_service_without_state_service() {
+        let mut system_service = SystemService::new(ResourceContainer::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        {
+            let runs = runs.clone();
+            system_service.add_boxed_system(
+                "stateless_system",
+                Box::new(move || {
+                    runs.fetch_add(1, Ordering::Relaxed);
+                    Ok(())
+                }),
+                None,
+            );
+        }
+
+        system_service.run_start().unwrap();
+        system_service.run_frame().unwrap();
+        system_service.run_end().unwrap();
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_panicking_system_returns_an_error() {
+        let mut system_service = new_system_service();
+        system_service.add_boxed_system(
+            "panicking_system",
+            Box::new(|| panic!("system failure")),
+            None,
+        );
+
+        let error = system_service.run_frame().unwrap_err();
+        assert!(error.get_message().contains("system failure"));
+        assert!(error.get_message().contains("panicking_system"));
+    }
+
+    #[test]
+    fn test_async_system_block_policy() {
+        let mut system_service = new_system_service();
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        let system_completed = completed.clone();
+        system_service.add_boxed_async_system(
+            "blocking_system",
+            Box::new(move || {
+                let completed = system_completed.clone();
+                Box::pin(async move {
+                    thread::sleep(Duration::from_millis(10));
+                    completed.fetch_add(1, Ordering::Relaxed);
+
+                    Ok(())
+                })
+            }),
+            Some(SystemParams {
+                async_policy: Some(AsyncSystemPolicy::Block),
+                ..Default::default()
+            }),
+        );
+
+        system_service.run_frame().unwrap();
+        assert_eq!(completed.load(Ordering::Relaxed), 1);
+
+        system_service.run_frame().unwrap();
+        assert_eq!(completed.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_script_async_system_rejects_block_policy() {
+        let mut system_service = new_system_service();
+        let counters = Arc::new(AsyncSystemCounters::default());
+
+        let result = system_service.add_script_async_system(
+            "script_system".to_string(),
+            released_system(counters.clone()),
+            Some(SystemParams {
+                async_policy: Some(AsyncSystemPolicy::Block),
+                ..Default::default()
+            }),
+        );
+        assert!(result.is_err());
+
+        // The rejected system is not added so the frame doesn't wait for its promise
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_script_async_system_does_not_block_the_frame() {
+        let mut system_service = new_system_service();
+        let counters = Arc::new(AsyncSystemCounters::default());
+
+        system_service
+            .add_script_async_system(
+                "script_system".to_string(),
+                released_system(counters.clone()),
+                None,
+            )
+            .unwrap();
+
+        // The promise is resolved only once the frame returns to the event loop
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 1);
+
+        counters.release();
+        counters.wait_completed(1);
+
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 2);
+
+        counters.release();
+    }
+
+    #[test]
+    fn test_async_system_skip_policy() {
+        let mut system_service = new_system_service();
+        let counters = add_released_system(&mut system_service, AsyncSystemPolicy::Skip);
+
+        // The system is not called while its future is running
+        system_service.run_frame().unwrap();
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 1);
+
+        counters.release();
+        counters.wait_completed(1);
+
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 2);
+
+        counters.release();
+    }
+
+    #[test]
+    fn test_async_system_queue_policy() {
+        let mut system_service = new_system_service();
+        let counters = add_released_system(&mut system_service, AsyncSystemPolicy::Queue);
+
+        // The system is called every frame but the futures run one after the other
+        system_service.run_frame().unwrap();
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 2);
+        counters.wait_started(1);
+        assert_eq!(counters.started.load(Ordering::Relaxed), 1);
+
+        counters.release();
+        counters.wait_completed(1);
+
+        system_service.run_frame().unwrap();
+        assert_eq!(counters.calls.load(Ordering::Relaxed), 3);
+        counters.wait_started(2);
+
+        counters.release();
+        counters.wait_completed(2);
+    }
+}
@@ -0,0 +1,89 @@
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{
+    any::FruityAny,
+    resource::ResourceContainer,
+    sync::{Arc, Mutex, RwLock},
+    FruityResult,
+};
+use std::fmt::{Debug, Formatter};
+
+/// A state machine over the application states, for example MainMenu, Loading, Playing and GameOver
+///
+/// A state change is applied by the `SystemService` at the beginning of the next frame, the dispose
+/// callbacks of the previous state are called then the startup systems of the new state are run
+/// The frame systems can be restricted to some states with the `states` system param
+#[derive(FruityAny)]
+#[fruity_export]
+pub struct StateService {
+    state: Arc<RwLock<Option<String>>>,
+    next_state: Mutex<Option<String>>,
+}
+
+#[fruity_export]
+impl StateService {
+    /// Returns a StateService
+    pub fn new(_resource_container: ResourceContainer) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(None)),
+            next_state: Mutex::new(None),
+        }
+    }
+
+    /// Get the current state
+    #[fruity_export]
+    pub fn get_state(&self) -> Option<String> {
+        self.state.read().clone()
+    }
+
+    /// Returns true if the application is in a given state
+    #[fruity_export]
+    pub fn is_in_state(&self, state: String) -> bool {
+        self.state.read().as_ref() == Some(&state)
+    }
+
+    /// Change the state, the transition is applied at the beginning of the next frame
+    /// If the state is changed several times during a frame, only the last one is kept
+    ///
+    /// # Arguments
+    /// * `state` - The new state
+    ///
+    #[fruity_export]
+    pub fn set_state(&self, state: String) -> FruityResult<()> {
+        *self.next_state.lock() = Some(state);
+        Ok(())
+    }
+
+    /// Get the state that will be applied at the beginning of the next frame
+    #[fruity_export]
+    pub fn get_next_state(&self) -> Option<String> {
+        self.next_state.lock().clone()
+    }
+
+    /// Get the state shared with the system pools
+    pub(crate) fn get_shared_state(&self) -> Arc<RwLock<Option<String>>> {
+        self.state.clone()
+    }
+
+    /// Enter a state again at the next transition, unless another state has been requested since
+    /// It's used when the systems are stopped so a restart enters the state again
+    pub(crate) fn reenter_state(&self, state: String) {
+        self.next_state.lock().get_or_insert(state);
+    }
+
+    /// Take the state that should be entered, returns None if the state doesn't change
+    pub(crate) fn take_next_state(&self) -> Option<String> {
+        let next_state = self.next_state.lock().take()?;
+
+        if self.state.read().as_ref() != Some(&next_state) {
+            Some(next_state)
+        } else {
+            None
+        }
+    }
+}
+
+impl Debug for StateService {
+    fn fmt(&self, _: &mut Formatter) -> Result<(), std::fmt::Error> {
+        Ok(())
+    }
+}
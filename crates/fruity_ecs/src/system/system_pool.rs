@@ -19,16 +19,28 @@ pub trait SystemPool<System: Sync + Send + 'static>: Send + Sync + 'static {
 
     /// Execute a system
     fn execute_system(&self, system: &System) -> FruityResult<()>;
+
+    /// Get the systems split into stages, the stages run one after the other and the systems of a stage run in parallel
+    fn stages(&self) -> FruityResult<Vec<Vec<&System>>> {
+        Ok(vec![self.iter().collect()])
+    }
 }
 
 impl<System: Sync + Send + 'static> dyn SystemPool<System> {
     /// Run all the systems in the system pool
     pub fn run_systems(&self) -> FruityResult<()> {
+        self.stages()?
+            .into_iter()
+            .try_for_each(|systems| self.run_stage(systems))
+    }
+
+    /// Run the systems of a stage
+    fn run_stage(&self, systems: Vec<&System>) -> FruityResult<()> {
         #[cfg(target_arch = "wasm32")]
         {
             profile_scope!("main_thread_systems");
 
-            self.iter()
+            systems
                 .into_iter()
                 .try_for_each(|system| self.execute_system(system))?;
 
@@ -37,8 +49,8 @@ impl<System: Sync + Send + 'static> dyn SystemPool<System> {
 
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let (main_thread_systems, parallel_systems): (Vec<_>, Vec<_>) = self
-                .iter()
+            let (main_thread_systems, parallel_systems): (Vec<_>, Vec<_>) = systems
+                .into_iter()
                 .partition(|system| self.is_main_thread_system(system));
 
             profile_scope!("parallel_systems");
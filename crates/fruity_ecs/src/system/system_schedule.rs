@@ -0,0 +1,300 @@
+use super::SystemParams;
+use fruity_game_engine::{FruityError, FruityResult};
+
+/// The ordering constraints and the accesses of a system
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SystemOrdering {
+    /// The system identifier, it's always a label of the system
+    pub(crate) identifier: String,
+    pub(crate) labels: Vec<String>,
+    pub(crate) before: Vec<String>,
+    pub(crate) after: Vec<String>,
+    pub(crate) reads: Vec<String>,
+    pub(crate) writes: Vec<String>,
+}
+
+impl SystemOrdering {
+    /// Returns a SystemOrdering from the params of a system
+    pub(crate) fn new(identifier: &str, params: &SystemParams) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            labels: params.labels.clone().unwrap_or_default(),
+            before: params.before.clone().unwrap_or_default(),
+            after: params.after.clone().unwrap_or_default(),
+            reads: params.reads.clone().unwrap_or_default(),
+            writes: params.writes.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Returns true if the system has a label
+    pub(crate) fn has_label(&self, label: &str) -> bool {
+        self.identifier == label || self.labels.iter().any(|other| other == label)
+    }
+
+    /// Returns true if the system should run before the other one
+    pub(crate) fn is_before(&self, other: &SystemOrdering) -> bool {
+        self.before.iter().any(|label| other.has_label(label))
+            || other.after.iter().any(|label| self.has_label(label))
+    }
+
+    /// Returns true if both systems access the same resource or component and one of them writes it
+    pub(crate) fn conflicts_with(&self, other: &SystemOrdering) -> bool {
+        self.writes
+            .iter()
+            .any(|access| other.reads.contains(access) || other.writes.contains(access))
+            || other
+                .writes
+                .iter()
+                .any(|access| self.reads.contains(access))
+    }
+}
+
+/// The systems of a pool split into stages
+///
+/// The stages run one after the other, the systems of a stage don't conflict and run in parallel
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SystemSchedule {
+    /// The system indexes of every stages
+    pub(crate) stages: Vec<Vec<usize>>,
+}
+
+impl SystemSchedule {
+    /// Build the schedule of a pool, returns an error if the ordering constraints contain a cycle
+    ///
+    /// Conflicting systems that are not ordered by a constraint run in their insertion order
+    pub(crate) fn build(systems: &[&SystemOrdering]) -> FruityResult<Self> {
+        let system_count = systems.len();
+
+        // Build the graph of the ordering constraints, successors[a] contains b if a runs before b
+        let mut successors = (0..system_count)
+            .map(|system| {
+                (0..system_count)
+                    .filter(|other| *other != system && systems[system].is_before(systems[*other]))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let order = Self::topological_order(&successors).map_err(|cycle| {
+            FruityError::GenericFailure(format!(
+                "The ordering constraints of the systems {} contain a cycle",
+                cycle
+                    .into_iter()
+                    .map(|system| systems[system].identifier.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?;
+
+        // Order the conflicting systems, the reachability is kept up to date so no cycle can be created
+        let mut reachable = Self::reachability(&order, &successors);
+        for system in 0..system_count {
+            for other in (system + 1)..system_count {
+                if systems[system].conflicts_with(systems[other])
+                    && !reachable[system][other]
+                    && !reachable[other][system]
+                {
+                    successors[system].push(other);
+
+                    let other_reachable = reachable[other].clone();
+                    for (from, from_reachable) in reachable.iter_mut().enumerate() {
+                        if from == system || from_reachable[system] {
+                            for (to, reached) in from_reachable.iter_mut().enumerate() {
+                                if to == other || other_reachable[to] {
+                                    *reached = true;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Every system runs in the stage after its last predecessor
+        let order = Self::topological_order(&successors).unwrap();
+        let mut system_stages = vec![0; system_count];
+        let mut stages = Vec::<Vec<usize>>::new();
+        for system in order {
+            let stage = system_stages[system];
+            if stages.len() <= stage {
+                stages.resize_with(stage + 1, Vec::new);
+            }
+            stages[stage].push(system);
+
+            for successor in successors[system].iter() {
+                system_stages[*successor] = system_stages[*successor].max(stage + 1);
+            }
+        }
+
+        stages.iter_mut().for_each(|stage| stage.sort());
+
+        Ok(Self { stages })
+    }
+
+    /// Sort the systems so every system comes after its predecessors, the insertion order is kept when possible
+    /// Returns the systems that are part of a cycle if there is one
+    fn topological_order(successors: &[Vec<usize>]) -> Result<Vec<usize>, Vec<usize>> {
+        let mut predecessor_counts = vec![0; successors.len()];
+        successors.iter().flatten().for_each(|successor| {
+            predecessor_counts[*successor] += 1;
+        });
+
+        let mut order = Vec::with_capacity(successors.len());
+        let mut visited = vec![false; successors.len()];
+        while let Some(system) = (0..successors.len())
+            .find(|system| !visited[*system] && predecessor_counts[*system] == 0)
+        {
+            visited[system] = true;
+            order.push(system);
+
+            successors[system].iter().for_each(|successor| {
+                predecessor_counts[*successor] -= 1;
+            });
+        }
+
+        if order.len() == successors.len() {
+            Ok(order)
+        } else {
+            Err((0..successors.len())
+                .filter(|system| !visited[*system])
+                .collect())
+        }
+    }
+
+    /// Get for every system the systems that run after it, reachable[a][b] is true if a runs before b
+    fn reachability(order: &[usize], successors: &[Vec<usize>]) -> Vec<Vec<bool>> {
+        let mut reachable = vec![vec![false; successors.len()]; successors.len()];
+        for system in order.iter().rev() {
+            for successor in successors[*system].iter() {
+                reachable[*system][*successor] = true;
+
+                let successor_reachable = reachable[*successor].clone();
+                for (reached, successor_reached) in
+                    reachable[*system].iter_mut().zip(successor_reachable)
+                {
+                    *reached |= successor_reached;
+                }
+            }
+        }
+
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ordering(identifier: &str) -> SystemOrdering {
+        SystemOrdering {
+            identifier: identifier.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    fn build(systems: &[SystemOrdering]) -> FruityResult<Vec<Vec<usize>>> {
+        SystemSchedule::build(&systems.iter().collect::<Vec<_>>()).map(|schedule| schedule.stages)
+    }
+
+    #[test]
+    fn test_build_runs_the_independent_systems_in_one_stage() {
+        let systems = [ordering("a"), ordering("b"), ordering("c")];
+
+        assert_eq!(build(&systems).unwrap(), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_build_follows_the_before_and_after_constraints() {
+        let systems = [
+            SystemOrdering {
+                after: labels(&["b"]),
+                ..ordering("a")
+            },
+            ordering("b"),
+            SystemOrdering {
+                before: labels(&["b"]),
+                ..ordering("c")
+            },
+        ];
+
+        assert_eq!(build(&systems).unwrap(), vec![vec![2], vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn test_build_matches_the_labels() {
+        let systems = [
+            SystemOrdering {
+                after: labels(&["physic"]),
+                ..ordering("draw")
+            },
+            SystemOrdering {
+                labels: labels(&["physic"]),
+                ..ordering("update_rigid_body")
+            },
+            SystemOrdering {
+                labels: labels(&["physic"]),
+                ..ordering("update_collider")
+            },
+        ];
+
+        assert_eq!(build(&systems).unwrap(), vec![vec![1, 2], vec![0]]);
+    }
+
+    #[test]
+    fn test_build_splits_the_conflicting_systems_in_insertion_order() {
+        let systems = [
+            SystemOrdering {
+                writes: labels(&["Transform2D"]),
+                ..ordering("a")
+            },
+            SystemOrdering {
+                reads: labels(&["Transform2D"]),
+                ..ordering("b")
+            },
+            SystemOrdering {
+                reads: labels(&["Transform2D"]),
+                ..ordering("c")
+            },
+            ordering("d"),
+        ];
+
+        assert_eq!(build(&systems).unwrap(), vec![vec![0, 3], vec![1, 2]]);
+    }
+
+    #[test]
+    fn test_build_keeps_the_constraints_over_the_insertion_order() {
+        let systems = [
+            SystemOrdering {
+                writes: labels(&["Transform2D"]),
+                after: labels(&["b"]),
+                ..ordering("a")
+            },
+            SystemOrdering {
+                writes: labels(&["Transform2D"]),
+                ..ordering("b")
+            },
+        ];
+
+        assert_eq!(build(&systems).unwrap(), vec![vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn test_build_fails_on_a_cycle() {
+        let systems = [
+            SystemOrdering {
+                before: labels(&["b"]),
+                ..ordering("a")
+            },
+            SystemOrdering {
+                before: labels(&["a"]),
+                ..ordering("b")
+            },
+            ordering("c"),
+        ];
+
+        assert!(build(&systems).is_err());
+    }
+}
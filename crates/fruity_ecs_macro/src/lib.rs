@@ -3,11 +3,11 @@ use fruity_game_engine_code_parser::FruityExportClassField;
 use fruity_game_engine_code_parser::FruityExportClassFieldName;
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::__private::TokenStream2;
 use syn::parse_macro_input;
 use syn::DeriveInput;
-use syn::__private::TokenStream2;
 
-#[proc_macro_derive(Component, attributes(serialize_skip))]
+#[proc_macro_derive(Component, attributes(serialize_skip, component))]
 pub fn derive_component(input: TokenStream) -> TokenStream {
     let derive_component_trait = derive_component_trait(input.clone());
     let derive_component_trait = proc_macro2::TokenStream::from(derive_component_trait);
@@ -38,9 +38,44 @@ pub fn derive_deserialize(input: TokenStream) -> TokenStream {
 }
 
 fn derive_component_trait(input: TokenStream) -> TokenStream {
-    let DeriveInput { ident, .. } = parse_macro_input!(input);
+    let DeriveInput { ident, attrs, .. } = parse_macro_input!(input);
     let fruity_ecs_crate = fruity_ecs_crate();
 
+    // A component marked with #[component(sparse_set)] is stored outside of the archetypes
+    let is_sparse_set = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("component"))
+        .filter_map(|attr| attr.parse_meta().ok())
+        .any(|meta| match meta {
+            syn::Meta::List(list) => list.nested.iter().any(|nested| match nested {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) => path.is_ident("sparse_set"),
+                _ => false,
+            }),
+            _ => false,
+        });
+
+    let storage = if is_sparse_set {
+        quote! {
+            fn get_storage(&self) -> Box<dyn #fruity_ecs_crate::component::ComponentStorage> {
+                Box::new(#fruity_ecs_crate::component::VecComponentStorage::<Self>::new_sparse_set())
+            }
+
+            fn is_sparse_set() -> bool {
+                true
+            }
+
+            fn is_in_sparse_set(&self) -> bool {
+                true
+            }
+        }
+    } else {
+        quote! {
+            fn get_storage(&self) -> Box<dyn #fruity_ecs_crate::component::ComponentStorage> {
+                Box::new(#fruity_ecs_crate::component::VecComponentStorage::<Self>::new())
+            }
+        }
+    };
+
     let output = quote! {
         impl #fruity_ecs_crate::component::Component for #ident {
             fn duplicate(&self) -> Box<dyn #fruity_ecs_crate::component::Component> {
@@ -55,9 +90,18 @@ fn derive_component_trait(input: TokenStream) -> TokenStream {
                 )
             }
 
-            fn get_storage(&self) -> Box<dyn #fruity_ecs_crate::component::ComponentStorage> {
-                Box::new(#fruity_ecs_crate::component::VecComponentStorage::<Self>::new())
+            fn get_component_type_index() -> #fruity_ecs_crate::component::ComponentTypeIndex {
+                static INDEX: std::sync::OnceLock<#fruity_ecs_crate::component::ComponentTypeIndex> =
+                    std::sync::OnceLock::new();
+
+                *INDEX.get_or_init(|| {
+                    #fruity_ecs_crate::component::ComponentTypeIndex::register(
+                        &fruity_game_engine::script_value::ScriptObjectType::of::<Self>()
+                    )
+                })
             }
+
+            #storage
         }
     };
 
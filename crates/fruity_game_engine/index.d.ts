@@ -46,41 +46,137 @@ export type SetupWorldMiddleware = (world: World, settings: Settings, next: Setu
 
 export type RunWorldMiddleware = (world: World, settings: Settings, next: RunWorldMiddlewareNext) => void
 
+/**
+ * A service for frame management
+ */
 export class FrameService {
 
+  /**
+   * Get the time before the previous frame as seconds
+   */
   getDelta(): number
+  /**
+   * Get the time elapsed since the app launched
+   */
   getElapsed(): number
 }
 
+/**
+ * A module for the engine
+ */
 export interface Module {
+  /**
+   * The name of the module
+   */
   name: string
+  /**
+   * The dependencies of the module
+   */
   dependencies: string[]
+  /**
+   * A function that initialize the module
+   */
   setup?: ((arg0: World, arg1: Settings) => void) | null | undefined | void
+  /**
+   * An async function that initialize the module
+   */
   setupAsync?: ((arg0: World, arg1: Settings) => Promise<unknown>) | null | undefined | void
+  /**
+   * A function that initialize the module resources
+   */
   loadResources?: ((arg0: World, arg1: Settings) => void) | null | undefined | void
+  /**
+   * An async function that initialize the module resources
+   */
   loadResourcesAsync?: ((arg0: World, arg1: Settings) => Promise<unknown>) | null | undefined | void
+  /**
+   * A middleware that occurs when the world setups
+   */
   setupWorldMiddleware?: SetupWorldMiddleware | null | undefined | void
+  /**
+   * A middleware that occurs when the world runs
+   */
   runWorldMiddleware?: RunWorldMiddleware | null | undefined | void
 }
 
+/**
+ * The resource manager
+ */
 export class ResourceContainer {
 
+  /**
+   * Get a required resource by it's identifier
+   * Panic if the resource is not known
+   *
+   * @typeParam T - The resource type
+   */
   require<T>(identifier: string): T
+  /**
+   * Get a resource by it's identifier without casting it
+   *
+   * @param identifier - The resource identifier
+   * @typeParam T - The resource type
+   */
   get<T>(identifier: string): T | null
+  /**
+   * Check if a resource identifier has already been registered
+   *
+   * @param identifier - The resource identifier
+   */
   contains(identifier: string): boolean
+  /**
+   * Add a resource into the collection
+   *
+   * @param identifier - The resource identifier
+   * @param resource - The resource object
+   */
   add(identifier: string, resource: JsIntrospectObject)
+  /**
+   * Remove a resource of the collection
+   *
+   * @param identifier - The resource identifier
+   */
   remove(identifier: string): void
+  /**
+   * Load many resources for settings
+   *
+   * @param settings - The settings of resources
+   */
   loadResourcesSettingsAsync(settings: Settings): Promise<unknown>
 }
 
+/**
+ * The main container of the ECS
+ */
 export class World {
 
+  /**
+   * Returns a World
+   */
   constructor(settings: Settings)
+  /**
+   * Register a module
+   */
   registerModule(module: Module): void
+  /**
+   * Load the modules
+   */
   setupModulesAsync(): Promise<unknown>
+  /**
+   * Load the resources
+   */
   loadResourcesAsync(): Promise<unknown>
+  /**
+   * Run the world
+   */
   setup(): void
+  /**
+   * Run the world
+   */
   run(): void
+  /**
+   * Get resource container
+   */
   getResourceContainer(): ResourceContainer
 }
 
@@ -32,33 +32,118 @@ pub enum FruityError {
     Unknown(String),
 }
 
-impl ToString for FruityError {
-    fn to_string(&self) -> String {
+impl FruityError {
+    /// Get the message of the error
+    pub fn get_message(&self) -> &str {
+        match self {
+            FruityError::Ok(message) => message,
+            FruityError::InvalidArg(message) => message,
+            FruityError::ObjectExpected(message) => message,
+            FruityError::StringExpected(message) => message,
+            FruityError::NameExpected(message) => message,
+            FruityError::FunctionExpected(message) => message,
+            FruityError::NumberExpected(message) => message,
+            FruityError::BooleanExpected(message) => message,
+            FruityError::ArrayExpected(message) => message,
+            FruityError::GenericFailure(message) => message,
+            FruityError::PendingException(message) => message,
+            FruityError::Cancelled(message) => message,
+            FruityError::EscapeCalledTwice(message) => message,
+            FruityError::HandleScopeMismatch(message) => message,
+            FruityError::CallbackScopeMismatch(message) => message,
+            FruityError::QueueFull(message) => message,
+            FruityError::Closing(message) => message,
+            FruityError::BigintExpected(message) => message,
+            FruityError::DateExpected(message) => message,
+            FruityError::ArrayBufferExpected(message) => message,
+            FruityError::DetachableArraybufferExpected(message) => message,
+            FruityError::WouldDeadlock(message) => message,
+            FruityError::NoExternalBuffersAllowed(message) => message,
+            FruityError::Unknown(message) => message,
+        }
+    }
+
+    /// Returns the same error kind with a transformed message
+    pub fn map_message(self, callback: impl FnOnce(String) -> String) -> Self {
         match self {
-            FruityError::Ok(message) => message.to_string(),
-            FruityError::InvalidArg(message) => message.to_string(),
-            FruityError::ObjectExpected(message) => message.to_string(),
-            FruityError::StringExpected(message) => message.to_string(),
-            FruityError::NameExpected(message) => message.to_string(),
-            FruityError::FunctionExpected(message) => message.to_string(),
-            FruityError::NumberExpected(message) => message.to_string(),
-            FruityError::BooleanExpected(message) => message.to_string(),
-            FruityError::ArrayExpected(message) => message.to_string(),
-            FruityError::GenericFailure(message) => message.to_string(),
-            FruityError::PendingException(message) => message.to_string(),
-            FruityError::Cancelled(message) => message.to_string(),
-            FruityError::EscapeCalledTwice(message) => message.to_string(),
-            FruityError::HandleScopeMismatch(message) => message.to_string(),
-            FruityError::CallbackScopeMismatch(message) => message.to_string(),
-            FruityError::QueueFull(message) => message.to_string(),
-            FruityError::Closing(message) => message.to_string(),
-            FruityError::BigintExpected(message) => message.to_string(),
-            FruityError::DateExpected(message) => message.to_string(),
-            FruityError::ArrayBufferExpected(message) => message.to_string(),
-            FruityError::DetachableArraybufferExpected(message) => message.to_string(),
-            FruityError::WouldDeadlock(message) => message.to_string(),
-            FruityError::NoExternalBuffersAllowed(message) => message.to_string(),
-            FruityError::Unknown(message) => message.to_string(),
+            FruityError::Ok(message) => FruityError::Ok(callback(message)),
+            FruityError::InvalidArg(message) => FruityError::InvalidArg(callback(message)),
+            FruityError::ObjectExpected(message) => FruityError::ObjectExpected(callback(message)),
+            FruityError::StringExpected(message) => FruityError::StringExpected(callback(message)),
+            FruityError::NameExpected(message) => FruityError::NameExpected(callback(message)),
+            FruityError::FunctionExpected(message) => {
+                FruityError::FunctionExpected(callback(message))
+            }
+            FruityError::NumberExpected(message) => FruityError::NumberExpected(callback(message)),
+            FruityError::BooleanExpected(message) => {
+                FruityError::BooleanExpected(callback(message))
+            }
+            FruityError::ArrayExpected(message) => FruityError::ArrayExpected(callback(message)),
+            FruityError::GenericFailure(message) => FruityError::GenericFailure(callback(message)),
+            FruityError::PendingException(message) => {
+                FruityError::PendingException(callback(message))
+            }
+            FruityError::Cancelled(message) => FruityError::Cancelled(callback(message)),
+            FruityError::EscapeCalledTwice(message) => {
+                FruityError::EscapeCalledTwice(callback(message))
+            }
+            FruityError::HandleScopeMismatch(message) => {
+                FruityError::HandleScopeMismatch(callback(message))
+            }
+            FruityError::CallbackScopeMismatch(message) => {
+                FruityError::CallbackScopeMismatch(callback(message))
+            }
+            FruityError::QueueFull(message) => FruityError::QueueFull(callback(message)),
+            FruityError::Closing(message) => FruityError::Closing(callback(message)),
+            FruityError::BigintExpected(message) => FruityError::BigintExpected(callback(message)),
+            FruityError::DateExpected(message) => FruityError::DateExpected(callback(message)),
+            FruityError::ArrayBufferExpected(message) => {
+                FruityError::ArrayBufferExpected(callback(message))
+            }
+            FruityError::DetachableArraybufferExpected(message) => {
+                FruityError::DetachableArraybufferExpected(callback(message))
+            }
+            FruityError::WouldDeadlock(message) => FruityError::WouldDeadlock(callback(message)),
+            FruityError::NoExternalBuffersAllowed(message) => {
+                FruityError::NoExternalBuffersAllowed(callback(message))
+            }
+            FruityError::Unknown(message) => FruityError::Unknown(callback(message)),
         }
     }
+
+    /// Add a line to the error message that tells where the error went through
+    /// Used to keep a trace when an error crosses the rust and the script boundaries
+    ///
+    /// # Arguments
+    /// * `context` - A description of the place, for example "system update_camera (module fruity_graphic)"
+    ///
+    pub fn with_context(self, context: &str) -> Self {
+        self.map_message(|message| format!("{}\n    at {}", message, context))
+    }
+}
+
+impl ToString for FruityError {
+    fn to_string(&self) -> String {
+        self.get_message().to_string()
+    }
+}
+
+/// Call a function and turn a panic into an error
+/// Used where a panic should not unwind across the script boundaries or stop the whole frame
+///
+/// On wasm32 a panic aborts instead of unwinding, so only the returned errors are caught there,
+/// a panic still stops the whole application
+pub fn catch_panic<R>(callback: impl FnOnce() -> FruityResult<R>) -> FruityResult<R> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(callback)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+
+        Err(FruityError::GenericFailure(format!(
+            "panicked: {}",
+            message
+        )))
+    })
 }
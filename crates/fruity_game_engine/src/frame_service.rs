@@ -1,4 +1,4 @@
-use crate::{any::FruityAny, export_impl, export_struct, resource::ResourceContainer};
+use crate::{any::FruityAny, fruity_export, resource::ResourceContainer};
 pub use fruity_game_engine_macro::export;
 use std::fmt::Debug;
 
@@ -22,14 +22,14 @@ fn now_in_seconds() -> f64 {
 /// A service for frame management
 // #[cfg(target_arch = "wasm32")]
 #[derive(FruityAny, Debug)]
-#[export_struct]
+#[fruity_export]
 pub struct FrameService {
     first_frame_instant: f64,
     last_frame_instant: f64,
     delta: f64,
 }
 
-#[export_impl]
+#[fruity_export]
 // #[cfg(target_arch = "wasm32")]
 impl FrameService {
     /// Returns a FrameService
@@ -50,13 +50,13 @@ impl FrameService {
     }
 
     /// Get the time before the previous frame as seconds
-    #[export]
+    #[fruity_export]
     pub fn get_delta(&self) -> f64 {
         self.delta
     }
 
     /// Get the time elapsed since the app launched
-    #[export]
+    #[fruity_export]
     pub fn get_elapsed(&self) -> f64 {
         now_in_seconds() - self.first_frame_instant
     }
@@ -1,5 +1,6 @@
 use crate::{
     any::FruityAny,
+    error::catch_panic,
     introspect::{IntrospectFields, IntrospectMethods},
     javascript::napi::class_constructors::NapiClassConstructors,
     profile_scope,
@@ -9,12 +10,14 @@ use crate::{
     FruityError, FruityResult,
 };
 use convert_case::{Case, Casing};
-use futures::{executor::block_on, FutureExt};
+use futures::{channel::oneshot, executor::block_on, FutureExt};
 use lazy_static::lazy_static;
 use napi::{
-    bindgen_prelude::{FromNapiValue, Promise, ToNapiValue},
+    bindgen_prelude::{FromNapiValue, ToNapiValue},
     check_status,
-    threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction},
+    threadsafe_function::{
+        ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    },
     Env, JsBigInt, JsFunction, JsNumber, JsObject, JsString, JsUnknown, NapiRaw, NapiValue, Ref,
     Task, ValueType,
 };
@@ -23,7 +26,6 @@ use std::{
     cell::RefCell, fmt::Debug, future::Future, marker::PhantomData, ops::Deref, path::Component,
     pin::Pin, rc::Rc, sync::Arc, thread, vec,
 };
-use tokio::runtime::Builder;
 
 mod class_constructors;
 
@@ -148,7 +150,7 @@ pub fn script_value_to_js_value(env: &Env, value: ScriptValue) -> FruityResult<J
                     .try_collect::<Vec<_>>()
                     .map_err(|e| e.into_napi())?;
 
-                let result = callback(args).map_err(|e| e.into_napi())?;
+                let result = catch_panic(|| callback(args)).map_err(|e| e.into_napi())?;
                 script_value_to_js_value(ctx.env, result).map_err(|e| e.into_napi())
             })
             .map_err(|e| FruityError::from_napi(e))?
@@ -239,19 +241,8 @@ pub fn js_value_to_script_value(env: &Env, value: JsUnknown) -> FruityResult<Scr
                     .is_promise()
                     .map_err(|e| FruityError::from_napi(e))?
                 {
-                    profile_scope!("js_value_to_script_value_array");
-                    // Convert js value to promise
-                    let promise = Promise::<ScriptValue>::from_unknown(js_object.into_unknown())
-                        .map_err(|e| FruityError::from_napi(e))?;
-
-                    // Convert promise to future
-                    let future = Box::pin(async move {
-                        let res = promise.await.map_err(|e| FruityError::from_napi(e));
-                        res
-                    })
-                        as Pin<Box<dyn Send + Future<Output = FruityResult<ScriptValue>>>>;
-
-                    ScriptValue::Future(future)
+                    profile_scope!("js_value_to_script_value_promise");
+                    ScriptValue::Future(js_promise_to_future(env, js_object)?)
                 } else {
                     profile_scope!("js_value_to_script_value_object");
 
@@ -328,17 +319,29 @@ pub fn js_value_to_script_value(env: &Env, value: JsUnknown) -> FruityResult<Scr
                     ScriptObjectType::from_type_id_value(type_id_value)
                 };
 
+                let js_func = JsSharedRef::new(env, js_func)?;
+
                 // Create a threadsafe function to call the function outside of the js thread
-                let thread_safe_func: ThreadsafeFunction<Vec<ScriptValue>, ErrorStrategy::Fatal> =
-                    js_func
+                // The function is called by the threadsafe function context in the js thread, so a thrown
+                // exception can be read there, the threadsafe function itself only calls a noop function
+                let thread_safe_func: ThreadsafeFunction<ThreadsafeCall, ErrorStrategy::Fatal> = {
+                    let js_func = SendWrapper::new(js_func.clone());
+                    env.create_function_from_closure("noop", |ctx| ctx.env.get_undefined())
+                        .map_err(|e| FruityError::from_napi(e))?
                         .create_threadsafe_function(
                             0,
-                            |ctx: ThreadSafeCallContext<Vec<ScriptValue>>| Ok(ctx.value),
+                            move |ctx: ThreadSafeCallContext<ThreadsafeCall>| {
+                                let (args, sender) = ctx.value;
+                                let result = call_js_function(&ctx.env, &js_func.inner(), args);
+                                sender.send(result).ok();
+
+                                Ok(Vec::<ScriptValue>::new())
+                            },
                         )
-                        .map_err(|e| FruityError::from_napi(e))?;
+                        .map_err(|e| FruityError::from_napi(e))?
+                };
                 let thread_safe_func = ThreadsafeFunctionSync(thread_safe_func);
 
-                let js_func = JsSharedRef::new(env, js_func)?;
                 let js_send_wrapper = SendWrapper::new((env.clone(), js_func));
 
                 ScriptValue::Callback {
@@ -346,38 +349,20 @@ pub fn js_value_to_script_value(env: &Env, value: JsUnknown) -> FruityResult<Scr
                     callback: Box::new(move |args| {
                         // Case the js function is called in the js thread, we call it directly
                         // Otherwise, we call the function in the js thread and wait for the result in our thread
-                        let result = if js_send_wrapper.valid() {
+                        if js_send_wrapper.valid() {
                             let (env, js_func) = js_send_wrapper.deref();
-
-                            // Get the js func from the reference
-                            let js_func = js_func.inner();
-
-                            // Convert all the others args as a JsUnknown
-                            let args = args
-                                .into_iter()
-                                .map(|elem| script_value_to_js_value(&env, elem))
-                                .try_collect::<Vec<_>>()?;
-
-                            // Call the function
-                            let result = js_func
-                                .call(None, &args)
-                                .map_err(|e| FruityError::from_napi(e))?;
-
-                            // Return the result
-                            let result = js_value_to_script_value(&env, result)?;
-                            Ok(result)
+                            call_js_function(env, &js_func.inner(), args)
                         } else {
-                            let result: ScriptValue = Builder::new_current_thread()
-                                .enable_all()
-                                .build()
-                                .unwrap()
-                                .block_on(thread_safe_func.call_async(args))
-                                .map_err(|e| FruityError::from_napi(e))?;
-
-                            Ok(result)
-                        };
+                            let (sender, receiver) = oneshot::channel();
+                            thread_safe_func
+                                .call((args, sender), ThreadsafeFunctionCallMode::NonBlocking);
 
-                        result
+                            block_on(receiver).map_err(|_| {
+                                FruityError::GenericFailure(
+                                    "The javascript function has not been called".to_string(),
+                                )
+                            })?
+                        }
                     }),
                 }
             }
@@ -393,13 +378,80 @@ pub fn js_value_to_script_value(env: &Env, value: JsUnknown) -> FruityResult<Scr
     )
 }
 
-struct ThreadsafeFunctionSync(ThreadsafeFunction<Vec<ScriptValue>, ErrorStrategy::Fatal>);
+/// Call a js function, the stack of a thrown exception is kept in the error
+fn call_js_function(
+    env: &Env,
+    js_func: &JsFunction,
+    args: Vec<ScriptValue>,
+) -> FruityResult<ScriptValue> {
+    // Convert all the args as a JsUnknown
+    let args = args
+        .into_iter()
+        .map(|elem| script_value_to_js_value(env, elem))
+        .try_collect::<Vec<_>>()?;
+
+    let result = js_func
+        .call(None, &args)
+        .map_err(|e| FruityError::from_napi_with_env(env, e))?;
+
+    js_value_to_script_value(env, result)
+}
+
+/// Convert a js promise to a future, the promise is settled in the js thread so the stack of a rejection
+/// can be kept in the error
+fn js_promise_to_future(
+    env: &Env,
+    promise: JsObject,
+) -> FruityResult<Pin<Box<dyn Send + Future<Output = FruityResult<ScriptValue>>>>> {
+    let (sender, receiver) = oneshot::channel::<FruityResult<ScriptValue>>();
+    let sender = Rc::new(RefCell::new(Some(sender)));
+
+    let resolve_sender = sender.clone();
+    let on_resolved = env
+        .create_function_from_closure("onResolved", move |ctx| {
+            let result = js_value_to_script_value(ctx.env, ctx.get::<JsUnknown>(0)?);
+            if let Some(sender) = resolve_sender.borrow_mut().take() {
+                sender.send(result).ok();
+            }
+
+            ctx.env.get_undefined()
+        })
+        .map_err(|e| FruityError::from_napi(e))?;
+
+    let on_rejected = env
+        .create_function_from_closure("onRejected", move |ctx| {
+            let error = FruityError::from_js_exception(ctx.get::<JsUnknown>(0)?);
+            if let Some(sender) = sender.borrow_mut().take() {
+                sender.send(Err(error)).ok();
+            }
+
+            ctx.env.get_undefined()
+        })
+        .map_err(|e| FruityError::from_napi(e))?;
+
+    promise
+        .get_named_property::<JsFunction>("then")
+        .map_err(|e| FruityError::from_napi(e))?
+        .call(Some(&promise), &[on_resolved, on_rejected])
+        .map_err(|e| FruityError::from_napi_with_env(env, e))?;
+
+    Ok(Box::pin(async move {
+        receiver.await.map_err(|_| {
+            FruityError::GenericFailure("The javascript promise has been dropped".to_string())
+        })?
+    }))
+}
+
+/// The args of a js function called from another thread and where to send its result
+type ThreadsafeCall = (Vec<ScriptValue>, oneshot::Sender<FruityResult<ScriptValue>>);
+
+struct ThreadsafeFunctionSync(ThreadsafeFunction<ThreadsafeCall, ErrorStrategy::Fatal>);
 
 unsafe impl Send for ThreadsafeFunctionSync {}
 unsafe impl Sync for ThreadsafeFunctionSync {}
 
 impl Deref for ThreadsafeFunctionSync {
-    type Target = ThreadsafeFunction<Vec<ScriptValue>, ErrorStrategy::Fatal>;
+    type Target = ThreadsafeFunction<ThreadsafeCall, ErrorStrategy::Fatal>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -658,6 +710,53 @@ impl TryFromScriptValue for JsIntrospectObject {
 }
 
 impl FruityError {
+    /// Convert a js error to a fruity_game_engine error, if a javascript exception is pending
+    /// it is cleared and it's stack is kept into the error message
+    pub fn from_napi_with_env(env: &Env, err: napi::Error) -> Self {
+        let mut is_exception_pending = false;
+        unsafe { napi_sys::napi_is_exception_pending(env.raw(), &mut is_exception_pending) };
+
+        if !is_exception_pending {
+            return FruityError::from_napi(err);
+        }
+
+        let mut exception = std::ptr::null_mut();
+        unsafe { napi_sys::napi_get_and_clear_last_exception(env.raw(), &mut exception) };
+
+        // The exception is cleared, it's not rethrown here: its stack or message is kept in a generic failure,
+        // that is thrown as a new error if it goes back to javascript
+        // The napi error reason is used if the exception can't be read
+        match unsafe { JsUnknown::from_raw(env.raw(), exception) } {
+            Ok(exception) => FruityError::from_js_exception(exception),
+            Err(_) => FruityError::GenericFailure(err.reason.to_string()),
+        }
+    }
+
+    /// Convert a thrown js value to a fruity_game_engine error, the javascript stack is kept if there is one
+    /// A thrown value that is not an error, like a string, is converted to a string as is
+    pub fn from_js_exception(exception: JsUnknown) -> Self {
+        let get_property = |name: &str| {
+            if exception.get_type().ok()? != ValueType::Object {
+                return None;
+            }
+
+            let exception = unsafe { exception.cast::<JsObject>() };
+            exception
+                .get_named_property::<JsUnknown>(name)
+                .ok()
+                .filter(|property| !matches!(property.get_type(), Ok(ValueType::Undefined)))
+        };
+
+        let message = get_property("stack")
+            .or_else(|| get_property("message"))
+            .unwrap_or(exception)
+            .coerce_to_string()
+            .and_then(|message| message.into_utf8())
+            .and_then(|message| message.as_str().map(|message| message.to_string()));
+
+        FruityError::GenericFailure(message.unwrap_or_default())
+    }
+
     /// Convert a js error to a fruity_game_engine error
     pub fn from_napi(err: napi::Error) -> Self {
         match err.status {
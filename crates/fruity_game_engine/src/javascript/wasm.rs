@@ -1,5 +1,6 @@
 use crate::{
     any::FruityAny,
+    error::catch_panic,
     introspect::{IntrospectFields, IntrospectMethods},
     script_value::{
         ScriptObject, ScriptObjectType, ScriptValue, TryFromScriptValue, TryIntoScriptValue,
@@ -9,10 +10,176 @@ use crate::{
 use convert_case::{Case, Casing};
 use js_sys::{JsString, Object, Reflect};
 use send_wrapper::SendWrapper;
-use std::{fmt::Debug, future::Future, ops::Deref, pin::Pin};
-use wasm_bindgen::{JsCast, JsError, JsValue};
+use std::{
+    cell::RefCell, collections::HashMap, fmt::Debug, future::Future, ops::Deref, pin::Pin, rc::Rc,
+};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsError, JsValue};
 use wasm_bindgen_futures::{future_to_promise, JsFuture};
 
+#[wasm_bindgen]
+extern "C" {
+    /// Binding over the javascript FinalizationRegistry
+    /// See https://developer.mozilla.org/docs/Web/JavaScript/Reference/Global_Objects/FinalizationRegistry
+    type FinalizationRegistry;
+
+    #[wasm_bindgen(constructor)]
+    fn new(cleanup: &js_sys::Function) -> FinalizationRegistry;
+
+    #[wasm_bindgen(method)]
+    fn register(
+        this: &FinalizationRegistry,
+        target: &JsValue,
+        held_value: &JsValue,
+        unregister_token: &JsValue,
+    );
+
+    #[wasm_bindgen(method)]
+    fn unregister(this: &FinalizationRegistry, unregister_token: &JsValue) -> bool;
+}
+
+/// Debug counters over the native objects that are exposed to javascript
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BridgedObjectStats {
+    /// Number of native objects currently owned by a javascript object
+    pub live: usize,
+    /// Total number of native objects that have been exposed to javascript
+    pub created: usize,
+    /// Total number of native objects that have been dropped by the garbage collector
+    pub finalized: usize,
+    /// Total number of native objects that have been moved back to rust
+    pub moved_back: usize,
+}
+
+/// A native object owned by a javascript object
+///
+/// The object is shared with the calls in progress, so a call that re-enters javascript keeps it alive
+/// and a reentrant call can't get a mutable reference while another reference is in use
+type BridgedObject = Rc<RefCell<Box<dyn ScriptObject>>>;
+
+/// The identifiers are never reused, so the accessors of a javascript object whose native object has been
+/// moved back to rust or released can't reach another native object
+///
+/// They are stored as javascript numbers, it would take a million objects per second during centuries to
+/// exceed the integers they represent exactly
+#[derive(Default)]
+struct BridgedObjects {
+    id_incrementer: u64,
+    objects: HashMap<u64, BridgedObject>,
+    stats: BridgedObjectStats,
+}
+
+thread_local! {
+    static BRIDGED_OBJECTS: RefCell<BridgedObjects> = RefCell::new(BridgedObjects::default());
+
+    static FINALIZATION_REGISTRY: FinalizationRegistry = {
+        let cleanup = wasm_bindgen::closure::Closure::wrap(Box::new(|held_value: JsValue| {
+            if let Some(rust_object_id) = held_value.as_f64() {
+                let rust_object = take_bridged_object(rust_object_id as u64, true);
+                drop(rust_object);
+            }
+        }) as Box<dyn Fn(JsValue)>);
+
+        FinalizationRegistry::new(cleanup.into_js_value().unchecked_ref())
+    };
+}
+
+/// Get the debug counters of the native objects exposed to javascript, can be used to find leaks
+/// It's exported to javascript as getBridgedObjectStats
+#[wasm_bindgen(js_name = getBridgedObjectStats)]
+pub fn get_bridged_object_stats() -> BridgedObjectStats {
+    BRIDGED_OBJECTS.with(|bridged_objects| bridged_objects.borrow().stats)
+}
+
+fn bridge_rust_object(rust_object: Box<dyn ScriptObject>) -> u64 {
+    BRIDGED_OBJECTS.with(|bridged_objects| {
+        let mut bridged_objects = bridged_objects.borrow_mut();
+
+        bridged_objects.id_incrementer += 1;
+        let rust_object_id = bridged_objects.id_incrementer;
+
+        bridged_objects
+            .objects
+            .insert(rust_object_id, Rc::new(RefCell::new(rust_object)));
+        bridged_objects.stats.live += 1;
+        bridged_objects.stats.created += 1;
+
+        rust_object_id
+    })
+}
+
+fn get_bridged_object(rust_object_id: u64) -> FruityResult<BridgedObject> {
+    // The borrow is released before calling the callbacks cause they can bridge other objects
+    BRIDGED_OBJECTS
+        .with(|bridged_objects| {
+            bridged_objects
+                .borrow()
+                .objects
+                .get(&rust_object_id)
+                .cloned()
+        })
+        .ok_or_else(|| {
+            FruityError::GenericFailure(
+                "The native object has already been moved or released".to_string(),
+            )
+        })
+}
+
+fn with_bridged_object<R>(
+    rust_object_id: u64,
+    callback: impl FnOnce(&Box<dyn ScriptObject>) -> FruityResult<R>,
+) -> FruityResult<R> {
+    let rust_object = get_bridged_object(rust_object_id)?;
+    let rust_object = rust_object.try_borrow().map_err(|_| {
+        FruityError::GenericFailure(
+            "The native object is mutably borrowed by a call in progress".to_string(),
+        )
+    })?;
+
+    callback(&rust_object)
+}
+
+fn with_bridged_object_mut<R>(
+    rust_object_id: u64,
+    callback: impl FnOnce(&mut Box<dyn ScriptObject>) -> FruityResult<R>,
+) -> FruityResult<R> {
+    let rust_object = get_bridged_object(rust_object_id)?;
+    let mut rust_object = rust_object.try_borrow_mut().map_err(|_| {
+        FruityError::GenericFailure(
+            "The native object is borrowed by a call in progress".to_string(),
+        )
+    })?;
+
+    callback(&mut rust_object)
+}
+
+fn take_bridged_object(rust_object_id: u64, finalized: bool) -> Option<Box<dyn ScriptObject>> {
+    let rust_object = BRIDGED_OBJECTS.with(|bridged_objects| {
+        let mut bridged_objects = bridged_objects.borrow_mut();
+
+        // An object used by a call in progress can't be moved back to rust, a finalized one is
+        // dropped when the last call returns
+        let is_in_use = Rc::strong_count(bridged_objects.objects.get(&rust_object_id)?) > 1;
+        if is_in_use && !finalized {
+            return None;
+        }
+
+        let rust_object = bridged_objects.objects.remove(&rust_object_id)?;
+        bridged_objects.stats.live -= 1;
+        if finalized {
+            bridged_objects.stats.finalized += 1;
+        } else {
+            bridged_objects.stats.moved_back += 1;
+        }
+
+        Some(rust_object)
+    })?;
+
+    Rc::try_unwrap(rust_object)
+        .ok()
+        .map(|rust_object| rust_object.into_inner())
+}
+
 /// Create a wasm js value from a script value
 pub fn script_value_to_js_value(value: ScriptValue) -> FruityResult<JsValue> {
     puffin::profile_scope!("script_value_to_js_value");
@@ -76,7 +243,8 @@ pub fn script_value_to_js_value(value: ScriptValue) -> FruityResult<JsValue> {
                         .try_collect::<Vec<_>>()
                         .map_err(|err| JsError::from(err))?;
 
-                    let result = callback(args).map_err(|err| JsError::from(err))?;
+                    let result =
+                        catch_panic(|| callback(args)).map_err(|err| JsError::from(err))?;
 
                     script_value_to_js_value(result).map_err(|err| err.into())
                 },
@@ -88,31 +256,36 @@ pub fn script_value_to_js_value(value: ScriptValue) -> FruityResult<JsValue> {
         ScriptValue::Object(rust_object) => {
             let js_object = js_sys::Object::new();
 
-            // Store the shared ptr of the native object into the js_object
-            // TODO: This is highly unsafe and should be reworked
-            let rust_object_ptr = Box::into_raw(Box::new(rust_object));
-            let rust_object_ptr_value = rust_object_ptr as *const () as u32;
-            let rust_object_ptr_js_value = JsValue::from_f64(rust_object_ptr_value.into());
-
+            // Store the native object into the bridged objects and keep it's identifier into the js_object
+            let rust_object_id = bridge_rust_object(rust_object);
             js_sys::Reflect::set(
                 &js_object,
                 &"__rust_reference".into(),
-                &rust_object_ptr_js_value,
+                &JsValue::from_f64(rust_object_id as f64),
             )
             .map_err(|err| FruityError::from(err))?;
 
-            // Define const method accessors
-            unsafe { rust_object_ptr.as_ref().unwrap() }
-                .get_field_names()?
+            // The native object will be dropped when the js_object is garbage collected
+            FINALIZATION_REGISTRY.with(|registry| {
+                registry.register(
+                    &js_object,
+                    &JsValue::from_f64(rust_object_id as f64),
+                    &js_object,
+                )
+            });
+
+            // Define field accessors
+            with_bridged_object(rust_object_id, |rust_object| rust_object.get_field_names())?
                 .into_iter()
                 .try_for_each(|field_name| {
                     // Define getter
                     let field_name_2 = field_name.clone();
                     let getter = wasm_bindgen::closure::Closure::wrap(Box::new(
                         move || -> Result<JsValue, JsError> {
-                            let result = unsafe { rust_object_ptr.as_ref().unwrap() }
-                                .get_field_value(&field_name_2)
-                                .map_err(|err| JsError::from(err))?;
+                            let result = with_bridged_object(rust_object_id, |rust_object| {
+                                rust_object.get_field_value(&field_name_2)
+                            })
+                            .map_err(|err| JsError::from(err))?;
 
                             script_value_to_js_value(result).map_err(|err| JsError::from(err))
                         },
@@ -126,9 +299,10 @@ pub fn script_value_to_js_value(value: ScriptValue) -> FruityResult<JsValue> {
                             let arg =
                                 js_value_to_script_value(arg).map_err(|err| JsError::from(err))?;
 
-                            unsafe { rust_object_ptr.as_mut().unwrap() }
-                                .set_field_value(&field_name_2, arg)
-                                .map_err(|err| JsError::from(err))?;
+                            with_bridged_object_mut(rust_object_id, |rust_object| {
+                                rust_object.set_field_value(&field_name_2, arg)
+                            })
+                            .map_err(|err| JsError::from(err))?;
 
                             Ok(())
                         },
@@ -152,87 +326,91 @@ pub fn script_value_to_js_value(value: ScriptValue) -> FruityResult<JsValue> {
                 })?;
 
             // Define const method accessors
-            unsafe { rust_object_ptr.as_ref().unwrap() }
-                .get_const_method_names()?
-                .into_iter()
-                .try_for_each(|method_name| {
-                    let method_name_2 = method_name.clone();
-                    let closure = wasm_bindgen::closure::Closure::wrap(Box::new(
-                        move |arg0: JsValue,
-                              arg1: JsValue,
-                              arg2: JsValue,
-                              arg3: JsValue,
-                              arg4: JsValue,
-                              arg5: JsValue,
-                              arg6: JsValue|
-                              -> Result<JsValue, JsError> {
-                            let args = vec![arg0, arg1, arg2, arg3, arg4, arg5, arg6];
-                            let args = args
-                                .into_iter()
-                                .map(|arg| js_value_to_script_value(arg))
-                                .try_collect::<Vec<_>>()
-                                .map_err(|err| JsError::from(err))?;
-
-                            let result = unsafe { rust_object_ptr.as_ref().unwrap() }
-                                .call_const_method(&method_name_2, args)
-                                .map_err(|err| JsError::from(err))?;
-
-                            script_value_to_js_value(result).map_err(|err| err.into())
-                        },
-                    )
-                        as Box<dyn Fn(_, _, _, _, _, _, _) -> _ + 'static>);
+            with_bridged_object(rust_object_id, |rust_object| {
+                rust_object.get_const_method_names()
+            })?
+            .into_iter()
+            .try_for_each(|method_name| {
+                let method_name_2 = method_name.clone();
+                let closure = wasm_bindgen::closure::Closure::wrap(Box::new(
+                    move |arg0: JsValue,
+                          arg1: JsValue,
+                          arg2: JsValue,
+                          arg3: JsValue,
+                          arg4: JsValue,
+                          arg5: JsValue,
+                          arg6: JsValue|
+                          -> Result<JsValue, JsError> {
+                        let args = vec![arg0, arg1, arg2, arg3, arg4, arg5, arg6];
+                        let args = args
+                            .into_iter()
+                            .map(|arg| js_value_to_script_value(arg))
+                            .try_collect::<Vec<_>>()
+                            .map_err(|err| JsError::from(err))?;
+
+                        let result = with_bridged_object(rust_object_id, |rust_object| {
+                            rust_object.call_const_method(&method_name_2, args)
+                        })
+                        .map_err(|err| JsError::from(err))?;
 
-                    js_sys::Reflect::set(
-                        &js_object,
-                        &method_name.clone().to_case(Case::Camel).into(),
-                        &closure.into_js_value(),
-                    )
-                    .map_err(|err| FruityError::from(err))?;
+                        script_value_to_js_value(result).map_err(|err| err.into())
+                    },
+                )
+                    as Box<dyn Fn(_, _, _, _, _, _, _) -> _ + 'static>);
 
-                    FruityResult::Ok(())
-                })?;
+                js_sys::Reflect::set(
+                    &js_object,
+                    &method_name.clone().to_case(Case::Camel).into(),
+                    &closure.into_js_value(),
+                )
+                .map_err(|err| FruityError::from(err))?;
+
+                FruityResult::Ok(())
+            })?;
 
             // Define mut method accessors
-            unsafe { rust_object_ptr.as_ref().unwrap() }
-                .get_mut_method_names()?
-                .into_iter()
-                .try_for_each(|method_name| {
-                    let method_name_2 = method_name.clone();
-
-                    let closure = wasm_bindgen::closure::Closure::wrap(Box::new(
-                        move |arg0: JsValue,
-                              arg1: JsValue,
-                              arg2: JsValue,
-                              arg3: JsValue,
-                              arg4: JsValue,
-                              arg5: JsValue,
-                              arg6: JsValue|
-                              -> Result<JsValue, JsError> {
-                            let args = vec![arg0, arg1, arg2, arg3, arg4, arg5, arg6];
-                            let args = args
-                                .into_iter()
-                                .map(|arg| js_value_to_script_value(arg))
-                                .try_collect::<Vec<_>>()
-                                .map_err(|err| JsError::from(err))?;
-
-                            let result = unsafe { rust_object_ptr.as_mut().unwrap() }
-                                .call_mut_method(&method_name_2, args)
-                                .map_err(|err| JsError::from(err))?;
-
-                            script_value_to_js_value(result).map_err(|err| err.into())
-                        },
-                    )
-                        as Box<dyn Fn(_, _, _, _, _, _, _) -> _ + 'static>);
+            with_bridged_object(rust_object_id, |rust_object| {
+                rust_object.get_mut_method_names()
+            })?
+            .into_iter()
+            .try_for_each(|method_name| {
+                let method_name_2 = method_name.clone();
+
+                let closure = wasm_bindgen::closure::Closure::wrap(Box::new(
+                    move |arg0: JsValue,
+                          arg1: JsValue,
+                          arg2: JsValue,
+                          arg3: JsValue,
+                          arg4: JsValue,
+                          arg5: JsValue,
+                          arg6: JsValue|
+                          -> Result<JsValue, JsError> {
+                        let args = vec![arg0, arg1, arg2, arg3, arg4, arg5, arg6];
+                        let args = args
+                            .into_iter()
+                            .map(|arg| js_value_to_script_value(arg))
+                            .try_collect::<Vec<_>>()
+                            .map_err(|err| JsError::from(err))?;
+
+                        let result = with_bridged_object_mut(rust_object_id, |rust_object| {
+                            rust_object.call_mut_method(&method_name_2, args)
+                        })
+                        .map_err(|err| JsError::from(err))?;
 
-                    js_sys::Reflect::set(
-                        &js_object,
-                        &method_name.clone().to_case(Case::Camel).into(),
-                        &closure.into_js_value(),
-                    )
-                    .map_err(|err| FruityError::from(err))?;
+                        script_value_to_js_value(result).map_err(|err| err.into())
+                    },
+                )
+                    as Box<dyn Fn(_, _, _, _, _, _, _) -> _ + 'static>);
 
-                    FruityResult::Ok(())
-                })?;
+                js_sys::Reflect::set(
+                    &js_object,
+                    &method_name.clone().to_case(Case::Camel).into(),
+                    &closure.into_js_value(),
+                )
+                .map_err(|err| FruityError::from(err))?;
+
+                FruityResult::Ok(())
+            })?;
 
             js_object.into()
         }
@@ -339,23 +517,41 @@ pub fn js_value_to_script_value(value: JsValue) -> FruityResult<ScriptValue> {
 
         ScriptValue::Future(future)
     } else if value.is_bigint() {
-        // Second case, the object is a big int
-        todo!()
+        // Second case, the object is a big int, it's read from its decimal form so no precision is lost
+        let decimal = String::from(
+            value
+                .unchecked_into::<js_sys::BigInt>()
+                .to_string(10)
+                .map_err(|_| FruityError::NumberExpected("Couldn't read a big int".to_string()))?,
+        );
+
+        match decimal.parse::<u64>() {
+            Ok(value) => ScriptValue::U64(value),
+            Err(_) => ScriptValue::I64(decimal.parse::<i64>().map_err(|_| {
+                FruityError::NumberExpected(format!("The big int {} overflows 64 bits", decimal))
+            })?),
+        }
     } else if value.is_object() {
         // Try to get the wrapped native value
         match js_sys::Reflect::get(&value, &"__rust_reference".into()) {
             Ok(ref_ptr_js_value) => {
                 if !ref_ptr_js_value.is_undefined() && !ref_ptr_js_value.is_null() {
                     // Third case, the object is a native object
-                    // Get the shared ptr of the native object from the js_object
-                    // TODO: This is highly unsafe and should be reworked
-                    let rust_object_ptr_value = ref_ptr_js_value.as_f64().unwrap() as u32;
-                    let native_object = *unsafe {
-                        Box::from_raw(
-                            rust_object_ptr_value as *const () as *const Box<dyn ScriptObject>
-                                as *mut Box<dyn ScriptObject>,
-                        )
-                    };
+                    // The native object is moved back to rust, so the js_object doesn't own it anymore
+                    let rust_object_id = ref_ptr_js_value.as_f64().unwrap() as u64;
+                    let native_object = take_bridged_object(rust_object_id, false).ok_or(
+                        FruityError::GenericFailure(
+                            "The native object has already been moved or released, or is used by a call in progress".to_string(),
+                        ),
+                    )?;
+                    FINALIZATION_REGISTRY.with(|registry| registry.unregister(&value));
+
+                    // The wrapper doesn't own the native object anymore, its accessors return an error
+                    js_sys::Reflect::delete_property(
+                        value.unchecked_ref(),
+                        &"__rust_reference".into(),
+                    )
+                    .map_err(|err| FruityError::from(err))?;
 
                     ScriptValue::Object(native_object)
                 } else {
@@ -567,16 +763,32 @@ impl TryFromScriptValue for JsIntrospectObject {
     }
 }
 
+/// Get the message of a javascript error, the javascript stack is kept if there is one
+fn js_error_message(err: &JsValue) -> String {
+    if let Some(message) = err.as_string() {
+        return message;
+    }
+
+    ["stack", "message"]
+        .iter()
+        .find_map(|property| {
+            Reflect::get(err, &JsString::from(*property).into())
+                .ok()
+                .and_then(|value| value.as_string())
+        })
+        .unwrap_or("".to_string())
+}
+
 impl From<JsValue> for FruityError {
     fn from(err: JsValue) -> Self {
-        FruityError::Unknown(err.as_string().unwrap_or("".to_string()))
+        FruityError::Unknown(js_error_message(&err))
     }
 }
 
 impl From<JsError> for FruityError {
     fn from(err: JsError) -> Self {
         let err: JsValue = err.into();
-        FruityError::Unknown(err.as_string().unwrap_or("".to_string()))
+        FruityError::Unknown(js_error_message(&err))
     }
 }
 
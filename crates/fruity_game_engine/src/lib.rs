@@ -18,7 +18,7 @@
 pub use error::{FruityError, FruityResult};
 pub use fruity_game_engine_macro::{
     export, export_constructor, export_enum, export_function, export_impl, export_struct,
-    export_trait, external, typescript, typescript_import,
+    export_trait, external, fruity_export, json_schema, typescript, typescript_import,
 };
 pub use lazy_static::lazy_static;
 pub use send_wrapper;
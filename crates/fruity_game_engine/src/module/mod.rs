@@ -1,7 +1,8 @@
+use crate::fruity_export;
 use crate::settings::Settings;
 use crate::world::{RunWorldMiddleware, SetupWorldMiddleware, World};
 use crate::{sync::Arc, FruityResult};
-use fruity_game_engine_macro::{export_impl, export_struct, FruityAny};
+use fruity_game_engine_macro::FruityAny;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::future::Future;
@@ -13,7 +14,7 @@ pub use modules_service::*;
 
 /// A module for the engine
 #[derive(Default, Clone, FruityAny)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct Module {
     /// The name of the module
     pub name: String,
@@ -52,7 +53,7 @@ pub struct Module {
     pub run_world_middleware: Option<RunWorldMiddleware>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Module {}
 
 impl Debug for Module {
@@ -1,4 +1,5 @@
 use super::{AnyResourceReference, ResourceReference};
+use crate::fruity_export;
 use crate::{
     any::FruityAny,
     introspect::{IntrospectFields, IntrospectMethods},
@@ -7,7 +8,7 @@ use crate::{
     sync::{Arc, RwLock},
     FruityError, FruityResult,
 };
-use fruity_game_engine_macro::{export, export_impl, export_struct};
+
 use futures::Future;
 use std::{any::TypeId, collections::HashMap, fmt::Debug, pin::Pin};
 
@@ -19,16 +20,17 @@ pub(crate) struct InnerResourceContainer {
     resources: HashMap<String, AnyResourceReference>,
     identifier_by_type: HashMap<TypeId, String>,
     resource_loaders: HashMap<String, ResourceLoader>,
+    current_module_name: Option<String>,
 }
 
 /// The resource manager
 #[derive(FruityAny, Clone)]
-#[export_struct]
+#[fruity_export]
 pub struct ResourceContainer {
     pub(crate) inner: Arc<RwLock<InnerResourceContainer>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ResourceContainer {
     /// Returns a ResourceContainer
     pub fn new() -> ResourceContainer {
@@ -37,10 +39,21 @@ impl ResourceContainer {
                 resources: HashMap::new(),
                 identifier_by_type: HashMap::new(),
                 resource_loaders: HashMap::new(),
+                current_module_name: None,
             })),
         }
     }
 
+    /// Get the name of the module that is currently set up or loading its resources
+    /// Can be used to know which module registers something, for example a system
+    pub fn get_current_module_name(&self) -> Option<String> {
+        self.inner.read().current_module_name.clone()
+    }
+
+    pub(crate) fn set_current_module_name(&self, name: Option<String>) {
+        self.inner.write().current_module_name = name;
+    }
+
     /// Get a required resource by it's identifier
     /// Panic if the resource is not known
     ///
@@ -76,7 +89,7 @@ impl ResourceContainer {
     /// # Generic Arguments
     /// * `T` - The resource type
     ///
-    #[export(name = "require" typescript = "require<T>(identifier: string): T")]
+    #[fruity_export(name = "require")]
     pub fn require_untyped(&self, identifier: String) -> AnyResourceReference {
         self.get_untyped(identifier).unwrap()
     }
@@ -110,7 +123,10 @@ impl ResourceContainer {
     /// # Arguments
     /// * `identifier` - The resource identifier
     ///
-    #[export(name = "get" typescript = "get<T>(identifier: string): T | null")]
+    /// # Generic Arguments
+    /// * `T` - The resource type
+    ///
+    #[fruity_export(name = "get")]
     pub fn get_untyped(&self, identifier: String) -> Option<AnyResourceReference> {
         let inner = self.inner.read();
 
@@ -135,7 +151,7 @@ impl ResourceContainer {
     /// # Arguments
     /// * `identifier` - The resource identifier
     ///
-    #[export(name = "contains")]
+    #[fruity_export(name = "contains")]
     pub fn contains_from_string(&self, identifier: String) -> bool {
         self.contains(&identifier)
     }
@@ -168,7 +184,7 @@ impl ResourceContainer {
     /// * `identifier` - The resource identifier
     /// * `resource` - The resource object
     ///
-    #[export(name = "add")]
+    #[fruity_export(name = "add")]
     pub fn add_js_resource(&self, identifier: String, resource: JsIntrospectObject) {
         self.add(&identifier, Box::new(resource));
     }
@@ -198,7 +214,7 @@ impl ResourceContainer {
     /// # Arguments
     /// * `identifier` - The resource identifier
     ///
-    #[export(name = "remove")]
+    #[fruity_export(name = "remove")]
     pub fn remove_from_string(&self, identifier: String) -> FruityResult<()> {
         self.remove(&identifier)
     }
@@ -249,7 +265,7 @@ impl ResourceContainer {
     /// # Arguments
     /// * `settings` - The settings of resources
     ///
-    #[export]
+    #[fruity_export]
     pub fn load_resources_settings_async(
         &self,
         settings: Settings,
@@ -1,25 +1,49 @@
 use std::future::Future;
 
 #[cfg(not(target_arch = "wasm32"))]
-use tokio::runtime::Builder;
+use crate::lazy_static;
+
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::spawn_local;
 
-use crate::FruityResult;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::FruityError;
+
+use crate::{
+    sync::{Arc, Mutex},
+    FruityResult,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+lazy_static! {
+    /// The runtime that runs the futures of the engine, it is shared so it is not created again at each call
+    static ref RUNTIME: Runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+}
 
 /// Wait synchronously for the end of a future
+///
+/// It can be called from a task of a multi threaded runtime, like the futures run with [`spawn`], the thread
+/// is given back to the runtime while waiting. It returns an error from a task of a single threaded runtime
+/// as nothing else could drive the future
 pub fn block_on<F, R>(future: F) -> FruityResult<R>
 where
     F: Future<Output = FruityResult<R>> + 'static,
     R: Default,
 {
     #[cfg(not(target_arch = "wasm32"))]
-    let result = Builder::new_current_thread()
-        .enable_all()
-        .build()
-        .unwrap()
-        .block_on(future);
+    let result = match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        Ok(_) => Err(FruityError::GenericFailure(
+            "Cannot wait synchronously for a future from a task of a single threaded runtime"
+                .to_string(),
+        )),
+        Err(_) => RUNTIME.block_on(future),
+    };
 
     #[cfg(target_arch = "wasm32")]
     let result = {
@@ -37,3 +61,67 @@ where
 
     result
 }
+
+/// A future that runs in the background, see [`spawn`]
+pub struct SpawnedFuture<R> {
+    result: Arc<Mutex<Option<R>>>,
+}
+
+impl<R> SpawnedFuture<R> {
+    /// Take the result of the future, returns None while the future is running
+    pub fn take_result(&self) -> Option<R> {
+        self.result.lock().take()
+    }
+}
+
+/// Run a future in the background, it is driven by the engine runtime or by the javascript event loop in wasm
+pub fn spawn<F, R>(future: F) -> SpawnedFuture<R>
+where
+    F: Future<Output = R> + Send + 'static,
+    R: Send + 'static,
+{
+    let result = Arc::new(Mutex::new(None));
+
+    let future_result = result.clone();
+    let future = async move {
+        let value = future.await;
+        future_result.lock().replace(value);
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    RUNTIME.spawn(future);
+
+    #[cfg(target_arch = "wasm32")]
+    spawn_local(future);
+
+    SpawnedFuture { result }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_block_on() {
+        assert_eq!(block_on(async { FruityResult::Ok(1) }).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_block_on_inside_a_runtime_task() {
+        let task = RUNTIME.spawn(async {
+            let value = block_on(async {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                FruityResult::Ok(1)
+            })?;
+
+            block_on(async move { FruityResult::Ok(value + 1) })
+        });
+
+        assert_eq!(RUNTIME.block_on(task).unwrap().unwrap(), 2);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_block_on_inside_a_single_threaded_runtime_is_an_error() {
+        assert!(block_on(async { FruityResult::Ok(1) }).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::fruity_export;
 use crate::{
     any::FruityAny,
     console_log,
@@ -9,9 +10,7 @@ use crate::{
     sync::{Arc, RwLock},
     FruityResult,
 };
-use fruity_game_engine_macro::{
-    export, export_constructor, export_impl, export_struct, typescript,
-};
+use fruity_game_engine_macro::typescript;
 use futures::Future;
 use std::{fmt::Debug, ops::Deref, pin::Pin};
 
@@ -59,16 +58,16 @@ struct InnerWorld {
 
 /// The main container of the ECS
 #[derive(FruityAny, Clone)]
-#[export_struct]
+#[fruity_export]
 pub struct World {
     inner: Arc<RwLock<InnerWorld>>,
     module_service: Arc<RwLock<ModulesService>>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl World {
     /// Returns a World
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(settings: Settings) -> World {
         let resource_container = ResourceContainer::new();
         Self::initialize(resource_container.clone(), &settings);
@@ -104,7 +103,7 @@ impl World {
     }
 
     /// Register a module
-    #[export]
+    #[fruity_export]
     pub fn register_module(&self, module: Module) -> FruityResult<()> {
         self.module_service
             .deref()
@@ -160,11 +159,12 @@ impl World {
     }
 
     /// Load the modules
-    #[export]
+    #[fruity_export]
     pub fn setup_modules_async(&self) -> Pin<Box<dyn Send + Future<Output = FruityResult<()>>>> {
         let world = self.clone();
         Box::pin(async move {
             let settings = world.inner.deref().read().settings.clone();
+            let resource_container = world.get_resource_container();
             let ordered_modules = {
                 let module_service = world.module_service.deref().read();
                 module_service.get_modules_ordered_by_dependencies()?
@@ -172,17 +172,28 @@ impl World {
 
             for module in ordered_modules.into_iter() {
                 console_log(&format!("Setup {}", &module.name));
+                let module_name = module.name.clone();
+                resource_container.set_current_module_name(Some(module_name.clone()));
 
-                if let Some(setup) = module.setup {
-                    setup(world.clone(), settings.clone())?;
-                }
+                let result = async {
+                    if let Some(setup) = module.setup {
+                        setup(world.clone(), settings.clone())?;
+                    }
 
-                if let Some(setup_async) = module.setup_async {
-                    let world = world.clone();
-                    let settings = settings.clone();
+                    if let Some(setup_async) = module.setup_async {
+                        let world = world.clone();
+                        let settings = settings.clone();
 
-                    setup_async(world.clone(), settings.clone()).await?;
+                        setup_async(world.clone(), settings.clone()).await?;
+                    }
+
+                    FruityResult::Ok(())
                 }
+                .await;
+
+                resource_container.set_current_module_name(None);
+                result
+                    .map_err(|err| err.with_context(&format!("setup of module {}", module_name)))?;
             }
 
             Ok(())
@@ -190,11 +201,12 @@ impl World {
     }
 
     /// Load the resources
-    #[export]
+    #[fruity_export]
     pub fn load_resources_async(&self) -> Pin<Box<dyn Send + Future<Output = FruityResult<()>>>> {
         let world = self.clone();
         Box::pin(async move {
             let settings = world.inner.deref().read().settings.clone();
+            let resource_container = world.get_resource_container();
             let ordered_modules = {
                 let module_service = world.module_service.deref().read();
                 module_service.get_modules_ordered_by_dependencies()?
@@ -202,17 +214,29 @@ impl World {
 
             for module in ordered_modules.into_iter() {
                 console_log(&format!("Load Resources {}", &module.name));
+                let module_name = module.name.clone();
+                resource_container.set_current_module_name(Some(module_name.clone()));
 
-                if let Some(load_resources) = module.load_resources {
-                    load_resources(world.clone(), settings.clone())?;
-                }
+                let result = async {
+                    if let Some(load_resources) = module.load_resources {
+                        load_resources(world.clone(), settings.clone())?;
+                    }
+
+                    if let Some(load_resources_async) = module.load_resources_async {
+                        let world = world.clone();
+                        let settings = settings.clone();
 
-                if let Some(load_resources_async) = module.load_resources_async {
-                    let world = world.clone();
-                    let settings = settings.clone();
+                        load_resources_async(world.clone(), settings.clone()).await?;
+                    }
 
-                    load_resources_async(world.clone(), settings.clone()).await?;
+                    FruityResult::Ok(())
                 }
+                .await;
+
+                resource_container.set_current_module_name(None);
+                result.map_err(|err| {
+                    err.with_context(&format!("resources loading of module {}", module_name))
+                })?;
             }
 
             Ok(())
@@ -220,7 +244,7 @@ impl World {
     }
 
     /// Run the world
-    #[export]
+    #[fruity_export]
     pub fn setup(&self) -> FruityResult<()> {
         profile_scope!("setup");
 
@@ -231,7 +255,7 @@ impl World {
     }
 
     /// Run the world
-    #[export]
+    #[fruity_export]
     pub fn run(&self) -> FruityResult<()> {
         profile_scope!("run");
 
@@ -299,7 +323,7 @@ impl World {
     }
 
     /// Get resource container
-    #[export]
+    #[fruity_export]
     pub fn get_resource_container(&self) -> ResourceContainer {
         let this = self.inner.deref().read();
         this.resource_container.clone()
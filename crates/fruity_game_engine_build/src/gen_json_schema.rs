@@ -0,0 +1,894 @@
+use convert_case::{Boundary, Case, Casing};
+use fruity_game_engine_code_parser::{
+    parse_attrs_items, parse_fruity_exports, parse_struct_item, FruityExport,
+    FruityExportAttributeParameters, FruityExportClass, FruityExportClassFieldName,
+};
+use serde_json::{json, Map, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::{fs::File, path::Path};
+use syn::ext::IdentExt;
+
+pub struct GenJsonSchemaArgs {
+    pub input: String,
+    pub output: String,
+}
+
+/// Types that are known by the generator
+struct SchemaContext {
+    /// Serializable structs that are declared in the crate
+    local_structs: Vec<String>,
+    /// Enums that are declared in the crate with their variants
+    local_enums: HashMap<String, Vec<String>>,
+    /// Types imported from other packages, the value is the package name
+    imported_types: HashMap<String, String>,
+    /// Schemas of the imported packages, none if the package has no schema
+    imported_schemas: RefCell<HashMap<String, Option<Value>>>,
+    /// Definitions copied from the imported schemas, named after their package
+    imported_definitions: RefCell<Map<String, Value>>,
+    /// Structs and enums that describe the resource settings with the types they use, they are written inline
+    settings_items: HashMap<String, syn::Item>,
+}
+
+/// The settings schema of a resource loader, declared with #[json_schema(resource = "...", ...)]
+struct ResourceSchema {
+    loader: String,
+    resource_type: String,
+    source: ResourceSchemaSource,
+}
+
+enum ResourceSchemaSource {
+    /// A json schema file, the path is relative to the crate root
+    Path(String),
+    /// A struct of the crate that describes the settings
+    Settings(String),
+}
+
+/// Generate a JSON Schema for all the serializable components and for the resource settings
+///
+/// The schema contains the following definitions:
+/// - the serialized fields of every serializable struct, identified by its class name
+/// - a "{ClassName}Component" definition for every component, it is the format used in scene files
+/// - a "component" definition that accepts any component of the crate
+/// - a "resource:{type}" definition for every resource loader marked with #[json_schema(...)], the settings
+///   are described by a struct of the crate with `settings = "..."` or by a json schema file with `path = "..."`
+/// - a "resource" definition that accepts any resource settings of the crate
+/// - a "{package}.{ClassName}" definition for every type imported from another package, it's copied
+///   from the schema of the installed package so the schema doesn't depend on where the packages are installed
+pub fn gen_json_schema(args: GenJsonSchemaArgs) {
+    let input_path = Path::new(&args.input);
+
+    // This is our tokenized version of Rust file ready to process
+    let input_syntax: syn::File = crate::syn_inline_mod::parse_and_inline_modules(input_path);
+
+    // Nothing to write, this crate doesn't expose any serializable data
+    let schema = match generate_json_schema(input_syntax) {
+        Some(schema) => schema,
+        None => return,
+    };
+
+    // Generate the schema file
+    let mut file = File::create(args.output).unwrap();
+    file.write_all(serde_json::to_string_pretty(&schema).unwrap().as_bytes())
+        .unwrap();
+    file.write_all(b"\n").unwrap();
+}
+
+/// Generate the JSON Schema of a parsed crate, none if it doesn't expose any serializable data
+fn generate_json_schema(input_syntax: syn::File) -> Option<Value> {
+    // Parse the resource settings schemas and the manual component implementations
+    // before the items are consumed
+    let resource_schemas = parse_resource_schemas(&input_syntax.items);
+    let manual_components = parse_manual_components(&input_syntax.items);
+    let settings_items = collect_settings_items(
+        &input_syntax.items,
+        resource_schemas
+            .iter()
+            .filter_map(|resource_schema| match &resource_schema.source {
+                ResourceSchemaSource::Settings(settings) => Some(settings.clone()),
+                ResourceSchemaSource::Path(_) => None,
+            })
+            .collect(),
+    );
+
+    // Parse the input items
+    let exports = parse_fruity_exports(input_syntax.items);
+
+    let classes = exports
+        .iter()
+        .filter_map(|export| match export {
+            FruityExport::Class(class) if is_serializable(class) => Some(class),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if classes.is_empty() && resource_schemas.is_empty() {
+        return None;
+    }
+
+    let context = SchemaContext {
+        local_structs: classes.iter().map(|class| class.name.to_string()).collect(),
+        local_enums: exports
+            .iter()
+            .filter_map(|export| match export {
+                FruityExport::Enum(enumeration) => Some((
+                    enumeration.name.to_string(),
+                    enumeration
+                        .variants
+                        .iter()
+                        .map(|variant| variant.to_string().to_case(Case::Camel))
+                        .collect(),
+                )),
+                _ => None,
+            })
+            .collect(),
+        imported_types: exports
+            .iter()
+            .flat_map(|export| match export {
+                FruityExport::ExternImports(extern_import) => extern_import
+                    .imported_items
+                    .iter()
+                    .map(|item| (item.to_string(), extern_import.package.clone()))
+                    .collect::<Vec<_>>(),
+                _ => vec![],
+            })
+            .collect(),
+        imported_schemas: RefCell::new(HashMap::new()),
+        imported_definitions: RefCell::new(Map::new()),
+        settings_items,
+    };
+
+    let mut definitions = Map::new();
+
+    // Generate the serializable structs definitions
+    classes.iter().for_each(|class| {
+        definitions.insert(
+            class.name.to_string(),
+            generate_class_schema(class, &context),
+        );
+    });
+
+    // Generate the components definitions, it's the format of a component in a scene file
+    let components = classes
+        .iter()
+        .filter(|class| {
+            has_derive(class, "Component") || manual_components.contains(&class.name.to_string())
+        })
+        .map(|class| {
+            let class_name = class.name.to_string();
+            let component_name = format!("{}Component", class_name);
+
+            definitions.insert(
+                component_name.clone(),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "className": { "const": class_name },
+                        "fields": { "$ref": format!("#/definitions/{}", class_name) },
+                    },
+                    "required": ["className"],
+                }),
+            );
+
+            json!({ "$ref": format!("#/definitions/{}", &component_name) })
+        })
+        .collect::<Vec<_>>();
+
+    if !components.is_empty() {
+        definitions.insert("component".to_string(), json!({ "anyOf": components }));
+    }
+
+    // Generate the resource settings definitions, it's the format of a resource in a settings file
+    let resources = resource_schemas
+        .into_iter()
+        .filter_map(|resource_schema| {
+            let schema = read_resource_schema(&resource_schema, &context)?;
+            let resource_type = resource_schema.resource_type;
+            let resource_name = format!("resource:{}", resource_type);
+
+            definitions.insert(
+                resource_name.clone(),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "type": { "const": resource_type },
+                    },
+                    "required": ["name", "type"],
+                    "allOf": [schema],
+                }),
+            );
+
+            Some(json!({ "$ref": format!("#/definitions/{}", &resource_name) }))
+        })
+        .collect::<Vec<_>>();
+
+    if !resources.is_empty() {
+        definitions.insert("resource".to_string(), json!({ "anyOf": resources }));
+    }
+
+    definitions.extend(context.imported_definitions.into_inner());
+
+    Some(json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": definitions,
+    }))
+}
+
+/// A struct is serializable if it derives Component or both Serialize and Deserialize
+fn is_serializable(class: &FruityExportClass) -> bool {
+    has_derive(class, "Component")
+        || (has_derive(class, "Serialize") && has_derive(class, "Deserialize"))
+}
+
+fn has_derive(class: &FruityExportClass, derive: &str) -> bool {
+    class
+        .attrs
+        .iter()
+        .any(|attr| attr.name == "derive" && attr.params.contains_key(derive))
+}
+
+/// Parse the resource settings schemas declared with #[json_schema(resource = "...", settings = "...")]
+/// or #[json_schema(resource = "...", path = "...")] on the resource loaders
+fn parse_resource_schemas(items: &[syn::Item]) -> Vec<ResourceSchema> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            syn::Item::Fn(item) => parse_attrs_items(&item.attrs)
+                .attrs
+                .into_iter()
+                .filter(|attr| attr.name == "json_schema")
+                .filter_map(|attr| {
+                    let loader = item.sig.ident.to_string();
+                    let resource_type = parse_string_param(&loader, "resource", &attr.params)?;
+                    let source = if attr.params.contains_key("settings") {
+                        ResourceSchemaSource::Settings(parse_string_param(
+                            &loader,
+                            "settings",
+                            &attr.params,
+                        )?)
+                    } else {
+                        ResourceSchemaSource::Path(parse_string_param(
+                            &loader,
+                            "path",
+                            &attr.params,
+                        )?)
+                    };
+
+                    Some(ResourceSchema {
+                        loader,
+                        resource_type,
+                        source,
+                    })
+                })
+                .collect::<Vec<_>>(),
+            syn::Item::Mod(item) => match &item.content {
+                Some(content) => parse_resource_schemas(&content.1),
+                None => vec![],
+            },
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Get the schema of the settings of a resource loader, none if it can't be read
+fn read_resource_schema(
+    resource_schema: &ResourceSchema,
+    context: &SchemaContext,
+) -> Option<Value> {
+    let loader = &resource_schema.loader;
+
+    match &resource_schema.source {
+        ResourceSchemaSource::Settings(settings) => match context.settings_items.get(settings) {
+            Some(syn::Item::Struct(item)) => Some(generate_settings_schema(
+                &parse_struct_item(item.clone()),
+                context,
+            )),
+            _ => {
+                warning(&format!(
+                    "The settings struct {} of {} is not declared in the crate, its schema is skipped",
+                    settings, loader
+                ));
+                None
+            }
+        },
+        ResourceSchemaSource::Path(path) => {
+            let content = std::fs::read_to_string(path)
+                .map_err(|error| {
+                    warning(&format!(
+                        "Failed to read the json schema {} of {}: {}",
+                        path, loader, error
+                    ))
+                })
+                .ok()?;
+
+            serde_json::from_str::<Value>(&content)
+                .map_err(|error| {
+                    warning(&format!(
+                        "Failed to parse the json schema {} of {}: {}",
+                        path, loader, error
+                    ))
+                })
+                .ok()
+        }
+    }
+}
+
+/// Collect the structs and enums that describe the resource settings and the ones their fields use
+fn collect_settings_items(items: &[syn::Item], roots: Vec<String>) -> HashMap<String, syn::Item> {
+    let mut declared_items = HashMap::new();
+    collect_declared_items(items, &mut declared_items);
+
+    let mut settings_items = HashMap::new();
+    let mut pending = roots;
+    while let Some(name) = pending.pop() {
+        if settings_items.contains_key(&name) {
+            continue;
+        }
+
+        if let Some(item) = declared_items.remove(&name) {
+            if let syn::Item::Struct(item) = &item {
+                item.fields
+                    .iter()
+                    .for_each(|field| collect_type_idents(&field.ty, &mut pending));
+            }
+
+            settings_items.insert(name, item);
+        }
+    }
+
+    settings_items
+}
+
+/// Collect the structs and enums declared in the crate by name
+fn collect_declared_items(items: &[syn::Item], declared_items: &mut HashMap<String, syn::Item>) {
+    items.iter().for_each(|item| match item {
+        syn::Item::Struct(struct_item) => {
+            declared_items.insert(struct_item.ident.to_string(), item.clone());
+        }
+        syn::Item::Enum(enum_item) => {
+            declared_items.insert(enum_item.ident.to_string(), item.clone());
+        }
+        syn::Item::Mod(item) => {
+            if let Some(content) = &item.content {
+                collect_declared_items(&content.1, declared_items);
+            }
+        }
+        _ => {}
+    });
+}
+
+/// Collect the names of the types a rust type is made of
+fn collect_type_idents(ty: &syn::Type, idents: &mut Vec<String>) {
+    match ty {
+        syn::Type::Array(arr) => collect_type_idents(&arr.elem, idents),
+        syn::Type::Paren(paren) => collect_type_idents(&paren.elem, idents),
+        syn::Type::Reference(reference) => collect_type_idents(&reference.elem, idents),
+        syn::Type::Slice(slice) => collect_type_idents(&slice.elem, idents),
+        syn::Type::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .for_each(|ty| collect_type_idents(ty, idents)),
+        syn::Type::Path(path) => {
+            let last_segment = path.path.segments.last().unwrap();
+            idents.push(last_segment.ident.to_string());
+
+            if let syn::PathArguments::AngleBracketed(ab) = &last_segment.arguments {
+                ab.args.iter().for_each(|arg| {
+                    if let syn::GenericArgument::Type(ty) = arg {
+                        collect_type_idents(ty, idents);
+                    }
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse the structs that implement Component without deriving it
+fn parse_manual_components(items: &[syn::Item]) -> Vec<String> {
+    items
+        .iter()
+        .flat_map(|item| match item {
+            syn::Item::Impl(item) => match (&item.trait_, &*item.self_ty) {
+                (Some((_, trait_path, _)), syn::Type::Path(self_ty))
+                    if trait_path.segments.last().unwrap().ident == "Component" =>
+                {
+                    vec![self_ty.path.segments.last().unwrap().ident.to_string()]
+                }
+                _ => vec![],
+            },
+            syn::Item::Mod(item) => match &item.content {
+                Some(content) => parse_manual_components(&content.1),
+                None => vec![],
+            },
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Get a string parameter of a #[json_schema(...)] attribute, the resource loader is skipped with
+/// a warning if it's missing
+fn parse_string_param(
+    loader: &str,
+    name: &str,
+    params: &FruityExportAttributeParameters,
+) -> Option<String> {
+    let value = params
+        .get(name)
+        .ok_or_else(|| {
+            warning(&format!(
+                "The json_schema attribute of {} requires a {} parameter, its schema is skipped",
+                loader, name
+            ))
+        })
+        .ok()?;
+
+    syn::parse2::<syn::LitStr>(value.clone())
+        .map(|value| value.value())
+        .map_err(|_| {
+            warning(&format!(
+                "The {} parameter of the json_schema attribute of {} should be a string, its schema is skipped",
+                name, loader
+            ))
+        })
+        .ok()
+}
+
+/// Generate the schema of the serialized fields of a struct
+/// The fields are written in camel case as in the scene files
+fn generate_class_schema(class: &FruityExportClass, context: &SchemaContext) -> Value {
+    let mut properties = Map::new();
+
+    class
+        .fields
+        .iter()
+        .filter(|field| !field.attrs.contains(&"serialize_skip".to_string()))
+        .for_each(|field| {
+            let name = field_name(&field.name);
+            let mut field_schema = rust_type_to_json_schema(&field.ty, context);
+            if let (Value::Object(field_schema), Some(description)) =
+                (&mut field_schema, generate_description(&field.docs))
+            {
+                field_schema.insert("description".to_string(), Value::String(description));
+            }
+
+            properties.insert(name, field_schema);
+        });
+
+    // Unknown fields are ignored by the deserialization so they are allowed
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+    });
+
+    if let (Value::Object(schema), Some(description)) =
+        (&mut schema, generate_description(&class.docs))
+    {
+        schema.insert("description".to_string(), Value::String(description));
+    }
+
+    schema
+}
+
+/// Generate the schema of a struct that describes resource settings
+/// The fields that are not optional are required, the optional ones can be omitted but are not nullable
+fn generate_settings_schema(class: &FruityExportClass, context: &SchemaContext) -> Value {
+    let required = class
+        .fields
+        .iter()
+        .filter(|field| !field.attrs.contains(&"serialize_skip".to_string()))
+        .filter(|field| option_inner_type(&field.ty).is_none())
+        .map(|field| field_name(&field.name))
+        .collect::<Vec<_>>();
+
+    let mut class = class.clone();
+    class.fields.iter_mut().for_each(|field| {
+        if let Some(ty) = option_inner_type(&field.ty) {
+            field.ty = ty.clone();
+        }
+    });
+
+    let mut schema = generate_class_schema(&class, context);
+
+    if let (Value::Object(schema), false) = (&mut schema, required.is_empty()) {
+        schema.insert("required".to_string(), json!(required));
+    }
+
+    schema
+}
+
+/// The fields are written in camel case, a raw identifier such as r#type is written without its prefix
+fn field_name(name: &FruityExportClassFieldName) -> String {
+    match name {
+        FruityExportClassFieldName::Named(name) => name.unraw().to_string().to_case(Case::Camel),
+        FruityExportClassFieldName::Unnamed(name) => name.to_string(),
+    }
+}
+
+/// Get T if the type is Option<T>
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let last_segment = match ty {
+        syn::Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+
+    match &last_segment.arguments {
+        syn::PathArguments::AngleBracketed(ab) if last_segment.ident == "Option" => {
+            ab.args.iter().find_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The description is the first paragraph of the doc comments
+fn generate_description(docs: &[String]) -> Option<String> {
+    let description = docs
+        .iter()
+        .take_while(|line| !line.trim().is_empty())
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if description.is_empty() {
+        None
+    } else {
+        Some(description)
+    }
+}
+
+/// Convert a rust type to the schema of its serialized value
+/// It should be kept in sync with the Serialize implementations of fruity_ecs
+fn rust_type_to_json_schema(ty: &syn::Type, context: &SchemaContext) -> Value {
+    match ty {
+        syn::Type::Array(arr) => {
+            let items = rust_type_to_json_schema(&arr.elem, context);
+
+            match &arr.len {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }) => {
+                    let len = len.base10_parse::<u64>().unwrap();
+                    json!({ "type": "array", "items": items, "minItems": len, "maxItems": len })
+                }
+                _ => json!({ "type": "array", "items": items }),
+            }
+        }
+        syn::Type::Paren(paren) => rust_type_to_json_schema(&paren.elem, context),
+        syn::Type::Reference(reference) => rust_type_to_json_schema(&reference.elem, context),
+        syn::Type::Slice(slice) => {
+            json!({ "type": "array", "items": rust_type_to_json_schema(&slice.elem, context) })
+        }
+        syn::Type::Tuple(tuple) => {
+            if tuple.elems.is_empty() {
+                json!({ "type": "null" })
+            } else {
+                json!({
+                    "type": "array",
+                    "items": tuple
+                        .elems
+                        .iter()
+                        .map(|ty| rust_type_to_json_schema(ty, context))
+                        .collect::<Vec<_>>(),
+                    "minItems": tuple.elems.len(),
+                    "maxItems": tuple.elems.len(),
+                })
+            }
+        }
+        syn::Type::Path(path) => {
+            let last_segment = path.path.segments.last().unwrap();
+            let generics = match &last_segment.arguments {
+                syn::PathArguments::AngleBracketed(ab) => ab
+                    .args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => Some(ty),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                _ => vec![],
+            };
+
+            path_type_to_json_schema(&last_segment.ident.to_string(), &generics, context)
+        }
+        _ => json!({}),
+    }
+}
+
+fn path_type_to_json_schema(
+    ident: &str,
+    generics: &[&syn::Type],
+    context: &SchemaContext,
+) -> Value {
+    let first_generic = || {
+        generics
+            .first()
+            .map(|ty| rust_type_to_json_schema(ty, context))
+            .unwrap_or(json!({}))
+    };
+
+    match ident {
+        "bool" => json!({ "type": "boolean" }),
+        "i8" | "i16" | "i32" | "i64" | "isize" => json!({ "type": "integer" }),
+        "u8" => json!({ "type": "integer", "minimum": 0, "maximum": 255 }),
+        "u16" | "u32" | "u64" | "usize" => json!({ "type": "integer", "minimum": 0 }),
+        "f32" | "f64" => json!({ "type": "number" }),
+        "char" | "str" | "String" => json!({ "type": "string" }),
+        "Box" | "Arc" | "Rc" | "SignalProperty" => first_generic(),
+        "Option" => json!({ "anyOf": [first_generic(), { "type": "null" }] }),
+        "Vec" | "VecDeque" | "HashSet" => json!({ "type": "array", "items": first_generic() }),
+        "HashMap" => json!({
+            "type": "object",
+            "additionalProperties": generics
+                .get(1)
+                .map(|ty| rust_type_to_json_schema(ty, context))
+                .unwrap_or(json!({})),
+        }),
+        "Range" => json!({
+            "type": "array",
+            "items": first_generic(),
+            "minItems": 2,
+            "maxItems": 2,
+        }),
+        "ResourceReference" | "AnyResourceReference" => json!({
+            "description": "Identifier of a resource",
+            "type": "string",
+        }),
+        "EntityReference" | "EntityId" => json!({
+            "description": "Local id of an entity",
+            "type": "integer",
+        }),
+        _ => {
+            if context.local_structs.contains(&ident.to_string()) {
+                json!({ "$ref": format!("#/definitions/{}", ident) })
+            } else if let Some(variants) = context.local_enums.get(ident) {
+                json!({ "enum": variants })
+            } else if let Some(item) = context.settings_items.get(ident) {
+                generate_settings_item_schema(item, context)
+            } else if let Some(package) = context.imported_types.get(ident) {
+                import_definition(package, ident, context)
+            } else {
+                // Unknown types such as Settings or ScriptValue accept any value
+                json!({})
+            }
+        }
+    }
+}
+
+/// Generate the schema of a type used by resource settings, it's written inline
+/// The enum variants are written in snake case as the resource loaders read them, so Vec2 is "vec2"
+fn generate_settings_item_schema(item: &syn::Item, context: &SchemaContext) -> Value {
+    match item {
+        syn::Item::Struct(item) => {
+            generate_settings_schema(&parse_struct_item(item.clone()), context)
+        }
+        syn::Item::Enum(item) => json!({
+            "enum": item
+                .variants
+                .iter()
+                .map(|variant| {
+                    variant
+                        .ident
+                        .to_string()
+                        .from_case(Case::Pascal)
+                        .without_boundaries(&Boundary::digits())
+                        .to_case(Case::Snake)
+                })
+                .collect::<Vec<_>>(),
+        }),
+        _ => json!({}),
+    }
+}
+
+/// Get a reference to a type imported from another package, the definition is copied from the schema
+/// of the installed package with the definitions it refers to
+///
+/// The type accepts any value if the package doesn't describe it, like the unknown types
+fn import_definition(package: &str, ident: &str, context: &SchemaContext) -> Value {
+    // A definition that is already imported into the other package keeps its name
+    let name = if ident.contains('.') {
+        ident.to_string()
+    } else {
+        format!("{}.{}", package, ident)
+    };
+    let reference = json!({ "$ref": format!("#/definitions/{}", &name) });
+
+    if context.imported_definitions.borrow().contains_key(&name) {
+        return reference;
+    }
+
+    let definition = context
+        .imported_schemas
+        .borrow_mut()
+        .entry(package.to_string())
+        .or_insert_with(|| read_imported_schema(package))
+        .as_ref()
+        .and_then(|schema| schema.get("definitions")?.get(ident).cloned());
+
+    match definition {
+        Some(mut definition) => {
+            // The name is reserved before following the references so a recursive type doesn't loop
+            context
+                .imported_definitions
+                .borrow_mut()
+                .insert(name.clone(), json!({}));
+
+            import_references(package, &mut definition, context);
+            context
+                .imported_definitions
+                .borrow_mut()
+                .insert(name, definition);
+
+            reference
+        }
+        None => json!({}),
+    }
+}
+
+/// Import the definitions referred by a definition copied from the schema of another package
+fn import_references(package: &str, value: &mut Value, context: &SchemaContext) {
+    match value {
+        Value::Object(object) => {
+            let ident = object
+                .get("$ref")
+                .and_then(|reference| reference.as_str())
+                .and_then(|reference| reference.strip_prefix("#/definitions/"))
+                .map(|ident| ident.to_string());
+
+            match ident {
+                Some(ident) => *value = import_definition(package, &ident, context),
+                None => object
+                    .values_mut()
+                    .for_each(|value| import_references(package, value, context)),
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| import_references(package, value, context)),
+        _ => {}
+    }
+}
+
+/// Read the schema of an installed package, none if the package doesn't expose any serializable data
+fn read_imported_schema(package: &str) -> Option<Value> {
+    let path = format!("node_modules/{}/schema.json", package);
+    let content = std::fs::read_to_string(&path).ok()?;
+
+    serde_json::from_str::<Value>(&content)
+        .map_err(|error| {
+            warning(&format!(
+                "Failed to parse the json schema {}: {}",
+                path, error
+            ))
+        })
+        .ok()
+}
+
+/// Report a problem to cargo without failing the build
+fn warning(message: &str) {
+    println!("cargo:warning={}", message);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_component_schema() {
+        let schema = generate_json_schema(syn::parse_quote! {
+            /// A position in the world
+            #[derive(Debug, Clone, Component)]
+            #[fruity_export]
+            pub struct Position {
+                /// Horizontal position
+                pub x_value: f32,
+                pub layer: Option<u32>,
+                pub tags: Vec<String>,
+                #[serialize_skip]
+                pub cache: f32,
+            }
+        })
+        .unwrap();
+
+        assert_eq!(
+            schema["definitions"]["Position"],
+            json!({
+                "description": "A position in the world",
+                "type": "object",
+                "properties": {
+                    "xValue": { "type": "number", "description": "Horizontal position" },
+                    "layer": { "anyOf": [{ "type": "integer", "minimum": 0 }, { "type": "null" }] },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                },
+            })
+        );
+        assert_eq!(
+            schema["definitions"]["PositionComponent"]["properties"]["className"],
+            json!({ "const": "Position" })
+        );
+        assert_eq!(
+            schema["definitions"]["component"],
+            json!({ "anyOf": [{ "$ref": "#/definitions/PositionComponent" }] })
+        );
+    }
+
+    #[test]
+    fn test_resource_settings_schema() {
+        let schema = generate_json_schema(syn::parse_quote! {
+            /// A resource loaded from a file
+            pub struct FileSchema {
+                /// Path of the file
+                pub path: String,
+                pub attributes: Option<Vec<FileAttributeSchema>>,
+            }
+
+            pub struct FileAttributeSchema {
+                pub r#type: FileAttributeTypeSchema,
+                pub location_0: u32,
+            }
+
+            pub enum FileAttributeTypeSchema {
+                Vec2,
+                ViewportSize,
+            }
+
+            #[json_schema(resource = "file", settings = "FileSchema")]
+            pub fn load_file() {}
+        })
+        .unwrap();
+
+        assert_eq!(
+            schema["definitions"]["resource:file"]["allOf"][0],
+            json!({
+                "description": "A resource loaded from a file",
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path of the file" },
+                    "attributes": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "type": { "enum": ["vec2", "viewport_size"] },
+                                "location0": { "type": "integer", "minimum": 0 },
+                            },
+                            "required": ["type", "location0"],
+                        },
+                    },
+                },
+                "required": ["path"],
+            })
+        );
+        assert_eq!(
+            schema["definitions"]["resource"],
+            json!({ "anyOf": [{ "$ref": "#/definitions/resource:file" }] })
+        );
+    }
+
+    #[test]
+    fn test_unknown_settings_struct_is_skipped() {
+        let schema = generate_json_schema(syn::parse_quote! {
+            #[json_schema(resource = "file", settings = "MissingSchema")]
+            pub fn load_file() {}
+        })
+        .unwrap();
+
+        assert!(schema["definitions"].get("resource:file").is_none());
+        assert!(schema["definitions"].get("resource").is_none());
+    }
+
+    #[test]
+    fn test_no_serializable_data() {
+        assert!(generate_json_schema(syn::parse_quote! {
+            pub struct Private {
+                value: f32,
+            }
+        })
+        .is_none());
+    }
+}
@@ -1,10 +1,40 @@
 use convert_case::{Case, Casing};
 use fruity_game_engine_code_parser::{
     parse_fruity_exports, FruityExport, FruityExportArg, FruityExportClassField,
-    FruityExportClassFieldName,
+    FruityExportClassFieldName, FruityExportGeneric,
 };
 use std::io::Write;
 use std::{fs::File, path::Path};
+use syn::visit_mut::VisitMut;
+
+/// Traits that only have a meaning in rust, they are ignored when used as generic bounds
+const RUST_ONLY_TRAITS: &[&str] = &[
+    "Send",
+    "Sync",
+    "Sized",
+    "Clone",
+    "Copy",
+    "Debug",
+    "Default",
+    "Any",
+    "FruityAny",
+    "IntrospectFields",
+    "IntrospectMethods",
+    "TryFromScriptValue",
+    "TryIntoScriptValue",
+    "From",
+    "Into",
+    "TryFrom",
+    "TryInto",
+];
+
+/// Type-erased values, if a function declares a script only generic, those are typed with it
+const TYPE_ERASED_IDENTS: &[&str] = &[
+    "AnyResourceReference",
+    "AnyComponentReference",
+    "ScriptValue",
+    "ScriptObject",
+];
 
 pub struct GenTsArgs {
     pub input: String,
@@ -15,19 +45,20 @@ pub fn gen_ts(args: GenTsArgs) {
     let input_path = Path::new(&args.input);
 
     // This is our tokenized version of Rust file ready to process
-    let input_syntax: syn::File = crate::syn_inline_mod::parse_and_inline_modules(&input_path);
+    let input_syntax: syn::File = crate::syn_inline_mod::parse_and_inline_modules(input_path);
 
     // Parse the input items
     let exports = parse_fruity_exports(input_syntax.items);
 
     // Generate the ts file
     let mut file = File::create(args.output).unwrap();
-    exports
-        .into_iter()
-        .for_each(|export| write_fruity_export(export, &mut file));
+    exports.into_iter().for_each(|export| {
+        file.write_all(generate_fruity_export_str(export).as_bytes())
+            .unwrap()
+    });
 }
 
-fn write_fruity_export(export: FruityExport, file: &mut File) {
+fn generate_fruity_export_str(export: FruityExport) -> String {
     let mut exports = Vec::<String>::new();
 
     // Generate exports
@@ -37,15 +68,17 @@ fn write_fruity_export(export: FruityExport, file: &mut File) {
             extern_import
                 .imported_items
                 .iter()
-                .for_each(|item| exports.push(format!("  {},\n", &item.to_string())));
-            exports.push(format!("}} from \"{}\"\n\n", &extern_import.package));
+                .for_each(|item| exports.push(format!("  {},\n", item)));
+            exports.push(format!("}} from \"{}\"\n\n", extern_import.package));
         }
         FruityExport::Raw(raw) => {
-            exports.push(format!("export {}\n\n", &raw));
+            exports.push(format!("export {}\n\n", raw));
         }
         FruityExport::Enum(enumeration) => {
+            exports.push(generate_docs_str(&enumeration.docs, ""));
+
             if let Some(typescript_overwrite) = enumeration.typescript_overwrite {
-                exports.push(format!("export {}\n", &typescript_overwrite));
+                exports.push(format!("export {}\n", typescript_overwrite));
             } else {
                 let name = enumeration
                     .name_overwrite
@@ -56,17 +89,19 @@ fn write_fruity_export(export: FruityExport, file: &mut File) {
                 let variants_str = enumeration
                     .variants
                     .into_iter()
-                    .map(|variant| format!("\"{}\"", &variant.to_string().to_case(Case::Camel),))
+                    .map(|variant| format!("\"{}\"", variant.to_string().to_case(Case::Camel),))
                     .filter(|ty| ty.as_str() != "")
                     .collect::<Vec<_>>()
                     .join(" | ");
 
-                exports.push(format!("export type {} = {}\n", &name, &variants_str));
+                exports.push(format!("export type {} = {}\n\n", name, variants_str));
             }
         }
         FruityExport::Fn(function) => {
+            exports.push(generate_docs_str(&function.docs, ""));
+
             if let Some(typescript_overwrite) = function.typescript_overwrite {
-                exports.push(format!("export {}\n", &typescript_overwrite));
+                exports.push(format!("export {}\n", typescript_overwrite));
             } else {
                 let name = function
                     .name_overwrite
@@ -75,24 +110,28 @@ fn write_fruity_export(export: FruityExport, file: &mut File) {
                     .to_string()
                     .to_case(Case::Camel);
 
+                let generics_str = generate_generics_str(&function.generics, "");
                 let args_str = generate_args_str(&function.args, "");
 
                 let return_str = match function.return_ty {
                     syn::ReturnType::Default => "".to_string(),
                     syn::ReturnType::Type(_, ty) => {
+                        let ty = apply_script_generics(&ty, &function.generics);
                         format!(": {}", rust_type_to_ts_type(&ty, false, ""))
                     }
                 };
 
                 exports.push(format!(
-                    "export function {}({}){}\n",
-                    &name, &args_str, &return_str
+                    "export function {}{}({}){}\n",
+                    name, generics_str, args_str, return_str
                 ));
             }
         }
         FruityExport::Class(class) => {
+            exports.push(generate_docs_str(&class.docs, ""));
+
             if let Some(typescript_overwrite) = class.typescript_overwrite {
-                exports.push(format!("export {}\n", &typescript_overwrite));
+                exports.push(format!("export {}\n", typescript_overwrite));
             } else {
                 let class_name = class
                     .name_overwrite
@@ -106,7 +145,12 @@ fn write_fruity_export(export: FruityExport, file: &mut File) {
                     "class"
                 };
 
-                exports.push(format!("export {} {} {{\n", &export_type, &class_name));
+                let generics_str = generate_generics_str(&class.generics, &class_name);
+
+                exports.push(format!(
+                    "export {} {}{} {{\n",
+                    export_type, class_name, generics_str
+                ));
 
                 let mut member_exports = Vec::<String>::new();
 
@@ -116,19 +160,23 @@ fn write_fruity_export(export: FruityExport, file: &mut File) {
 
                 // Generate constructor exports
                 if let Some(constructor) = class.constructor {
+                    let docs_str = generate_docs_str(&constructor.docs, "  ");
+
                     if let Some(typescript_overwrite) = constructor.typescript_overwrite {
-                        member_exports.push(typescript_overwrite);
+                        member_exports.push(format!("{}  {}\n", docs_str, typescript_overwrite));
                     } else {
                         let args_str = generate_args_str(&constructor.args, &class_name);
 
-                        member_exports.push(format!("constructor({})", args_str));
+                        member_exports.push(format!("{}  constructor({})\n", docs_str, args_str));
                     }
                 }
 
                 // Generate method exports
                 class.methods.into_iter().for_each(|method| {
+                    let docs_str = generate_docs_str(&method.docs, "  ");
+
                     if let Some(typescript_overwrite) = method.typescript_overwrite {
-                        member_exports.push(typescript_overwrite);
+                        member_exports.push(format!("{}  {}\n", docs_str, typescript_overwrite));
                     } else {
                         let name = method
                             .name_overwrite
@@ -137,39 +185,38 @@ fn write_fruity_export(export: FruityExport, file: &mut File) {
                             .to_string()
                             .to_case(Case::Camel);
 
+                        let generics_str = generate_generics_str(&method.generics, &class_name);
                         let args_str = generate_args_str(&method.args, &class_name);
 
                         let return_str = match method.return_ty {
                             syn::ReturnType::Default => "".to_string(),
                             syn::ReturnType::Type(_, ty) => {
+                                let ty = apply_script_generics(&ty, &method.generics);
                                 format!(": {}", rust_type_to_ts_type(&ty, false, &class_name))
                             }
                         };
 
-                        member_exports.push(format!("{}({}){}", name, args_str, return_str));
+                        member_exports.push(format!(
+                            "{}  {}{}({}){}\n",
+                            docs_str, name, generics_str, args_str, return_str
+                        ));
                     }
                 });
 
                 // Write member exports
-                let member_exports_string = member_exports
-                    .into_iter()
-                    .map(|export| format!("  {}\n", &export))
-                    .collect::<Vec<_>>()
-                    .join("");
-                exports.push(member_exports_string);
+                exports.push(member_exports.join(""));
                 exports.push("}\n\n".to_string());
             }
         }
     }
 
-    // Write all exports
-    file.write_all(exports.join("").as_bytes()).unwrap();
+    exports.join("")
 }
 
-fn generate_args_str(args: &Vec<FruityExportArg>, self_ident: &str) -> String {
+fn generate_args_str(args: &[FruityExportArg], self_ident: &str) -> String {
     let mut has_next_optional = true;
 
-    let mut reversed_args = args.clone();
+    let mut reversed_args = args.to_vec();
     reversed_args.reverse();
 
     let reversed_args = reversed_args
@@ -180,14 +227,14 @@ fn generate_args_str(args: &Vec<FruityExportArg>, self_ident: &str) -> String {
             let result = if is_optional && has_next_optional {
                 format!(
                     "{}?: {}",
-                    &arg.name.to_string().to_case(Case::Camel),
+                    arg.name.to_string().to_case(Case::Camel),
                     rust_type_to_ts_type(&arg.ty, true, self_ident)
                 )
             } else {
                 has_next_optional = false;
                 format!(
                     "{}: {}",
-                    &arg.name.to_string().to_case(Case::Camel),
+                    arg.name.to_string().to_case(Case::Camel),
                     rust_type_to_ts_type(&arg.ty, true, self_ident)
                 )
             };
@@ -202,7 +249,7 @@ fn generate_args_str(args: &Vec<FruityExportArg>, self_ident: &str) -> String {
     args.join(", ")
 }
 
-fn generate_fields_str(fields: &Vec<FruityExportClassField>, self_ident: &str) -> String {
+fn generate_fields_str(fields: &[FruityExportClassField], self_ident: &str) -> String {
     fields
         .iter()
         .filter(|field| field.public)
@@ -210,17 +257,17 @@ fn generate_fields_str(fields: &Vec<FruityExportClassField>, self_ident: &str) -
             let is_optional = is_rust_type_optional(&field.ty);
             let field_type = rust_type_to_ts_type(&field.ty, true, self_ident);
 
-            if is_optional {
+            let field_str = if is_optional {
                 match &field.name {
                     FruityExportClassFieldName::Named(name) => {
                         format!(
                             "  {}?: {}",
-                            &name.to_string().to_case(Case::Camel),
+                            name.to_string().to_case(Case::Camel),
                             field_type
                         )
                     }
                     FruityExportClassFieldName::Unnamed(name) => {
-                        format!("  {}?: {}", &name.to_string(), field_type)
+                        format!("  {}?: {}", name, field_type)
                     }
                 }
             } else {
@@ -228,21 +275,208 @@ fn generate_fields_str(fields: &Vec<FruityExportClassField>, self_ident: &str) -
                     FruityExportClassFieldName::Named(name) => {
                         format!(
                             "  {}: {}",
-                            &name.to_string().to_case(Case::Camel),
+                            name.to_string().to_case(Case::Camel),
                             field_type
                         )
                     }
                     FruityExportClassFieldName::Unnamed(name) => {
-                        format!("  {}: {}", &name.to_string(), field_type)
+                        format!("  {}: {}", name, field_type)
                     }
                 }
-            }
+            };
+
+            format!("{}{}", generate_docs_str(&field.docs, "  "), field_str)
         })
         .collect::<Vec<_>>()
         .join("\n")
         + "\n"
 }
 
+/// Generate a TSDoc comment from rust doc comments
+/// The "# Arguments" and "# Generic Arguments" sections are converted into @param and @typeParam tags
+/// The fenced rust code is removed, the typescript and javascript code is converted into @example tags
+/// so the "# Examples" section titles are removed too
+fn generate_docs_str(docs: &[String], indent: &str) -> String {
+    let mut current_tag: Option<&str> = None;
+    let mut current_code_block: Option<bool> = None;
+    let mut lines = docs
+        .iter()
+        .filter_map(|line| {
+            let trimmed_line = line.trim();
+
+            // Detect the fenced code, the boolean is true if the code is kept
+            if let Some(language) = trimmed_line.strip_prefix("```") {
+                return match current_code_block.take() {
+                    Some(is_kept) => is_kept.then(|| line.clone()),
+                    None => {
+                        let is_kept =
+                            matches!(language.trim(), "ts" | "typescript" | "js" | "javascript");
+                        current_code_block = Some(is_kept);
+                        is_kept.then(|| format!("@example\n{}", line))
+                    }
+                };
+            }
+
+            if let Some(is_kept) = current_code_block {
+                return is_kept.then(|| line.clone());
+            }
+
+            // Detect the sections that are converted into tags
+            if let Some(section) = trimmed_line.strip_prefix("# ") {
+                // The examples are written with @example tags
+                if matches!(section, "Example" | "Examples") {
+                    current_tag = None;
+                    return None;
+                }
+
+                current_tag = match section {
+                    "Arguments" => Some("@param"),
+                    "Generic Arguments" => Some("@typeParam"),
+                    _ => None,
+                };
+
+                return match current_tag {
+                    Some(_) => None,
+                    None => Some(line.clone()),
+                };
+            }
+
+            match current_tag {
+                Some(tag) => {
+                    let item = trimmed_line.strip_prefix("* `")?;
+                    let (name, description) = item.split_once('`')?;
+                    let description = description.trim().trim_start_matches("-").trim();
+
+                    let name = if tag == "@param" {
+                        name.to_case(Case::Camel)
+                    } else {
+                        name.to_string()
+                    };
+
+                    Some(format!("{} {} - {}", tag, name, description))
+                }
+                None => Some(line.clone()),
+            }
+        })
+        .flat_map(|line| {
+            line.split('\n')
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+        })
+        .map(|line| line.replace("*/", "*\\/"))
+        .collect::<Vec<_>>();
+
+    // Remove the empty lines left by the removed code
+    while lines.last().map(|line| line.trim().is_empty()) == Some(true) {
+        lines.pop();
+    }
+
+    if lines.is_empty() {
+        return "".to_string();
+    }
+
+    let lines = lines
+        .into_iter()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{} *\n", indent)
+            } else {
+                format!("{} * {}\n", indent, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("");
+
+    format!("{}/**\n{}{} */\n", indent, lines, indent)
+}
+
+/// Generate the typescript type parameters, for example <T, U extends Resource>
+fn generate_generics_str(generics: &[FruityExportGeneric], self_ident: &str) -> String {
+    if generics.is_empty() {
+        return "".to_string();
+    }
+
+    let generics = generics
+        .iter()
+        .map(|generic| {
+            let bounds = generic
+                .bounds
+                .iter()
+                .filter(|bound| match bound.segments.last() {
+                    Some(segment) => {
+                        !RUST_ONLY_TRAITS.contains(&segment.ident.to_string().as_str())
+                    }
+                    None => false,
+                })
+                .map(|bound| {
+                    rust_type_to_ts_type(
+                        &syn::Type::Path(syn::TypePath {
+                            qself: None,
+                            path: bound.clone(),
+                        }),
+                        true,
+                        self_ident,
+                    )
+                })
+                .filter(|ty| ty.as_str() != "")
+                .collect::<Vec<_>>();
+
+            if !bounds.is_empty() {
+                format!("{} extends {}", generic.name, bounds.join(" & "))
+            } else {
+                generic.name.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("<{}>", generics)
+}
+
+/// Replace the type-erased idents of a type by the script only generics, in their declaration order
+/// For example, with a script only generic T, Option<AnyResourceReference> becomes Option<T>
+/// Only the plain type-erased idents are replaced, the other types are kept as they are
+fn apply_script_generics(ty: &syn::Type, generics: &[FruityExportGeneric]) -> syn::Type {
+    struct ScriptGenericVisitor<'a> {
+        generics: std::slice::Iter<'a, syn::Ident>,
+    }
+
+    impl<'a> VisitMut for ScriptGenericVisitor<'a> {
+        fn visit_type_path_mut(&mut self, type_path: &mut syn::TypePath) {
+            let is_type_erased = type_path.qself.is_none()
+                && match type_path.path.segments.last() {
+                    Some(segment) => {
+                        segment.arguments.is_empty()
+                            && TYPE_ERASED_IDENTS.contains(&segment.ident.to_string().as_str())
+                    }
+                    None => false,
+                };
+
+            if is_type_erased {
+                if let Some(generic) = self.generics.next() {
+                    type_path.path = syn::Path::from(generic.clone());
+                }
+            } else {
+                syn::visit_mut::visit_type_path_mut(self, type_path);
+            }
+        }
+    }
+
+    let script_generics = generics
+        .iter()
+        .filter(|generic| generic.script_only)
+        .map(|generic| generic.name.clone())
+        .collect::<Vec<_>>();
+
+    let mut ty = ty.clone();
+    ScriptGenericVisitor {
+        generics: script_generics.iter(),
+    }
+    .visit_type_mut(&mut ty);
+
+    ty
+}
+
 /// arg_or_return_type should be true if arg
 fn rust_type_to_ts_type(ty: &syn::Type, arg_or_return_type: bool, self_ident: &str) -> String {
     match ty {
@@ -262,7 +496,7 @@ fn rust_type_to_ts_type(ty: &syn::Type, arg_or_return_type: bool, self_ident: &s
             rust_type_to_ts_type(&paren.elem, arg_or_return_type, self_ident)
         }
         syn::Type::Path(path) => {
-            let last_segment = path.path.segments.iter().last().unwrap();
+            let last_segment = path.path.segments.last().unwrap();
             let ident = format_type_ident(&last_segment.ident);
             format_type_generics(
                 &ident,
@@ -281,26 +515,41 @@ fn rust_type_to_ts_type(ty: &syn::Type, arg_or_return_type: bool, self_ident: &s
                 rust_type_to_ts_type(&slice.elem, arg_or_return_type, self_ident)
             )
         }
-        syn::Type::TraitObject(trait_object) => trait_object
-            .bounds
-            .clone()
-            .into_iter()
-            .filter_map(|bound| match bound {
-                syn::TypeParamBound::Trait(bound) => Some(rust_type_to_ts_type(
-                    &syn::Type::Path(syn::TypePath {
-                        qself: None,
-                        path: bound.path,
-                    }),
-                    arg_or_return_type,
-                    self_ident,
-                )),
-                syn::TypeParamBound::Lifetime(_) => None,
-            })
-            .filter(|ty| ty.as_str() != "")
-            .collect::<Vec<_>>()
-            .join(" | "),
+        // A trait object implements all its traits, so it's typed as their intersection
+        // The traits that only have a meaning in rust are ignored as in the generic bounds
+        syn::Type::TraitObject(trait_object) => {
+            let traits = trait_object
+                .bounds
+                .clone()
+                .into_iter()
+                .filter_map(|bound| match bound {
+                    syn::TypeParamBound::Trait(bound) => Some(bound.path),
+                    syn::TypeParamBound::Lifetime(_) => None,
+                })
+                .filter(|path| match path.segments.last() {
+                    Some(segment) => {
+                        !RUST_ONLY_TRAITS.contains(&segment.ident.to_string().as_str())
+                    }
+                    None => false,
+                })
+                .map(|path| {
+                    rust_type_to_ts_type(
+                        &syn::Type::Path(syn::TypePath { qself: None, path }),
+                        arg_or_return_type,
+                        self_ident,
+                    )
+                })
+                .filter(|ty| ty.as_str() != "")
+                .collect::<Vec<_>>();
+
+            if traits.is_empty() {
+                "unknown".to_string()
+            } else {
+                traits.join(" & ")
+            }
+        }
         syn::Type::Tuple(tuple) => {
-            if tuple.elems.len() == 0 {
+            if tuple.elems.is_empty() {
                 "void".to_string()
             } else {
                 format!(
@@ -308,7 +557,7 @@ fn rust_type_to_ts_type(ty: &syn::Type, arg_or_return_type: bool, self_ident: &s
                     tuple
                         .elems
                         .iter()
-                        .map(|ty| rust_type_to_ts_type(&ty, arg_or_return_type, self_ident))
+                        .map(|ty| rust_type_to_ts_type(ty, arg_or_return_type, self_ident))
                         .collect::<Vec<_>>()
                         .join(", ")
                 )
@@ -323,8 +572,8 @@ fn rust_type_to_ts_type(ty: &syn::Type, arg_or_return_type: bool, self_ident: &s
 fn is_rust_type_optional(ty: &syn::Type) -> bool {
     match ty {
         syn::Type::Path(path) => {
-            let last_segment = path.path.segments.iter().last().unwrap();
-            last_segment.ident.to_string() == "Option"
+            let last_segment = path.path.segments.last().unwrap();
+            last_segment.ident == "Option"
         }
         _ => false,
     }
@@ -533,7 +782,7 @@ fn format_type_generics(
 
                 let return_ty = match &parenthesized.output {
                     syn::ReturnType::Default => "void".to_string(),
-                    syn::ReturnType::Type(_, ty) => rust_type_to_ts_type(&ty, true, self_ident),
+                    syn::ReturnType::Type(_, ty) => rust_type_to_ts_type(ty, true, self_ident),
                 };
 
                 format!("(({}) => {})", args, return_ty)
@@ -541,3 +790,201 @@ fn format_type_generics(
         },
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn docs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn ts_type(ty: syn::Type) -> String {
+        rust_type_to_ts_type(&ty, false, "")
+    }
+
+    fn generate_ts(file: syn::File) -> String {
+        parse_fruity_exports(file.items)
+            .into_iter()
+            .map(generate_fruity_export_str)
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_docs_str() {
+        let docs_str = generate_docs_str(
+            &docs(&[
+                "Get a resource by identifier",
+                "",
+                "# Arguments",
+                "* `resource_identifier` - The resource identifier",
+                "",
+                "# Generic Arguments",
+                "* `T` - The resource type",
+                "",
+                "# Examples",
+                "```rust",
+                "let resource = resource_container.get::<T>(\"id\");",
+                "```",
+                "```ts",
+                "const resource = resourceContainer.get(\"id\") /* typed */;",
+                "```",
+            ]),
+            "  ",
+        );
+
+        assert_eq!(
+            docs_str,
+            [
+                "  /**",
+                "   * Get a resource by identifier",
+                "   *",
+                "   * @param resourceIdentifier - The resource identifier",
+                "   * @typeParam T - The resource type",
+                "   * @example",
+                "   * ```ts",
+                "   * const resource = resourceContainer.get(\"id\") /* typed *\\/;",
+                "   * ```",
+                "   */",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_generate_docs_str_without_docs() {
+        assert_eq!(generate_docs_str(&[], ""), "");
+        assert_eq!(
+            generate_docs_str(&docs(&["```rust", "let a = 1;", "```"]), ""),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_apply_script_generics() {
+        let generics = vec![
+            FruityExportGeneric {
+                name: syn::parse_quote!(U),
+                bounds: vec![],
+                script_only: false,
+            },
+            FruityExportGeneric {
+                name: syn::parse_quote!(T),
+                bounds: vec![],
+                script_only: true,
+            },
+        ];
+
+        // Only the first type-erased ident is typed with the only script generic
+        let ty = apply_script_generics(
+            &syn::parse_quote!(FruityResult<Option<(AnyResourceReference, ScriptValue)>>),
+            &generics,
+        );
+        assert_eq!(ts_type(ty), "[T, ScriptValue] | null");
+
+        // The types with generic arguments are kept as they are
+        let ty = apply_script_generics(
+            &syn::parse_quote!(Vec<ResourceReference<dyn TextureResource>>),
+            &generics,
+        );
+        assert_eq!(ts_type(ty), "ResourceReference<TextureResource>[]");
+    }
+
+    #[test]
+    fn test_trait_objects() {
+        assert_eq!(
+            ts_type(syn::parse_quote!(Box<dyn Component + Named + Send + Sync>)),
+            "Component & Named"
+        );
+        assert_eq!(ts_type(syn::parse_quote!(Box<dyn Any + Send>)), "unknown");
+        assert_eq!(
+            ts_type(syn::parse_quote!(
+                Arc<dyn Fn(u32, String) -> bool + Send + Sync>
+            )),
+            "((arg0: number, arg1: string) => boolean)"
+        );
+    }
+
+    #[test]
+    fn test_generate_class() {
+        let ts = generate_ts(syn::parse_quote! {
+            /// A container of values
+            #[derive(Debug, FruityAny)]
+            #[fruity_export]
+            pub struct Container<T: Named + Send> {
+                /// The values, in insertion order
+                pub values: Vec<T>,
+                pub label: Option<String>,
+                inner: u32,
+            }
+
+            #[fruity_export]
+            impl<T: Named + Send> Container<T> {
+                /// Find a value by name
+                ///
+                /// # Arguments
+                /// * `name` - The value name
+                #[fruity_export]
+                pub fn find(&self, name: String) -> Option<T> {
+                    unimplemented!()
+                }
+
+                /// Get the resource attached to a value
+                ///
+                /// # Generic Arguments
+                /// * `R` - The resource type
+                #[fruity_export]
+                pub fn get_resource(&self, index: usize) -> Option<AnyResourceReference> {
+                    unimplemented!()
+                }
+            }
+        });
+
+        assert_eq!(
+            ts,
+            [
+                "/**",
+                " * A container of values",
+                " */",
+                "export class Container<T extends Named> {",
+                "  /**",
+                "   * The values, in insertion order",
+                "   */",
+                "  values: T[]",
+                "  label?: string | null | undefined | void",
+                "  /**",
+                "   * Find a value by name",
+                "   *",
+                "   * @param name - The value name",
+                "   */",
+                "  find(name: string): T | null",
+                "  /**",
+                "   * Get the resource attached to a value",
+                "   *",
+                "   * @typeParam R - The resource type",
+                "   */",
+                "  getResource<R>(index: number): R | null",
+                "}",
+                "",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_skip_the_test_modules() {
+        let ts = generate_ts(syn::parse_quote! {
+            #[cfg(test)]
+            mod test {
+                #[fruity_export]
+                pub struct Link {
+                    pub entity: Option<EntityReference>,
+                }
+            }
+        });
+
+        assert_eq!(ts, "");
+    }
+}
@@ -1,7 +1,9 @@
 use gen_js::GenJsArgs;
+use gen_json_schema::GenJsonSchemaArgs;
 use gen_ts::GenTsArgs;
 use npm_rs::{NodeEnv, NpmEnv};
 mod gen_js;
+mod gen_json_schema;
 mod gen_ts;
 mod syn_inline_mod;
 
@@ -9,6 +11,7 @@ pub struct FruityBuildArgs {
     pub input: String,
     pub js_file: Option<String>,
     pub ts_file: Option<String>,
+    pub json_schema_file: Option<String>,
 }
 
 impl Default for FruityBuildArgs {
@@ -17,6 +20,7 @@ impl Default for FruityBuildArgs {
             input: "src/lib.rs".to_string(),
             js_file: Some("index.js".to_string()),
             ts_file: Some("index.d.ts".to_string()),
+            json_schema_file: Some("schema.json".to_string()),
         }
     }
 }
@@ -46,4 +50,11 @@ pub fn fruity_build_with_args(args: FruityBuildArgs) {
             output: ts_file.clone(),
         });
     }
+
+    if let Some(json_schema_file) = &args.json_schema_file {
+        gen_json_schema::gen_json_schema(GenJsonSchemaArgs {
+            input: args.input.clone(),
+            output: json_schema_file.clone(),
+        });
+    }
 }
@@ -49,6 +49,19 @@ pub struct FruityExportAttribute {
 /// }
 pub type FruityExportAttributeParameters = HashMap<String, TokenStream>;
 
+/// A generic type parameter
+#[derive(Clone, Debug)]
+pub struct FruityExportGeneric {
+    /// Name of the generic, in fn test<T: Resource>(), it is T
+    pub name: syn::Ident,
+    /// Trait bounds of the generic, in fn test<T: Resource>(), it is [Resource]
+    pub bounds: Vec<syn::Path>,
+    /// Is the generic only known by the scripting language
+    /// Those are declared in the "# Generic Arguments" doc section but not in the rust signature,
+    /// it is used for functions that returns a type-erased value like an AnyResourceReference
+    pub script_only: bool,
+}
+
 /// A function
 #[derive(Clone)]
 pub struct FruityExportFn {
@@ -58,6 +71,10 @@ pub struct FruityExportFn {
     pub name_overwrite: Option<syn::Ident>,
     /// Attributes
     pub attrs: Vec<FruityExportAttribute>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
+    /// Generic type parameters
+    pub generics: Vec<FruityExportGeneric>,
     /// Args
     pub args: Vec<FruityExportArg>,
     /// Return type
@@ -76,6 +93,8 @@ pub struct FruityExportConstructor {
     pub name: syn::Path,
     /// Attributes
     pub attrs: Vec<FruityExportAttribute>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
     /// Args
     pub args: Vec<FruityExportArg>,
     /// Return type
@@ -107,6 +126,8 @@ pub struct FruityExportClassField {
     pub ty: syn::Type,
     /// Attributes
     pub attrs: Vec<String>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
 }
 
 /// A class method
@@ -118,6 +139,10 @@ pub struct FruityExportClassMethod {
     pub name_overwrite: Option<syn::Ident>,
     /// Attributes
     pub attrs: Vec<FruityExportAttribute>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
+    /// Generic type parameters
+    pub generics: Vec<FruityExportGeneric>,
     /// The receiver, correspond to &self, &mut self or nothing
     pub receiver: FruityExportReceiver,
     /// Args
@@ -140,6 +165,10 @@ pub struct FruityExportClass {
     pub name_overwrite: Option<syn::Ident>,
     /// Attributes
     pub attrs: Vec<FruityExportAttribute>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
+    /// Generic type parameters
+    pub generics: Vec<FruityExportGeneric>,
     /// Factory
     pub constructor: Option<FruityExportConstructor>,
     /// Fields
@@ -162,6 +191,8 @@ pub struct FruityExportEnum {
     pub name_overwrite: Option<syn::Ident>,
     /// Attributes
     pub attrs: Vec<FruityExportAttribute>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
     /// Variants, the potential values that the enum can take
     pub variants: Vec<syn::Ident>,
     /// Typescript overwrite, is used by the typescript build script
@@ -195,11 +226,7 @@ pub enum FruityExport {
 
 /// Parse all the items that are exposed to the javascript
 pub fn parse_fruity_exports(items: Vec<syn::Item>) -> Vec<FruityExport> {
-    let exports = items
-        .into_iter()
-        .map(|item| parse_item(item))
-        .flatten()
-        .collect::<Vec<_>>();
+    let exports = items.into_iter().flat_map(parse_item).collect::<Vec<_>>();
 
     merge_all_fruity_exports(exports)
 }
@@ -209,10 +236,7 @@ fn parse_item(item: syn::Item) -> Vec<FruityExport> {
     let mut res2 = match item {
         syn::Item::Const(_) => vec![],
         syn::Item::Enum(item) => {
-            if item.attrs.iter().any(|attr| match attr.path.get_ident() {
-                Some(ident) => ident.to_string() == "export_enum",
-                None => false,
-            }) {
+            if has_attr(&item.attrs, &["export_enum", "fruity_export"]) {
                 vec![FruityExport::Enum(parse_enum_item(item))]
             } else {
                 vec![]
@@ -220,10 +244,7 @@ fn parse_item(item: syn::Item) -> Vec<FruityExport> {
         }
         syn::Item::ExternCrate(_) => vec![],
         syn::Item::Fn(item) => {
-            if item.attrs.iter().any(|attr| match attr.path.get_ident() {
-                Some(ident) => ident.to_string() == "export_function",
-                None => false,
-            }) {
+            if has_attr(&item.attrs, &["export_function", "fruity_export"]) {
                 vec![FruityExport::Fn(parse_fn_item(item))]
             } else {
                 vec![]
@@ -231,10 +252,7 @@ fn parse_item(item: syn::Item) -> Vec<FruityExport> {
         }
         syn::Item::ForeignMod(_) => vec![],
         syn::Item::Impl(item) => {
-            if item.attrs.iter().any(|attr| match attr.path.get_ident() {
-                Some(ident) => ident.to_string() == "export_impl",
-                None => false,
-            }) {
+            if has_attr(&item.attrs, &["export_impl", "fruity_export"]) {
                 vec![FruityExport::Class(parse_impl_item(item))]
             } else {
                 vec![]
@@ -243,21 +261,19 @@ fn parse_item(item: syn::Item) -> Vec<FruityExport> {
         syn::Item::Macro(_) => vec![],
         syn::Item::Macro2(_) => vec![],
         syn::Item::Mod(item) => match item.content {
+            // The test modules are not built into the library, their items are not exposed
+            Some(_) if is_test_only(&item.attrs) => vec![],
             Some(content) => content
                 .1
                 .into_iter()
-                .map(|item| parse_item(item))
-                .flatten()
+                .flat_map(parse_item)
                 .collect::<Vec<_>>(),
             None => vec![],
         },
         syn::Item::Static(_) => vec![],
         syn::Item::Struct(item) => {
             // Check if the struct has the attr export_struct
-            let has_export_struct = item.attrs.iter().any(|attr| match attr.path.get_ident() {
-                Some(ident) => ident.to_string() == "export_struct",
-                None => false,
-            });
+            let has_export_struct = has_attr(&item.attrs, &["export_struct", "fruity_export"]);
 
             // Check if the struct derive TryFromScriptValue or TryIntoScriptValue
             let derives = parse_derive_attr(item.attrs.clone())
@@ -274,10 +290,7 @@ fn parse_item(item: syn::Item) -> Vec<FruityExport> {
             }
         }
         syn::Item::Trait(item) => {
-            if item.attrs.iter().any(|attr| match attr.path.get_ident() {
-                Some(ident) => ident.to_string() == "export_trait",
-                None => false,
-            }) {
+            if has_attr(&item.attrs, &["export_trait", "fruity_export"]) {
                 vec![FruityExport::Class(parse_trait_item(item))]
             } else {
                 vec![]
@@ -295,6 +308,47 @@ fn parse_item(item: syn::Item) -> Vec<FruityExport> {
     res1
 }
 
+/// Check if an item has one of the given attributes
+fn has_attr(attrs: &[syn::Attribute], names: &[&str]) -> bool {
+    attrs.iter().any(|attr| match attr.path.get_ident() {
+        Some(ident) => names.contains(&ident.to_string().as_str()),
+        None => false,
+    })
+}
+
+/// Check if an item is only built for the tests, it is marked with #[cfg(test)]
+fn is_test_only(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path.is_ident("cfg")
+            && matches!(attr.parse_args::<syn::Ident>(), Ok(ident) if ident == "test")
+    })
+}
+
+/// Check if a method is exported as a class constructor
+/// It can be #[export_constructor] or #[fruity_export(constructor)]
+fn is_exported_constructor(method: &FruityExportClassMethod) -> bool {
+    method.attrs.iter().any(|attr| {
+        attr.name == "export_constructor"
+            || (attr.name == "fruity_export" && is_attr_flag_enabled(attr, "constructor"))
+    })
+}
+
+/// Check if a method is exported as a class method
+/// It can be #[export] or #[fruity_export]
+fn is_exported_method(method: &FruityExportClassMethod) -> bool {
+    method.attrs.iter().any(|attr| {
+        attr.name == "export"
+            || (attr.name == "fruity_export" && !is_attr_flag_enabled(attr, "constructor"))
+    })
+}
+
+fn is_attr_flag_enabled(attr: &FruityExportAttribute, flag: &str) -> bool {
+    attr.params
+        .get(flag)
+        .map(|value| value.to_string().as_str() == "true")
+        .unwrap_or(false)
+}
+
 /// Parse a function item
 pub fn parse_fn_item(item: syn::ItemFn) -> FruityExportFn {
     let args = item
@@ -323,13 +377,12 @@ pub fn parse_fn_item(item: syn::ItemFn) -> FruityExportFn {
                 ),
         },
         name_overwrite: parsed_attrs.name_overwrite,
+        generics: parse_generics(&item.sig.generics, &parsed_attrs.docs),
         attrs: parsed_attrs.attrs,
+        docs: parsed_attrs.docs,
         args,
         return_ty: item.sig.output,
-        is_async: match item.sig.asyncness {
-            Some(_) => true,
-            None => false,
-        },
+        is_async: item.sig.asyncness.is_some(),
         typescript_overwrite: parsed_attrs.typescript_overwrite,
     }
 }
@@ -337,6 +390,8 @@ pub fn parse_fn_item(item: syn::ItemFn) -> FruityExportFn {
 /// Parse an trait item for a struct
 pub fn parse_trait_item(item: syn::ItemTrait) -> FruityExportClass {
     let name = item.ident;
+    let parsed_attrs = parse_attrs_items(&item.attrs);
+    let generics = parse_generics(&item.generics, &parsed_attrs.docs);
 
     let class_functions = item
         .items
@@ -352,21 +407,16 @@ pub fn parse_trait_item(item: syn::ItemTrait) -> FruityExportClass {
         .collect::<Vec<_>>();
 
     let methods = class_functions
-        .clone()
         .into_iter()
-        .filter_map(|method| {
-            method
-                .attrs
-                .iter()
-                .find(|attr| attr.name == "export")
-                .map(|_| method.clone())
-        })
+        .filter(is_exported_method)
         .collect::<Vec<_>>();
 
     FruityExportClass {
         name,
         name_overwrite: None,
         attrs: vec![],
+        docs: parsed_attrs.docs,
+        generics,
         constructor: None,
         fields: vec![],
         methods,
@@ -426,14 +476,13 @@ fn parse_trait_method(item: &syn::TraitItemMethod) -> FruityExportClassMethod {
     FruityExportClassMethod {
         name,
         name_overwrite: parsed_attrs.name_overwrite,
+        generics: parse_generics(&item.sig.generics, &parsed_attrs.docs),
         attrs: parsed_attrs.attrs,
+        docs: parsed_attrs.docs,
         receiver,
         args,
         return_ty: item.sig.output.clone(),
-        is_async: match item.sig.asyncness {
-            Some(_) => true,
-            None => false,
-        },
+        is_async: item.sig.asyncness.is_some(),
         typescript_overwrite: parsed_attrs.typescript_overwrite,
     }
 }
@@ -441,7 +490,17 @@ fn parse_trait_method(item: &syn::TraitItemMethod) -> FruityExportClassMethod {
 /// Parse an impl item for a struct
 pub fn parse_impl_item(item: syn::ItemImpl) -> FruityExportClass {
     let self_ty = item.self_ty;
-    let name = syn::Ident::new(&quote! { #self_ty }.to_string(), Span::call_site());
+    let parsed_attrs = parse_attrs_items(&item.attrs);
+    let generics = parse_generics(&item.generics, &parsed_attrs.docs);
+
+    // The generic arguments are not part of the name, so impl<T> Test<T> is merged with struct Test<T>
+    let name = match self_ty.as_ref() {
+        syn::Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) => segment.ident.clone(),
+            None => syn::Ident::new(&quote! { #self_ty }.to_string(), Span::call_site()),
+        },
+        _ => syn::Ident::new(&quote! { #self_ty }.to_string(), Span::call_site()),
+    };
 
     let class_functions = item
         .items
@@ -459,14 +518,7 @@ pub fn parse_impl_item(item: syn::ItemImpl) -> FruityExportClass {
     let constructor = class_functions
         .clone()
         .into_iter()
-        .filter_map(|method| {
-            method
-                .attrs
-                .iter()
-                .find(|attr| attr.name == "export_constructor")
-                .map(|_| method.clone())
-        })
-        .last()
+        .rfind(is_exported_constructor)
         .map(|method| FruityExportConstructor {
             name: syn::Path {
                 leading_colon: None,
@@ -476,6 +528,7 @@ pub fn parse_impl_item(item: syn::ItemImpl) -> FruityExportClass {
                     ),
             },
             attrs: method.attrs,
+            docs: method.docs,
             args: method.args,
             return_ty: method.return_ty,
             is_async: method.is_async,
@@ -483,21 +536,16 @@ pub fn parse_impl_item(item: syn::ItemImpl) -> FruityExportClass {
         });
 
     let methods = class_functions
-        .clone()
         .into_iter()
-        .filter_map(|method| {
-            method
-                .attrs
-                .iter()
-                .find(|attr| attr.name == "export")
-                .map(|_| method.clone())
-        })
+        .filter(is_exported_method)
         .collect::<Vec<_>>();
 
     FruityExportClass {
         name,
         name_overwrite: None,
         attrs: vec![],
+        docs: parsed_attrs.docs,
+        generics,
         constructor,
         fields: vec![],
         methods,
@@ -522,6 +570,7 @@ pub fn parse_enum_item(item: syn::ItemEnum) -> FruityExportEnum {
         name,
         name_overwrite: parsed_attrs.name_overwrite,
         attrs: parsed_attrs.attrs,
+        docs: parsed_attrs.docs,
         variants,
         typescript_overwrite: parsed_attrs.typescript_overwrite,
     }
@@ -578,14 +627,13 @@ fn parse_impl_method(item: &syn::ImplItemMethod) -> FruityExportClassMethod {
     FruityExportClassMethod {
         name,
         name_overwrite: parsed_attrs.name_overwrite,
+        generics: parse_generics(&item.sig.generics, &parsed_attrs.docs),
         attrs: parsed_attrs.attrs,
+        docs: parsed_attrs.docs,
         receiver,
         args,
         return_ty: item.sig.output.clone(),
-        is_async: match item.sig.asyncness {
-            Some(_) => true,
-            None => false,
-        },
+        is_async: item.sig.asyncness.is_some(),
         typescript_overwrite: parsed_attrs.typescript_overwrite,
     }
 }
@@ -594,11 +642,14 @@ fn parse_impl_method(item: &syn::ImplItemMethod) -> FruityExportClassMethod {
 pub fn parse_struct_item(item: syn::ItemStruct) -> FruityExportClass {
     let name = item.ident;
     let parsed_attrs = parse_attrs_items(&item.attrs);
+    let generics = parse_generics(&item.generics, &parsed_attrs.docs);
 
     FruityExportClass {
         name,
         name_overwrite: parsed_attrs.name_overwrite,
         attrs: parsed_attrs.attrs,
+        docs: parsed_attrs.docs,
+        generics,
         constructor: None,
         fields: parse_struct_fields(&item.fields),
         methods: vec![],
@@ -615,11 +666,7 @@ pub fn parse_struct_fields(fields: &syn::Fields) -> Vec<FruityExportClassField>
             .iter()
             .map(|field| match &field.ident {
                 Some(ident) => FruityExportClassField {
-                    public: if let syn::Visibility::Public(_) = field.vis {
-                        true
-                    } else {
-                        false
-                    },
+                    public: matches!(field.vis, syn::Visibility::Public(_)),
                     name: FruityExportClassFieldName::Named(ident.clone()),
                     ty: field.ty.clone(),
                     attrs: field
@@ -633,6 +680,7 @@ pub fn parse_struct_fields(fields: &syn::Fields) -> Vec<FruityExportClassField>
                             }
                         })
                         .collect(),
+                    docs: parse_docs(&field.attrs),
                 },
                 None => unimplemented!(),
             })
@@ -644,11 +692,7 @@ pub fn parse_struct_fields(fields: &syn::Fields) -> Vec<FruityExportClassField>
                 .iter()
                 .enumerate()
                 .map(|(index, field)| FruityExportClassField {
-                    public: if let syn::Visibility::Public(_) = field.vis {
-                        true
-                    } else {
-                        false
-                    },
+                    public: matches!(field.vis, syn::Visibility::Public(_)),
                     name: FruityExportClassFieldName::Unnamed(index),
                     ty: field.ty.clone(),
                     attrs: field
@@ -662,6 +706,7 @@ pub fn parse_struct_fields(fields: &syn::Fields) -> Vec<FruityExportClassField>
                             }
                         })
                         .collect(),
+                    docs: parse_docs(&field.attrs),
                 })
                 .collect()
         }
@@ -675,6 +720,8 @@ pub fn parse_struct_fields(fields: &syn::Fields) -> Vec<FruityExportClassField>
 pub struct ParseAttrsItemResult {
     /// Attributes
     pub attrs: Vec<FruityExportAttribute>,
+    /// Doc comments, one entry per line
+    pub docs: Vec<String>,
     /// You can override the name with #[export_struct(name = "custom")]
     pub name_overwrite: Option<syn::Ident>,
     /// Typescript overwrite, is used by the typescript build script
@@ -685,10 +732,10 @@ pub struct ParseAttrsItemResult {
 }
 
 /// Parse the attributes of an exported item
-pub fn parse_attrs_items(items: &Vec<syn::Attribute>) -> ParseAttrsItemResult {
+pub fn parse_attrs_items(items: &[syn::Attribute]) -> ParseAttrsItemResult {
     // Parse attributes
     let attrs = items
-        .into_iter()
+        .iter()
         .filter_map(|item| {
             item.clone()
                 .path
@@ -704,39 +751,21 @@ pub fn parse_attrs_items(items: &Vec<syn::Attribute>) -> ParseAttrsItemResult {
                             _ => None,
                         })
                         .find(|_| true)
-                        .map(|e| e.stream())
-                        .map(|e| {
-                            e.into_iter()
-                                .enumerate()
-                                .group_by(|(index, _)| index / 3)
-                                .into_iter()
-                                .map(|(_, metas)| {
-                                    metas
-                                        .into_iter()
-                                        .map(|(_, metas)| metas.into())
-                                        .collect::<Vec<TokenStream>>()
-                                })
-                                .filter(|metas| metas.len() == 3)
-                                .filter(|metas| metas[1].to_string() == "=")
-                                .map(|metas| (metas[0].to_string(), metas[2].clone()))
-                                .collect::<HashMap<String, TokenStream>>()
-                        })
+                        .map(|e| parse_attr_params(e.stream()))
                         .unwrap_or(HashMap::new()),
                 })
         })
         .collect_vec();
 
     // Extract common overwrites by attributes
-    let export_attr = attrs
-        .iter()
-        .filter(|attr| {
-            attr.name == "export_function"
-                || attr.name == "export_impl"
-                || attr.name == "export_struct"
-                || attr.name == "export_constructor"
-                || attr.name == "export"
-        })
-        .last();
+    let export_attr = attrs.iter().rfind(|attr| {
+        attr.name == "export_function"
+            || attr.name == "export_impl"
+            || attr.name == "export_struct"
+            || attr.name == "export_constructor"
+            || attr.name == "export"
+            || attr.name == "fruity_export"
+    });
 
     // Extract name overwrite
     let name_overwrite = if let Some(export_attr) = export_attr {
@@ -772,12 +801,148 @@ pub fn parse_attrs_items(items: &Vec<syn::Attribute>) -> ParseAttrsItemResult {
 
     ParseAttrsItemResult {
         attrs,
+        docs: parse_docs(items),
         name_overwrite,
         typescript_overwrite,
         from_raw_js_object,
     }
 }
 
+/// Parse the doc comments of an item, each line of the doc is an entry
+pub fn parse_docs(attrs: &[syn::Attribute]) -> Vec<String> {
+    let docs = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit),
+                ..
+            })) => Some(lit.value()),
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(" ")
+                .unwrap_or(&line)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+
+    // Remove the empty trailing lines
+    let len = docs.len() - docs.iter().rev().take_while(|line| line.is_empty()).count();
+    docs.into_iter().take(len).collect()
+}
+
+/// Parse the items of a doc section, a doc section is written like this:
+/// # Arguments
+/// * `identifier` - The resource identifier
+///
+/// The result is a list of (name, description), here [("identifier", "The resource identifier")]
+pub fn parse_doc_section(docs: &[String], section: &str) -> Vec<(String, String)> {
+    docs.iter()
+        .skip_while(|line| line.trim() != format!("# {}", section))
+        .skip(1)
+        .take_while(|line| !line.trim().starts_with("#"))
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix("* `")?;
+            let (name, description) = line.split_once('`')?;
+            let description = description.trim().trim_start_matches("-").trim();
+
+            Some((name.to_string(), description.to_string()))
+        })
+        .collect()
+}
+
+/// Parse the generic type parameters of an item
+/// The script only generics are the ones declared in the "# Generic Arguments" doc section
+/// that are not in the rust signature
+pub fn parse_generics(generics: &syn::Generics, docs: &[String]) -> Vec<FruityExportGeneric> {
+    let mut result = generics
+        .type_params()
+        .map(|param| {
+            // Get the bounds declared in the parameter and the ones in the where clause
+            let where_bounds = generics
+                .where_clause
+                .iter()
+                .flat_map(|where_clause| where_clause.predicates.iter())
+                .filter_map(|predicate| match predicate {
+                    syn::WherePredicate::Type(predicate) => match &predicate.bounded_ty {
+                        syn::Type::Path(path) if path.path.is_ident(&param.ident) => {
+                            Some(predicate.bounds.iter())
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .flatten();
+
+            let bounds = param
+                .bounds
+                .iter()
+                .chain(where_bounds)
+                .filter_map(|bound| match bound {
+                    syn::TypeParamBound::Trait(bound) => match bound.modifier {
+                        syn::TraitBoundModifier::None => Some(bound.path.clone()),
+                        syn::TraitBoundModifier::Maybe(_) => None,
+                    },
+                    syn::TypeParamBound::Lifetime(_) => None,
+                })
+                .collect::<Vec<_>>();
+
+            FruityExportGeneric {
+                name: param.ident.clone(),
+                bounds,
+                script_only: false,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let script_only_generics = parse_doc_section(docs, "Generic Arguments")
+        .into_iter()
+        .filter(|(name, _)| !result.iter().any(|generic| generic.name == name))
+        .map(|(name, _)| FruityExportGeneric {
+            name: syn::Ident::new(&name, Span::call_site()),
+            bounds: vec![],
+            script_only: true,
+        })
+        .collect::<Vec<_>>();
+
+    result.extend(script_only_generics);
+    result
+}
+
+/// Parse the parameters of an attribute, parameters can be separated by commas or not
+/// A parameter without value is a flag, for example in #[fruity_export(constructor, name = "new")],
+/// the result is:
+/// {
+///   "constructor" => true,
+///   "name" => "new"
+/// }
+fn parse_attr_params(tokens: TokenStream) -> FruityExportAttributeParameters {
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    let mut params = HashMap::new();
+
+    let mut index = 0;
+    while index < tokens.len() {
+        if let TokenTree::Ident(key) = &tokens[index] {
+            match (tokens.get(index + 1), tokens.get(index + 2)) {
+                (Some(TokenTree::Punct(punct)), Some(value)) if punct.as_char() == '=' => {
+                    params.insert(key.to_string(), value.clone().into());
+                    index += 3;
+                }
+                _ => {
+                    params.insert(key.to_string(), quote! { true });
+                    index += 1;
+                }
+            }
+        } else {
+            index += 1;
+        }
+    }
+
+    params
+}
+
 /// Merge two list of FruityExport
 /// Combine the classes fields and methods, to merge the impl and struct informations
 fn merge_all_fruity_exports(exports: Vec<FruityExport>) -> Vec<FruityExport> {
@@ -836,19 +1001,33 @@ fn merge_all_fruity_exports(exports: Vec<FruityExport>) -> Vec<FruityExport> {
             let constructor = classes
                 .iter()
                 .filter_map(|class| class.constructor.clone())
-                .last();
+                .next_back();
 
             // Get the name overwrite
             let name_overwrite = classes
                 .iter()
                 .filter_map(|class| class.name_overwrite.clone())
-                .last();
+                .next_back();
 
             // Get the typescript overwrite
             let typescript_overwrite = classes
                 .iter()
                 .filter_map(|class| class.typescript_overwrite.clone())
-                .last();
+                .next_back();
+
+            // Get the docs, the struct one is preferred over the impl one
+            let docs = classes
+                .iter()
+                .map(|class| class.docs.clone())
+                .find(|docs| !docs.is_empty())
+                .unwrap_or_default();
+
+            // Get the generics
+            let generics = classes
+                .iter()
+                .map(|class| class.generics.clone())
+                .find(|generics| !generics.is_empty())
+                .unwrap_or_default();
 
             // Get the typescript overwrite
             let from_raw_js_object = classes.iter().any(|class| class.from_raw_js_object);
@@ -856,27 +1035,24 @@ fn merge_all_fruity_exports(exports: Vec<FruityExport>) -> Vec<FruityExport> {
             // Get attrs of the class
             let mut attrs = classes
                 .iter()
-                .map(|class| class.attrs.iter())
-                .flatten()
-                .map(|attr| attr.clone())
+                .flat_map(|class| class.attrs.iter())
+                .cloned()
                 .collect::<Vec<_>>();
             attrs.dedup_by(|attr1, attr2| attr1.name == attr2.name);
 
             // Get fields of the class
             let mut fields = classes
                 .iter()
-                .map(|class| class.fields.iter())
-                .flatten()
-                .map(|field| field.clone())
+                .flat_map(|class| class.fields.iter())
+                .cloned()
                 .collect::<Vec<_>>();
             fields.dedup_by(|field1, field2| field1.name == field2.name);
 
             // Get methods of the class
             let mut methods = classes
                 .iter()
-                .map(|class| class.methods.iter())
-                .flatten()
-                .map(|method| method.clone())
+                .flat_map(|class| class.methods.iter())
+                .cloned()
                 .collect::<Vec<_>>();
             methods.dedup_by(|method1, method2| method1.name == method2.name);
 
@@ -885,6 +1061,8 @@ fn merge_all_fruity_exports(exports: Vec<FruityExport>) -> Vec<FruityExport> {
                 name,
                 name_overwrite,
                 attrs,
+                docs,
+                generics,
                 constructor,
                 fields,
                 methods,
@@ -892,7 +1070,7 @@ fn merge_all_fruity_exports(exports: Vec<FruityExport>) -> Vec<FruityExport> {
                 from_raw_js_object,
             }
         })
-        .map(|class| FruityExport::Class(class))
+        .map(FruityExport::Class)
         .collect::<Vec<_>>();
 
     // Merge function entries (here this consist of just taking the first entry, cause a function should have a unique name)
@@ -914,7 +1092,7 @@ fn merge_all_fruity_exports(exports: Vec<FruityExport>) -> Vec<FruityExport> {
                     }
                     FruityExport::Class(_) => None,
                 })
-                .last()
+                .next_back()
         })
         .map(|export| FruityExport::Fn(export.clone()))
         .collect::<Vec<_>>();
@@ -997,7 +1175,7 @@ fn parse_item_typescript_attr(item: &syn::Item) -> Vec<FruityExport> {
                 .get_ident()
                 .map(|ident| (ident.clone(), attr.clone()))
         })
-        .filter(|(ident, _attr)| ident.to_string() == "typescript")
+        .filter(|(ident, _attr)| ident == "typescript")
         .map(|(_, attr)| attr.tokens)
         .filter_map(|tokens| match tokens.into_iter().last().unwrap() {
             TokenTree::Group(group) => Some(group.stream()),
@@ -1011,7 +1189,7 @@ fn parse_item_typescript_attr(item: &syn::Item) -> Vec<FruityExport> {
             TokenTree::Punct(_) => None,
             TokenTree::Literal(lit) => Some(rem_first_and_last(&lit.to_string()).to_string()),
         })
-        .map(|typescript| FruityExport::Raw(typescript))
+        .map(FruityExport::Raw)
         .collect::<Vec<_>>();
 
     let mut res2 = attrs
@@ -1021,7 +1199,7 @@ fn parse_item_typescript_attr(item: &syn::Item) -> Vec<FruityExport> {
                 .get_ident()
                 .map(|ident| (ident.clone(), attr.clone()))
         })
-        .filter(|(ident, _attr)| ident.to_string() == "typescript_import")
+        .filter(|(ident, _attr)| ident == "typescript_import")
         .map(|(_, attr)| attr.tokens)
         .filter_map(|tokens| match tokens.into_iter().last().unwrap() {
             TokenTree::Group(group) => Some(group.stream().into_iter().collect::<Vec<_>>()),
@@ -1035,7 +1213,7 @@ fn parse_item_typescript_attr(item: &syn::Item) -> Vec<FruityExport> {
             }
 
             if let TokenTree::Ident(second_token) = &tokens[1] {
-                if second_token.to_string() != "from" {
+                if second_token != "from" {
                     return None;
                 }
             } else {
@@ -1092,7 +1270,7 @@ pub fn parse_derive_attr(attrs: Vec<syn::Attribute>) -> Vec<syn::Ident> {
                 .get_ident()
                 .map(|ident| (ident.clone(), attr.clone()))
         })
-        .filter(|(ident, _attr)| ident.to_string() == "derive")
+        .filter(|(ident, _attr)| ident == "derive")
         .map(|(_, attr)| attr.tokens)
         .filter_map(|tokens| match tokens.into_iter().last().unwrap() {
             TokenTree::Group(group) => Some(group.stream()),
@@ -1100,7 +1278,7 @@ pub fn parse_derive_attr(attrs: Vec<syn::Attribute>) -> Vec<syn::Ident> {
             TokenTree::Punct(_) => None,
             TokenTree::Literal(_) => None,
         })
-        .map(|tokens| {
+        .flat_map(|tokens| {
             tokens.into_iter().filter_map(|tokens| match tokens {
                 TokenTree::Group(_) => None,
                 TokenTree::Ident(ident) => Some(ident),
@@ -1108,6 +1286,5 @@ pub fn parse_derive_attr(attrs: Vec<syn::Attribute>) -> Vec<syn::Ident> {
                 TokenTree::Literal(_) => None,
             })
         })
-        .flatten()
         .collect::<Vec<_>>()
 }
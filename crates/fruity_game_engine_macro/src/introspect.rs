@@ -449,6 +449,8 @@ pub(crate) fn intern_export_impl(item: ItemImpl) -> TokenStream2 {
                         },
                     name_overwrite: Some(exported_struct.name.clone()),
                     attrs: constructor.attrs,
+                    docs: constructor.docs,
+                    generics: vec![],
                     args: constructor.args,
                     return_ty: constructor.return_ty,
                     is_async: constructor.is_async,
@@ -495,6 +497,8 @@ pub(crate) fn intern_export_impl(item: ItemImpl) -> TokenStream2 {
                         },
                     name_overwrite: None,
                     attrs: vec![],
+                    docs: vec![],
+                    generics: vec![],
                     args: vec![],
                     return_ty: syn::ReturnType::Type(
                         Default::default(),
@@ -555,6 +559,8 @@ pub(crate) fn intern_export_impl(item: ItemImpl) -> TokenStream2 {
                         },
                     name_overwrite: Some(exported_struct.name.clone()),
                     attrs: constructor.attrs,
+                    docs: constructor.docs,
+                    generics: vec![],
                     args: constructor.args,
                     return_ty: constructor.return_ty,
                     is_async: constructor.is_async,
@@ -601,6 +607,8 @@ pub(crate) fn intern_export_impl(item: ItemImpl) -> TokenStream2 {
                         },
                     name_overwrite: None,
                     attrs: vec![],
+                    docs: vec![],
+                    generics: vec![],
                     args: vec![],
                     return_ty: syn::ReturnType::Type(
                         Default::default(),
@@ -15,7 +15,7 @@ use syn::Item;
 use syn::ItemEnum;
 use syn::ItemFn;
 use syn::__private::TokenStream2;
-use syn::{parse_macro_input, ItemImpl, ItemStruct};
+use syn::{parse_macro_input, parse_quote, ItemImpl, ItemStruct};
 use utils::fruity_crate;
 
 #[cfg(feature = "wasm-platform")]
@@ -76,41 +76,37 @@ pub fn typescript(_attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn export_function(_attr: TokenStream, input: TokenStream) -> TokenStream {
-    #[cfg(feature = "wasm-platform")]
-    let napi_function_export = quote! {};
-
-    #[cfg(not(feature = "wasm-platform"))]
-    let napi_function_export = {
-        let input = input.clone();
-        let item: ItemFn = parse_macro_input!(input);
-        let exported_fn = parse_fn_item(item);
-
-        napi_function_export(exported_fn, Some(Case::Camel))
-    };
-
-    #[cfg(not(feature = "wasm-platform"))]
-    let wasm_function_export = quote! {};
-
-    #[cfg(feature = "wasm-platform")]
-    let wasm_function_export = {
-        let input = input.clone();
-        let item: ItemFn = parse_macro_input!(input);
-        let exported_fn = parse_fn_item(item);
+pub fn json_schema(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
 
-        wasm_function_export(exported_fn, Some(Case::Camel))
-    };
+#[proc_macro_attribute]
+pub fn export_function(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let input2 = input.clone();
+    let item: ItemFn = parse_macro_input!(input2);
+    let export_function = intern_export_function(item);
 
     let input: TokenStream2 = input.clone().into();
     let output = quote! {
         #input
-        #napi_function_export
-        #wasm_function_export
+        #export_function
     };
 
     output.into()
 }
 
+fn intern_export_function(item: ItemFn) -> TokenStream2 {
+    let exported_fn = parse_fn_item(item);
+
+    #[cfg(not(feature = "wasm-platform"))]
+    let function_export = napi_function_export(exported_fn, Some(Case::Camel));
+
+    #[cfg(feature = "wasm-platform")]
+    let function_export = wasm_function_export(exported_fn, Some(Case::Camel));
+
+    function_export
+}
+
 #[proc_macro_attribute]
 pub fn export_struct(attr: TokenStream, input: TokenStream) -> TokenStream {
     let input2 = input.clone();
@@ -174,3 +170,136 @@ pub fn export_impl(_attr: TokenStream, input: TokenStream) -> TokenStream {
 
     output.into()
 }
+
+/// Common export attribute, it can be used on structs, enums, impls, traits and functions
+///
+/// In an exported impl or trait, the methods can be marked with #[fruity_export] to be exported
+/// or with #[fruity_export(constructor)] to be used as the class constructor
+#[proc_macro_attribute]
+pub fn fruity_export(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let input2 = input.clone();
+    let item: Item = parse_macro_input!(input2);
+
+    match item {
+        Item::Struct(_) => export_struct(attr, input),
+        Item::Enum(mut item) => {
+            let attr: TokenStream2 = attr.into();
+            // The attribute is removed by the compiler, we put it back to keep the parameters
+            item.attrs.push(parse_quote! { #[fruity_export(#attr)] });
+            let export_enum = intern_export_enum(item);
+
+            let input: TokenStream2 = input.into();
+            let output = quote! {
+                #input
+                #export_enum
+            };
+
+            output.into()
+        }
+        Item::Impl(item) => {
+            let export_impl = intern_export_impl(item.clone());
+            let item = strip_fruity_export_impl_attrs(item);
+
+            let output = quote! {
+                #item
+                #export_impl
+            };
+
+            output.into()
+        }
+        Item::Trait(mut item) => {
+            // The methods of a trait object are called through the introspection of the implementation,
+            // so nothing is generated for the trait itself, we only check that it can be introspected
+            let export_errors = check_exported_trait(&item);
+            item.items.iter_mut().for_each(|item| {
+                if let syn::TraitItem::Method(method) = item {
+                    method.attrs.retain(|attr| !is_fruity_export_attr(attr));
+                }
+            });
+
+            let output = quote! {
+                #item
+                #export_errors
+            };
+
+            output.into()
+        }
+        Item::Fn(mut item) => {
+            let attr: TokenStream2 = attr.into();
+            // The attribute is removed by the compiler, we put it back to keep the parameters
+            item.attrs.push(parse_quote! { #[fruity_export(#attr)] });
+            let export_function = intern_export_function(item);
+
+            let input: TokenStream2 = input.into();
+            let output = quote! {
+                #input
+                #export_function
+            };
+
+            output.into()
+        }
+        item => syn::Error::new_spanned(
+            item,
+            "fruity_export can only be used on structs, enums, impls, traits and functions",
+        )
+        .to_compile_error()
+        .into(),
+    }
+}
+
+fn is_fruity_export_attr(attr: &syn::Attribute) -> bool {
+    attr.path.is_ident("fruity_export")
+}
+
+/// An exported trait should extend IntrospectFields and IntrospectMethods and its exported methods
+/// should take self, they are the ones that are exposed by the trait objects
+fn check_exported_trait(item: &syn::ItemTrait) -> TokenStream2 {
+    let has_supertrait = |name: &str| {
+        item.supertraits.iter().any(|supertrait| match supertrait {
+            syn::TypeParamBound::Trait(bound) => match bound.path.segments.last() {
+                Some(segment) => segment.ident == name,
+                None => false,
+            },
+            _ => false,
+        })
+    };
+
+    let mut errors = ["IntrospectFields", "IntrospectMethods"]
+        .into_iter()
+        .filter(|name| !has_supertrait(name))
+        .map(|name| {
+            syn::Error::new_spanned(
+                &item.ident,
+                format!("an exported trait should extend {}", name),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    item.items.iter().for_each(|trait_item| {
+        if let syn::TraitItem::Method(method) = trait_item {
+            if method.attrs.iter().any(is_fruity_export_attr) && method.sig.receiver().is_none() {
+                errors.push(syn::Error::new_spanned(
+                    &method.sig,
+                    "an exported trait method should take self",
+                ));
+            }
+        }
+    });
+
+    errors
+        .into_iter()
+        .map(|error| error.to_compile_error())
+        .collect()
+}
+
+/// Remove the #[fruity_export] attributes from the methods of an impl, they are only
+/// markers that are read while exporting the impl
+fn strip_fruity_export_impl_attrs(mut item: ItemImpl) -> ItemImpl {
+    item.items.iter_mut().for_each(|item| {
+        if let syn::ImplItem::Method(method) = item {
+            method.attrs.retain(|attr| !is_fruity_export_attr(attr));
+        }
+    });
+
+    item
+}
@@ -16,8 +16,11 @@ export type MaterialParam =
   | { type: 'matrix4', value: Matrix4 }
 
 export type ShaderBindingVisibility = "vertex" | "fragment"
+
 export type ShaderBindingType = "texture" | "sampler" | "uniform"
+
 export type ShaderInstanceAttributeType = "int" | "uint" | "float" | "vector2D" | "vector4D"
+
 export class Color {
   r: number
   g: number
@@ -97,44 +100,165 @@ export class TextureResource {
   getSize(): [number, number]
 }
 
+/**
+ * A vector in 2D dimension
+ */
 export class Vector2D {
+  /**
+   * Horizontal component
+   */
   x: number
+  /**
+   * Vertical component
+   */
   y: number
+  /**
+   * Create a new `Vector2D` with the provided components.
+   */
   constructor(x: number, y: number)
+  /**
+   * Returns a vector with only the horizontal component of the current one
+   */
   horizontal(): Vector2D
+  /**
+   * Returns a vector with only the vertical component of the current one
+   */
   vertical(): Vector2D
+  /**
+   * Get the absolute value of the vector
+   */
   abs(): Vector2D
+  /**
+   * Returns a vector perpendicular to the current one.
+   */
   normal(): Vector2D
+  /**
+   * Get the scalar/dot product of the two `Vector2D`.
+   */
   dot(v2: Vector2D): number
+  /**
+   * Get the squared length of a `Vector2D`. This is more performant than using
+   * `length()` -- which is only available for `Vector2D<f32>` and `Vector2D<f64>`
+   * -- as it does not perform any square root operation.
+   */
   lengthSquared(): number
+  /**
+   * Linearly interpolates between two vectors
+   */
   lerp(end: Vector2D, progress: number): Vector2D
+  /**
+   * Get the length of the vector. If possible, favour `length_squared()` over
+   * this function, as it is more performant.
+   */
   length(): number
+  /**
+   * Get a new vector with the same direction as this vector, but with a length
+   * of 1.0. If the the length of the vector is 0, then the original vector is
+   * returned.
+   */
   normalize(): Vector2D
+  /**
+   * Get the vector's direction in radians.
+   */
   angle(): number
+  /**
+   * Check if the point is in a triangle
+   */
   inTriangle(p1: Vector2D, p2: Vector2D, p3: Vector2D): boolean
+  /**
+   * Check if the point is in a circle
+   */
   inCircle(center: Vector2D, radius: number): boolean
+  /**
+   * Add two vectors
+   */
   add(rhs: Vector2D): Vector2D
+  /**
+   * Subtract two vectors
+   */
   sub(rhs: Vector2D): Vector2D
+  /**
+   * Multiply a vector by a number
+   */
   mul(rhs: number): Vector2D
+  /**
+   * Divide a vector by a number
+   */
   div(rhs: number): Vector2D
 }
 
+/**
+ * A vector in 3D dimension
+ */
 export class Vector3D {
+  /**
+   * Horizontal component
+   */
   x: number
+  /**
+   * Vertical component
+   */
   y: number
+  /**
+   * Depth component
+   */
   z: number
+  /**
+   * Create a new `Vector3D` with the provided components.
+   */
   constructor(x: number, y: number, z: number)
+  /**
+   * Returns a vector with only the horizontal component of the current one
+   */
   horizontal(): Vector3D
+  /**
+   * Returns a vector with only the vertical component of the current one
+   */
   vertical(): Vector3D
+  /**
+   * Returns a vector with only the depth component of the current one
+   */
   depth(): Vector3D
+  /**
+   * Get the scalar/dot product of the two `Vector3D`.
+   */
   dot(v2: Vector3D): number
+  /**
+   * Get the squared length of a `Vector3D`. This is more performant than using
+   * `length()` -- which is only available for `Vector3D<f32>` and `Vector3D<f64>`
+   * -- as it does not perform any square root operation.
+   */
   lengthSquared(): number
+  /**
+   * Linearly interpolates between two vectors
+   */
   lerp(end: Vector3D, progress: number): Vector3D
+  /**
+   * Get the length of the vector. If possible, favour `length_squared()` over
+   * this function, as it is more performant.
+   */
   length(): number
+  /**
+   * Get a new vector with the same direction as this vector, but with a length
+   * of 1.0. If the the length of the vector is 0, then the original vector is
+   * returned.
+   */
   normalize(): Vector3D
+  /**
+   * Add two vectors
+   */
   add(rhs: Vector3D): Vector3D
+  /**
+   * Subtract two vectors
+   */
   sub(rhs: Vector3D): Vector3D
+  /**
+   * Multiply a vector by a number
+   */
   mul(rhs: number): Vector3D
+  /**
+   * Divide a vector by a number
+   */
   div(rhs: number): Vector3D
 }
 
@@ -144,4 +268,7 @@ export class Vertex {
   normal: Vector3D
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityGraphicModule(): Module
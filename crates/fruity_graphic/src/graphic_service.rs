@@ -10,6 +10,7 @@ use crate::resources::texture_resource::TextureResource;
 use crate::resources::texture_resource::TextureResourceSettings;
 use crate::Vector2D;
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::introspect::IntrospectFields;
 use fruity_game_engine::introspect::IntrospectMethods;
 use fruity_game_engine::resource::ResourceReference;
@@ -17,7 +18,7 @@ use fruity_game_engine::script_value::ScriptValue;
 use fruity_game_engine::script_value::TryFromScriptValue;
 use fruity_game_engine::script_value::TryIntoScriptValue;
 use fruity_game_engine::FruityError;
-use fruity_game_engine::{export, export_trait};
+
 use fruity_game_engine::{typescript, FruityResult};
 use maplit::hashmap;
 use std::collections::HashMap;
@@ -221,15 +222,15 @@ impl TryFromScriptValue for MaterialParam {
     }
 }
 
-#[export_trait]
+#[fruity_export]
 pub trait GraphicService: IntrospectFields + IntrospectMethods + Send + Sync {
-    #[export]
+    #[fruity_export]
     fn start_draw(&mut self) -> FruityResult<()>;
 
-    #[export]
+    #[fruity_export]
     fn end_draw(&mut self);
 
-    #[export]
+    #[fruity_export]
     fn render_scene(
         &self,
         view_proj: Matrix4,
@@ -237,34 +238,34 @@ pub trait GraphicService: IntrospectFields + IntrospectMethods + Send + Sync {
         target: Option<ResourceReference<dyn TextureResource>>,
     );
 
-    #[export]
+    #[fruity_export]
     fn get_camera_transform(&self) -> Matrix4;
 
-    #[export]
+    #[fruity_export]
     fn resize(&mut self, width: u32, height: u32);
 
-    #[export]
+    #[fruity_export]
     fn world_position_to_viewport_position(&self, pos: Vector2D) -> (u32, u32);
 
-    #[export]
+    #[fruity_export]
     fn viewport_position_to_world_position(&self, x: u32, y: u32) -> Vector2D;
 
-    #[export]
+    #[fruity_export]
     fn get_cursor_position(&self) -> Vector2D;
 
-    #[export]
+    #[fruity_export]
     fn is_cursor_hover_scene(&self) -> bool;
 
-    #[export]
+    #[fruity_export]
     fn get_viewport_offset(&self) -> (u32, u32);
 
-    #[export]
+    #[fruity_export]
     fn set_viewport_offset(&self, x: u32, y: u32);
 
-    #[export]
+    #[fruity_export]
     fn get_viewport_size(&self) -> (u32, u32);
 
-    #[export]
+    #[fruity_export]
     fn set_viewport_size(&self, x: u32, y: u32);
 
     fn draw_mesh(
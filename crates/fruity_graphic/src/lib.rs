@@ -7,9 +7,10 @@ use crate::resources::material_resource::load_material;
 use crate::resources::shader_resource::load_shader;
 use crate::resources::texture_resource::load_texture;
 use fruity_ecs::serialization::SerializationService;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
 use fruity_game_engine::sync::Arc;
-use fruity_game_engine::{export_function, typescript_import};
+use fruity_game_engine::typescript_import;
 
 pub mod graphic_service;
 pub mod math;
@@ -18,7 +19,7 @@ pub mod resources;
 #[typescript_import({ResourceReference, Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_graphic_module() -> Module {
     Module {
         name: "fruity_graphic".to_string(),
@@ -1,14 +1,15 @@
 use crate::{math::vector2d::Vector2D, Matrix4};
 use cgmath::{Angle, Rad, SquareMatrix};
 use fruity_ecs::serialization::{Deserialize, Serialize};
-use fruity_game_engine::{any::FruityAny, export, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use std::ops::Mul;
 
 #[derive(Debug, FruityAny, Serialize, Deserialize, Clone, Copy)]
-#[export_struct]
+#[fruity_export]
 pub struct Matrix3(pub [[f32; 3]; 3]);
 
-#[export_impl]
+#[fruity_export]
 impl Matrix3 {
     pub fn new_identity() -> Matrix3 {
         Matrix3(cgmath::Matrix3::identity().into())
@@ -29,17 +30,17 @@ impl Matrix3 {
         Matrix3(cgmath::Matrix3::from_nonuniform_scale(vec.x, vec.y).into())
     }
 
-    #[export]
+    #[fruity_export]
     pub fn translation(&self) -> Vector2D {
         Vector2D::new(self.0[2][0], self.0[2][1])
     }
 
-    #[export]
+    #[fruity_export]
     pub fn rotation(&self) -> f32 {
         f32::atan(self.0[0][1] / self.0[0][0])
     }
 
-    #[export]
+    #[fruity_export]
     pub fn scale(&self) -> Vector2D {
         // TODO: Take in care negative scaling
         Vector2D::new(
@@ -48,7 +49,7 @@ impl Matrix3 {
         )
     }
 
-    #[export]
+    #[fruity_export]
     pub fn invert(&self) -> Matrix3 {
         if let Some(result) = cgmath::Matrix3::from(self.0).invert() {
             Matrix3(result.into())
@@ -1,13 +1,14 @@
 use cgmath::SquareMatrix;
 use fruity_ecs::serialization::{Deserialize, Serialize};
-use fruity_game_engine::{any::FruityAny, export, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use std::ops::Mul;
 
 #[derive(Debug, FruityAny, Serialize, Deserialize, Clone, Copy)]
-#[export_struct]
+#[fruity_export]
 pub struct Matrix4(pub [[f32; 4]; 4]);
 
-#[export_impl]
+#[fruity_export]
 impl Matrix4 {
     pub fn identity() -> Matrix4 {
         Matrix4(cgmath::Matrix4::identity().into())
@@ -17,7 +18,7 @@ impl Matrix4 {
         Matrix4(cgmath::ortho(left, right, bottom, top, near, far).into())
     }
 
-    #[export]
+    #[fruity_export]
     pub fn invert(&self) -> Matrix4 {
         if let Some(result) = cgmath::Matrix4::from(self.0).invert() {
             Matrix4(result.into())
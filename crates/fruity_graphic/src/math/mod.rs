@@ -1,7 +1,8 @@
 use bytemuck::{Pod, Zeroable};
 use css_color_parser::Color as CssColor;
 use fruity_ecs::serialization::{Deserialize, Serialize};
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use std::str::FromStr;
 
 pub mod matrix3;
@@ -11,7 +12,7 @@ pub mod vector3d;
 
 #[repr(C)]
 #[derive(Debug, FruityAny, Serialize, Deserialize, Copy, Clone, Pod, Zeroable)]
-#[export_struct]
+#[fruity_export]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -19,9 +20,9 @@ pub struct Color {
     pub a: f32,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Color {
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
         Self { r, g, b, a }
     }
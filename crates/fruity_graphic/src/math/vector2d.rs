@@ -1,6 +1,7 @@
 use crate::math::{matrix3::Matrix3, matrix4::Matrix4};
 use fruity_ecs::serialization::{Deserialize, Serialize};
-use fruity_game_engine::{any::FruityAny, export, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
 /// A vector in 2D dimension
@@ -17,7 +18,7 @@ use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
     bytemuck::Pod,
     bytemuck::Zeroable,
 )]
-#[export_struct]
+#[fruity_export]
 pub struct Vector2D {
     /// Horizontal component
     pub x: f32,
@@ -26,10 +27,10 @@ pub struct Vector2D {
     pub y: f32,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Vector2D {
     /// Create a new `Vector2D` with the provided components.
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(x: f32, y: f32) -> Vector2D {
         Self { x, y }
     }
@@ -42,7 +43,7 @@ impl Vector2D {
     /// let v = Vector2D::new(10, 20);
     /// assert_eq!(Vector2D::new(10, 0), v.horizontal());
     /// ```
-    #[export]
+    #[fruity_export]
     pub fn horizontal(&self) -> Self {
         Self {
             x: self.x,
@@ -57,7 +58,7 @@ impl Vector2D {
     /// use vector2d::Vector2D;
     /// let v = Vector2D::new(10, 20);
     /// assert_eq!(Vector2D::new(0, 20), v.vertical());
-    #[export]
+    #[fruity_export]
     pub fn vertical(&self) -> Self {
         Self {
             x: Default::default(),
@@ -66,7 +67,7 @@ impl Vector2D {
     }
 
     /// Get the absolute value of the vector
-    #[export]
+    #[fruity_export]
     pub fn abs(&self) -> Vector2D {
         Vector2D::new(self.x.abs(), self.y.abs())
     }
@@ -79,7 +80,7 @@ impl Vector2D {
     /// let v = Vector2D::new(21.3, -98.1);
     /// assert_eq!(Vector2D::new(98.1, 21.3), v.normal());
     /// ```
-    #[export]
+    #[fruity_export]
     pub fn normal(&self) -> Self {
         Self {
             x: -self.y,
@@ -88,7 +89,7 @@ impl Vector2D {
     }
 
     /// Get the scalar/dot product of the two `Vector2D`.
-    #[export]
+    #[fruity_export]
     pub fn dot(&self, v2: Self) -> f32 {
         self.x * v2.x + self.y * v2.y
     }
@@ -96,20 +97,20 @@ impl Vector2D {
     /// Get the squared length of a `Vector2D`. This is more performant than using
     /// `length()` -- which is only available for `Vector2D<f32>` and `Vector2D<f64>`
     /// -- as it does not perform any square root operation.
-    #[export]
+    #[fruity_export]
     pub fn length_squared(&self) -> f32 {
         self.x * self.x + self.y * self.y
     }
 
     /// Linearly interpolates between two vectors
-    #[export]
+    #[fruity_export]
     pub fn lerp(&self, end: Self, progress: f32) -> Self {
         *self + ((end - *self) * progress)
     }
 
     /// Get the length of the vector. If possible, favour `length_squared()` over
     /// this function, as it is more performant.
-    #[export]
+    #[fruity_export]
     pub fn length(&self) -> f32 {
         f32::sqrt(self.length_squared())
     }
@@ -117,7 +118,7 @@ impl Vector2D {
     /// Get a new vector with the same direction as this vector, but with a length
     /// of 1.0. If the the length of the vector is 0, then the original vector is
     /// returned.
-    #[export]
+    #[fruity_export]
     pub fn normalize(&self) -> Self {
         let len = self.length();
         if len == 0.0 {
@@ -128,13 +129,13 @@ impl Vector2D {
     }
 
     /// Get the vector's direction in radians.
-    #[export]
+    #[fruity_export]
     pub fn angle(&self) -> f32 {
         self.y.atan2(self.x)
     }
 
     /// Check if the point is in a triangle
-    #[export]
+    #[fruity_export]
     pub fn in_triangle(&self, p1: Vector2D, p2: Vector2D, p3: Vector2D) -> bool {
         pub fn sign(p1: &Vector2D, p2: &Vector2D, p3: &Vector2D) -> f32 {
             (p1.x - p3.x) * (p2.y - p3.y) - (p2.x - p3.x) * (p1.y - p3.y)
@@ -151,31 +152,31 @@ impl Vector2D {
     }
 
     /// Check if the point is in a circle
-    #[export]
+    #[fruity_export]
     pub fn in_circle(&self, center: Vector2D, radius: f32) -> bool {
         return (*self - center).length() <= radius;
     }
 
     /// Add two vectors
-    #[export]
+    #[fruity_export]
     pub fn add(&self, rhs: Vector2D) -> Vector2D {
         return *self + rhs;
     }
 
     /// Subtract two vectors
-    #[export]
+    #[fruity_export]
     pub fn sub(&self, rhs: Vector2D) -> Vector2D {
         return *self + rhs;
     }
 
     /// Multiply a vector by a number
-    #[export]
+    #[fruity_export]
     pub fn mul(&self, rhs: f32) -> Vector2D {
         return *self * rhs;
     }
 
     /// Divide a vector by a number
-    #[export]
+    #[fruity_export]
     pub fn div(&self, rhs: f32) -> Vector2D {
         return *self / rhs;
     }
@@ -1,6 +1,7 @@
 use crate::math::matrix4::Matrix4;
 use fruity_ecs::serialization::{Deserialize, Serialize};
-use fruity_game_engine::{any::FruityAny, export, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
 /// A vector in 3D dimension
@@ -17,7 +18,7 @@ use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
     bytemuck::Pod,
     bytemuck::Zeroable,
 )]
-#[export_struct]
+#[fruity_export]
 pub struct Vector3D {
     /// Horizontal component
     pub x: f32,
@@ -29,10 +30,10 @@ pub struct Vector3D {
     pub z: f32,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Vector3D {
     /// Create a new `Vector3D` with the provided components.
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(x: f32, y: f32, z: f32) -> Vector3D {
         Self { x, y, z }
     }
@@ -45,7 +46,7 @@ impl Vector3D {
     /// let v = Vector3D::new(10, 20, 40);
     /// assert_eq!(Vector3D::new(10, 0, 0), v.horizontal());
     /// ```
-    #[export]
+    #[fruity_export]
     pub fn horizontal(&self) -> Self {
         Self {
             x: self.x,
@@ -61,7 +62,7 @@ impl Vector3D {
     /// use vector3d::Vector3D;
     /// let v = Vector3D::new(10, 20, 40);
     /// assert_eq!(Vector3D::new(0, 20, 0), v.vertical());
-    #[export]
+    #[fruity_export]
     pub fn vertical(&self) -> Self {
         Self {
             x: Default::default(),
@@ -77,7 +78,7 @@ impl Vector3D {
     /// use vector3d::Vector3D;
     /// let v = Vector3D::new(10, 20, 40);
     /// assert_eq!(Vector3D::new(0, 0, 40), v.depth());
-    #[export]
+    #[fruity_export]
     pub fn depth(&self) -> Self {
         Self {
             x: Default::default(),
@@ -87,7 +88,7 @@ impl Vector3D {
     }
 
     /// Get the scalar/dot product of the two `Vector3D`.
-    #[export]
+    #[fruity_export]
     pub fn dot(&self, v2: Self) -> f32 {
         self.x * v2.x + self.y * v2.y + self.z * v2.z
     }
@@ -95,20 +96,20 @@ impl Vector3D {
     /// Get the squared length of a `Vector3D`. This is more performant than using
     /// `length()` -- which is only available for `Vector3D<f32>` and `Vector3D<f64>`
     /// -- as it does not perform any square root operation.
-    #[export]
+    #[fruity_export]
     pub fn length_squared(&self) -> f32 {
         self.x * self.x + self.y * self.y + self.z * self.z
     }
 
     /// Linearly interpolates between two vectors
-    #[export]
+    #[fruity_export]
     pub fn lerp(&self, end: Self, progress: f32) -> Self {
         *self + ((end - *self) * progress)
     }
 
     /// Get the length of the vector. If possible, favour `length_squared()` over
     /// this function, as it is more performant.
-    #[export]
+    #[fruity_export]
     pub fn length(&self) -> f32 {
         f32::sqrt(self.length_squared())
     }
@@ -116,7 +117,7 @@ impl Vector3D {
     /// Get a new vector with the same direction as this vector, but with a length
     /// of 1.0. If the the length of the vector is 0, then the original vector is
     /// returned.
-    #[export]
+    #[fruity_export]
     pub fn normalize(&self) -> Self {
         let len = self.length();
         if len == 0.0 {
@@ -127,25 +128,25 @@ impl Vector3D {
     }
 
     /// Add two vectors
-    #[export]
+    #[fruity_export]
     pub fn add(&self, rhs: Self) -> Self {
         return *self + rhs;
     }
 
     /// Subtract two vectors
-    #[export]
+    #[fruity_export]
     pub fn sub(&self, rhs: Self) -> Self {
         return *self + rhs;
     }
 
     /// Multiply a vector by a number
-    #[export]
+    #[fruity_export]
     pub fn mul(&self, rhs: f32) -> Self {
         return *self * rhs;
     }
 
     /// Divide a vector by a number
-    #[export]
+    #[fruity_export]
     pub fn div(&self, rhs: f32) -> Self {
         return *self / rhs;
     }
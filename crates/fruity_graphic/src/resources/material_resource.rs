@@ -2,16 +2,17 @@ use crate::graphic_service::GraphicService;
 use crate::resources::shader_resource::ShaderResource;
 use crate::resources::texture_resource::TextureResource;
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::introspect::{IntrospectFields, IntrospectMethods};
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::resource::ResourceReference;
 use fruity_game_engine::settings::Settings;
-use fruity_game_engine::{export_trait, FruityResult};
+use fruity_game_engine::{json_schema, FruityResult};
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 
-#[export_trait]
+#[fruity_export]
 pub trait MaterialResource: IntrospectFields + IntrospectMethods + Send + Sync {
     fn get_shader(&self) -> Option<ResourceReference<dyn ShaderResource>>;
 }
@@ -69,6 +70,49 @@ pub enum MaterialSettingsInstanceAttribute {
     },
 }
 
+/// A material, it binds values to a shader
+///
+/// Describes the settings of a material resource, it's used to generate the json schema
+pub struct MaterialSchema {
+    /// Identifier of the shader resource
+    pub shader: String,
+    pub bindings: Option<Vec<MaterialBindingSchema>>,
+    pub instance_attributes: Option<Vec<MaterialInstanceAttributeSchema>>,
+}
+
+pub struct MaterialBindingSchema {
+    pub r#type: MaterialBindingTypeSchema,
+    /// Identifier of the texture resource, only used by texture bindings
+    pub value: Option<String>,
+    pub bind_group: u32,
+}
+
+pub enum MaterialBindingTypeSchema {
+    Texture,
+    Camera,
+    ViewportSize,
+    RenderSurfaceSize,
+}
+
+/// The locations used depend on the type, a matrix4 uses the four locations and a rect the first two
+pub struct MaterialInstanceAttributeSchema {
+    pub name: String,
+    pub r#type: MaterialInstanceAttributeTypeSchema,
+    pub location: Option<u32>,
+    pub location_0: Option<u32>,
+    pub location_1: Option<u32>,
+    pub location_2: Option<u32>,
+    pub location_3: Option<u32>,
+}
+
+pub enum MaterialInstanceAttributeTypeSchema {
+    Matrix4,
+    Rect,
+    Vec2,
+    Vec4,
+}
+
+#[json_schema(resource = "material", settings = "MaterialSchema")]
 pub fn load_material(
     identifier: &str,
     settings: Settings,
@@ -1,8 +1,9 @@
 use crate::graphic_service::GraphicService;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
     any::FruityAny,
-    export_enum, export_impl, export_struct, export_trait,
     introspect::{IntrospectFields, IntrospectMethods},
+    json_schema,
     resource::ResourceContainer,
     settings::Settings,
     utils::read_file_to_string_async,
@@ -10,40 +11,40 @@ use fruity_game_engine::{
 };
 use std::{future::Future, pin::Pin};
 
-#[export_trait]
+#[fruity_export]
 pub trait ShaderResource: IntrospectFields + IntrospectMethods + Send + Sync {}
 
 #[derive(Debug, Default, Clone, FruityAny)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct ShaderResourceSettings {
     pub binding_groups: Vec<ShaderBindingGroup>,
     pub instance_attributes: Vec<ShaderInstanceAttribute>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ShaderResourceSettings {}
 
 #[derive(Debug, Default, Clone, FruityAny)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct ShaderBindingGroup {
     pub bindings: Vec<ShaderBinding>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ShaderBindingGroup {}
 
 #[derive(Debug, Default, Clone, FruityAny)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct ShaderBinding {
     pub visibility: ShaderBindingVisibility,
     pub ty: ShaderBindingType,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ShaderBinding {}
 
 #[derive(Debug, Clone)]
-#[export_enum]
+#[fruity_export]
 pub enum ShaderBindingVisibility {
     Vertex,
     Fragment,
@@ -56,7 +57,7 @@ impl Default for ShaderBindingVisibility {
 }
 
 #[derive(Debug, Clone)]
-#[export_enum]
+#[fruity_export]
 pub enum ShaderBindingType {
     Texture,
     Sampler,
@@ -70,17 +71,17 @@ impl Default for ShaderBindingType {
 }
 
 #[derive(Debug, Default, Clone, FruityAny)]
-#[export_struct(from_raw_js_object = true)]
+#[fruity_export(from_raw_js_object = true)]
 pub struct ShaderInstanceAttribute {
     pub location: u32,
     pub ty: ShaderInstanceAttributeType,
 }
 
-#[export_impl]
+#[fruity_export]
 impl ShaderInstanceAttribute {}
 
 #[derive(Debug, Clone)]
-#[export_enum]
+#[fruity_export]
 pub enum ShaderInstanceAttributeType {
     Int,
     Uint,
@@ -95,6 +96,46 @@ impl Default for ShaderInstanceAttributeType {
     }
 }
 
+/// A shader loaded from a wgsl file
+///
+/// Describes the settings of a shader resource, it's used to generate the json schema
+pub struct ShaderSchema {
+    /// Path of the wgsl file
+    pub path: String,
+    pub binding_groups: Option<Vec<Vec<ShaderBindingSchema>>>,
+    pub instance_attributes: Option<Vec<ShaderInstanceAttributeSchema>>,
+}
+
+pub struct ShaderBindingSchema {
+    pub visibility: ShaderBindingVisibilitySchema,
+    pub r#type: ShaderBindingTypeSchema,
+}
+
+pub enum ShaderBindingVisibilitySchema {
+    Vertex,
+    Fragment,
+}
+
+pub enum ShaderBindingTypeSchema {
+    Texture,
+    Sampler,
+    Uniform,
+}
+
+pub struct ShaderInstanceAttributeSchema {
+    pub location: u32,
+    pub r#type: ShaderInstanceAttributeTypeSchema,
+}
+
+pub enum ShaderInstanceAttributeTypeSchema {
+    Int,
+    Uint,
+    Float,
+    Vec2,
+    Vec4,
+}
+
+#[json_schema(resource = "shader", settings = "ShaderSchema")]
 pub fn load_shader(
     identifier: &str,
     settings: Settings,
@@ -1,7 +1,8 @@
 use crate::graphic_service::GraphicService;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
-    export, export_trait,
     introspect::{IntrospectFields, IntrospectMethods},
+    json_schema,
     resource::ResourceContainer,
     settings::Settings,
     utils::read_file_to_bytes_async,
@@ -11,12 +12,23 @@ use std::{future::Future, pin::Pin};
 
 pub struct TextureResourceSettings {}
 
-#[export_trait]
+#[fruity_export]
 pub trait TextureResource: IntrospectFields + IntrospectMethods + Send + Sync {
-    #[export]
+    #[fruity_export]
     fn get_size(&self) -> (u32, u32);
 }
 
+/// A texture loaded from an image file
+///
+/// Describes the settings of a texture resource, it's used to generate the json schema
+pub struct TextureSchema {
+    /// Path of the image file
+    pub path: Option<String>,
+    /// Content of the image file, used instead of the path if provided
+    pub bytes: Option<Vec<u8>>,
+}
+
+#[json_schema(resource = "texture", settings = "TextureSchema")]
 pub fn load_texture(
     identifier: &str,
     settings: Settings,
@@ -17,6 +17,9 @@ export class Camera {
   far: number
   target?: ResourceReference<TextureResource> | null | undefined | void
   backgroundColor: Color
+  /**
+   * Returns a new Camera
+   */
   constructor()
 }
 
@@ -33,11 +36,17 @@ export class Graphic2dService {
 
 export class Rotate2D {
   angle: number
+  /**
+   * Returns a new Rotate2D
+   */
   constructor(angle: number)
 }
 
 export class Scale2D {
   vec: Vector2D
+  /**
+   * Returns a new Camera
+   */
   constructor(vec: Vector2D)
 }
 
@@ -45,17 +54,29 @@ export class Sprite {
   material?: ResourceReference<MaterialResource> | null | undefined | void
   texture?: ResourceReference<TextureResource> | null | undefined | void
   zIndex: number
+  /**
+   * Returns a new Camera
+   */
   constructor(material: ResourceReference<MaterialResource> | null | undefined | void, texture: ResourceReference<TextureResource> | null | undefined | void, zIndex: number)
 }
 
 export class Transform2D {
   transform: Matrix3
+  /**
+   * Returns a new Camera
+   */
   constructor()
 }
 
 export class Translate2D {
   vec: Vector2D
+  /**
+   * Returns a new Camera
+   */
   constructor(vec: Vector2D)
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityGraphic2DModule(): Module
@@ -1,12 +1,13 @@
 use fruity_ecs::component::Component;
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::resource::ResourceReference;
-use fruity_game_engine::{export_constructor, export_impl, export_struct};
+
 use fruity_graphic::math::Color;
 use fruity_graphic::resources::texture_resource::TextureResource;
 
 #[derive(Debug, Clone, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Camera {
     pub near: f32,
     pub far: f32,
@@ -14,10 +15,10 @@ pub struct Camera {
     pub background_color: Color,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Camera {
     /// Returns a new Camera
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new() -> Camera {
         Self::default()
     }
@@ -1,16 +1,17 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 
 #[derive(Debug, Clone, Default, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Rotate2D {
     pub angle: f32,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Rotate2D {
     /// Returns a new Rotate2D
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(angle: f32) -> Rotate2D {
         Self { angle }
     }
@@ -1,17 +1,18 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_graphic::math::vector2d::Vector2D;
 
 #[derive(Debug, Clone, Default, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Scale2D {
     pub vec: Vector2D,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Scale2D {
     /// Returns a new Camera
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(vec: Vector2D) -> Scale2D {
         Self { vec }
     }
@@ -1,22 +1,23 @@
 use fruity_ecs::component::Component;
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::resource::ResourceReference;
-use fruity_game_engine::{export_constructor, export_impl, export_struct};
+
 use fruity_graphic::resources::material_resource::MaterialResource;
 use fruity_graphic::resources::texture_resource::TextureResource;
 
 #[derive(Debug, Clone, Default, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Sprite {
     pub material: Option<ResourceReference<dyn MaterialResource>>,
     pub texture: Option<ResourceReference<dyn TextureResource>>,
     pub z_index: i32,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Sprite {
     /// Returns a new Camera
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(
         material: Option<ResourceReference<dyn MaterialResource>>,
         texture: Option<ResourceReference<dyn TextureResource>>,
@@ -1,8 +1,6 @@
 use adjacent_pair_iterator::AdjacentPairIterator;
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::resource::ResourceReference;
 use fruity_game_engine::FruityError;
@@ -20,7 +18,7 @@ use std::f32::consts::PI;
 use std::ops::Range;
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Graphic2dService {
     graphic_service: ResourceReference<dyn GraphicService>,
     resource_container: ResourceContainer,
@@ -30,7 +28,7 @@ pub struct Graphic2dService {
     draw_arc_material: ResourceReference<dyn MaterialResource>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl Graphic2dService {
     pub fn new(resource_container: ResourceContainer) -> FruityResult<Self> {
         let graphic_service = resource_container.require::<dyn GraphicService>();
@@ -73,7 +71,7 @@ impl Graphic2dService {
         })
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_quad(
         &self,
         identifier: u64,
@@ -91,7 +89,7 @@ impl Graphic2dService {
         graphic_service.draw_mesh(identifier, mesh.clone(), material, params, z_index)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_line(
         &self,
         pos1: Vector2D,
@@ -115,7 +113,7 @@ impl Graphic2dService {
         );
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_polyline(
         &self,
         points: Vec<Vector2D>,
@@ -129,7 +127,7 @@ impl Graphic2dService {
             .for_each(|(pos1, pos2)| self.draw_line(pos1, pos2, width, color, z_index, transform));
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_dotted_line(
         &self,
         pos1: Vector2D,
@@ -153,7 +151,7 @@ impl Graphic2dService {
         );
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_rect(
         &self,
         bottom_left: Vector2D,
@@ -179,7 +177,7 @@ impl Graphic2dService {
         );
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_arc(
         &self,
         center: Vector2D,
@@ -212,7 +210,7 @@ impl Graphic2dService {
         );
     }
 
-    #[export]
+    #[fruity_export]
     pub fn draw_circle(
         &self,
         center: Vector2D,
@@ -10,9 +10,10 @@ use crate::systems::draw_sprite::draw_sprite;
 use crate::systems::update_transform_2d::update_transform_2d;
 use fruity_ecs::serialization::SerializationService;
 use fruity_ecs::system::{SystemParams, SystemService};
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
 use fruity_game_engine::sync::Arc;
-use fruity_game_engine::{export_function, typescript_import};
+use fruity_game_engine::typescript_import;
 
 pub mod components;
 pub mod graphic_2d_service;
@@ -22,7 +23,7 @@ pub mod systems;
 #[typescript_import({TextureResource, Color, MaterialResource, MaterialParam, Vector2D, Matrix3} from "fruity_graphic")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_graphic_2d_module() -> Module {
     Module {
         name: "fruity_graphic_2d".to_string(),
@@ -65,8 +66,9 @@ pub fn create_fruity_graphic_2d_module() -> Module {
                 "draw_sprite",
                 &draw_sprite as &'static (dyn Fn(_, _) -> _ + Send + Sync),
                 Some(SystemParams {
-                    pool_index: Some(98),
+                    pool_index: Some(95),
                     ignore_pause: Some(true),
+                    after: Some(vec!["update_transform_2d".to_string()]),
                     ..Default::default()
                 }),
             );
@@ -75,8 +77,9 @@ pub fn create_fruity_graphic_2d_module() -> Module {
                 "draw_camera",
                 &draw_camera as &'static (dyn Fn(_, _) -> _ + Send + Sync),
                 Some(SystemParams {
-                    pool_index: Some(99),
+                    pool_index: Some(95),
                     ignore_pause: Some(true),
+                    after: Some(vec!["draw_sprite".to_string()]),
                     ..Default::default()
                 }),
             );
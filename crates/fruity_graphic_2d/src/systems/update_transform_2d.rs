@@ -2,35 +2,62 @@ use crate::Rotate2D;
 use crate::Scale2D;
 use crate::Transform2D;
 use crate::Translate2D;
+use fruity_ecs::query::Added;
+use fruity_ecs::query::Changed;
+use fruity_ecs::query::Or;
 use fruity_ecs::query::Query;
+use fruity_ecs::query::Removed;
 use fruity_ecs::query::WithMut;
 use fruity_ecs::query::WithOptional;
 use fruity_game_engine::FruityResult;
 use fruity_graphic::math::matrix3::Matrix3;
 
+/// Compute the transform of the entities whose translation, rotation or scale changed since the last frame
 pub fn update_transform_2d(
-    query: Query<(
-        WithMut<Transform2D>,
-        WithOptional<Translate2D>,
-        WithOptional<Rotate2D>,
-        WithOptional<Scale2D>,
-    )>,
+    query: Query<
+        (
+            WithMut<Transform2D>,
+            WithOptional<Translate2D>,
+            WithOptional<Rotate2D>,
+            WithOptional<Scale2D>,
+        ),
+        Or<(
+            Added<Transform2D>,
+            Changed<Translate2D>,
+            Changed<Rotate2D>,
+            Changed<Scale2D>,
+            Removed<Translate2D>,
+            Removed<Rotate2D>,
+            Removed<Scale2D>,
+        )>,
+    >,
 ) -> FruityResult<()> {
     query.for_each(|(mut transform, translate_2d, rotate_2d, scale_2d)| {
-        transform.transform = Matrix3::new_identity();
+        transform.transform = compute_local_transform_2d(translate_2d, rotate_2d, scale_2d);
 
-        if let Some(translate_2d) = translate_2d {
-            transform.transform = transform.transform * Matrix3::new_translation(translate_2d.vec);
-        }
+        Ok(())
+    })
+}
 
-        if let Some(rotate_2d) = rotate_2d {
-            transform.transform = transform.transform * Matrix3::new_rotation(rotate_2d.angle);
-        }
+/// Compute the transform of an entity from its own translation, rotation and scale
+pub fn compute_local_transform_2d(
+    translate_2d: Option<&Translate2D>,
+    rotate_2d: Option<&Rotate2D>,
+    scale_2d: Option<&Scale2D>,
+) -> Matrix3 {
+    let mut transform = Matrix3::new_identity();
 
-        if let Some(scale_2d) = scale_2d {
-            transform.transform = transform.transform * Matrix3::new_scaling(scale_2d.vec);
-        }
+    if let Some(translate_2d) = translate_2d {
+        transform = transform * Matrix3::new_translation(translate_2d.vec);
+    }
 
-        Ok(())
-    })
+    if let Some(rotate_2d) = rotate_2d {
+        transform = transform * Matrix3::new_rotation(rotate_2d.angle);
+    }
+
+    if let Some(scale_2d) = scale_2d {
+        transform = transform * Matrix3::new_scaling(scale_2d.vec);
+    }
+
+    transform
 }
@@ -49,4 +49,7 @@ export class WgpuTextureResource {
   getSize(): [number, number]
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityGraphicWgpuModule(): Module
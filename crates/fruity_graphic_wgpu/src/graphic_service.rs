@@ -5,9 +5,7 @@ use crate::resources::shader_resource::WgpuShaderResource;
 use crate::resources::texture_resource::WgpuTextureResource;
 use crate::utils::encode_into_bytes;
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::profile_scope;
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::resource::ResourceReference;
@@ -96,7 +94,7 @@ struct RenderInstance {
 }
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct WgpuGraphicService {
     state: Option<State>,
     instance: Arc<wgpu::Instance>,
@@ -718,9 +716,9 @@ impl WgpuGraphicService {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl GraphicService for WgpuGraphicService {
-    #[export]
+    #[fruity_export]
     fn start_draw(&mut self) -> FruityResult<()> {
         profile_scope!("start_draw");
 
@@ -751,7 +749,7 @@ impl GraphicService for WgpuGraphicService {
         Ok(())
     }
 
-    #[export]
+    #[fruity_export]
     fn end_draw(&mut self) {
         profile_scope!("end_draw");
 
@@ -777,7 +775,7 @@ impl GraphicService for WgpuGraphicService {
         render_bundles.clear();
     }
 
-    #[export]
+    #[fruity_export]
     fn render_scene(
         &self,
         view_proj: Matrix4,
@@ -849,14 +847,14 @@ impl GraphicService for WgpuGraphicService {
         });
     }
 
-    #[export]
+    #[fruity_export]
     fn get_camera_transform(&self) -> Matrix4 {
         let state = self.state.as_ref().unwrap();
         let camera_transform = state.camera_transform.read();
         camera_transform.clone()
     }
 
-    #[export]
+    #[fruity_export]
     fn resize(&mut self, width: u32, height: u32) {
         if let Some(mut state) = self.state.as_mut() {
             state.config.width = width;
@@ -931,7 +929,7 @@ impl GraphicService for WgpuGraphicService {
         Ok(Box::new(resource))
     }
 
-    #[export]
+    #[fruity_export]
     fn world_position_to_viewport_position(&self, pos: Vector2D) -> (u32, u32) {
         let viewport_offset = self.get_viewport_offset();
         let viewport_size = self.get_viewport_size();
@@ -947,7 +945,7 @@ impl GraphicService for WgpuGraphicService {
         )
     }
 
-    #[export]
+    #[fruity_export]
     fn viewport_position_to_world_position(&self, x: u32, y: u32) -> Vector2D {
         let viewport_offset = self.get_viewport_offset();
         let viewport_size = self.get_viewport_size();
@@ -962,7 +960,7 @@ impl GraphicService for WgpuGraphicService {
         camera_transform.invert() * cursor_pos
     }
 
-    #[export]
+    #[fruity_export]
     fn get_cursor_position(&self) -> Vector2D {
         // Get informations from the resource dependencies
         let cursor_position = {
@@ -973,7 +971,7 @@ impl GraphicService for WgpuGraphicService {
         self.viewport_position_to_world_position(cursor_position.0, cursor_position.1)
     }
 
-    #[export]
+    #[fruity_export]
     fn is_cursor_hover_scene(&self) -> bool {
         // Get informations from the resource dependencies
         let cursor_position = {
@@ -992,25 +990,25 @@ impl GraphicService for WgpuGraphicService {
         cursor_pos.x >= 0.0 && cursor_pos.x < 1.0 && cursor_pos.y >= 0.0 && cursor_pos.y < 1.0
     }
 
-    #[export]
+    #[fruity_export]
     fn get_viewport_offset(&self) -> (u32, u32) {
         let viewport_offset = self.viewport_offset.read();
         viewport_offset.clone()
     }
 
-    #[export]
+    #[fruity_export]
     fn set_viewport_offset(&self, x: u32, y: u32) {
         let mut viewport_offset = self.viewport_offset.write();
         *viewport_offset = (x, y);
     }
 
-    #[export]
+    #[fruity_export]
     fn get_viewport_size(&self) -> (u32, u32) {
         let viewport_size = self.viewport_size.read();
         viewport_size.clone()
     }
 
-    #[export]
+    #[fruity_export]
     fn set_viewport_size(&self, x: u32, y: u32) {
         let mut viewport_size = self.viewport_size.write();
         *viewport_size = (x, y);
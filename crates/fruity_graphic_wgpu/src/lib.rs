@@ -1,9 +1,9 @@
 #![feature(async_closure)]
 
 use crate::graphic_service::WgpuGraphicService;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
-    export_function, module::Module, settings::Settings, sync::Arc, typescript_import,
-    world::World, FruityResult,
+    module::Module, settings::Settings, sync::Arc, typescript_import, world::World, FruityResult,
 };
 use fruity_graphic::graphic_service::GraphicService;
 
@@ -17,7 +17,7 @@ pub mod world_fn;
 #[typescript_import({Matrix4, Color, TextureResource, Vector2D, ShaderResource, MeshResourceSettings, ShaderResourceSettings} from "fruity_graphic")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_graphic_wgpu_module() -> Module {
     Module {
         name: "fruity_graphic_platform".to_string(),
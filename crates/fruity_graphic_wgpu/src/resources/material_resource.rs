@@ -3,9 +3,7 @@ use crate::resources::texture_resource::WgpuTextureResource;
 use crate::utils::insert_in_hashmap_vec;
 use crate::WgpuGraphicService;
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::resource::ResourceReference;
 use fruity_game_engine::sync::Arc;
 use fruity_game_engine::FruityError;
@@ -55,7 +53,7 @@ pub enum InstanceField {
 }
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct WgpuMaterialResource {
     pub(crate) params: MaterialResourceSettings,
     pub(crate) binding_groups: Vec<(u32, Arc<wgpu::BindGroup>)>,
@@ -299,9 +297,9 @@ impl WgpuMaterialResource {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl MaterialResource for WgpuMaterialResource {
-    #[export]
+    #[fruity_export]
     fn get_shader(&self) -> Option<ResourceReference<dyn ShaderResource>> {
         self.params.shader.clone()
     }
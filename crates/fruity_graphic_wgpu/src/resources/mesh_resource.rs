@@ -1,12 +1,11 @@
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_graphic::resources::mesh_resource::MeshResource;
 use fruity_graphic::resources::mesh_resource::MeshResourceSettings;
 use wgpu::util::DeviceExt;
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct WgpuMeshResource {
     pub params: MeshResourceSettings,
     pub(crate) vertex_buffer: wgpu::Buffer,
@@ -42,5 +41,5 @@ impl WgpuMeshResource {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl MeshResource for WgpuMeshResource {}
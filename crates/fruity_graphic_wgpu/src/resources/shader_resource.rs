@@ -1,7 +1,6 @@
 use crate::wgpu_bridge::VERTEX_DESC;
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_graphic::resources::shader_resource::ShaderBinding;
 use fruity_graphic::resources::shader_resource::ShaderBindingGroup;
 use fruity_graphic::resources::shader_resource::ShaderBindingType;
@@ -13,7 +12,7 @@ use fruity_graphic::resources::shader_resource::ShaderResourceSettings;
 use std::mem::size_of;
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct WgpuShaderResource {
     pub params: ShaderResourceSettings,
     pub(crate) render_pipeline: wgpu::RenderPipeline,
@@ -209,5 +208,5 @@ impl WgpuShaderResource {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl ShaderResource for WgpuShaderResource {}
@@ -1,12 +1,13 @@
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::sync::Arc;
-use fruity_game_engine::{export, export_impl, export_struct, FruityResult};
+use fruity_game_engine::FruityResult;
 use fruity_graphic::resources::texture_resource::TextureResource;
 use image::GenericImageView;
 use std::num::NonZeroU32;
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct WgpuTextureResource {
     pub(crate) view: wgpu::TextureView,
     pub(crate) bind_group: Arc<wgpu::BindGroup>,
@@ -275,9 +276,9 @@ impl WgpuTextureResource {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl TextureResource for WgpuTextureResource {
-    #[export]
+    #[fruity_export]
     fn get_size(&self) -> (u32, u32) {
         self.size
     }
@@ -7,9 +7,21 @@ import {
   EntityLocation,
 } from "fruity_ecs"
 
+/**
+ * A component for an entity that as a parent or at least is part of the hierarchy
+ */
 export class Parent {
+  /**
+   * The parent id
+   */
   parent: SignalProperty<EntityReference | null | undefined | void>
+  /**
+   * Returns a new Parent
+   */
   constructor()
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityHierarchyModule(): Module
@@ -2,15 +2,15 @@ use fruity_ecs::{
     entity::EntityReference,
     serialization::{Deserialize, Serialize},
 };
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::{
-    any::FruityAny, export_constructor, export_impl, export_struct, script_value::ScriptObjectType,
-    signal::SignalProperty, FruityResult,
+    any::FruityAny, script_value::ScriptObjectType, signal::SignalProperty, FruityResult,
 };
 use std::ops::Deref;
 
 /// A component for an entity that as a parent or at least is part of the hierarchy
 #[derive(Debug, Clone, Default, Serialize, Deserialize, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Parent {
     /// The parent id
     pub parent: SignalProperty<Option<EntityReference>>,
@@ -50,10 +50,10 @@ impl fruity_ecs::component::Component for Parent {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl Parent {
     /// Returns a new Parent
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new() -> Parent {
         Self::default()
     }
@@ -9,11 +9,14 @@
 
 use crate::components::parent::Parent;
 use crate::systems::delete_cascade::delete_cascade;
+use fruity_ecs::entity::EntityService;
 use fruity_ecs::serialization::SerializationService;
 use fruity_ecs::system::{StartupSystemParams, SystemService};
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
 use fruity_game_engine::sync::Arc;
-use fruity_game_engine::{export_function, typescript_import};
+use fruity_game_engine::typescript_import;
+use std::ops::Deref;
 
 /// Components of the module
 pub mod components;
@@ -25,7 +28,7 @@ pub mod systems;
 #[typescript_import({EntityLocation} from "fruity_ecs")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_hierarchy_module() -> Module {
     Module {
         name: "fruity_hierarchy".to_string(),
@@ -38,6 +41,21 @@ pub fn create_fruity_hierarchy_module() -> Module {
 
             serialization_service.register_component::<Parent>();
 
+            // The hierarchy paths of the entity service are resolved with the Parent component
+            let entity_service = resource_container.require::<EntityService>();
+            entity_service
+                .read()
+                .set_parent_resolver(|entity_reference| {
+                    let parent = {
+                        let entity_reader = entity_reference.read()?;
+                        entity_reader
+                            .get_component_by_type::<Parent>()
+                            .and_then(|parent| parent.parent.deref().clone())
+                    };
+
+                    parent.map(|parent| parent.get_entity_id()).transpose()
+                });
+
             let system_service = resource_container.require::<SystemService>();
             let mut system_service = system_service.write();
 
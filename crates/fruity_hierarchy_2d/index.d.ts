@@ -2,4 +2,7 @@ import {
   Module,
 } from "fruity_game_engine"
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityHierarchy2DModule(): Module
@@ -1,13 +1,14 @@
 use crate::systems::transform_2d_cascade::transform_2d_cascade;
 use fruity_ecs::system::{SystemParams, SystemService};
-use fruity_game_engine::{export_function, module::Module, sync::Arc, typescript_import};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{module::Module, sync::Arc, typescript_import};
 
 pub mod systems;
 
 #[typescript_import({Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_hierarchy_2d_module() -> Module {
     Module {
         name: "fruity_hierarchy_2d".to_string(),
@@ -26,8 +27,10 @@ pub fn create_fruity_hierarchy_2d_module() -> Module {
                 "transform_2d_cascade",
                 &transform_2d_cascade as &'static (dyn Fn(_) -> _ + Send + Sync),
                 Some(SystemParams {
-                    pool_index: Some(96),
+                    pool_index: Some(95),
                     ignore_pause: Some(true),
+                    after: Some(vec!["update_transform_2d".to_string()]),
+                    before: Some(vec!["draw_sprite".to_string()]),
                     ..Default::default()
                 }),
             );*/
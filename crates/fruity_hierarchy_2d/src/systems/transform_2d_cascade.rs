@@ -1,24 +1,190 @@
+use fruity_ecs::component::SystemTicks;
 use fruity_ecs::query::Query;
 use fruity_ecs::query::With;
 use fruity_ecs::query::WithMut;
+use fruity_ecs::query::WithOptional;
 use fruity_game_engine::FruityResult;
+use fruity_graphic_2d::components::rotate_2d::Rotate2D;
+use fruity_graphic_2d::components::scale_2d::Scale2D;
 use fruity_graphic_2d::components::transform_2d::Transform2D;
+use fruity_graphic_2d::components::translate_2d::Translate2D;
+use fruity_graphic_2d::systems::update_transform_2d::compute_local_transform_2d;
 use fruity_hierarchy::components::parent::Parent;
 use std::ops::Deref;
 
+/// Apply the parent transform to the children whose transform or parent transform changed since the last frame
+///
+/// A child transform that `update_transform_2d` computed again only holds its own translation, rotation and scale,
+/// so the parent transform is multiplied into it. When only the parent moved, the child transform still holds the
+/// previous parent transform, so it is computed again from its own translation, rotation and scale first
+///
+/// The children are stored in archetypes ordered by their depth in the hierarchy, so a parent is always cascaded
+/// before its children and a cascaded parent is seen as changed by them
 pub fn transform_2d_cascade(
-    query: Query<(With<Parent>, WithMut<Transform2D>)>,
+    query: Query<(
+        With<Parent>,
+        WithMut<Transform2D>,
+        WithOptional<Translate2D>,
+        WithOptional<Rotate2D>,
+        WithOptional<Scale2D>,
+    )>,
 ) -> FruityResult<()> {
-    query.for_each(move |(child, mut transform)| {
-        // Apply the parent transform to the child
-        if let Some(parent_entity) = &child.parent.deref().deref() {
-            if let Some(parent_transform) =
-                parent_entity.read()?.get_component_by_type::<Transform2D>()
-            {
-                transform.transform = parent_transform.transform * transform.transform;
+    let system_ticks = SystemTicks::current();
+
+    query.for_each(
+        move |(child, mut transform, translate_2d, rotate_2d, scale_2d)| {
+            // Apply the parent transform to the child
+            if let Some(parent_entity) = &child.parent.deref().deref() {
+                let parent_reader = parent_entity.read()?;
+
+                if let Some(parent_transform) = parent_reader.get_component_by_type::<Transform2D>()
+                {
+                    let is_parent_changed = parent_reader
+                        .get_component_ticks_by_type::<Transform2D>()
+                        .map(|ticks| system_ticks.is_newer(ticks.get_changed()))
+                        .unwrap_or(false);
+
+                    if transform.is_changed() {
+                        transform.transform = parent_transform.transform * transform.transform;
+                    } else if is_parent_changed {
+                        transform.transform = parent_transform.transform
+                            * compute_local_transform_2d(translate_2d, rotate_2d, scale_2d);
+                    }
+                }
             }
-        }
 
-        Ok(())
-    })
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fruity_ecs::component::{increment_change_tick, Component, ExtensionComponentService};
+    use fruity_ecs::entity::{EntityId, EntityService};
+    use fruity_ecs::query::{Added, Changed, Or, Removed};
+    use fruity_game_engine::resource::ResourceContainer;
+    use fruity_game_engine::signal::SignalProperty;
+    use fruity_graphic::math::vector2d::Vector2D;
+    use fruity_graphic_2d::systems::update_transform_2d::update_transform_2d;
+
+    fn new_entity_service() -> EntityService {
+        let resource_container = ResourceContainer::new();
+        resource_container.add::<ExtensionComponentService>(
+            "extension_component_service",
+            Box::new(ExtensionComponentService::new(resource_container.clone())),
+        );
+
+        EntityService::new(resource_container)
+    }
+
+    /// Run the transform systems once, as the frame service would do with their previous run ticks
+    fn run_transform_systems(entity_service: &EntityService, last_runs: &mut [u64; 2]) {
+        let update_ticks = SystemTicks {
+            last_run: last_runs[0],
+            this_run: increment_change_tick(),
+        };
+        update_ticks
+            .scope(|| {
+                update_transform_2d(entity_service.query_filtered::<_, Or<(
+                    Added<Transform2D>,
+                    Changed<Translate2D>,
+                    Changed<Rotate2D>,
+                    Changed<Scale2D>,
+                    Removed<Translate2D>,
+                    Removed<Rotate2D>,
+                    Removed<Scale2D>,
+                )>>())
+            })
+            .unwrap();
+        last_runs[0] = update_ticks.this_run;
+
+        let cascade_ticks = SystemTicks {
+            last_run: last_runs[1],
+            this_run: increment_change_tick(),
+        };
+        cascade_ticks
+            .scope(|| transform_2d_cascade(entity_service.query()))
+            .unwrap();
+        last_runs[1] = cascade_ticks.this_run;
+    }
+
+    fn get_translation(entity_service: &EntityService, entity_id: EntityId) -> Vector2D {
+        entity_service
+            .get_entity_reference(entity_id)
+            .unwrap()
+            .read()
+            .unwrap()
+            .get_component_by_type::<Transform2D>()
+            .unwrap()
+            .transform
+            .translation()
+    }
+
+    #[test]
+    fn test_moving_only_the_parent_updates_the_child() {
+        let entity_service = new_entity_service();
+
+        let parent_id = entity_service
+            .create_entity(
+                "parent".to_string(),
+                true,
+                vec![
+                    Box::new(Transform2D::new()) as Box<dyn Component>,
+                    Box::new(Translate2D::new(Vector2D::new(1.0, 0.0))),
+                ],
+            )
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let parent_reference = entity_service.get_entity_reference(parent_id).unwrap();
+        let child_id = entity_service
+            .create_entity(
+                "child".to_string(),
+                true,
+                vec![
+                    Box::new(Parent {
+                        parent: SignalProperty::new(Some(parent_reference.clone())),
+                    }) as Box<dyn Component>,
+                    Box::new(Transform2D::new()),
+                    Box::new(Translate2D::new(Vector2D::new(0.0, 2.0))),
+                ],
+            )
+            .unwrap();
+        unsafe { entity_service.apply_pending_mutations() }.unwrap();
+
+        let mut last_runs = [0, 0];
+        run_transform_systems(&entity_service, &mut last_runs);
+        assert_eq!(
+            get_translation(&entity_service, child_id),
+            Vector2D::new(1.0, 2.0)
+        );
+
+        // Move the parent only, the child translation is left untouched
+        increment_change_tick();
+        parent_reference
+            .write()
+            .unwrap()
+            .get_component_by_type_mut::<Translate2D>()
+            .unwrap()
+            .vec = Vector2D::new(5.0, 0.0);
+
+        run_transform_systems(&entity_service, &mut last_runs);
+        assert_eq!(
+            get_translation(&entity_service, parent_id),
+            Vector2D::new(5.0, 0.0)
+        );
+        assert_eq!(
+            get_translation(&entity_service, child_id),
+            Vector2D::new(5.0, 2.0)
+        );
+
+        // Nothing moved, the parent transform is not applied twice
+        run_transform_systems(&entity_service, &mut last_runs);
+        assert_eq!(
+            get_translation(&entity_service, child_id),
+            Vector2D::new(5.0, 2.0)
+        );
+    }
 }
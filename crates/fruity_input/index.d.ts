@@ -33,10 +33,25 @@ export class InputService {
 
 export class Modifiers {
   0: number
+  /**
+   * Returns `true` if the shift key is pressed.
+   */
   hasShift(): boolean
+  /**
+   * Returns `true` if the control key is pressed.
+   */
   hasCtrl(): boolean
+  /**
+   * Returns `true` if the alt key is pressed.
+   */
   hasAlt(): boolean
+  /**
+   * Returns `true` if the logo key is pressed.
+   */
   hasLogo(): boolean
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityInputModule(): Module
@@ -1,7 +1,6 @@
 use crate::InputService;
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::profile_scope;
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::resource::ResourceReference;
@@ -30,7 +29,7 @@ impl Debug for DragAction {
 }
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct DragService {
     current_drag_action: RwLock<Option<DragAction>>,
     input_service: ResourceReference<InputService>,
@@ -44,7 +43,7 @@ impl Drop for DragService {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl DragService {
     pub fn new(resource_container: ResourceContainer) -> Self {
         let input_service = resource_container.require::<InputService>();
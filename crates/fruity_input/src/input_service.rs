@@ -1,7 +1,5 @@
 use fruity_game_engine::any::FruityAny;
-use fruity_game_engine::export;
-use fruity_game_engine::export_impl;
-use fruity_game_engine::export_struct;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::settings::Settings;
 use fruity_game_engine::signal::Signal;
@@ -16,35 +14,35 @@ pub const ALT: u32 = 0x04;
 pub const LOGO: u32 = 0x08;
 
 #[derive(Default, Debug, Clone, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct Modifiers(pub u32);
 
-#[export_impl]
+#[fruity_export]
 impl Modifiers {
     /// Returns `true` if the shift key is pressed.
-    #[export]
+    #[fruity_export]
     pub fn has_shift(&self) -> bool {
         self.0 & (1 << (SHIFT - 1)) != 0
     }
     /// Returns `true` if the control key is pressed.
-    #[export]
+    #[fruity_export]
     pub fn has_ctrl(&self) -> bool {
         self.0 & (1 << (CTRL - 1)) != 0
     }
     /// Returns `true` if the alt key is pressed.
-    #[export]
+    #[fruity_export]
     pub fn has_alt(&self) -> bool {
         self.0 & (1 << (ALT - 1)) != 0
     }
     /// Returns `true` if the logo key is pressed.
-    #[export]
+    #[fruity_export]
     pub fn has_logo(&self) -> bool {
         self.0 & (1 << (LOGO - 1)) != 0
     }
 }
 
 #[derive(Debug, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct InputService {
     pub input_map: HashMap<String, String>,
     pub pressed_inputs: HashSet<String>,
@@ -58,7 +56,7 @@ pub struct InputService {
     pub on_released: Signal<String>,
 }
 
-#[export_impl]
+#[fruity_export]
 impl InputService {
     pub fn new(_resource_container: ResourceContainer) -> InputService {
         InputService {
@@ -96,27 +94,27 @@ impl InputService {
         Ok(())
     }
 
-    #[export]
+    #[fruity_export]
     pub fn register_input(&mut self, input: String, source: String) {
         self.input_map.insert(source, input);
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_pressed(&self, input: String) -> bool {
         self.pressed_inputs.contains(&input)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_source_pressed(&self, source: String) -> bool {
         self.pressed_sources.contains(&source)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_pressed_this_frame(&self, input: String) -> bool {
         self.pressed_this_frame_inputs.contains(&input)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_keyboard_pressed_this_frame(&self, mut source: String) -> bool {
         source.retain(|c| !c.is_whitespace());
 
@@ -134,22 +132,22 @@ impl InputService {
         result
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_source_pressed_this_frame(&self, source: String) -> bool {
         self.pressed_this_frame_sources.contains(&source)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_released_this_frame(&self, input: String) -> bool {
         self.released_this_frame_inputs.contains(&input)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn is_source_released_this_frame(&self, source: String) -> bool {
         self.released_this_frame_sources.contains(&source)
     }
 
-    #[export]
+    #[fruity_export]
     pub fn notify_pressed(&mut self, source: String) -> FruityResult<()> {
         self.pressed_sources.insert(source.clone());
         self.pressed_this_frame_sources.insert(source.clone());
@@ -165,7 +163,7 @@ impl InputService {
         Ok(())
     }
 
-    #[export]
+    #[fruity_export]
     pub fn notify_released(&mut self, source: String) -> FruityResult<()> {
         self.pressed_sources.remove(&source);
         self.released_this_frame_sources.insert(source.clone());
@@ -181,7 +179,7 @@ impl InputService {
         Ok(())
     }
 
-    #[export]
+    #[fruity_export]
     pub fn handle_frame_end(&mut self) {
         self.pressed_this_frame_sources.clear();
         self.pressed_this_frame_inputs.clear();
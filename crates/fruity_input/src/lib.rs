@@ -1,7 +1,8 @@
 use drag_service::DragService;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
 use fruity_game_engine::sync::Arc;
-use fruity_game_engine::{export_function, typescript_import};
+use fruity_game_engine::typescript_import;
 use input_service::InputService;
 
 pub mod drag_service;
@@ -10,7 +11,7 @@ pub mod input_service;
 #[typescript_import({Signal, Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_input_module() -> Module {
     Module {
         name: "fruity_abstract_input".to_string(),
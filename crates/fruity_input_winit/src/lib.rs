@@ -1,4 +1,4 @@
-use fruity_game_engine::export_function;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
 use fruity_game_engine::sync::Arc;
 use fruity_game_engine::typescript_import;
@@ -20,7 +20,7 @@ use winit::event::WindowEvent;
 #[typescript_import({Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_input_winit_module() -> Module {
     Module {
         name: "fruity_input".to_string(),
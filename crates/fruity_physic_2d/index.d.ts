@@ -9,13 +9,22 @@ import {
 export class CircleCollider {
   center: Vector2D
   radius: number
+  /**
+   * Returns a new RectCollider
+   */
   constructor(center: Vector2D, radius: number)
 }
 
 export class RectCollider {
   bottomLeft: Vector2D
   topRight: Vector2D
+  /**
+   * Returns a new RectCollider
+   */
   constructor(bottomLeft: Vector2D, topRight: Vector2D)
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityPhysic2DModule(): Module
@@ -1,9 +1,10 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_graphic::math::vector2d::Vector2D;
 
 #[derive(Debug, Clone, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct CircleCollider {
     pub center: Vector2D,
     pub radius: f32,
@@ -18,10 +19,10 @@ impl Default for CircleCollider {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl CircleCollider {
     /// Returns a new RectCollider
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(center: Vector2D, radius: f32) -> CircleCollider {
         Self { center, radius }
     }
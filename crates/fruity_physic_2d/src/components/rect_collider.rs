@@ -1,9 +1,10 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_constructor, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_graphic::math::vector2d::Vector2D;
 
 #[derive(Debug, Clone, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct RectCollider {
     pub bottom_left: Vector2D,
     pub top_right: Vector2D,
@@ -18,10 +19,10 @@ impl Default for RectCollider {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl RectCollider {
     /// Returns a new RectCollider
-    #[export_constructor]
+    #[fruity_export(constructor)]
     pub fn new(bottom_left: Vector2D, top_right: Vector2D) -> RectCollider {
         Self {
             bottom_left,
@@ -1,7 +1,8 @@
 use crate::components::circle_collider::CircleCollider;
 use crate::components::rect_collider::RectCollider;
 use fruity_ecs::serialization::SerializationService;
-use fruity_game_engine::{export_function, module::Module, sync::Arc, typescript_import};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{module::Module, sync::Arc, typescript_import};
 
 pub mod components;
 
@@ -9,7 +10,7 @@ pub mod components;
 #[typescript_import({Vector2D} from "fruity_graphic")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_physic_2d_module() -> Module {
     Module {
         name: "fruity_physic_2d".to_string(),
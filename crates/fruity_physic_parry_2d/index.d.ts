@@ -14,4 +14,7 @@ export class RigidBody {
 
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityPhysicParry2DModule(): Module
@@ -1,9 +1,10 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use parry2d::shape::Ball;
 
 #[derive(Debug, Clone, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct ParryCircleCollider {
     #[serialize_skip]
     pub(crate) shape: Ball,
@@ -17,5 +18,5 @@ impl Default for ParryCircleCollider {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl ParryCircleCollider {}
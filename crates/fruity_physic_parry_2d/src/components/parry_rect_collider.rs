@@ -1,10 +1,11 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use nalgebra::Vector2;
 use parry2d::shape::Cuboid;
 
 #[derive(Debug, Clone, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct ParryRectCollider {
     #[serialize_skip]
     pub(crate) shape: Cuboid,
@@ -18,5 +19,5 @@ impl Default for ParryRectCollider {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl ParryRectCollider {}
@@ -1,9 +1,10 @@
 use fruity_ecs::component::Component;
-use fruity_game_engine::{any::FruityAny, export_impl, export_struct};
+use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 
 #[derive(Debug, Default, Clone, Component, FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct RigidBody {}
 
-#[export_impl]
+#[fruity_export]
 impl RigidBody {}
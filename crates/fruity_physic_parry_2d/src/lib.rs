@@ -7,7 +7,8 @@ use crate::systems::update_rigid_body::update_rigid_body;
 use fruity_ecs::component::ExtensionComponentService;
 use fruity_ecs::serialization::SerializationService;
 use fruity_ecs::system::{SystemParams, SystemService};
-use fruity_game_engine::{export_function, module::Module, sync::Arc, typescript_import};
+use fruity_game_engine::fruity_export;
+use fruity_game_engine::{module::Module, sync::Arc, typescript_import};
 use fruity_physic_2d::components::circle_collider::CircleCollider;
 use fruity_physic_2d::components::rect_collider::RectCollider;
 
@@ -17,7 +18,7 @@ pub mod systems;
 #[typescript_import({Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_physic_parry_2d_module() -> Module {
     Module {
         name: "fruity_physic_parry_2d".to_string(),
@@ -70,7 +71,11 @@ pub fn create_fruity_physic_parry_2d_module() -> Module {
                 &update_rigid_body as &'static (dyn Fn(_) -> _ + Send + Sync),
                 Some(SystemParams {
                     ignore_pause: Some(true),
-                    pool_index: Some(73),
+                    pool_index: Some(72),
+                    after: Some(vec![
+                        "update_rect_collider".to_string(),
+                        "update_circle_collider".to_string(),
+                    ]),
                     ..Default::default()
                 }),
             );
@@ -1,12 +1,13 @@
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
-use fruity_game_engine::{export_function, typescript_import};
+use fruity_game_engine::typescript_import;
 
 pub mod window_service;
 
 #[typescript_import({Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_windows_module() -> Module {
     Module {
         name: "fruity_abstract_windows".to_string(),
@@ -12,4 +12,7 @@ export class WinitWindowService {
   onEventsCleared: Signal<void>
 }
 
+/**
+ * Returns the module, ready to be registered into the fruity_game_engine
+ */
 export function createFruityWindowsWinitModule(): Module
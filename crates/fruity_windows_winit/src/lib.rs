@@ -1,6 +1,7 @@
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::module::Module;
 use fruity_game_engine::sync::Arc;
-use fruity_game_engine::{export_function, typescript_import};
+use fruity_game_engine::typescript_import;
 
 pub mod window_service;
 pub mod world_fn;
@@ -8,7 +9,7 @@ pub mod world_fn;
 #[typescript_import({Signal, Module} from "fruity_game_engine")]
 
 /// Returns the module, ready to be registered into the fruity_game_engine
-#[export_function]
+#[fruity_export]
 pub fn create_fruity_windows_winit_module() -> Module {
     Module {
         name: "fruity_windows".to_string(),
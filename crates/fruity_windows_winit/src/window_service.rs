@@ -1,8 +1,9 @@
 use fruity_game_engine::any::FruityAny;
+use fruity_game_engine::fruity_export;
 use fruity_game_engine::resource::ResourceContainer;
 use fruity_game_engine::send_wrapper::SendWrapper;
 use fruity_game_engine::signal::Signal;
-use fruity_game_engine::{export, export_impl, export_struct, FruityResult};
+use fruity_game_engine::{export, FruityResult};
 use fruity_windows::window_service::WindowService;
 use std::fmt::Debug;
 use winit::dpi::LogicalSize;
@@ -17,7 +18,7 @@ unsafe impl Sync for WinitWindowService {}
 unsafe impl Send for WinitWindowService {}
 
 #[derive(FruityAny)]
-#[export_struct]
+#[fruity_export]
 pub struct WinitWindowService {
     window: Window,
     event_loop: Option<SendWrapper<EventLoop<()>>>,
@@ -36,7 +37,7 @@ impl Debug for WinitWindowService {
     }
 }
 
-#[export_impl]
+#[fruity_export]
 impl WinitWindowService {
     pub fn new(
         _resource_container: ResourceContainer,
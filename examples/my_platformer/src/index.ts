@@ -152,7 +152,7 @@ world.registerModule({
     });*/
 
     /*systemService.addStartupSystem("test startup 3", () => {
-      let createdEntityId: number | null = null;
+      let createdEntityId: bigint | null = null;
       const materialResource = resourceContainer.get<MaterialResource>(
         "./src/assets/material.material"
       );
@@ -185,7 +185,7 @@ world.registerModule({
       });
 
       let handle2 = inputService.onReleased.addObserver((input) => {
-        if (input === "Action 1" && createdEntityId) {
+        if (input === "Action 1" && createdEntityId !== null) {
           console.log("Remove");
           entityService.removeEntity(createdEntityId);
         }